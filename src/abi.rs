@@ -0,0 +1,646 @@
+//! ABI compatibility check between two versions of a shared library: the
+//! exported dynamic symbols, the signatures of exported functions and the
+//! layouts of every type reachable from them
+
+use crate::{analyze_elf_from_bytes_with_dwarf, ElfInfo, FunctionInfo, TypeInfo, VariableInfo};
+use anyhow::{Context, Result};
+use goblin::elf::sym::{STB_GLOBAL, STB_GNU_UNIQUE, STB_WEAK, STT_FUNC, STT_GNU_IFUNC, STT_TLS};
+use goblin::elf::sym::{STV_DEFAULT, STV_PROTECTED};
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AbiReport {
+    /// No breaking change was found
+    pub compatible: bool,
+    pub old_soname: Option<String>,
+    pub new_soname: Option<String>,
+    pub breaking_changes: usize,
+    pub compatible_changes: usize,
+    /// Breaking changes first
+    pub changes: Vec<AbiChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AbiChange {
+    pub kind: String,     // "symbol", "function", "variable", "type"
+    pub severity: String, // "breaking", "compatible"
+    pub name: String,
+    pub description: String,
+}
+
+/// Symbol exported through `.dynsym`: defined, global or weak, and visible
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ExportedSymbol {
+    pub name: String,
+    pub kind: String, // "function", "object", "tls"
+    pub size: u64,
+}
+
+/// Compare the shared libraries at `old_path` and `new_path`
+pub fn check_abi_files(old_path: &str, new_path: &str) -> Result<AbiReport> {
+    let old = fs::read(old_path).with_context(|| format!("Failed to read ELF file: {old_path}"))?;
+    let new = fs::read(new_path).with_context(|| format!("Failed to read ELF file: {new_path}"))?;
+    check_abi_from_bytes(&old, &new)
+}
+
+/// Compare two shared libraries in memory (WebAssembly-compatible)
+pub fn check_abi_from_bytes(old: &[u8], new: &[u8]) -> Result<AbiReport> {
+    let (old_soname, old_symbols) = exported_symbols(old)?;
+    let (new_soname, new_symbols) = exported_symbols(new)?;
+    let mut report = check_abi(
+        &old_symbols,
+        &analyze_elf_from_bytes_with_dwarf(old)?,
+        &new_symbols,
+        &analyze_elf_from_bytes_with_dwarf(new)?,
+    );
+    report.old_soname = old_soname;
+    report.new_soname = new_soname;
+    Ok(report)
+}
+
+/// `DT_SONAME` and the exported dynamic symbols of an ELF file
+pub fn exported_symbols(buffer: &[u8]) -> Result<(Option<String>, Vec<ExportedSymbol>)> {
+    let elf = match Object::parse(buffer)? {
+        Object::Elf(elf) => elf,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+
+    let mut symbols = Vec::new();
+    for symbol in elf.dynsyms.iter() {
+        let exported = symbol.st_shndx != 0
+            && matches!(symbol.st_bind(), STB_GLOBAL | STB_WEAK | STB_GNU_UNIQUE)
+            && matches!(symbol.st_visibility(), STV_DEFAULT | STV_PROTECTED);
+        let Some(name) = elf.dynstrtab.get_at(symbol.st_name) else {
+            continue;
+        };
+        if !exported || name.is_empty() {
+            continue;
+        }
+        let kind = match symbol.st_type() {
+            STT_FUNC | STT_GNU_IFUNC => "function",
+            STT_TLS => "tls",
+            _ => "object",
+        };
+        symbols.push(ExportedSymbol {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            size: symbol.st_size,
+        });
+    }
+    symbols.sort_by(|a, b| a.name.cmp(&b.name));
+    symbols.dedup_by(|a, b| a.name == b.name);
+    Ok((elf.soname.map(str::to_string), symbols))
+}
+
+/// Compare exported symbols, then the DWARF signatures of exported functions
+/// and variables, then the layouts of all types reachable from them
+pub fn check_abi(
+    old_symbols: &[ExportedSymbol],
+    old_info: &ElfInfo,
+    new_symbols: &[ExportedSymbol],
+    new_info: &ElfInfo,
+) -> AbiReport {
+    let mut changes = Vec::new();
+    let new_by_name: BTreeMap<&str, &ExportedSymbol> =
+        new_symbols.iter().map(|s| (s.name.as_str(), s)).collect();
+    let old_by_name: BTreeMap<&str, &ExportedSymbol> =
+        old_symbols.iter().map(|s| (s.name.as_str(), s)).collect();
+
+    let old_functions = functions_by_symbol(&old_info.functions);
+    let new_functions = functions_by_symbol(&new_info.functions);
+    let old_variables = variables_by_symbol(&old_info.variables);
+    let new_variables = variables_by_symbol(&new_info.variables);
+    let mut old_roots = Vec::new();
+    let mut new_roots = Vec::new();
+
+    for old_symbol in old_symbols {
+        let name = old_symbol.name.as_str();
+        let Some(new_symbol) = new_by_name.get(name) else {
+            changes.push(change(
+                "symbol",
+                true,
+                name,
+                format!("exported {} removed", old_symbol.kind),
+            ));
+            continue;
+        };
+        if new_symbol.kind != old_symbol.kind {
+            changes.push(change(
+                "symbol",
+                true,
+                name,
+                format!("changed from {} to {}", old_symbol.kind, new_symbol.kind),
+            ));
+            continue;
+        }
+        if old_symbol.kind != "function" && old_symbol.size != new_symbol.size {
+            // Executables hold copy relocations sized for the old object
+            changes.push(change(
+                "symbol",
+                true,
+                name,
+                format!(
+                    "object size changed from {} to {} bytes",
+                    old_symbol.size, new_symbol.size
+                ),
+            ));
+        }
+
+        if let (Some(old), Some(new)) = (old_functions.get(name), new_functions.get(name)) {
+            compare_function(name, old, new, &mut changes);
+            old_roots.extend(function_types(old));
+            new_roots.extend(function_types(new));
+        }
+        if let (Some(old), Some(new)) = (old_variables.get(name), new_variables.get(name)) {
+            if old.type_info.name != new.type_info.name {
+                changes.push(change(
+                    "variable",
+                    !same_underlying(&old.type_info, &new.type_info),
+                    name,
+                    format!(
+                        "type changed from `{}` to `{}`",
+                        old.type_info.name, new.type_info.name
+                    ),
+                ));
+            }
+            old_roots.push(&old.type_info);
+            new_roots.push(&new.type_info);
+        }
+    }
+    for new_symbol in new_symbols {
+        if !old_by_name.contains_key(new_symbol.name.as_str()) {
+            changes.push(change(
+                "symbol",
+                false,
+                &new_symbol.name,
+                format!("exported {} added", new_symbol.kind),
+            ));
+        }
+    }
+
+    let old_types = reachable_types(&old_roots, &old_info.types);
+    let new_types = reachable_types(&new_roots, &new_info.types);
+    for (name, old_type) in &old_types {
+        if let Some(new_type) = new_types.get(name) {
+            compare_type(name, old_type, new_type, &mut changes);
+        }
+    }
+
+    changes.sort_by_key(|change| change.severity != "breaking");
+    let breaking_changes = changes.iter().filter(|c| c.severity == "breaking").count();
+    AbiReport {
+        compatible: breaking_changes == 0,
+        breaking_changes,
+        compatible_changes: changes.len() - breaking_changes,
+        changes,
+        ..Default::default()
+    }
+}
+
+fn change(kind: &str, breaking: bool, name: &str, description: String) -> AbiChange {
+    AbiChange {
+        kind: kind.to_string(),
+        severity: if breaking { "breaking" } else { "compatible" }.to_string(),
+        name: name.to_string(),
+        description,
+    }
+}
+
+/// Function definitions keyed by the symbol they export
+fn functions_by_symbol(functions: &[FunctionInfo]) -> BTreeMap<&str, &FunctionInfo> {
+    let mut by_symbol = BTreeMap::new();
+    for function in functions.iter().filter(|f| f.address != 0) {
+        let symbol = function.linkage_name.as_deref().unwrap_or(&function.name);
+        by_symbol.entry(symbol).or_insert(function);
+    }
+    by_symbol
+}
+
+fn variables_by_symbol(variables: &[VariableInfo]) -> BTreeMap<&str, &VariableInfo> {
+    let mut by_symbol = BTreeMap::new();
+    for variable in variables.iter().filter(|v| v.address.is_some()) {
+        let symbol = variable.linkage_name.as_deref().unwrap_or(&variable.name);
+        by_symbol.entry(symbol).or_insert(variable);
+    }
+    by_symbol
+}
+
+fn function_types(function: &FunctionInfo) -> Vec<&TypeInfo> {
+    function
+        .return_type
+        .iter()
+        .chain(function.parameters.iter().map(|p| &p.type_info))
+        .collect()
+}
+
+/// Renamed typedefs and qualifiers over an unchanged type do not change the ABI
+fn same_underlying(old: &TypeInfo, new: &TypeInfo) -> bool {
+    let (old, new) = (old.underlying(), new.underlying());
+    old.name == new.name && old.size == new.size
+}
+
+fn compare_function(
+    name: &str,
+    old: &FunctionInfo,
+    new: &FunctionInfo,
+    changes: &mut Vec<AbiChange>,
+) {
+    let type_name =
+        |type_info: Option<&TypeInfo>| type_info.map_or("void".to_string(), |t| t.name.clone());
+    let (old_return, new_return) = (old.return_type.as_ref(), new.return_type.as_ref());
+    if type_name(old_return) != type_name(new_return) {
+        let breaking = match (old_return, new_return) {
+            (Some(old), Some(new)) => !same_underlying(old, new),
+            // Callers of a void function ignore whatever is returned now
+            (None, Some(_)) => false,
+            _ => true,
+        };
+        changes.push(change(
+            "function",
+            breaking,
+            name,
+            format!(
+                "return type changed from `{}` to `{}`",
+                type_name(old_return),
+                type_name(new_return)
+            ),
+        ));
+    }
+
+    if old.parameters.len() != new.parameters.len() {
+        changes.push(change(
+            "function",
+            true,
+            name,
+            format!(
+                "parameter count changed from {} to {}",
+                old.parameters.len(),
+                new.parameters.len()
+            ),
+        ));
+        return;
+    }
+    for (index, (old_parameter, new_parameter)) in
+        old.parameters.iter().zip(&new.parameters).enumerate()
+    {
+        let (old_type, new_type) = (&old_parameter.type_info, &new_parameter.type_info);
+        if old_type.name != new_type.name {
+            changes.push(change(
+                "function",
+                !same_underlying(old_type, new_type),
+                name,
+                format!(
+                    "parameter {} `{}` changed type from `{}` to `{}`",
+                    index + 1,
+                    old_parameter.name,
+                    old_type.name,
+                    new_type.name
+                ),
+            ));
+        }
+    }
+}
+
+/// Named structs, classes, unions and enums reachable from `roots` through
+/// members, pointers, typedefs, arrays and function pointers, using the
+/// complete definitions from `types`
+fn reachable_types<'a>(
+    roots: &[&'a TypeInfo],
+    types: &'a [TypeInfo],
+) -> BTreeMap<String, &'a TypeInfo> {
+    let mut definitions: BTreeMap<&str, &TypeInfo> = BTreeMap::new();
+    for type_info in types.iter().filter(|t| t.size.is_some()) {
+        definitions.entry(type_key(type_info)).or_insert(type_info);
+    }
+
+    let mut reachable = BTreeMap::new();
+    let mut pending: Vec<&TypeInfo> = roots.to_vec();
+    while let Some(type_info) = pending.pop() {
+        let mut type_info = type_info;
+        if matches!(
+            type_info.kind.as_str(),
+            "struct" | "class" | "union" | "enum"
+        ) && type_info.name != "<anonymous>"
+        {
+            let key = type_key(type_info);
+            if reachable.contains_key(key) {
+                continue;
+            }
+            // Pointer targets are resolved shallowly, without members
+            if let Some(definition) = definitions.get(key) {
+                type_info = definition;
+            }
+            reachable.insert(key.to_string(), type_info);
+        }
+
+        pending.extend(type_info.target.as_deref());
+        pending.extend(type_info.members.iter().map(|m| &m.type_info));
+        if let Some(array) = &type_info.array {
+            pending.push(&array.element_type);
+        }
+        if let Some(subroutine) = &type_info.subroutine {
+            pending.extend(subroutine.return_type.as_deref());
+            pending.extend(&subroutine.parameters);
+        }
+        if let Some(variant_part) = &type_info.variant_part {
+            pending.extend(variant_part.variants.iter().map(|v| &v.payload));
+        }
+        if let Some(class) = &type_info.class {
+            pending.extend(
+                class
+                    .bases
+                    .iter()
+                    .filter_map(|base| definitions.get(base.name.as_str()).copied()),
+            );
+        }
+    }
+    reachable
+}
+
+fn type_key(type_info: &TypeInfo) -> &str {
+    type_info
+        .qualified_name
+        .as_deref()
+        .unwrap_or(&type_info.name)
+}
+
+fn describe(value: Option<u64>) -> String {
+    value.map_or("unknown".to_string(), |value| value.to_string())
+}
+
+fn compare_type(name: &str, old: &TypeInfo, new: &TypeInfo, changes: &mut Vec<AbiChange>) {
+    if old.size != new.size {
+        changes.push(change(
+            "type",
+            true,
+            name,
+            format!(
+                "size changed from {} to {} bytes",
+                describe(old.size),
+                describe(new.size)
+            ),
+        ));
+    }
+    if old.alignment != new.alignment && old.alignment.is_some() && new.alignment.is_some() {
+        changes.push(change(
+            "type",
+            true,
+            name,
+            format!(
+                "alignment changed from {} to {}",
+                describe(old.alignment),
+                describe(new.alignment)
+            ),
+        ));
+    }
+
+    for old_member in &old.members {
+        match new.members.iter().find(|m| m.name == old_member.name) {
+            None => changes.push(change(
+                "type",
+                true,
+                name,
+                format!("member `{}` removed", old_member.name),
+            )),
+            Some(new_member) => {
+                if (old_member.offset, old_member.bit_offset)
+                    != (new_member.offset, new_member.bit_offset)
+                {
+                    changes.push(change(
+                        "type",
+                        true,
+                        name,
+                        format!(
+                            "member `{}` moved from offset {} to {}",
+                            old_member.name, old_member.offset, new_member.offset
+                        ),
+                    ));
+                }
+                let (old_type, new_type) = (&old_member.type_info, &new_member.type_info);
+                if old_type.name != new_type.name || old_member.bit_size != new_member.bit_size {
+                    changes.push(change(
+                        "type",
+                        !same_underlying(old_type, new_type)
+                            || old_member.bit_size != new_member.bit_size,
+                        name,
+                        format!(
+                            "member `{}` changed type from `{}` to `{}`",
+                            old_member.name, old_type.name, new_type.name
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+    for new_member in &new.members {
+        if !old.members.iter().any(|m| m.name == new_member.name) {
+            // Only harmless when it fits into former padding of an unchanged size
+            changes.push(change(
+                "type",
+                old.size != new.size,
+                name,
+                format!(
+                    "member `{}` added at offset {}",
+                    new_member.name, new_member.offset
+                ),
+            ));
+        }
+    }
+
+    for old_enumerator in &old.enumerators {
+        match new
+            .enumerators
+            .iter()
+            .find(|e| e.name == old_enumerator.name)
+        {
+            None => changes.push(change(
+                "type",
+                true,
+                name,
+                format!("enumerator `{}` removed", old_enumerator.name),
+            )),
+            Some(new_enumerator) if new_enumerator.value != old_enumerator.value => {
+                changes.push(change(
+                    "type",
+                    true,
+                    name,
+                    format!(
+                        "enumerator `{}` changed value from {} to {}",
+                        old_enumerator.name, old_enumerator.value, new_enumerator.value
+                    ),
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    for new_enumerator in &new.enumerators {
+        if !old
+            .enumerators
+            .iter()
+            .any(|e| e.name == new_enumerator.name)
+        {
+            changes.push(change(
+                "type",
+                false,
+                name,
+                format!("enumerator `{}` added", new_enumerator.name),
+            ));
+        }
+    }
+
+    if let (Some(old_class), Some(new_class)) = (&old.class, &new.class) {
+        let bases = |class: &crate::ClassInfo| {
+            class
+                .bases
+                .iter()
+                .map(|b| (b.name.clone(), b.offset, b.is_virtual))
+                .collect::<Vec<_>>()
+        };
+        if bases(old_class) != bases(new_class) {
+            changes.push(change(
+                "type",
+                true,
+                name,
+                "base classes changed".to_string(),
+            ));
+        }
+        // Any change to the virtual table breaks callers and derived classes
+        let vtable = |class: &crate::ClassInfo| {
+            class
+                .methods
+                .iter()
+                .filter(|m| m.virtuality != "none")
+                .map(|m| {
+                    (
+                        m.linkage_name.clone().unwrap_or(m.name.clone()),
+                        m.vtable_slot,
+                    )
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let (old_vtable, new_vtable) = (vtable(old_class), vtable(new_class));
+        for (method, slot) in &old_vtable {
+            match new_vtable.get(method) {
+                None => changes.push(change(
+                    "type",
+                    true,
+                    name,
+                    format!("virtual method `{method}` removed"),
+                )),
+                Some(new_slot) if new_slot != slot => changes.push(change(
+                    "type",
+                    true,
+                    name,
+                    format!("virtual method `{method}` moved from slot {slot:?} to {new_slot:?}"),
+                )),
+                Some(_) => {}
+            }
+        }
+        for method in new_vtable.keys().filter(|m| !old_vtable.contains_key(*m)) {
+            changes.push(change(
+                "type",
+                true,
+                name,
+                format!("virtual method `{method}` added"),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnumeratorInfo, MemberInfo};
+
+    fn scalar(name: &str, size: u64) -> TypeInfo {
+        TypeInfo {
+            name: name.to_string(),
+            size: Some(size),
+            kind: "basic".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn member(name: &str, offset: u64, type_info: TypeInfo) -> MemberInfo {
+        MemberInfo {
+            name: name.to_string(),
+            offset,
+            type_info,
+            ..Default::default()
+        }
+    }
+
+    fn severities(old: &TypeInfo, new: &TypeInfo) -> Vec<(String, String)> {
+        let mut changes = Vec::new();
+        compare_type("t", old, new, &mut changes);
+        changes
+            .into_iter()
+            .map(|c| (c.severity, c.description))
+            .collect()
+    }
+
+    #[test]
+    fn test_member_added_into_padding_is_compatible() {
+        let old = TypeInfo {
+            name: "t".to_string(),
+            kind: "struct".to_string(),
+            size: Some(8),
+            members: vec![
+                member("a", 0, scalar("int", 4)),
+                member("b", 4, scalar("char", 1)),
+            ],
+            ..Default::default()
+        };
+        let mut new = old.clone();
+        new.members.push(member("c", 5, scalar("char", 1)));
+        assert_eq!(
+            severities(&old, &new),
+            vec![(
+                "compatible".to_string(),
+                "member `c` added at offset 5".to_string()
+            )]
+        );
+
+        new.size = Some(12);
+        new.members[1].offset = 8;
+        let changes = severities(&old, &new);
+        assert!(changes.iter().all(|(severity, _)| severity == "breaking"));
+        assert_eq!(changes.len(), 3);
+    }
+
+    #[test]
+    fn test_enumerators() {
+        let enumerator = |name: &str, value: i64| EnumeratorInfo {
+            name: name.to_string(),
+            value,
+        };
+        let old = TypeInfo {
+            name: "t".to_string(),
+            kind: "enum".to_string(),
+            size: Some(4),
+            enumerators: vec![enumerator("A", 0), enumerator("B", 1)],
+            ..Default::default()
+        };
+        let mut new = old.clone();
+        new.enumerators.push(enumerator("C", 2));
+        assert_eq!(severities(&old, &new)[0].0, "compatible");
+        new.enumerators[1].value = 5;
+        assert_eq!(severities(&old, &new)[0].0, "breaking");
+    }
+
+    #[test]
+    fn test_typedef_rename_is_compatible() {
+        let typedef = |name: &str| TypeInfo {
+            name: name.to_string(),
+            kind: "typedef".to_string(),
+            size: Some(8),
+            target: Some(Box::new(scalar("long unsigned int", 8))),
+            ..Default::default()
+        };
+        assert!(same_underlying(&typedef("size_t"), &typedef("uint64_t")));
+        assert!(!same_underlying(&typedef("size_t"), &scalar("int", 4)));
+    }
+}
@@ -0,0 +1,912 @@
+//! C header generation from DWARF types: structs, unions, enums, typedefs
+//! and function prototypes, with explicit padding members and a block of
+//! static asserts that checks every size and member offset against DWARF
+
+use crate::layout::type_alignment;
+use crate::{ElfInfo, FunctionInfo, MemberInfo, TypeInfo};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Emit a C header for the named types in `elf_info` (all of them when
+/// `type_names` is empty, together with prototypes of C functions) and every
+/// type they depend on
+pub fn generate_c_header(elf_info: &ElfInfo, type_names: &[String]) -> String {
+    let mut writer = HeaderWriter::new(elf_info);
+
+    for type_info in writer.index.roots(type_names) {
+        writer.require(type_info, true);
+    }
+
+    let mut prototypes = Vec::new();
+    if type_names.is_empty() {
+        for variable in elf_info.variables.iter().filter(|v| v.scope == "global") {
+            writer.require(&variable.type_info, false);
+        }
+        let mut seen = HashSet::new();
+        for function in elf_info.functions.iter().filter(|f| is_c_function(f)) {
+            if seen.insert(function.name.as_str()) {
+                prototypes.push(writer.prototype(function));
+            }
+        }
+    }
+
+    let guard = "HIMMEL_GENERATED_H";
+    let mut out = format!(
+        "/* Generated by himmel from DWARF debug information */\n\
+         #ifndef {guard}\n#define {guard}\n\n\
+         #include <stddef.h>\n#include <stdint.h>\n"
+    );
+    if !writer.forward.is_empty() {
+        out.push('\n');
+        for tag in &writer.forward {
+            out.push_str(&format!("{tag};\n"));
+        }
+    }
+    for definition in &writer.definitions {
+        out.push('\n');
+        out.push_str(definition);
+    }
+    if !prototypes.is_empty() {
+        out.push('\n');
+        for prototype in &prototypes {
+            out.push_str(&format!("{prototype};\n"));
+        }
+    }
+    if !writer.asserts.is_empty() {
+        out.push_str("\n/* Layout checks */\n");
+        for assertion in &writer.asserts {
+            out.push_str(assertion);
+            out.push('\n');
+        }
+    }
+    out.push_str(&format!("\n#endif /* {guard} */\n"));
+    out
+}
+
+/// Functions with C linkage and a plain identifier for a name
+fn is_c_function(function: &FunctionInfo) -> bool {
+    function.address != 0
+        && function.name != "main"
+        && is_identifier(&function.name)
+        && !is_reserved(&function.name)
+        && function
+            .linkage_name
+            .as_ref()
+            .is_none_or(|linkage_name| *linkage_name == function.name)
+        && function
+            .qualified_name
+            .as_ref()
+            .is_none_or(|qualified_name| *qualified_name == function.name)
+}
+
+/// Names reserved for the compiler and runtime (`__libc_csu_init`, `_GLOBAL__sub_I_x`)
+fn is_reserved(name: &str) -> bool {
+    name.starts_with("__")
+        || name
+            .strip_prefix('_')
+            .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_uppercase()))
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Turn a DWARF name (`ns::Type`, `_vptr.Base`, `&[u8]`) into a C identifier
+pub(crate) fn c_identifier(name: &str) -> String {
+    let mut identifier: String = name
+        .replace("::", "__")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+/// C spelling of base types that only exist in other languages
+fn c_base_type(type_info: &TypeInfo) -> String {
+    let name = type_info.name.as_str();
+    let mapped = match (name, type_info.size) {
+        ("u8", _) => "uint8_t",
+        ("u16", _) => "uint16_t",
+        ("u32", _) => "uint32_t",
+        ("u64", _) => "uint64_t",
+        ("u128", _) => "unsigned __int128",
+        ("i8", _) => "int8_t",
+        ("i16", _) => "int16_t",
+        ("i32", _) => "int32_t",
+        ("i64", _) => "int64_t",
+        ("i128", _) => "__int128",
+        ("usize", _) => "uintptr_t",
+        ("isize", _) => "intptr_t",
+        ("f32", _) => "float",
+        ("f64", _) => "double",
+        ("bool", _) => "_Bool",
+        // Rust `char` is a 32-bit Unicode scalar value
+        ("char", Some(4)) | ("char32_t", _) => "uint32_t",
+        ("char16_t", _) => "uint16_t",
+        ("char8_t", _) => "unsigned char",
+        ("decltype(nullptr)", _) => "void *",
+        // Rust unit type
+        ("()", _) => "void",
+        _ => return name.to_string(),
+    };
+    mapped.to_string()
+}
+
+pub(crate) fn type_key(type_info: &TypeInfo) -> &str {
+    type_info
+        .qualified_name
+        .as_deref()
+        .unwrap_or(&type_info.name)
+}
+
+pub(crate) fn is_aggregate(type_info: &TypeInfo) -> bool {
+    matches!(
+        type_info.kind.as_str(),
+        "struct" | "class" | "union" | "enum" | "slice" | "str" | "trait_object"
+    )
+}
+
+/// `struct`, `union` or `enum`; Rust enums with data become opaque structs
+fn tag_keyword(type_info: &TypeInfo) -> &'static str {
+    match type_info.kind.as_str() {
+        "union" => "union",
+        "enum" if type_info.variant_part.is_none() => "enum",
+        _ => "struct",
+    }
+}
+
+fn join(base: &str, declarator: &str) -> String {
+    if declarator.is_empty() {
+        base.to_string()
+    } else {
+        format!("{base} {declarator}")
+    }
+}
+
+/// Complete definitions of the types in an `ElfInfo`, for resolving
+/// declarations that DWARF emits without members
+pub(crate) struct TypeIndex<'a> {
+    elf_info: &'a ElfInfo,
+    /// Complete definitions by qualified name; the first layout of an ODR conflict wins
+    complete: HashMap<&'a str, &'a TypeInfo>,
+}
+
+impl<'a> TypeIndex<'a> {
+    pub(crate) fn new(elf_info: &'a ElfInfo) -> Self {
+        let mut complete = HashMap::new();
+        for type_info in elf_info.types.iter().filter(|t| t.size.is_some()) {
+            if type_info.name != "<anonymous>" {
+                complete.entry(type_key(type_info)).or_insert(type_info);
+            }
+        }
+        TypeIndex { elf_info, complete }
+    }
+
+    /// Named types matching `type_names` (all of them when it is empty).
+    /// Typedefs are not listed in `ElfInfo.types`, so they are looked up where
+    /// variables, functions and members use them
+    pub(crate) fn roots(&self, type_names: &[String]) -> Vec<&'a TypeInfo> {
+        let named = self
+            .elf_info
+            .types
+            .iter()
+            .filter(|t| t.name != "<anonymous>");
+        if type_names.is_empty() {
+            return named.collect();
+        }
+        let mut roots: Vec<&'a TypeInfo> = named
+            .filter(|t| {
+                type_names
+                    .iter()
+                    .any(|name| *name == t.name || t.qualified_name.as_ref() == Some(name))
+            })
+            .collect();
+        let mut typedefs = HashMap::new();
+        let uses = self
+            .elf_info
+            .variables
+            .iter()
+            .map(|variable| &variable.type_info)
+            .chain(self.elf_info.functions.iter().flat_map(|function| {
+                function
+                    .parameters
+                    .iter()
+                    .map(|parameter| &parameter.type_info)
+                    .chain(&function.return_type)
+            }))
+            .chain(&self.elf_info.types);
+        for type_info in uses {
+            collect_typedefs(type_info, &mut typedefs);
+        }
+        for name in type_names {
+            if !roots
+                .iter()
+                .any(|t| t.name == *name || t.qualified_name.as_ref() == Some(name))
+            {
+                roots.extend(typedefs.get(name.as_str()).copied());
+            }
+        }
+        roots
+    }
+
+    /// Complete definition of the base class or type named `name`
+    pub(crate) fn get(&self, name: &str) -> Option<&'a TypeInfo> {
+        self.complete.get(name).copied()
+    }
+
+    /// The complete definition of `type_info`: aggregates behind pointers are
+    /// resolved without members, anonymous ones are matched by declaration site
+    pub(crate) fn definition(&self, type_info: &'a TypeInfo) -> &'a TypeInfo {
+        if !is_aggregate(type_info)
+            || !type_info.members.is_empty()
+            || !type_info.enumerators.is_empty()
+        {
+            return type_info;
+        }
+        if type_info.name != "<anonymous>" {
+            return self.get(type_key(type_info)).unwrap_or(type_info);
+        }
+        self.elf_info
+            .types
+            .iter()
+            .find(|candidate| {
+                candidate.name == "<anonymous>"
+                    && candidate.kind == type_info.kind
+                    && candidate.size == type_info.size
+                    && type_info.decl_location.is_some()
+                    && candidate
+                        .decl_location
+                        .as_ref()
+                        .map(|l| (&l.file, l.line, l.column))
+                        == type_info
+                            .decl_location
+                            .as_ref()
+                            .map(|l| (&l.file, l.line, l.column))
+            })
+            .unwrap_or(type_info)
+    }
+
+    /// Data members of `type_info` and its non-virtual bases, each with the
+    /// bit offset of the subobject that holds it
+    pub(crate) fn collect_members(
+        &self,
+        type_info: &'a TypeInfo,
+        base_bits: u64,
+        members: &mut Vec<(u64, &'a MemberInfo)>,
+    ) {
+        if let Some(class) = &type_info.class {
+            for base in &class.bases {
+                if let (Some(offset), Some(definition)) = (base.offset, self.get(&base.name)) {
+                    self.collect_members(definition, base_bits + offset * 8, members);
+                }
+            }
+        }
+        members.extend(type_info.members.iter().map(|member| (base_bits, member)));
+    }
+}
+
+fn collect_typedefs<'a>(type_info: &'a TypeInfo, typedefs: &mut HashMap<&'a str, &'a TypeInfo>) {
+    if type_info.kind == "typedef" {
+        typedefs.entry(type_key(type_info)).or_insert(type_info);
+    }
+    if let Some(target) = &type_info.target {
+        collect_typedefs(target, typedefs);
+    }
+    if let Some(array) = &type_info.array {
+        collect_typedefs(&array.element_type, typedefs);
+    }
+    if let Some(subroutine) = &type_info.subroutine {
+        for parameter in &subroutine.parameters {
+            collect_typedefs(parameter, typedefs);
+        }
+        if let Some(return_type) = &subroutine.return_type {
+            collect_typedefs(return_type, typedefs);
+        }
+    }
+    for member in &type_info.members {
+        collect_typedefs(&member.type_info, typedefs);
+    }
+}
+
+struct HeaderWriter<'a> {
+    index: TypeIndex<'a>,
+    emitted: HashSet<String>,
+    in_progress: HashSet<String>,
+    enumerators: HashSet<String>,
+    forward: BTreeSet<String>,
+    definitions: Vec<String>,
+    asserts: Vec<String>,
+}
+
+impl<'a> HeaderWriter<'a> {
+    fn new(elf_info: &'a ElfInfo) -> Self {
+        HeaderWriter {
+            index: TypeIndex::new(elf_info),
+            emitted: HashSet::new(),
+            in_progress: HashSet::new(),
+            enumerators: HashSet::new(),
+            forward: BTreeSet::new(),
+            definitions: Vec::new(),
+            asserts: Vec::new(),
+        }
+    }
+
+    fn definition(&self, type_info: &'a TypeInfo) -> &'a TypeInfo {
+        self.index.definition(type_info)
+    }
+
+    /// Emit whatever `type_info` needs to be usable: typedefs and enums must
+    /// be declared, aggregates used by value (or as array elements) complete
+    fn require(&mut self, type_info: &'a TypeInfo, by_value: bool) {
+        match type_info.kind.as_str() {
+            "typedef" => self.emit_typedef(type_info),
+            "const" | "volatile" | "restrict" | "atomic" => {
+                if let Some(target) = &type_info.target {
+                    self.require(target, by_value);
+                }
+            }
+            "pointer" | "reference" => {
+                if let Some(target) = &type_info.target {
+                    self.require(target, false);
+                }
+            }
+            "array" => {
+                if let Some(array) = &type_info.array {
+                    self.require(&array.element_type, true);
+                }
+            }
+            "subroutine" => {
+                if let Some(subroutine) = &type_info.subroutine {
+                    if let Some(return_type) = &subroutine.return_type {
+                        self.require(return_type, false);
+                    }
+                    for parameter in &subroutine.parameters {
+                        self.require(parameter, false);
+                    }
+                }
+            }
+            _ if is_aggregate(type_info) => {
+                let definition = self.definition(type_info);
+                if definition.name == "<anonymous>" {
+                    // Defined inline where it is used
+                    self.require_members(definition);
+                } else if by_value || tag_keyword(definition) == "enum" {
+                    self.emit_aggregate(definition);
+                } else {
+                    self.forward.insert(format!(
+                        "{} {}",
+                        tag_keyword(definition),
+                        c_identifier(type_key(definition))
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn require_members(&mut self, type_info: &'a TypeInfo) {
+        for member in &type_info.members {
+            self.require(&member.type_info, true);
+        }
+        if let Some(class) = &type_info.class {
+            for base in class.bases.iter().filter(|base| base.offset.is_some()) {
+                if let Some(definition) = self.index.get(&base.name) {
+                    self.emit_aggregate(definition);
+                }
+            }
+        }
+    }
+
+    fn emit_typedef(&mut self, typedef: &'a TypeInfo) {
+        let name = c_identifier(type_key(typedef));
+        if self.emitted.contains(&name) || self.in_progress.contains(&name) {
+            return;
+        }
+        if matches!(
+            name.as_str(),
+            "size_t" | "ptrdiff_t" | "wchar_t" | "intptr_t" | "uintptr_t"
+        ) || (name.ends_with("_t")
+            && name.trim_start_matches('u').starts_with("int")
+            && name[..name.len() - 2]
+                .trim_start_matches(|c: char| !c.is_ascii_digit())
+                .parse::<u32>()
+                .is_ok())
+        {
+            // Provided by <stddef.h> and <stdint.h>
+            return;
+        }
+        self.in_progress.insert(name.clone());
+
+        let target = typedef.target.as_deref();
+        if let Some(target) = target {
+            // A typedef of a struct only needs the tag, which is forward declared
+            self.require(target, false);
+        }
+        let declaration = match target {
+            Some(target) => format!("typedef {};\n", self.declare(target, &name)),
+            None => format!("typedef void {name};\n"),
+        };
+        if let Some(target) = target.map(|t| self.definition(t.underlying())) {
+            if is_aggregate(target) && target.name == "<anonymous>" {
+                self.assert_layout(&name, target);
+            }
+        }
+
+        self.in_progress.remove(&name);
+        self.emitted.insert(name);
+        self.definitions.push(declaration);
+    }
+
+    fn emit_aggregate(&mut self, type_info: &'a TypeInfo) {
+        let spelled = format!(
+            "{} {}",
+            tag_keyword(type_info),
+            c_identifier(type_key(type_info))
+        );
+        if self.emitted.contains(&spelled) || self.in_progress.contains(&spelled) {
+            return;
+        }
+        if type_info.size.is_none() {
+            if tag_keyword(type_info) != "enum" {
+                self.forward.insert(spelled);
+            }
+            return;
+        }
+        self.in_progress.insert(spelled.clone());
+        self.require_members(type_info);
+        let body = self.body(type_info, "");
+        let attributes = self.attributes(type_info);
+        let definition = if tag_keyword(type_info) == "enum" && !attributes.is_empty() {
+            format!(
+                "enum{attributes} {} {body};\n",
+                c_identifier(type_key(type_info))
+            )
+        } else {
+            format!("{spelled} {body}{attributes};\n")
+        };
+        self.assert_layout(&spelled, type_info);
+
+        self.in_progress.remove(&spelled);
+        self.emitted.insert(spelled.clone());
+        if tag_keyword(type_info) != "enum" {
+            self.forward.insert(spelled);
+        }
+        self.definitions.push(definition);
+    }
+
+    fn assert_layout(&mut self, spelled: &str, type_info: &TypeInfo) {
+        if let Some(size) = type_info.size {
+            self.asserts.push(format!(
+                "_Static_assert(sizeof({spelled}) == {size}, \"sizeof({spelled})\");"
+            ));
+        }
+        if tag_keyword(type_info) != "struct" || type_info.variant_part.is_some() {
+            return;
+        }
+        for member in &type_info.members {
+            if member.bit_size.is_none()
+                && !is_anonymous_member(member)
+                && !is_zero_sized(&member.type_info)
+            {
+                let name = c_identifier(&member.name);
+                self.asserts.push(format!(
+                    "_Static_assert(offsetof({spelled}, {name}) == {}, \"offsetof({spelled}, {name})\");",
+                    member.offset
+                ));
+            }
+        }
+    }
+
+    /// `__attribute__`s needed to reproduce the DWARF layout
+    fn attributes(&self, type_info: &TypeInfo) -> String {
+        let mut attributes = Vec::new();
+        let size = type_info.size.unwrap_or(0);
+        match tag_keyword(type_info) {
+            "enum" => {
+                if size > 0 && size < 4 {
+                    attributes.push("packed".to_string());
+                }
+            }
+            _ if type_info.variant_part.is_some() => {
+                let alignment = type_alignment(type_info);
+                if alignment > 1 {
+                    attributes.push(format!("aligned({alignment})"));
+                }
+            }
+            _ => {
+                let natural = type_info
+                    .members
+                    .iter()
+                    .map(member_alignment)
+                    .max()
+                    .unwrap_or(1);
+                let packed = !size.is_multiple_of(natural)
+                    || type_info.members.iter().any(|member| {
+                        member.bit_size.is_none() && member.offset % member_alignment(member) != 0
+                    });
+                if packed {
+                    attributes.push("packed".to_string());
+                }
+                if let Some(alignment) = type_info.alignment {
+                    if alignment > natural || (packed && alignment > 1) {
+                        attributes.push(format!("aligned({alignment})"));
+                    }
+                }
+            }
+        }
+        if attributes.is_empty() {
+            String::new()
+        } else {
+            format!(" __attribute__(({}))", attributes.join(", "))
+        }
+    }
+
+    /// `{ ... }` of an aggregate with explicit padding members, indented by `indent`
+    fn body(&mut self, type_info: &'a TypeInfo, indent: &str) -> String {
+        let inner = format!("{indent}    ");
+        let mut lines = Vec::new();
+        let size = type_info.size.unwrap_or(0);
+
+        if tag_keyword(type_info) == "enum" {
+            for enumerator in &type_info.enumerators {
+                let mut name = c_identifier(&enumerator.name);
+                // Enumerators share one namespace in C
+                if !self.enumerators.insert(name.clone()) {
+                    name = format!("{}_{name}", c_identifier(type_key(type_info)));
+                    self.enumerators.insert(name.clone());
+                }
+                let value = match enumerator.value {
+                    // The literal 9223372036854775808 does not fit in any signed type
+                    i64::MIN => "-9223372036854775807 - 1".to_string(),
+                    value => value.to_string(),
+                };
+                lines.push(format!("{inner}{name} = {value},"));
+            }
+            // C sizes an enum by its values, so a sentinel widens it to the
+            // fixed underlying type of a C++ `enum class` or Rust `#[repr]` enum
+            let sentinel = match size {
+                2 => Some("0x100"),
+                8 => Some("0x100000000"),
+                _ if type_info.enumerators.is_empty() => Some("0"),
+                _ => None,
+            };
+            if let Some(value) = sentinel {
+                let name = c_identifier(type_key(type_info));
+                lines.push(format!("{inner}__{name}_size = {value},"));
+            }
+        } else if type_info.variant_part.is_some() || type_info.members.is_empty() && size > 0 {
+            lines.push(format!("{inner}unsigned char __opaque[{size}];"));
+        } else if tag_keyword(type_info) == "union" {
+            for member in type_info
+                .members
+                .iter()
+                .filter(|m| !is_zero_sized(&m.type_info))
+            {
+                let name = if is_anonymous_member(member) {
+                    String::new()
+                } else {
+                    c_identifier(&member.name)
+                };
+                let line = self.member_line(member, &name, &inner);
+                lines.push(line);
+            }
+            let largest = type_info
+                .members
+                .iter()
+                .filter_map(|member| member.type_info.size)
+                .max()
+                .unwrap_or(0);
+            if largest < size {
+                lines.push(format!("{inner}unsigned char __size[{size}];"));
+            }
+        } else {
+            // Base class members are flattened in: the Itanium C++ ABI reuses
+            // the tail padding of a base, which a nested C struct cannot
+            let mut members = Vec::new();
+            self.index.collect_members(type_info, 0, &mut members);
+            let mut names = HashSet::new();
+            let mut fields: Vec<(u64, u64, String)> = Vec::new();
+            for (base_bits, member) in members {
+                if is_zero_sized(&member.type_info) {
+                    // C has no zero-sized members (`()`, `PhantomData<T>`)
+                    continue;
+                }
+                let start = base_bits + member.bit_offset.unwrap_or(member.offset * 8);
+                let end = match member.bit_size {
+                    Some(bits) => start + bits,
+                    None => start + member.type_info.size.unwrap_or(0) * 8,
+                };
+                let mut name = if is_anonymous_member(member) {
+                    String::new()
+                } else {
+                    c_identifier(&member.name)
+                };
+                if !name.is_empty() && !names.insert(name.clone()) {
+                    name = format!("{name}_{}", start / 8);
+                }
+                let line = self.member_line(member, &name, &inner);
+                fields.push((start, end, line));
+            }
+            fields.sort_by_key(|(start, _, _)| *start);
+
+            let mut cursor: u64 = 0;
+            let mut padding = 0;
+            for (start, end, line) in fields {
+                let (from, to) = (cursor.div_ceil(8), start / 8);
+                if to > from {
+                    lines.push(format!(
+                        "{inner}unsigned char __pad{padding}[{}];",
+                        to - from
+                    ));
+                    padding += 1;
+                }
+                lines.push(line);
+                cursor = cursor.max(end);
+            }
+            let used = cursor.div_ceil(8);
+            if size > used {
+                lines.push(format!(
+                    "{inner}unsigned char __pad{padding}[{}];",
+                    size - used
+                ));
+            }
+        }
+
+        format!("{{\n{}\n{indent}}}", lines.join("\n"))
+    }
+
+    fn member_line(&mut self, member: &'a MemberInfo, name: &str, indent: &str) -> String {
+        let alignment = match member.alignment {
+            Some(alignment) if alignment > type_alignment(&member.type_info) => {
+                format!("_Alignas({alignment}) ")
+            }
+            _ => String::new(),
+        };
+        let declaration = self.declare_indented(&member.type_info, name, indent);
+        match member.bit_size {
+            Some(bits) => format!("{indent}{alignment}{declaration} : {bits};"),
+            None => format!("{indent}{alignment}{declaration};"),
+        }
+    }
+
+    fn declare(&mut self, type_info: &'a TypeInfo, declarator: &str) -> String {
+        self.declare_indented(type_info, declarator, "")
+    }
+
+    /// C declaration of `declarator` with type `type_info`, e.g. `int (*name)[4]`
+    fn declare_indented(
+        &mut self,
+        type_info: &'a TypeInfo,
+        declarator: &str,
+        indent: &str,
+    ) -> String {
+        match type_info.kind.as_str() {
+            "pointer" | "reference" => {
+                let inner = match type_info.target.as_deref() {
+                    Some(target) if matches!(target.kind.as_str(), "array" | "subroutine") => {
+                        format!("(*{declarator})")
+                    }
+                    _ => format!("*{declarator}"),
+                };
+                match type_info.target.as_deref() {
+                    Some(target) => self.declare_indented(target, &inner, indent),
+                    None => format!("void {inner}"),
+                }
+            }
+            "const" | "volatile" | "restrict" | "atomic" => {
+                let qualifier = match type_info.kind.as_str() {
+                    "atomic" => "_Atomic",
+                    kind => kind,
+                };
+                match type_info.target.as_deref() {
+                    Some(target) if target.kind == "pointer" => {
+                        self.declare_indented(target, &join(qualifier, declarator), indent)
+                    }
+                    Some(target) => {
+                        format!(
+                            "{qualifier} {}",
+                            self.declare_indented(target, declarator, indent)
+                        )
+                    }
+                    None => join(&format!("{qualifier} void"), declarator),
+                }
+            }
+            "array" => {
+                let Some(array) = &type_info.array else {
+                    return join("unsigned char", declarator);
+                };
+                let mut declarator = declarator.to_string();
+                for dimension in &array.dimensions {
+                    match dimension.count {
+                        Some(count) if !array.flexible => {
+                            declarator.push_str(&format!("[{count}]"))
+                        }
+                        _ => declarator.push_str("[]"),
+                    }
+                }
+                self.declare_indented(&array.element_type, &declarator, indent)
+            }
+            "subroutine" => {
+                let Some(subroutine) = &type_info.subroutine else {
+                    return join("void", &format!("{declarator}()"));
+                };
+                let mut parameters: Vec<String> = subroutine
+                    .parameters
+                    .iter()
+                    .map(|parameter| self.declare(parameter, ""))
+                    .collect();
+                if subroutine.variadic && !parameters.is_empty() {
+                    parameters.push("...".to_string());
+                } else if subroutine.variadic {
+                    // `(...)` needs C23; an unprototyped declaration accepts any arguments
+                } else if parameters.is_empty() && subroutine.prototyped {
+                    parameters.push("void".to_string());
+                }
+                let declarator = format!("{declarator}({})", parameters.join(", "));
+                match subroutine.return_type.as_deref() {
+                    Some(return_type) => self.declare_indented(return_type, &declarator, indent),
+                    None => join("void", &declarator),
+                }
+            }
+            "typedef" => join(&c_identifier(type_key(type_info)), declarator),
+            "basic" => join(&c_base_type(type_info), declarator),
+            _ if is_aggregate(type_info) => {
+                let definition = self.definition(type_info);
+                if definition.name == "<anonymous>" {
+                    let body = if definition.size.is_some() {
+                        self.body(definition, indent)
+                    } else {
+                        String::from("{ unsigned char __opaque; }")
+                    };
+                    join(&format!("{} {body}", tag_keyword(definition)), declarator)
+                } else {
+                    join(
+                        &format!(
+                            "{} {}",
+                            tag_keyword(definition),
+                            c_identifier(type_key(definition))
+                        ),
+                        declarator,
+                    )
+                }
+            }
+            _ => match type_info.size {
+                Some(size) => join("unsigned char", &format!("{declarator}[{size}]")),
+                None => join("void", declarator),
+            },
+        }
+    }
+
+    fn prototype(&mut self, function: &'a FunctionInfo) -> String {
+        if let Some(return_type) = &function.return_type {
+            self.require(return_type, false);
+        }
+        let mut parameters = Vec::new();
+        for parameter in &function.parameters {
+            self.require(&parameter.type_info, false);
+            let name = if is_identifier(&parameter.name) {
+                parameter.name.clone()
+            } else {
+                String::new()
+            };
+            parameters.push(self.declare(&parameter.type_info, &name));
+        }
+        if parameters.is_empty() {
+            parameters.push("void".to_string());
+        }
+        let declarator = format!("{}({})", function.name, parameters.join(", "));
+        match &function.return_type {
+            Some(return_type) => self.declare(return_type, &declarator),
+            None => join("void", &declarator),
+        }
+    }
+}
+
+pub(crate) fn is_zero_sized(type_info: &TypeInfo) -> bool {
+    type_info.size == Some(0) && type_info.kind != "array"
+}
+
+pub(crate) fn is_anonymous_member(member: &MemberInfo) -> bool {
+    member.name.is_empty() || member.name == "<unknown>"
+}
+
+fn member_alignment(member: &MemberInfo) -> u64 {
+    member
+        .alignment
+        .unwrap_or_else(|| type_alignment(&member.type_info))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(name: &str, size: u64) -> TypeInfo {
+        TypeInfo {
+            name: name.to_string(),
+            size: Some(size),
+            kind: "basic".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn derived(kind: &str, target: TypeInfo) -> TypeInfo {
+        TypeInfo {
+            name: String::new(),
+            kind: kind.to_string(),
+            size: Some(8),
+            target: Some(Box::new(target)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_declarators() {
+        let elf_info = ElfInfo {
+            architecture: "x86_64".to_string(),
+            entry_point: 0,
+            sections: Vec::new(),
+            section_headers: Vec::new(),
+            file_type: "executable".to_string(),
+            endianness: "little_endian".to_string(),
+            functions: Vec::new(),
+            variables: Vec::new(),
+            types: Vec::new(),
+            type_conflicts: Vec::new(),
+        };
+        let mut writer = HeaderWriter::new(&elf_info);
+
+        let const_pointer = derived("const", derived("pointer", scalar("char", 1)));
+        assert_eq!(writer.declare(&const_pointer, "p"), "char *const p");
+        let pointer_to_const = derived("pointer", derived("const", scalar("char", 1)));
+        assert_eq!(writer.declare(&pointer_to_const, "p"), "const char *p");
+
+        let callback = derived(
+            "pointer",
+            TypeInfo {
+                kind: "subroutine".to_string(),
+                subroutine: Some(crate::SubroutineInfo {
+                    return_type: Some(Box::new(scalar("int", 4))),
+                    parameters: vec![scalar("u8", 1)],
+                    prototyped: true,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        );
+        assert_eq!(writer.declare(&callback, "cb"), "int (*cb)(uint8_t)");
+
+        let matrix = TypeInfo {
+            kind: "array".to_string(),
+            array: Some(crate::ArrayInfo {
+                element_type: Box::new(scalar("float", 4)),
+                dimensions: vec![
+                    crate::ArrayDimension {
+                        count: Some(3),
+                        ..Default::default()
+                    },
+                    crate::ArrayDimension {
+                        count: Some(4),
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            writer.declare(&derived("pointer", matrix), "m"),
+            "float (*m)[3][4]"
+        );
+    }
+
+    #[test]
+    fn test_c_identifiers() {
+        assert_eq!(c_identifier("ns::Type"), "ns__Type");
+        assert_eq!(c_identifier("_vptr.Base"), "_vptr_Base");
+        assert_eq!(c_identifier("2d"), "_2d");
+    }
+}
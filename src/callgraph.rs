@@ -0,0 +1,537 @@
+//! Static call graph: direct calls and tail calls decoded from each
+//! function body, merged with the DWARF `DW_TAG_call_site` entries the
+//! compiler records, and the indirect call sites neither can resolve
+//!
+//! Instructions taking the address of a function, such as `_start` passing
+//! `main` to `__libc_start_main`, add a `reference` edge: the function may
+//! be called from wherever the pointer goes. Queries restrict the graph to
+//! the functions calling a function, directly or through others, or to the
+//! functions reachable from one, following references as possible calls.
+
+use crate::disasm::{Disassembler, Instruction};
+use crate::SourceLocation;
+use crate::{demangle_symbol, get_die_linkage_name, get_die_name, load_dwarf_sections};
+use anyhow::{Context, Result};
+use goblin::elf::header::EM_ARM;
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::fs;
+
+type Reader<'a> = gimli::EndianSlice<'a, gimli::LittleEndian>;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CallGraph {
+    /// Function containing the ELF entry point
+    pub entry: Option<String>,
+    pub functions: Vec<CallGraphFunction>,
+    pub edges: Vec<CallEdge>,
+    /// Calls through a register or memory operand with no known target
+    pub indirect_calls: Vec<IndirectCall>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CallGraphFunction {
+    /// Symbol name, suffixed with `@0x<address>` when several functions share it
+    pub name: String,
+    /// None for functions of other objects, called through the PLT or GOT
+    pub address: Option<u64>,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CallEdge {
+    pub caller: String,
+    pub callee: String,
+    pub kind: String, // "call", "tail_call", "reference"
+    /// Addresses of the call instructions
+    pub sites: Vec<u64>,
+    pub origin: String, // "code", "dwarf", "both"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct IndirectCall {
+    pub caller: String,
+    pub address: u64,
+    /// Disassembled instruction, e.g. `call *%rax`
+    pub instruction: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+/// A callee found in code or DWARF: a function of this file by index, or
+/// an external function by name
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Callee {
+    Local(usize),
+    External(String),
+}
+
+/// Caller index, callee and edge kind
+type EdgeKey = (usize, Callee, &'static str);
+
+impl CallGraph {
+    /// Functions `function` calls or tail-calls, each once
+    pub fn callees(&self, function: &str) -> Vec<&str> {
+        let mut callees: Vec<&str> = self
+            .edges
+            .iter()
+            .filter(|edge| edge.caller == function && edge.kind != "reference")
+            .map(|edge| edge.callee.as_str())
+            .collect();
+        callees.sort_unstable();
+        callees.dedup();
+        callees
+    }
+
+    /// Names of the functions matching `name`: the unique name, or every
+    /// function of that symbol name or demangled name without its path
+    pub fn resolve(&self, name: &str) -> Vec<&str> {
+        self.functions
+            .iter()
+            .map(|function| function.name.as_str())
+            .filter(|&unique| {
+                let base = unique.rsplit_once("@0x").map_or(unique, |(base, _)| base);
+                unique == name || base == name || base.rsplit("::").next() == Some(name)
+            })
+            .collect()
+    }
+
+    /// Functions reachable from `roots` along call edges, including the roots
+    pub fn reachable_from(&self, roots: &[&str]) -> HashSet<String> {
+        self.closure(roots, |edge| (&edge.caller, &edge.callee))
+    }
+
+    /// Functions from which one of `targets` is reachable, including the targets
+    pub fn callers_of(&self, targets: &[&str]) -> HashSet<String> {
+        self.closure(targets, |edge| (&edge.callee, &edge.caller))
+    }
+
+    fn closure<'e>(
+        &'e self,
+        start: &[&str],
+        direction: impl Fn(&'e CallEdge) -> (&'e String, &'e String),
+    ) -> HashSet<String> {
+        let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+        for edge in &self.edges {
+            let (from, to) = direction(edge);
+            adjacency.entry(from).or_default().push(to);
+        }
+        let mut seen: HashSet<String> = start.iter().map(|name| name.to_string()).collect();
+        let mut queue: VecDeque<&str> = start.iter().copied().collect();
+        while let Some(name) = queue.pop_front() {
+            for &next in adjacency.get(name).into_iter().flatten() {
+                if seen.insert(next.to_string()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    /// Keep only the functions in `names` and the edges and indirect calls between them
+    pub fn retain(&mut self, names: &HashSet<String>) {
+        self.functions
+            .retain(|function| names.contains(&function.name));
+        self.edges
+            .retain(|edge| names.contains(&edge.caller) && names.contains(&edge.callee));
+        self.indirect_calls
+            .retain(|call| names.contains(&call.caller));
+    }
+
+    /// Graphviz rendering: external functions and tail calls dashed,
+    /// references dotted, edges known only from DWARF gray, and indirect
+    /// call counts in the labels
+    pub fn to_dot(&self) -> String {
+        let mut indirect: HashMap<&str, usize> = HashMap::new();
+        for call in &self.indirect_calls {
+            *indirect.entry(&call.caller).or_default() += 1;
+        }
+        let mut dot = String::from("digraph callgraph {\n    node [shape=box];\n");
+        for function in &self.functions {
+            let mut attributes = Vec::new();
+            if let Some(&count) = indirect.get(function.name.as_str()) {
+                let plural = if count == 1 { "" } else { "s" };
+                attributes.push(format!(
+                    "label=\"{}\\n{count} indirect call{plural}\"",
+                    escape(&function.name)
+                ));
+            }
+            if function.address.is_none() {
+                attributes.push("style=dashed".to_string());
+            }
+            if self.entry.as_deref() == Some(function.name.as_str()) {
+                attributes.push("penwidth=2".to_string());
+            }
+            let _ = write!(dot, "    \"{}\"", escape(&function.name));
+            if !attributes.is_empty() {
+                let _ = write!(dot, " [{}]", attributes.join(", "));
+            }
+            dot.push_str(";\n");
+        }
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            match edge.kind.as_str() {
+                "tail_call" => attributes.push("style=dashed"),
+                "reference" => attributes.push("style=dotted"),
+                _ => {}
+            }
+            if edge.origin == "dwarf" {
+                attributes.push("color=gray");
+            }
+            let _ = write!(
+                dot,
+                "    \"{}\" -> \"{}\"",
+                escape(&edge.caller),
+                escape(&edge.callee)
+            );
+            if !attributes.is_empty() {
+                let _ = write!(dot, " [{}]", attributes.join(", "));
+            }
+            dot.push_str(";\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Build the call graph of the ELF file at `path`
+pub fn call_graph_files(path: &str) -> Result<CallGraph> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    call_graph_from_bytes(&buffer)
+}
+
+/// Build the call graph from an ELF byte buffer (WebAssembly-compatible)
+pub fn call_graph_from_bytes(buffer: &[u8]) -> Result<CallGraph> {
+    let (entry, machine) = match Object::parse(buffer)? {
+        Object::Elf(elf) => (elf.header.e_entry, elf.header.e_machine),
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+    let disassembler = Disassembler::new(buffer)?;
+    let symbols = disassembler.functions();
+    // Thumb function symbols and the entry point have the low bit set, but
+    // the branches to them do not
+    let code_address = |address: u64| match machine {
+        EM_ARM => address & !1,
+        _ => address,
+    };
+    let entry = code_address(entry);
+    let functions: Vec<(String, u64, u64)> = symbols
+        .iter()
+        .map(|(name, address, size)| (name.clone(), code_address(*address), *size))
+        .collect();
+    let names = unique_names(&functions);
+    let containing = |address: u64| {
+        let index = functions.partition_point(|&(_, start, _)| start <= address);
+        index
+            .checked_sub(1)
+            .filter(|&i| address - functions[i].1 < functions[i].2)
+    };
+    let by_name: HashMap<&str, usize> = functions
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(index, (name, _, _))| (name.as_str(), index))
+        .collect();
+    let local_or_external = |name: &str| match by_name.get(name) {
+        Some(&index) => Callee::Local(index),
+        None => Callee::External(name.to_string()),
+    };
+
+    // Call sites, seen in code, seen in DWARF
+    let mut edges: BTreeMap<EdgeKey, (Vec<u64>, bool, bool)> = BTreeMap::new();
+    let mut indirect: BTreeMap<u64, (usize, Instruction)> = BTreeMap::new();
+    // End address of each call instruction, as DWARF records return addresses
+    let mut call_ends: HashMap<u64, u64> = HashMap::new();
+    let mut sites = HashMap::new();
+    for (index, (name, address, size)) in symbols.iter().enumerate() {
+        let function = disassembler.disassemble(name, *address, *size);
+        let range = function.address..function.address.saturating_add(function.size);
+        for instruction in function.instructions {
+            if matches!(instruction.kind.as_str(), "call" | "indirect_call") {
+                call_ends.insert(
+                    instruction.address.saturating_add(instruction.length),
+                    instruction.address,
+                );
+            }
+            let imported = instruction.symbol.as_deref().and_then(|symbol| {
+                symbol
+                    .strip_suffix("@plt")
+                    .or_else(|| symbol.strip_suffix("@got"))
+            });
+            let target = instruction.target.map(code_address);
+            let (callee, kind) = match (instruction.kind.as_str(), target, imported) {
+                ("call" | "indirect_call", _, Some(imported)) => {
+                    (local_or_external(imported), "call")
+                }
+                ("call", Some(target), None) => match containing(target) {
+                    // Reading the program counter, as i386 PIC code does
+                    Some(callee) if callee == index => continue,
+                    Some(callee) => (Callee::Local(callee), "call"),
+                    None => continue,
+                },
+                ("jump" | "branch", Some(target), imported) if !range.contains(&target) => {
+                    match (imported, containing(target)) {
+                        (Some(imported), _) => (local_or_external(imported), "tail_call"),
+                        (None, Some(callee)) if functions[callee].1 == target => {
+                            (Callee::Local(callee), "tail_call")
+                        }
+                        _ => continue,
+                    }
+                }
+                ("other", Some(target), imported) => match (imported, containing(target)) {
+                    (Some(imported), _) => (local_or_external(imported), "reference"),
+                    (None, Some(callee)) if functions[callee].1 == target && callee != index => {
+                        (Callee::Local(callee), "reference")
+                    }
+                    _ => continue,
+                },
+                ("indirect_call", _, None) => {
+                    indirect.insert(instruction.address, (index, instruction));
+                    continue;
+                }
+                _ => continue,
+            };
+            sites.insert(instruction.address, (index, callee.clone(), kind));
+            let edge = edges.entry((index, callee, kind)).or_default();
+            edge.0.push(instruction.address);
+            edge.1 = true;
+        }
+    }
+
+    for site in dwarf_call_sites(buffer).unwrap_or_default() {
+        let Some(&address) = call_ends.get(&site.return_address) else {
+            continue;
+        };
+        let (Some(caller), Some(callee_name)) = (containing(address), site.callee) else {
+            continue;
+        };
+        let callee = local_or_external(&callee_name);
+        // The same call found in code, possibly under an alias of the callee
+        if let Some(edge) = sites.get(&address).and_then(|key| edges.get_mut(key)) {
+            edge.2 = true;
+            continue;
+        }
+        indirect.remove(&address);
+        let kind = if site.tail_call { "tail_call" } else { "call" };
+        let edge = edges.entry((caller, callee, kind)).or_default();
+        edge.0.push(address);
+        edge.2 = true;
+    }
+
+    let mut graph = CallGraph {
+        entry: containing(entry).map(|index| names[index].clone()),
+        functions: functions
+            .iter()
+            .zip(&names)
+            .map(|((_, address, size), name)| CallGraphFunction {
+                name: name.clone(),
+                address: Some(*address),
+                size: *size,
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let mut externals = HashSet::new();
+    for ((caller, callee, kind), (sites, in_code, in_dwarf)) in edges {
+        let callee = match callee {
+            Callee::Local(index) => names[index].clone(),
+            Callee::External(name) => {
+                if externals.insert(name.clone()) {
+                    graph.functions.push(CallGraphFunction {
+                        name: name.clone(),
+                        address: None,
+                        size: 0,
+                    });
+                }
+                name
+            }
+        };
+        graph.edges.push(CallEdge {
+            caller: names[caller].clone(),
+            callee,
+            kind: kind.to_string(),
+            sites,
+            origin: match (in_code, in_dwarf) {
+                (true, true) => "both",
+                (true, false) => "code",
+                _ => "dwarf",
+            }
+            .to_string(),
+        });
+    }
+    graph.indirect_calls = indirect
+        .into_values()
+        .map(|(caller, instruction)| IndirectCall {
+            caller: names[caller].clone(),
+            address: instruction.address,
+            instruction: format!("{} {}", instruction.mnemonic, instruction.operands)
+                .trim_end()
+                .to_string(),
+            location: disassembler.location(instruction.address),
+        })
+        .collect();
+    Ok(graph)
+}
+
+/// Function names made unique by suffixing the address of duplicates,
+/// such as static functions of the same name in several files
+fn unique_names(functions: &[(String, u64, u64)]) -> Vec<String> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for (name, _, _) in functions {
+        *counts.entry(name).or_default() += 1;
+    }
+    functions
+        .iter()
+        .map(|(name, address, _)| match counts[name.as_str()] {
+            1 => name.clone(),
+            _ => format!("{name}@{address:#x}"),
+        })
+        .collect()
+}
+
+/// A `DW_TAG_call_site` (or `DW_TAG_GNU_call_site`) entry
+struct CallSite {
+    /// Address after the call instruction
+    return_address: u64,
+    /// Name of the called subprogram; None for calls through a pointer
+    callee: Option<String>,
+    tail_call: bool,
+}
+
+fn dwarf_call_sites(buffer: &[u8]) -> Result<Vec<CallSite>> {
+    let object_file = object::File::parse(buffer)?;
+    let sections = load_dwarf_sections(&object_file)?;
+    let dwarf = sections.borrow(|section| gimli::EndianSlice::new(section, gimli::LittleEndian));
+    let mut sites = Vec::new();
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        let unit = dwarf.unit(header)?;
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs()? {
+            if !matches!(
+                entry.tag(),
+                gimli::DW_TAG_call_site | gimli::DW_TAG_GNU_call_site
+            ) {
+                continue;
+            }
+            let return_address = match entry
+                .attr_value(gimli::DW_AT_call_return_pc)?
+                .or(entry.attr_value(gimli::DW_AT_low_pc)?)
+            {
+                Some(value) => dwarf.attr_address(&unit, value)?,
+                None => None,
+            };
+            let Some(return_address) = return_address.filter(|&address| address != 0) else {
+                continue;
+            };
+            let origin = entry
+                .attr_value(gimli::DW_AT_call_origin)?
+                .or(entry.attr_value(gimli::DW_AT_abstract_origin)?);
+            let callee = match origin {
+                Some(origin) => callee_name(&dwarf, &unit, origin)?,
+                None => None,
+            };
+            let tail_call = entry.attr(gimli::DW_AT_call_tail_call)?.is_some()
+                || entry.attr(gimli::DW_AT_GNU_tail_call)?.is_some();
+            sites.push(CallSite {
+                return_address,
+                callee,
+                tail_call,
+            });
+        }
+    }
+    Ok(sites)
+}
+
+/// Demangled linkage name or DWARF name of the subprogram `origin` refers to
+fn callee_name(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    origin: gimli::AttributeValue<Reader>,
+) -> Result<Option<String>> {
+    let offset = match origin {
+        gimli::AttributeValue::UnitRef(offset) => offset,
+        gimli::AttributeValue::DebugInfoRef(offset) => match offset.to_unit_offset(&unit.header) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    let entry = unit.entry(offset)?;
+    if let Some(linkage_name) = get_die_linkage_name(dwarf, unit, &entry)? {
+        return Ok(Some(demangle_symbol(&linkage_name).unwrap_or(linkage_name)));
+    }
+    get_die_name(dwarf, unit, &entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(caller: &str, callee: &str) -> CallEdge {
+        CallEdge {
+            caller: caller.to_string(),
+            callee: callee.to_string(),
+            kind: "call".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_reachable_and_callers() {
+        let graph = CallGraph {
+            edges: vec![
+                edge("main", "parse"),
+                edge("parse", "next"),
+                edge("next", "parse"),
+                edge("init", "next"),
+            ],
+            ..Default::default()
+        };
+        let reachable = graph.reachable_from(&["main"]);
+        assert_eq!(reachable.len(), 3);
+        assert!(!reachable.contains("init"));
+
+        let callers = graph.callers_of(&["next"]);
+        assert_eq!(callers.len(), 4);
+        assert_eq!(graph.callees("parse"), ["next"]);
+    }
+
+    #[test]
+    fn test_unique_names_and_dot() {
+        let functions = vec![
+            ("helper".to_string(), 0x1000, 16),
+            ("main".to_string(), 0x1010, 32),
+            ("helper".to_string(), 0x1030, 16),
+        ];
+        let names = unique_names(&functions);
+        assert_eq!(names, ["helper@0x1000", "main", "helper@0x1030"]);
+
+        let graph = CallGraph {
+            entry: Some("main".to_string()),
+            functions: names
+                .iter()
+                .map(|name| CallGraphFunction {
+                    name: name.clone(),
+                    address: Some(0),
+                    size: 0,
+                })
+                .collect(),
+            edges: vec![edge("main", "helper@0x1000")],
+            indirect_calls: vec![IndirectCall {
+                caller: "main".to_string(),
+                ..Default::default()
+            }],
+        };
+        assert_eq!(graph.resolve("helper").len(), 2);
+        let dot = graph.to_dot();
+        assert!(dot.contains("\"main\" -> \"helper@0x1000\";"));
+        assert!(dot.contains("1 indirect call\""));
+    }
+}
@@ -0,0 +1,233 @@
+//! CFI coverage: which function symbols have an FDE in `.eh_frame` or
+//! `.debug_frame`, the unwind rules of each, and FDEs whose range does not
+//! match the symbol size or the DWARF `low_pc`/`high_pc` of the function
+//!
+//! Code without CFI, typically hand-written assembly, cannot be unwound
+//! through by debuggers, profilers or C++ exceptions.
+
+use crate::analyze_elf_from_bytes_with_dwarf;
+use crate::unwind::{FunctionUnwind, UnwindRow, Unwinder};
+use anyhow::{Context, Result};
+use goblin::elf::header::EM_ARM;
+use goblin::elf::sym::{STT_FUNC, STT_GNU_IFUNC};
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CfiReport {
+    pub functions: Vec<FunctionCfi>,
+    /// Functions with an FDE
+    pub covered: usize,
+    pub uncovered: usize,
+    /// Covered functions whose FDE range disagrees with the symbol or DWARF
+    pub mismatched: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FunctionCfi {
+    pub name: String,
+    pub address: u64,
+    /// 0 when the symbol has no `.size`, as is common in assembly
+    pub size: u64,
+    /// Sections with an FDE covering the start of the function
+    pub sections: Vec<String>,
+    pub fde_start: Option<u64>,
+    pub fde_end: Option<u64>,
+    /// Code range of the function's DWARF subprogram
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dwarf_low_pc: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dwarf_high_pc: Option<u64>,
+    /// How the FDE range disagrees with the symbol and DWARF ranges
+    pub mismatches: Vec<String>,
+    /// CFA and register rules of the FDE at each PC range
+    pub rows: Vec<UnwindRow>,
+}
+
+impl FunctionCfi {
+    pub fn covered(&self) -> bool {
+        !self.sections.is_empty()
+    }
+}
+
+impl CfiReport {
+    /// Keep only functions without CFI or with mismatched FDE ranges
+    pub fn retain_issues(&mut self) {
+        self.functions
+            .retain(|function| !function.covered() || !function.mismatches.is_empty());
+    }
+}
+
+/// Report CFI coverage of every function symbol in the ELF file at `path`
+pub fn cfi_coverage_files(path: &str) -> Result<CfiReport> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    cfi_coverage_from_bytes(&buffer)
+}
+
+/// Report CFI coverage from an ELF byte buffer (WebAssembly-compatible)
+pub fn cfi_coverage_from_bytes(buffer: &[u8]) -> Result<CfiReport> {
+    let elf = match Object::parse(buffer)? {
+        Object::Elf(elf) => elf,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+    let tables = Unwinder::new(buffer)?.tables();
+    // Out-of-line subprograms only; declarations and inlined instances have no address
+    let dwarf_ranges: HashMap<u64, u64> = analyze_elf_from_bytes_with_dwarf(buffer)?
+        .functions
+        .iter()
+        .filter(|function| function.address != 0)
+        .filter_map(|function| {
+            let high_pc = function.address.checked_add(function.size?)?;
+            Some((function.address, high_pc))
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    let mut report = CfiReport::default();
+    for (symtab, strtab) in [(&elf.syms, &elf.strtab), (&elf.dynsyms, &elf.dynstrtab)] {
+        for symbol in symtab.iter() {
+            if symbol.st_shndx == 0
+                || symbol.st_value == 0
+                || !matches!(symbol.st_type(), STT_FUNC | STT_GNU_IFUNC)
+            {
+                continue;
+            }
+            let Some(name) = strtab.get_at(symbol.st_name).filter(|n| !n.is_empty()) else {
+                continue;
+            };
+            if !seen.insert((symbol.st_value, name)) {
+                continue;
+            }
+            let mut function = FunctionCfi {
+                name: name.to_string(),
+                // Thumb function symbols have the low bit set, unlike FDEs and DWARF ranges
+                address: match elf.header.e_machine {
+                    EM_ARM => symbol.st_value & !1,
+                    _ => symbol.st_value,
+                },
+                size: symbol.st_size,
+                ..Default::default()
+            };
+            let fdes = covering(&tables, function.address);
+            if let Some(fde) = fdes.first() {
+                function.sections = fdes.iter().map(|fde| fde.section.clone()).collect();
+                function.fde_start = Some(fde.start);
+                function.fde_end = Some(fde.end);
+                function.rows = fde.rows.clone();
+            }
+            if let Some(&high_pc) = dwarf_ranges.get(&function.address) {
+                function.dwarf_low_pc = Some(function.address);
+                function.dwarf_high_pc = Some(high_pc);
+            }
+            function.mismatches = mismatches(&function);
+            report.functions.push(function);
+        }
+    }
+    report
+        .functions
+        .sort_by(|a, b| a.address.cmp(&b.address).then(a.name.cmp(&b.name)));
+
+    report.covered = report.functions.iter().filter(|f| f.covered()).count();
+    report.uncovered = report.functions.len() - report.covered;
+    report.mismatched = report
+        .functions
+        .iter()
+        .filter(|f| !f.mismatches.is_empty())
+        .count();
+    Ok(report)
+}
+
+/// FDEs whose range contains `address`, `.eh_frame` first
+fn covering(tables: &[FunctionUnwind], address: u64) -> Vec<&FunctionUnwind> {
+    let end = tables.partition_point(|table| table.start <= address);
+    let mut fdes: Vec<&FunctionUnwind> = tables[..end]
+        .iter()
+        .filter(|table| address < table.end)
+        .collect();
+    fdes.sort_by_key(|table| table.section != ".eh_frame");
+    fdes
+}
+
+fn mismatches(function: &FunctionCfi) -> Vec<String> {
+    let (Some(fde_start), Some(fde_end)) = (function.fde_start, function.fde_end) else {
+        return Vec::new();
+    };
+    let mut mismatches = Vec::new();
+    if fde_start != function.address {
+        mismatches.push(format!(
+            "FDE starts at {fde_start:#x}, {} bytes before the symbol",
+            function.address - fde_start
+        ));
+    }
+    let symbol_end = function.address.saturating_add(function.size);
+    if function.size > 0 && fde_end != symbol_end {
+        mismatches.push(format!(
+            "FDE ends at {fde_end:#x}, the symbol at {symbol_end:#x}"
+        ));
+    }
+    if let Some(high_pc) = function.dwarf_high_pc {
+        if fde_end != high_pc {
+            mismatches.push(format!(
+                "FDE ends at {fde_end:#x}, DWARF high_pc is {high_pc:#x}"
+            ));
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fde(start: u64, end: u64, section: &str) -> FunctionUnwind {
+        FunctionUnwind {
+            start,
+            end,
+            section: section.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_covering_prefers_eh_frame() {
+        let tables = vec![
+            fde(0x1000, 0x1040, ".debug_frame"),
+            fde(0x1000, 0x1040, ".eh_frame"),
+            fde(0x1040, 0x1080, ".eh_frame"),
+        ];
+        let fdes = covering(&tables, 0x1010);
+        assert_eq!(fdes.len(), 2);
+        assert_eq!(fdes[0].section, ".eh_frame");
+        assert!(covering(&tables, 0x1080).is_empty());
+    }
+
+    #[test]
+    fn test_mismatches() {
+        let mut function = FunctionCfi {
+            address: 0x1000,
+            size: 0x40,
+            fde_start: Some(0x1000),
+            fde_end: Some(0x1040),
+            dwarf_high_pc: Some(0x1040),
+            ..Default::default()
+        };
+        assert!(mismatches(&function).is_empty());
+
+        function.size = 0x48;
+        function.dwarf_high_pc = Some(0x1048);
+        let found = mismatches(&function);
+        assert_eq!(found.len(), 2);
+        assert!(found[0].contains("the symbol at 0x1048"));
+
+        // Without a `.size` only DWARF can disagree
+        function.size = 0;
+        function.dwarf_high_pc = None;
+        assert!(mismatches(&function).is_empty());
+
+        // A corrupt symbol size does not overflow
+        function.size = u64::MAX;
+        assert_eq!(mismatches(&function).len(), 1);
+    }
+}
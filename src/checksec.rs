@@ -0,0 +1,476 @@
+//! Hardening audit in the manner of `checksec`: PIE, RELRO, non-executable
+//! stack, stack canaries, FORTIFY_SOURCE, CET/BTI/PAC from GNU property
+//! notes, RPATH and writable executable segments, each passing, warning or
+//! failing
+//!
+//! Everything is read from the ELF structure: program headers, the dynamic
+//! section, symbols and notes. Symbols only show that a mitigation was
+//! used somewhere, not that every function has it.
+
+use anyhow::{Context, Result};
+use goblin::elf::dynamic::{DF_1_NOW, DF_1_PIE, DF_BIND_NOW, DT_BIND_NOW};
+use goblin::elf::header::{EM_386, EM_AARCH64, EM_X86_64, ET_DYN, ET_EXEC, ET_REL};
+use goblin::elf::program_header::{PF_W, PF_X, PT_GNU_RELRO, PT_GNU_STACK, PT_LOAD};
+use goblin::elf::Elf;
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+
+const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc000_0000;
+const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc000_0002;
+
+/// Functions glibc has a `__<name>_chk` variant of under `_FORTIFY_SOURCE`
+const FORTIFIABLE: &[&str] = &[
+    "confstr",
+    "fgets",
+    "fgetws",
+    "fprintf",
+    "fread",
+    "fwprintf",
+    "getcwd",
+    "getgroups",
+    "gethostname",
+    "getlogin_r",
+    "gets",
+    "getwd",
+    "mbsnrtowcs",
+    "mbsrtowcs",
+    "mbstowcs",
+    "memcpy",
+    "memmove",
+    "mempcpy",
+    "memset",
+    "pread",
+    "pread64",
+    "printf",
+    "read",
+    "readlink",
+    "readlinkat",
+    "realpath",
+    "recv",
+    "recvfrom",
+    "snprintf",
+    "sprintf",
+    "stpcpy",
+    "stpncpy",
+    "strcat",
+    "strcpy",
+    "strncat",
+    "strncpy",
+    "swprintf",
+    "syslog",
+    "ttyname_r",
+    "vfprintf",
+    "vfwprintf",
+    "vprintf",
+    "vsnprintf",
+    "vsprintf",
+    "vswprintf",
+    "vsyslog",
+    "vwprintf",
+    "wcrtomb",
+    "wcscat",
+    "wcscpy",
+    "wcsncat",
+    "wcsncpy",
+    "wcsnrtombs",
+    "wcsrtombs",
+    "wcstombs",
+    "wctomb",
+    "wmemcpy",
+    "wmemmove",
+    "wmempcpy",
+    "wmemset",
+    "wprintf",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ChecksecReport {
+    pub checks: Vec<SecurityCheck>,
+    pub passed: usize,
+    pub warnings: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SecurityCheck {
+    pub name: String, // "pie", "relro", "nx", "canary", "fortify", "cet", "bti", "pac", "rpath", "wx_segments"
+    pub status: String, // "pass", "warn", "fail"
+    pub detail: String,
+}
+
+impl ChecksecReport {
+    /// Status of the check named `name`, if it applies to the file
+    pub fn status(&self, name: &str) -> Option<&str> {
+        self.checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| check.status.as_str())
+    }
+
+    fn push(&mut self, name: &str, status: &str, detail: impl Into<String>) {
+        match status {
+            "pass" => self.passed += 1,
+            "warn" => self.warnings += 1,
+            _ => self.failed += 1,
+        }
+        self.checks.push(SecurityCheck {
+            name: name.to_string(),
+            status: status.to_string(),
+            detail: detail.into(),
+        });
+    }
+}
+
+/// Audit the hardening of the ELF file at `path`
+pub fn checksec_files(path: &str) -> Result<ChecksecReport> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    checksec_from_bytes(&buffer)
+}
+
+/// Audit the hardening of an ELF byte buffer (WebAssembly-compatible)
+pub fn checksec_from_bytes(buffer: &[u8]) -> Result<ChecksecReport> {
+    let elf = match Object::parse(buffer)? {
+        Object::Elf(elf) => elf,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+    let mut report = ChecksecReport::default();
+    let (flags, flags_1) = elf
+        .dynamic
+        .as_ref()
+        .map_or((0, 0), |dynamic| (dynamic.info.flags, dynamic.info.flags_1));
+    let segment = |kind| {
+        elf.program_headers
+            .iter()
+            .find(|header| header.p_type == kind)
+    };
+
+    match elf.header.e_type {
+        ET_EXEC => report.push("pie", "fail", "Not PIE: loaded at a fixed address"),
+        ET_DYN if elf.interpreter.is_some() || flags_1 & DF_1_PIE != 0 => {
+            report.push("pie", "pass", "PIE")
+        }
+        ET_DYN => report.push("pie", "pass", "Shared object"),
+        ET_REL => report.push("pie", "warn", "Relocatable object: decided when linking"),
+        _ => report.push("pie", "fail", "Not an executable or shared object"),
+    }
+
+    let bind_now = flags & DF_BIND_NOW != 0
+        || flags_1 & DF_1_NOW != 0
+        || elf
+            .dynamic
+            .as_ref()
+            .is_some_and(|dynamic| dynamic.dyns.iter().any(|entry| entry.d_tag == DT_BIND_NOW));
+    match (segment(PT_GNU_RELRO), &elf.dynamic) {
+        (None, _) => report.push("relro", "fail", "No RELRO"),
+        (Some(_), None) => report.push("relro", "pass", "Full RELRO (statically linked)"),
+        (Some(_), Some(_)) if bind_now => report.push("relro", "pass", "Full RELRO"),
+        (Some(_), Some(_)) => report.push(
+            "relro",
+            "warn",
+            "Partial RELRO: without BIND_NOW the GOT of lazily bound functions stays writable",
+        ),
+    }
+
+    match segment(PT_GNU_STACK) {
+        Some(header) if header.p_flags & PF_X != 0 => {
+            report.push("nx", "fail", "PT_GNU_STACK makes the stack executable")
+        }
+        Some(_) => report.push("nx", "pass", "Non-executable stack"),
+        None => report.push(
+            "nx",
+            "warn",
+            "No PT_GNU_STACK: the kernel decides whether the stack is executable",
+        ),
+    }
+
+    let symbols = symbol_names(&elf);
+    let canaries: Vec<&str> = [
+        "__stack_chk_fail",
+        "__stack_chk_fail_local",
+        "__stack_chk_guard",
+    ]
+    .into_iter()
+    .filter(|name| symbols.defined.contains(name) || symbols.imported.contains(name))
+    .collect();
+    if canaries.is_empty() {
+        report.push(
+            "canary",
+            "warn",
+            "No stack protector symbols: built without -fstack-protector, or no function needed a canary",
+        );
+    } else {
+        report.push("canary", "pass", format!("Uses {}", canaries.join(", ")));
+    }
+
+    let fortified: BTreeSet<&str> = symbols
+        .defined
+        .iter()
+        .chain(&symbols.imported)
+        .filter_map(|name| name.strip_prefix("__")?.strip_suffix("_chk"))
+        .filter(|name| FORTIFIABLE.contains(name))
+        .collect();
+    let unfortified: Vec<&str> = FORTIFIABLE
+        .iter()
+        .copied()
+        .filter(|name| symbols.imported.contains(name) && !fortified.contains(name))
+        .collect();
+    match (fortified.is_empty(), unfortified.is_empty()) {
+        (true, true) => report.push("fortify", "pass", "No fortifiable functions called"),
+        (true, false) => report.push(
+            "fortify",
+            "warn",
+            format!(
+                "Not fortified: calls {} without _chk variants",
+                unfortified.join(", ")
+            ),
+        ),
+        (false, _) => {
+            let mut detail = format!(
+                "{} fortified: {}",
+                fortified.len(),
+                fortified.iter().copied().collect::<Vec<_>>().join(", ")
+            );
+            // Partially fortified, as checksec counts it
+            let status = if unfortified.is_empty() {
+                "pass"
+            } else {
+                detail.push_str(&format!(
+                    "; {} unfortified: {}",
+                    unfortified.len(),
+                    unfortified.join(", ")
+                ));
+                "warn"
+            };
+            report.push("fortify", status, detail);
+        }
+    }
+
+    let features = gnu_properties(&elf, buffer);
+    match elf.header.e_machine {
+        EM_X86_64 | EM_386 => {
+            let bits = features.get(&GNU_PROPERTY_X86_FEATURE_1_AND);
+            let ibt = bits.is_some_and(|bits| bits & 1 != 0);
+            let shstk = bits.is_some_and(|bits| bits & 2 != 0);
+            match (ibt, shstk) {
+                (true, true) => report.push("cet", "pass", "IBT and SHSTK"),
+                (true, false) => report.push("cet", "warn", "IBT only, no shadow stack"),
+                (false, true) => {
+                    report.push("cet", "warn", "SHSTK only, no indirect branch tracking")
+                }
+                (false, false) => report.push(
+                    "cet",
+                    "fail",
+                    "No IBT or SHSTK property: built without -fcf-protection",
+                ),
+            }
+        }
+        EM_AARCH64 => {
+            let bits = features
+                .get(&GNU_PROPERTY_AARCH64_FEATURE_1_AND)
+                .copied()
+                .unwrap_or(0);
+            if bits & 1 != 0 {
+                report.push("bti", "pass", "Branch target identification");
+            } else {
+                report.push(
+                    "bti",
+                    "fail",
+                    "No BTI property: built without -mbranch-protection=bti",
+                );
+            }
+            if bits & 2 != 0 {
+                report.push("pac", "pass", "Pointer authentication of return addresses");
+            } else {
+                report.push(
+                    "pac",
+                    "fail",
+                    "No PAC property: built without -mbranch-protection=pac-ret",
+                );
+            }
+        }
+        _ => {}
+    }
+
+    let paths: Vec<(&str, &str)> = elf
+        .rpaths
+        .iter()
+        .map(|path| ("RPATH", *path))
+        .chain(elf.runpaths.iter().map(|path| ("RUNPATH", *path)))
+        .collect();
+    let insecure: Vec<&str> = paths
+        .iter()
+        .flat_map(|(_, path)| path.split(':'))
+        .filter(|entry| !search_path_is_safe(entry))
+        .collect();
+    let described: Vec<String> = paths
+        .iter()
+        .map(|(kind, path)| format!("{kind} {path}"))
+        .collect();
+    if paths.is_empty() {
+        report.push("rpath", "pass", "No RPATH or RUNPATH");
+    } else if !insecure.is_empty() {
+        report.push(
+            "rpath",
+            "fail",
+            format!(
+                "{}: relative to the working directory: {:?}",
+                described.join(", "),
+                insecure
+            ),
+        );
+    } else {
+        report.push("rpath", "warn", described.join(", "));
+    }
+
+    let writable_code: Vec<String> = elf
+        .program_headers
+        .iter()
+        .filter(|header| {
+            header.p_type == PT_LOAD && header.p_flags & PF_W != 0 && header.p_flags & PF_X != 0
+        })
+        .map(|header| format!("{:#x}", header.p_vaddr))
+        .collect();
+    if writable_code.is_empty() {
+        report.push("wx_segments", "pass", "No writable and executable segment");
+    } else {
+        report.push(
+            "wx_segments",
+            "fail",
+            format!(
+                "Writable and executable LOAD segments at {}",
+                writable_code.join(", ")
+            ),
+        );
+    }
+    Ok(report)
+}
+
+pub(crate) struct SymbolNames<'a> {
+    pub(crate) defined: BTreeSet<&'a str>,
+    /// Undefined symbols, resolved in other objects at load time
+    pub(crate) imported: BTreeSet<&'a str>,
+}
+
+/// Names of the symbols of both symbol tables, without version suffixes
+pub(crate) fn symbol_names<'a>(elf: &Elf<'a>) -> SymbolNames<'a> {
+    let mut names = SymbolNames {
+        defined: BTreeSet::new(),
+        imported: BTreeSet::new(),
+    };
+    for (symtab, strtab) in [(&elf.syms, &elf.strtab), (&elf.dynsyms, &elf.dynstrtab)] {
+        for symbol in symtab.iter() {
+            let Some(name) = strtab
+                .get_at(symbol.st_name)
+                .filter(|name| !name.is_empty())
+            else {
+                continue;
+            };
+            // Versioned names in the static symbol table, e.g. `printf@GLIBC_2.2.5`
+            let name = name.split('@').next().unwrap_or(name);
+            if symbol.st_shndx == 0 {
+                names.imported.insert(name);
+            } else {
+                names.defined.insert(name);
+            }
+        }
+    }
+    names
+}
+
+/// Whether a search path entry is absolute or relative to the object
+/// itself rather than to the working directory
+fn search_path_is_safe(entry: &str) -> bool {
+    entry.starts_with('/') || entry.starts_with("$ORIGIN") || entry.starts_with("${ORIGIN}")
+}
+
+/// 4-byte values of the GNU property notes, such as the `*_FEATURE_1_AND`
+/// bits, by property type
+fn gnu_properties(elf: &Elf, buffer: &[u8]) -> HashMap<u32, u32> {
+    let mut properties = HashMap::new();
+    let notes = elf
+        .iter_note_headers(buffer)
+        .or_else(|| elf.iter_note_sections(buffer, Some(".note.gnu.property")));
+    for note in notes.into_iter().flatten() {
+        let Ok(note) = note else {
+            break;
+        };
+        if note.name == "GNU" && note.n_type == NT_GNU_PROPERTY_TYPE_0 {
+            properties.extend(parse_gnu_properties(
+                note.desc,
+                if elf.is_64 { 8 } else { 4 },
+                elf.little_endian,
+            ));
+        }
+    }
+    properties
+}
+
+/// Properties of an `NT_GNU_PROPERTY_TYPE_0` note: type, size and data,
+/// each padded to the word size; only 4-byte values are kept
+fn parse_gnu_properties(desc: &[u8], align: usize, little_endian: bool) -> Vec<(u32, u32)> {
+    let word = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = desc.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+    let mut properties = Vec::new();
+    let mut offset = 0;
+    while let (Some(kind), Some(size)) = (word(offset), word(offset + 4)) {
+        let size = size as usize;
+        // A property running past the end of the note is corrupt
+        if size > desc.len() - offset - 8 {
+            break;
+        }
+        if size == 4 {
+            if let Some(value) = word(offset + 8) {
+                properties.push((kind, value));
+            }
+        }
+        offset += 8 + size.div_ceil(align) * align;
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gnu_properties() {
+        let mut desc = Vec::new();
+        // x86 ISA needed: 4 bytes padded to 8
+        desc.extend_from_slice(&0xc000_8002u32.to_le_bytes());
+        desc.extend_from_slice(&4u32.to_le_bytes());
+        desc.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        // IBT | SHSTK
+        desc.extend_from_slice(&GNU_PROPERTY_X86_FEATURE_1_AND.to_le_bytes());
+        desc.extend_from_slice(&4u32.to_le_bytes());
+        desc.extend_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0]);
+        let properties = parse_gnu_properties(&desc, 8, true);
+        assert_eq!(
+            properties,
+            vec![(0xc000_8002, 1), (GNU_PROPERTY_X86_FEATURE_1_AND, 3)]
+        );
+        // Truncated
+        assert!(parse_gnu_properties(&desc[..6], 8, true).is_empty());
+        // A size past the end of the note stops parsing instead of overflowing
+        desc[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(parse_gnu_properties(&desc, 8, true), vec![(0xc000_8002, 1)]);
+    }
+
+    #[test]
+    fn test_search_path_is_safe() {
+        assert!(search_path_is_safe("/opt/lib"));
+        assert!(search_path_is_safe("$ORIGIN/../lib"));
+        assert!(search_path_is_safe("${ORIGIN}"));
+        assert!(!search_path_is_safe("lib"));
+        assert!(!search_path_is_safe(""));
+        assert!(!search_path_is_safe("./lib"));
+    }
+}
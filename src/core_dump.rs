@@ -0,0 +1,519 @@
+//! Core dump analysis: the process and its threads with their registers from
+//! the `NT_PRPSINFO` and `NT_PRSTATUS` notes, mapped files from `NT_FILE`,
+//! the auxiliary vector from `NT_AUXV`, and the values of globals read from
+//! the dumped memory using the DWARF types of the matching executable
+
+use crate::unwind::{Backtrace, Unwinder};
+use crate::values::{global_values, GlobalValue, Image};
+use crate::{analyze_elf_from_bytes_basic, analyze_elf_from_bytes_with_dwarf};
+use anyhow::{Context, Result};
+use goblin::elf::header::{EM_386, EM_AARCH64, EM_ARM, EM_RISCV, EM_X86_64};
+use goblin::elf::note::{NT_FILE, NT_PRPSINFO, NT_PRSTATUS};
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const NT_AUXV: u32 = 6;
+const AT_NULL: u64 = 0;
+const AT_PLATFORM: u64 = 15;
+const AT_ENTRY: u64 = 9;
+const AT_EXECFN: u64 = 31;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CoreReport {
+    pub architecture: String,
+    /// Executable the core was produced by, according to its mapped files
+    pub executable: Option<String>,
+    /// Offset the executable was loaded at above its link addresses
+    pub load_bias: u64,
+    pub process: Option<ProcessInfo>,
+    /// Signal that terminated the process
+    pub signal: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub signal_name: String,
+    /// The thread that received the signal comes first
+    pub threads: Vec<ThreadInfo>,
+    pub mapped_files: Vec<MappedFile>,
+    pub auxv: Vec<AuxvEntry>,
+    /// Globals of the executable as they were in memory when the core was written
+    pub variables: Vec<GlobalValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ProcessInfo {
+    pub pid: i32,
+    pub ppid: i32,
+    pub pgrp: i32,
+    pub sid: i32,
+    pub uid: u32,
+    pub gid: u32,
+    /// One-letter state as in `ps`: R, S, D, T, Z
+    pub state: String,
+    /// Command name, truncated to 15 bytes by the kernel
+    pub name: String,
+    /// Command line, truncated to 80 bytes by the kernel
+    pub args: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ThreadInfo {
+    pub tid: i32,
+    /// Signal pending on this thread when the core was written
+    pub signal: i32,
+    pub pc: u64,
+    pub sp: u64,
+    /// Symbol containing the program counter, with the offset into it
+    pub function: Option<String>,
+    pub registers: Vec<RegisterValue>,
+    /// Call stack recovered from the call frame information of the executable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<Backtrace>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RegisterValue {
+    pub name: String,
+    pub value: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MappedFile {
+    pub start: u64,
+    pub end: u64,
+    /// Offset into the file in bytes
+    pub offset: u64,
+    pub path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AuxvEntry {
+    /// `AT_*` name, or the number of an unknown entry
+    pub key: String,
+    pub value: u64,
+    /// The string an `AT_EXECFN` or `AT_PLATFORM` entry points to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+/// Analyze the core dump at `core_path` with the executable that produced it
+pub fn analyze_core_files(core_path: &str, executable_path: &str) -> Result<CoreReport> {
+    let core =
+        fs::read(core_path).with_context(|| format!("Failed to read ELF file: {core_path}"))?;
+    let executable = fs::read(executable_path)
+        .with_context(|| format!("Failed to read ELF file: {executable_path}"))?;
+    let mut report = analyze_core_from_bytes(&core, &executable)?;
+
+    let name = executable_path
+        .rsplit('/')
+        .next()
+        .unwrap_or(executable_path);
+    if let Some(path) = &report.executable {
+        if path.rsplit('/').next() != Some(name) {
+            report.warnings.push(format!(
+                "core was produced by {path}, not {executable_path}"
+            ));
+        }
+    }
+    Ok(report)
+}
+
+/// Analyze a core dump and its executable in memory (WebAssembly-compatible)
+pub fn analyze_core_from_bytes(core: &[u8], executable: &[u8]) -> Result<CoreReport> {
+    let core_info = analyze_elf_from_bytes_basic(core)?;
+    if core_info.file_type != "core_dump" {
+        anyhow::bail!("File is not a core dump");
+    }
+    let elf = match Object::parse(core)? {
+        Object::Elf(elf) => elf,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+    let notes = Notes {
+        little_endian: elf.little_endian,
+        word: if elf.is_64 { 8 } else { 4 },
+        machine: elf.header.e_machine,
+    };
+
+    let mut report = CoreReport {
+        architecture: core_info.architecture,
+        ..Default::default()
+    };
+    for note in elf.iter_note_headers(core).into_iter().flatten() {
+        let Ok(note) = note else {
+            report.warnings.push("malformed note".to_string());
+            break;
+        };
+        if note.name != "CORE" {
+            continue;
+        }
+        match note.n_type {
+            NT_PRSTATUS => report.threads.push(notes.thread(note.desc)),
+            NT_PRPSINFO => report.process = Some(notes.process(note.desc)),
+            NT_FILE => report.mapped_files = notes.mapped_files(note.desc),
+            NT_AUXV => report.auxv = notes.auxv(note.desc),
+            _ => {}
+        }
+    }
+    if let Some(thread) = report.threads.first() {
+        report.signal = thread.signal;
+        report.signal_name = signal_name(thread.signal).to_string();
+    }
+
+    // Position-independent executables are loaded above their link address
+    let executable_entry = match Object::parse(executable)? {
+        Object::Elf(executable) => executable.header.e_entry,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+    let entry = report
+        .auxv
+        .iter()
+        .find(|entry| entry.key == "AT_ENTRY")
+        .map(|entry| entry.value);
+    match entry {
+        Some(entry) => report.load_bias = entry.wrapping_sub(executable_entry),
+        None => report
+            .warnings
+            .push("no AT_ENTRY in the auxiliary vector; assuming no load bias".to_string()),
+    }
+    report.executable = entry.and_then(|entry| {
+        report
+            .mapped_files
+            .iter()
+            .find(|file| file.start <= entry && entry < file.end)
+            .map(|file| file.path.clone())
+    });
+
+    let image = Image::from_core(core, executable, report.load_bias)?;
+    for entry in &mut report.auxv {
+        if matches!(entry.key.as_str(), "AT_EXECFN" | "AT_PLATFORM") {
+            entry.text = read_string(&image, entry.value);
+        }
+    }
+    let unwinder = Unwinder::new(executable)?;
+    for thread in &mut report.threads {
+        thread.function = image.describe(thread.pc);
+        match unwinder.backtrace(&image, &thread.registers) {
+            Ok(mut backtrace) => {
+                for frame in &mut backtrace.frames {
+                    if frame.function.is_none() {
+                        frame.module = mapped_file(&report.mapped_files, frame.pc);
+                    }
+                }
+                thread.backtrace = Some(backtrace);
+            }
+            Err(e) => report.warnings.push(format!("thread {}: {e}", thread.tid)),
+        }
+    }
+    let elf_info = analyze_elf_from_bytes_with_dwarf(executable)?;
+    report.variables = global_values(&elf_info, &image);
+    Ok(report)
+}
+
+/// `path + offset` of a code address in a mapped file, the offset being into the file
+fn mapped_file(files: &[MappedFile], address: u64) -> Option<String> {
+    let file = files
+        .iter()
+        .find(|file| file.start <= address && address < file.end)?;
+    let offset = (address - file.start).checked_add(file.offset)?;
+    Some(format!("{} + {offset:#x}", file.path))
+}
+
+/// Decoder for the `CORE` notes of one word size, byte order and machine
+struct Notes {
+    little_endian: bool,
+    word: usize,
+    machine: u16,
+}
+
+impl Notes {
+    fn uint(&self, data: &[u8], offset: usize, size: usize) -> u64 {
+        let Some(bytes) = data.get(offset..offset + size) else {
+            return 0;
+        };
+        let mut value = 0u64;
+        for i in 0..size {
+            let byte = if self.little_endian {
+                bytes[size - 1 - i]
+            } else {
+                bytes[i]
+            };
+            value = value << 8 | u64::from(byte);
+        }
+        value
+    }
+
+    fn int(&self, data: &[u8], offset: usize) -> i32 {
+        self.uint(data, offset, 4) as i32
+    }
+
+    fn word(&self, data: &[u8], offset: usize) -> u64 {
+        self.uint(data, offset, self.word)
+    }
+
+    /// `struct elf_prstatus`: signal information, ids, times, then the general registers
+    fn thread(&self, data: &[u8]) -> ThreadInfo {
+        let cursig = self.uint(data, 12, 2) as i16;
+        // pr_sigpend and pr_sighold are words after the 12-byte siginfo and pr_cursig
+        let ids = 16 + 2 * self.word;
+        // pr_utime, pr_stime, pr_cutime and pr_cstime are pairs of words
+        let registers_offset = ids + 16 + 8 * self.word;
+
+        let names = register_names(self.machine);
+        let registers: Vec<RegisterValue> = names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| RegisterValue {
+                name: name.to_string(),
+                value: self.word(data, registers_offset + index * self.word),
+            })
+            .collect();
+        let (pc, sp) = match self.machine {
+            EM_X86_64 => ("rip", "rsp"),
+            EM_386 => ("eip", "esp"),
+            _ => ("pc", "sp"),
+        };
+        let register = |name: &str| {
+            registers
+                .iter()
+                .find(|register| register.name == name)
+                .map_or(0, |register| register.value)
+        };
+        let (pc, sp) = (register(pc), register(sp));
+        ThreadInfo {
+            tid: self.int(data, ids),
+            signal: i32::from(cursig),
+            pc,
+            sp,
+            function: None,
+            registers,
+            backtrace: None,
+        }
+    }
+
+    /// `struct elf_prpsinfo`
+    fn process(&self, data: &[u8]) -> ProcessInfo {
+        // 32-bit x86 and Arm still use 16-bit uid_t in core notes
+        let (flag_end, id_size) = match (self.word, self.machine) {
+            (8, _) => (16, 4),
+            (_, EM_386 | EM_ARM) => (8, 2),
+            _ => (8, 4),
+        };
+        let ids = flag_end + 2 * id_size;
+        let string = |offset: usize, length: usize| {
+            let bytes = data.get(offset..offset + length).unwrap_or_default();
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end])
+                .trim_end()
+                .to_string()
+        };
+        ProcessInfo {
+            pid: self.int(data, ids),
+            ppid: self.int(data, ids + 4),
+            pgrp: self.int(data, ids + 8),
+            sid: self.int(data, ids + 12),
+            uid: self.uint(data, flag_end, id_size) as u32,
+            gid: self.uint(data, flag_end + id_size, id_size) as u32,
+            state: data
+                .get(1)
+                .map(|&sname| char::from(sname).to_string())
+                .unwrap_or_default(),
+            name: string(ids + 16, 16),
+            args: string(ids + 32, 80),
+        }
+    }
+
+    /// Count and page size, then start, end and page offset of each
+    /// mapping, then their NUL-terminated paths
+    fn mapped_files(&self, data: &[u8]) -> Vec<MappedFile> {
+        let count = self.word(data, 0);
+        let page_size = self.word(data, self.word);
+        // A count of more mappings than the note has room for is corrupt
+        if count > (data.len() / (3 * self.word)) as u64 {
+            return Vec::new();
+        }
+        let count = count as usize;
+        let names_offset = (2 + 3 * count) * self.word;
+        let mut names = data
+            .get(names_offset..)
+            .unwrap_or_default()
+            .split(|&b| b == 0)
+            .map(|name| String::from_utf8_lossy(name).into_owned());
+        (0..count)
+            .map_while(|index| {
+                let entry = (2 + 3 * index) * self.word;
+                Some(MappedFile {
+                    start: self.word(data, entry),
+                    end: self.word(data, entry + self.word),
+                    offset: self
+                        .word(data, entry + 2 * self.word)
+                        .checked_mul(page_size)?,
+                    path: names.next()?,
+                })
+            })
+            .collect()
+    }
+
+    fn auxv(&self, data: &[u8]) -> Vec<AuxvEntry> {
+        data.chunks_exact(2 * self.word)
+            .map(|pair| (self.word(pair, 0), self.word(pair, self.word)))
+            .take_while(|&(key, _)| key != AT_NULL)
+            .map(|(key, value)| AuxvEntry {
+                key: auxv_name(key),
+                value,
+                text: None,
+            })
+            .collect()
+    }
+}
+
+fn read_string(image: &Image, address: u64) -> Option<String> {
+    let mut bytes = Vec::new();
+    while bytes.len() < 4096 {
+        let byte = *image
+            .read(address.checked_add(bytes.len() as u64)?, 1)?
+            .first()?;
+        if byte == 0 {
+            return Some(String::from_utf8_lossy(&bytes).into_owned());
+        }
+        bytes.push(byte);
+    }
+    None
+}
+
+/// General registers in `elf_gregset_t` order
+fn register_names(machine: u16) -> Vec<String> {
+    let names: &[&str] = match machine {
+        EM_X86_64 => &[
+            "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx",
+            "rdx", "rsi", "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base",
+            "gs_base", "ds", "es", "fs", "gs",
+        ],
+        EM_386 => &[
+            "ebx", "ecx", "edx", "esi", "edi", "ebp", "eax", "ds", "es", "fs", "gs", "orig_eax",
+            "eip", "cs", "eflags", "esp", "ss",
+        ],
+        EM_AARCH64 => {
+            let mut names: Vec<String> = (0..31).map(|i| format!("x{i}")).collect();
+            names.extend(["sp", "pc", "pstate"].map(String::from));
+            return names;
+        }
+        EM_ARM => &[
+            "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "sp",
+            "lr", "pc", "cpsr", "orig_r0",
+        ],
+        EM_RISCV => &[
+            "pc", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3",
+            "a4", "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11",
+            "t3", "t4", "t5", "t6",
+        ],
+        _ => &[],
+    };
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn auxv_name(key: u64) -> String {
+    let name = match key {
+        3 => "AT_PHDR",
+        4 => "AT_PHENT",
+        5 => "AT_PHNUM",
+        6 => "AT_PAGESZ",
+        7 => "AT_BASE",
+        8 => "AT_FLAGS",
+        AT_ENTRY => "AT_ENTRY",
+        11 => "AT_UID",
+        12 => "AT_EUID",
+        13 => "AT_GID",
+        14 => "AT_EGID",
+        AT_PLATFORM => "AT_PLATFORM",
+        16 => "AT_HWCAP",
+        17 => "AT_CLKTCK",
+        23 => "AT_SECURE",
+        24 => "AT_BASE_PLATFORM",
+        25 => "AT_RANDOM",
+        26 => "AT_HWCAP2",
+        AT_EXECFN => "AT_EXECFN",
+        32 => "AT_SYSINFO",
+        33 => "AT_SYSINFO_EHDR",
+        27 => "AT_RSEQ_FEATURE_SIZE",
+        28 => "AT_RSEQ_ALIGN",
+        51 => "AT_MINSIGSTKSZ",
+        _ => return key.to_string(),
+    };
+    name.to_string()
+}
+
+fn signal_name(signal: i32) -> &'static str {
+    match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        5 => "SIGTRAP",
+        6 => "SIGABRT",
+        7 => "SIGBUS",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        10 => "SIGUSR1",
+        11 => "SIGSEGV",
+        12 => "SIGUSR2",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        24 => "SIGXCPU",
+        25 => "SIGXFSZ",
+        31 => "SIGSYS",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapped_files_note() {
+        let notes = Notes {
+            little_endian: true,
+            word: 8,
+            machine: EM_X86_64,
+        };
+        let mut data = Vec::new();
+        for word in [2u64, 4096, 0x1000, 0x2000, 0, 0x5000, 0x6000, 3] {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        data.extend_from_slice(b"/bin/app\0/lib/libc.so.6\0");
+
+        let files = notes.mapped_files(&data);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "/bin/app");
+        assert_eq!((files[0].start, files[0].end), (0x1000, 0x2000));
+        assert_eq!(files[1].path, "/lib/libc.so.6");
+        assert_eq!(files[1].offset, 3 * 4096);
+
+        // A corrupt count or page offset must not overflow
+        data[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(notes.mapped_files(&data).is_empty());
+        data[..8].copy_from_slice(&2u64.to_le_bytes());
+        data[56..64].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(notes.mapped_files(&data).len(), 1);
+    }
+
+    #[test]
+    fn test_auxv_note_stops_at_null() {
+        let notes = Notes {
+            little_endian: false,
+            word: 4,
+            machine: EM_ARM,
+        };
+        let mut data = Vec::new();
+        for word in [6u32, 4096, AT_ENTRY as u32, 0x10400, 99, 1, 0, 0, 6, 1] {
+            data.extend_from_slice(&word.to_be_bytes());
+        }
+
+        let auxv = notes.auxv(&data);
+        let keys: Vec<&str> = auxv.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, ["AT_PAGESZ", "AT_ENTRY", "99"]);
+        assert_eq!(auxv[1].value, 0x10400);
+    }
+}
@@ -0,0 +1,394 @@
+//! Dead code: functions nothing can reach from the ways a program is
+//! entered, and the bytes removing them would reclaim
+//!
+//! A function is live when the entry point is in it, it is exported as a
+//! dynamic symbol, or its address appears where something may jump to it:
+//! an init/fini array, an interrupt vector table, a relocation or pointer
+//! in data, or code not covered by a function symbol. Everything these
+//! reach along the call graph, including address-taken references, is live
+//! too; the rest is dead.
+
+use crate::callgraph::{call_graph_from_bytes, CallGraphFunction};
+use crate::disasm::Disassembler;
+use anyhow::{Context, Result};
+use goblin::elf::header::EM_ARM;
+use goblin::elf::section_header::{
+    SectionHeader, SHF_ALLOC, SHF_EXECINSTR, SHT_FINI_ARRAY, SHT_INIT_ARRAY, SHT_PREINIT_ARRAY,
+    SHT_PROGBITS,
+};
+use goblin::elf::sym::{STB_GLOBAL, STB_WEAK, STT_FUNC, STT_GNU_IFUNC};
+use goblin::elf::Elf;
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeadCodeReport {
+    /// Functions of this file considered
+    pub functions: usize,
+    /// Their total size in bytes
+    pub total_size: u64,
+    /// Functions the program can enter directly, with why
+    pub roots: Vec<RootFunction>,
+    pub dead: Vec<DeadFunction>,
+    /// Total size of the dead functions
+    pub reclaimable: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RootFunction {
+    pub name: String,
+    /// "entry", "exported", "init_fini", "vector_table", "relocation", "data_pointer", "untyped_code"
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DeadFunction {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
+    /// Dead functions calling or referencing it; empty when nothing does
+    pub referenced_by: Vec<String>,
+}
+
+/// Find the dead functions of the ELF file at `path`
+pub fn dead_code_files(path: &str) -> Result<DeadCodeReport> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    dead_code_from_bytes(&buffer)
+}
+
+/// Find the dead functions from an ELF byte buffer (WebAssembly-compatible)
+pub fn dead_code_from_bytes(buffer: &[u8]) -> Result<DeadCodeReport> {
+    let elf = match Object::parse(buffer)? {
+        Object::Elf(elf) => elf,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+    let graph = call_graph_from_bytes(buffer)?;
+    let starts: HashMap<u64, usize> = graph
+        .functions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, function)| Some((function.address?, index)))
+        .collect();
+    // Arm pointers to Thumb code have the low bit set, as do their symbols,
+    // while the call graph has the code addresses
+    let function_at = |value: u64| match elf.header.e_machine {
+        EM_ARM => starts.get(&(value & !1)).copied(),
+        _ => starts.get(&value).copied(),
+    };
+
+    let mut roots: BTreeMap<usize, BTreeSet<&str>> = BTreeMap::new();
+    if let Some(entry) = &graph.entry {
+        if let Some(index) = graph.functions.iter().position(|f| &f.name == entry) {
+            roots.entry(index).or_default().insert("entry");
+        }
+    }
+    for symbol in elf.dynsyms.iter() {
+        if symbol.st_shndx != 0
+            && matches!(symbol.st_type(), STT_FUNC | STT_GNU_IFUNC)
+            && matches!(symbol.st_bind(), STB_GLOBAL | STB_WEAK)
+        {
+            if let Some(index) = function_at(symbol.st_value) {
+                roots.entry(index).or_default().insert("exported");
+            }
+        }
+    }
+    for (place, value, relocated) in data_references(&elf, buffer) {
+        let Some(index) = function_at(value) else {
+            continue;
+        };
+        let reason = match table_kind(&elf, place) {
+            Some(kind) => kind,
+            None if relocated => "relocation",
+            None => "data_pointer",
+        };
+        roots.entry(index).or_default().insert(reason);
+    }
+    for (vectors, target) in untyped_code_targets(&elf, buffer, &graph.functions)? {
+        if let Some(index) = function_at(target) {
+            let reason = if vectors {
+                "vector_table"
+            } else {
+                "untyped_code"
+            };
+            roots.entry(index).or_default().insert(reason);
+        }
+    }
+
+    let root_names: Vec<&str> = roots
+        .keys()
+        .map(|&index| graph.functions[index].name.as_str())
+        .collect();
+    let live = graph.reachable_from(&root_names);
+
+    let mut report = DeadCodeReport::default();
+    for (index, reasons) in &roots {
+        report.roots.push(RootFunction {
+            name: graph.functions[*index].name.clone(),
+            reasons: reasons.iter().map(|reason| reason.to_string()).collect(),
+        });
+    }
+    for function in &graph.functions {
+        let Some(address) = function.address else {
+            continue;
+        };
+        report.functions += 1;
+        report.total_size += function.size;
+        if live.contains(&function.name) {
+            continue;
+        }
+        let referenced_by: BTreeSet<String> = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.callee == function.name && edge.caller != function.name)
+            .map(|edge| edge.caller.clone())
+            .collect();
+        report.reclaimable += function.size;
+        report.dead.push(DeadFunction {
+            name: function.name.clone(),
+            address,
+            size: function.size,
+            referenced_by: referenced_by.into_iter().collect(),
+        });
+    }
+    report
+        .dead
+        .sort_by(|a, b| b.size.cmp(&a.size).then(a.address.cmp(&b.address)));
+    Ok(report)
+}
+
+/// `init_fini` or `vector_table` when the allocated section containing
+/// `address` is an init/fini array or an interrupt vector table
+fn table_kind(elf: &Elf, address: u64) -> Option<&'static str> {
+    let section = elf.section_headers.iter().find(|section| {
+        section.sh_flags & u64::from(SHF_ALLOC) != 0
+            && section.sh_addr <= address
+            && address < section.sh_addr.saturating_add(section.sh_size)
+    })?;
+    let name = elf.shdr_strtab.get_at(section.sh_name).unwrap_or_default();
+    if matches!(
+        section.sh_type,
+        SHT_INIT_ARRAY | SHT_FINI_ARRAY | SHT_PREINIT_ARRAY
+    ) || matches!(name, ".ctors" | ".dtors")
+    {
+        Some("init_fini")
+    } else if name.contains("vector") {
+        Some("vector_table")
+    } else {
+        None
+    }
+}
+
+/// Addresses written into data: relocation targets outside code, and every
+/// aligned pointer-sized word of the allocated data sections, as
+/// (place, value, relocated)
+fn data_references(elf: &Elf, buffer: &[u8]) -> Vec<(u64, u64, bool)> {
+    let data_sections: Vec<&SectionHeader> = elf
+        .section_headers
+        .iter()
+        .filter(|section| {
+            let name = elf.shdr_strtab.get_at(section.sh_name).unwrap_or_default();
+            matches!(
+                section.sh_type,
+                SHT_PROGBITS | SHT_INIT_ARRAY | SHT_FINI_ARRAY | SHT_PREINIT_ARRAY
+            ) && section.sh_flags & u64::from(SHF_ALLOC) != 0
+                && section.sh_flags & u64::from(SHF_EXECINSTR) == 0
+                && !name.starts_with(".eh_frame")
+                && name != ".gcc_except_table"
+        })
+        .collect();
+    let in_data = |address: u64| {
+        data_sections.iter().any(|section| {
+            section.sh_addr <= address && address < section.sh_addr.saturating_add(section.sh_size)
+        })
+    };
+
+    let mut references = Vec::new();
+    for relocation in elf
+        .dynrelas
+        .iter()
+        .chain(elf.dynrels.iter())
+        .chain(elf.pltrelocs.iter())
+    {
+        let base = match relocation.r_sym {
+            0 => 0,
+            sym => match elf.dynsyms.get(sym) {
+                Some(symbol) if symbol.st_shndx != 0 => symbol.st_value,
+                _ => continue,
+            },
+        };
+        if in_data(relocation.r_offset) {
+            let target = base.wrapping_add_signed(relocation.r_addend.unwrap_or(0));
+            references.push((relocation.r_offset, target, true));
+        }
+    }
+    // Static relocations kept by `--emit-relocs`, and the allocated
+    // sections: the dynamic ones above against `.dynsym`, or the
+    // `IRELATIVE` relocations of a static executable naming its ifunc
+    // resolvers by address alone
+    for (index, relocations) in &elf.shdr_relocs {
+        let allocated = elf
+            .section_headers
+            .get(*index)
+            .is_none_or(|section| section.sh_flags & u64::from(SHF_ALLOC) != 0);
+        for relocation in relocations.iter() {
+            let base = match relocation.r_sym {
+                0 => 0,
+                _ if allocated => continue,
+                sym => match elf.syms.get(sym) {
+                    Some(symbol) if symbol.st_shndx != 0 => symbol.st_value,
+                    _ => continue,
+                },
+            };
+            if in_data(relocation.r_offset) {
+                let target = base.wrapping_add_signed(relocation.r_addend.unwrap_or(0));
+                references.push((relocation.r_offset, target, true));
+            }
+        }
+    }
+
+    // A relocated word holds the addend or nothing meaningful until loaded
+    let relocated: HashSet<u64> = references.iter().map(|&(place, _, _)| place).collect();
+    let pointer_size = if elf.is_64 { 8 } else { 4 };
+    for section in data_sections {
+        let Some(data) = usize::try_from(section.sh_offset)
+            .ok()
+            .zip(usize::try_from(section.sh_size).ok())
+            .and_then(|(offset, size)| buffer.get(offset..offset.checked_add(size)?))
+        else {
+            continue;
+        };
+        for (place, value) in pointer_words(data, section.sh_addr, pointer_size, elf.little_endian)
+        {
+            if !relocated.contains(&place) {
+                references.push((place, value, false));
+            }
+        }
+    }
+    references
+}
+
+/// Branch and reference targets of executable code no function symbol
+/// covers, such as startup code and vector tables written in assembly
+/// without `.type`, with whether the section is a vector table
+fn untyped_code_targets(
+    elf: &Elf,
+    buffer: &[u8],
+    functions: &[CallGraphFunction],
+) -> Result<Vec<(bool, u64)>> {
+    let mut covered: Vec<(u64, u64)> = functions
+        .iter()
+        .filter_map(|function| {
+            let start = function.address? & !1;
+            Some((start, start.saturating_add(function.size)))
+        })
+        .collect();
+    covered.sort_unstable();
+    let disassembler = Disassembler::new(buffer)?;
+    let mut targets = HashSet::new();
+    for section in &elf.section_headers {
+        if section.sh_flags & u64::from(SHF_EXECINSTR) == 0
+            || section.sh_flags & u64::from(SHF_ALLOC) == 0
+            || section.sh_type != SHT_PROGBITS
+        {
+            continue;
+        }
+        let name = elf.shdr_strtab.get_at(section.sh_name).unwrap_or_default();
+        // Stubs jumping through the GOT to imported functions
+        if name.starts_with(".plt") {
+            continue;
+        }
+        let vectors = name.contains("vector");
+        let end = section.sh_addr.saturating_add(section.sh_size);
+        let gaps = gaps(section.sh_addr, end, &covered);
+        for (start, stop) in gaps {
+            let code = disassembler.disassemble("", start, stop - start);
+            for instruction in code.instructions {
+                if let Some(target) = instruction.target {
+                    targets.insert((vectors, target));
+                }
+            }
+        }
+    }
+    Ok(targets.into_iter().collect())
+}
+
+/// Non-zero aligned pointer-sized words of `data` loaded at `address`, by place
+fn pointer_words(
+    data: &[u8],
+    address: u64,
+    pointer_size: u64,
+    little_endian: bool,
+) -> Vec<(u64, u64)> {
+    let skip = (pointer_size - address % pointer_size) % pointer_size;
+    let Some(aligned) = data.get(skip as usize..) else {
+        return Vec::new();
+    };
+    aligned
+        .chunks_exact(pointer_size as usize)
+        .enumerate()
+        .filter_map(|(i, word)| {
+            let mut bytes = [0u8; 8];
+            let value = if little_endian {
+                bytes[..word.len()].copy_from_slice(word);
+                u64::from_le_bytes(bytes)
+            } else {
+                bytes[8 - word.len()..].copy_from_slice(word);
+                u64::from_be_bytes(bytes)
+            };
+            (value != 0).then_some((address.wrapping_add(skip + i as u64 * pointer_size), value))
+        })
+        .collect()
+}
+
+/// Parts of `start..end` outside the sorted `covered` ranges
+fn gaps(start: u64, end: u64, covered: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut gaps = Vec::new();
+    let mut position = start;
+    for &(from, to) in covered
+        .iter()
+        .filter(|&&(from, to)| from < end && start < to)
+    {
+        if position < from {
+            gaps.push((position, from));
+        }
+        position = position.max(to);
+    }
+    if position < end {
+        gaps.push((position, end));
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointer_words() {
+        // Starts 2 bytes past alignment: the first word is at 0x1004
+        let data = [
+            0xff, 0xff, 0x40, 0x11, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80,
+        ];
+        assert_eq!(
+            pointer_words(&data, 0x1002, 4, true),
+            vec![(0x1004, 0x1140)]
+        );
+        assert_eq!(
+            pointer_words(&[0x00, 0x00, 0x80, 0x01], 0x2000, 4, false),
+            vec![(0x2000, 0x8001)]
+        );
+        assert!(pointer_words(&data[..3], 0x1002, 8, true).is_empty());
+    }
+
+    #[test]
+    fn test_gaps() {
+        let covered = [(0x1010, 0x1020), (0x1018, 0x1030), (0x1040, 0x1050)];
+        assert_eq!(
+            gaps(0x1000, 0x1060, &covered),
+            vec![(0x1000, 0x1010), (0x1030, 0x1040), (0x1050, 0x1060)]
+        );
+        assert!(gaps(0x1010, 0x1030, &covered).is_empty());
+    }
+}
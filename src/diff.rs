@@ -0,0 +1,459 @@
+//! Comparison of two builds: section and symbol sizes, function signatures
+//! and struct layouts, with a size summary for code review
+
+use crate::size::{analyze_sizes_from_bytes, SizeEntry, SizeReport};
+use crate::{
+    analyze_elf_from_bytes_with_dwarf, layout_fingerprint, ElfInfo, FunctionInfo, MemberDifference,
+    MemberLayout, TypeInfo,
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DiffReport {
+    pub summary: DiffSummary,
+    /// Added, removed and resized sections, largest change first
+    pub sections: Vec<SizeChange>,
+    /// Added, removed and resized symbols, largest change first
+    pub symbols: Vec<SizeChange>,
+    pub functions: Vec<FunctionChange>,
+    pub types: Vec<TypeChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DiffSummary {
+    pub old_file_size: u64,
+    pub new_file_size: u64,
+    pub file_delta: i64,
+    pub old_vm_size: u64,
+    pub new_vm_size: u64,
+    pub vm_delta: i64,
+    pub symbols_added: usize,
+    pub symbols_removed: usize,
+    pub symbols_resized: usize,
+    pub functions_changed: usize,
+    pub types_changed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SizeChange {
+    pub name: String,
+    pub status: String, // "added", "removed", "resized"
+    pub old_file_size: u64,
+    pub new_file_size: u64,
+    pub file_delta: i64,
+    pub old_vm_size: u64,
+    pub new_vm_size: u64,
+    pub vm_delta: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FunctionChange {
+    pub name: String,
+    pub status: String, // "added", "removed", "changed"
+    pub old_signature: Option<String>,
+    pub new_signature: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TypeChange {
+    pub name: String,
+    pub status: String, // "added", "removed", "changed"
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    /// Members whose placement differs; `layouts` holds the old and the new placement
+    pub members: Vec<MemberDifference>,
+}
+
+/// Compare the ELF files at `old_path` and `new_path`
+pub fn diff_files(old_path: &str, new_path: &str) -> Result<DiffReport> {
+    let old = fs::read(old_path).with_context(|| format!("Failed to read ELF file: {old_path}"))?;
+    let new = fs::read(new_path).with_context(|| format!("Failed to read ELF file: {new_path}"))?;
+    diff_from_bytes(&old, &new)
+}
+
+/// Compare two ELF files in memory (WebAssembly-compatible)
+pub fn diff_from_bytes(old: &[u8], new: &[u8]) -> Result<DiffReport> {
+    Ok(diff(
+        &analyze_elf_from_bytes_with_dwarf(old)?,
+        &analyze_sizes_from_bytes(old)?,
+        &analyze_elf_from_bytes_with_dwarf(new)?,
+        &analyze_sizes_from_bytes(new)?,
+    ))
+}
+
+/// Compare two analyses; the size reports must not be truncated
+pub fn diff(
+    old_info: &ElfInfo,
+    old_sizes: &SizeReport,
+    new_info: &ElfInfo,
+    new_sizes: &SizeReport,
+) -> DiffReport {
+    let sections = diff_sizes(&old_sizes.sections, &new_sizes.sections);
+    let symbols = diff_sizes(&old_sizes.symbols, &new_sizes.symbols);
+    let functions = diff_functions(&old_info.functions, &new_info.functions);
+    let types = diff_types(&old_info.types, &new_info.types);
+
+    let count = |status: &str| symbols.iter().filter(|c| c.status == status).count();
+    let summary = DiffSummary {
+        old_file_size: old_sizes.file_size,
+        new_file_size: new_sizes.file_size,
+        file_delta: delta(old_sizes.file_size, new_sizes.file_size),
+        old_vm_size: old_sizes.vm_size,
+        new_vm_size: new_sizes.vm_size,
+        vm_delta: delta(old_sizes.vm_size, new_sizes.vm_size),
+        symbols_added: count("added"),
+        symbols_removed: count("removed"),
+        symbols_resized: count("resized"),
+        functions_changed: functions.len(),
+        types_changed: types.len(),
+    };
+
+    DiffReport {
+        summary,
+        sections,
+        symbols,
+        functions,
+        types,
+    }
+}
+
+impl DiffReport {
+    /// Markdown summary for a code review comment: totals, the largest
+    /// `limit` section and symbol changes, then signature and layout changes
+    pub fn to_markdown(&self, limit: usize) -> String {
+        let summary = &self.summary;
+        let mut out = String::from("| | Old | New | Delta |\n|---|---:|---:|---:|\n");
+        out.push_str(&format!(
+            "| File size | {} | {} | {} |\n",
+            summary.old_file_size,
+            summary.new_file_size,
+            format_delta(summary.file_delta, summary.old_file_size)
+        ));
+        out.push_str(&format!(
+            "| VM size | {} | {} | {} |\n",
+            summary.old_vm_size,
+            summary.new_vm_size,
+            format_delta(summary.vm_delta, summary.old_vm_size)
+        ));
+
+        for (title, changes) in [("Sections", &self.sections), ("Symbols", &self.symbols)] {
+            if changes.is_empty() {
+                continue;
+            }
+            out.push_str(&format!(
+                "\n### {title}\n\n| Name | Status | VM delta | File delta |\n|---|---|---:|---:|\n"
+            ));
+            for change in changes.iter().take(limit) {
+                out.push_str(&format!(
+                    "| `{}` | {} | {} | {} |\n",
+                    change.name,
+                    change.status,
+                    format_delta(change.vm_delta, change.old_vm_size),
+                    format_delta(change.file_delta, change.old_file_size)
+                ));
+            }
+            if changes.len() > limit {
+                out.push_str(&format!("\n…and {} more\n", changes.len() - limit));
+            }
+        }
+
+        if !self.functions.is_empty() {
+            out.push_str("\n### Functions\n\n");
+            for change in &self.functions {
+                let signature = |signature: &Option<String>| {
+                    signature
+                        .as_deref()
+                        .map(|s| format!("`{s}`"))
+                        .unwrap_or_default()
+                };
+                out.push_str(&match change.status.as_str() {
+                    "changed" => format!(
+                        "- {} → {}\n",
+                        signature(&change.old_signature),
+                        signature(&change.new_signature)
+                    ),
+                    "added" => format!("- added {}\n", signature(&change.new_signature)),
+                    _ => format!("- removed {}\n", signature(&change.old_signature)),
+                });
+            }
+        }
+
+        if !self.types.is_empty() {
+            out.push_str("\n### Types\n\n");
+            for change in &self.types {
+                let size = |size: Option<u64>| size.map_or("?".to_string(), |s| s.to_string());
+                out.push_str(&match change.status.as_str() {
+                    "changed" => format!(
+                        "- `{}`: {} → {} bytes",
+                        change.name,
+                        size(change.old_size),
+                        size(change.new_size)
+                    ),
+                    "added" => format!(
+                        "- added `{}` ({} bytes)",
+                        change.name,
+                        size(change.new_size)
+                    ),
+                    _ => format!(
+                        "- removed `{}` ({} bytes)",
+                        change.name,
+                        size(change.old_size)
+                    ),
+                });
+                if !change.members.is_empty() {
+                    let members: Vec<String> = change
+                        .members
+                        .iter()
+                        .map(|member| format!("`{}`", member.name))
+                        .collect();
+                    out.push_str(&format!(" (members {})", members.join(", ")));
+                }
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+fn delta(old: u64, new: u64) -> i64 {
+    new as i64 - old as i64
+}
+
+fn format_delta(delta: i64, old: u64) -> String {
+    if old == 0 || delta == 0 {
+        format!("{delta:+}")
+    } else {
+        format!("{delta:+} ({:+.1}%)", delta as f64 * 100.0 / old as f64)
+    }
+}
+
+fn diff_sizes(old: &[SizeEntry], new: &[SizeEntry]) -> Vec<SizeChange> {
+    let mut pairs: BTreeMap<&str, (Option<&SizeEntry>, Option<&SizeEntry>)> = BTreeMap::new();
+    for entry in old {
+        pairs.entry(&entry.name).or_default().0 = Some(entry);
+    }
+    for entry in new {
+        pairs.entry(&entry.name).or_default().1 = Some(entry);
+    }
+
+    let mut changes: Vec<SizeChange> = pairs
+        .into_iter()
+        .filter_map(|(name, (old, new))| {
+            let status = match (old, new) {
+                (None, _) => "added",
+                (_, None) => "removed",
+                (Some(old), Some(new)) if old == new => return None,
+                _ => "resized",
+            };
+            let (old_file_size, old_vm_size) = old.map_or((0, 0), |e| (e.file_size, e.vm_size));
+            let (new_file_size, new_vm_size) = new.map_or((0, 0), |e| (e.file_size, e.vm_size));
+            Some(SizeChange {
+                name: name.to_string(),
+                status: status.to_string(),
+                old_file_size,
+                new_file_size,
+                file_delta: delta(old_file_size, new_file_size),
+                old_vm_size,
+                new_vm_size,
+                vm_delta: delta(old_vm_size, new_vm_size),
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| {
+        let magnitude = |c: &SizeChange| c.vm_delta.unsigned_abs().max(c.file_delta.unsigned_abs());
+        magnitude(b)
+            .cmp(&magnitude(a))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    changes
+}
+
+/// C-style signature of a function, e.g. `int parse(const char *, int)`
+pub fn function_signature(function: &FunctionInfo) -> String {
+    let name = function.qualified_name.as_ref().unwrap_or(&function.name);
+    let return_type = function
+        .return_type
+        .as_ref()
+        .map_or("void", |return_type| return_type.name.as_str());
+    let parameters: Vec<&str> = function
+        .parameters
+        .iter()
+        .map(|parameter| parameter.type_info.name.as_str())
+        .collect();
+    format!("{return_type} {name}({})", parameters.join(", "))
+}
+
+fn diff_functions(old: &[FunctionInfo], new: &[FunctionInfo]) -> Vec<FunctionChange> {
+    // Declarations have no address; the first definition of a name wins
+    let signatures = |functions: &[FunctionInfo]| {
+        let mut signatures: BTreeMap<String, String> = BTreeMap::new();
+        for function in functions.iter().filter(|f| f.address != 0) {
+            let name = function
+                .linkage_name
+                .clone()
+                .unwrap_or_else(|| function.name.clone());
+            signatures
+                .entry(name)
+                .or_insert_with(|| function_signature(function));
+        }
+        signatures
+    };
+    let (old, new) = (signatures(old), signatures(new));
+
+    let mut changes = Vec::new();
+    for (name, old_signature) in &old {
+        match new.get(name) {
+            Some(new_signature) if new_signature == old_signature => {}
+            new_signature => changes.push(FunctionChange {
+                name: name.clone(),
+                status: if new_signature.is_some() {
+                    "changed"
+                } else {
+                    "removed"
+                }
+                .to_string(),
+                old_signature: Some(old_signature.clone()),
+                new_signature: new_signature.cloned(),
+            }),
+        }
+    }
+    for (name, new_signature) in new.iter().filter(|(name, _)| !old.contains_key(*name)) {
+        changes.push(FunctionChange {
+            name: name.clone(),
+            status: "added".to_string(),
+            old_signature: None,
+            new_signature: Some(new_signature.clone()),
+        });
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
+
+fn diff_types(old: &[TypeInfo], new: &[TypeInfo]) -> Vec<TypeChange> {
+    // Complete definitions keyed by qualified name; the first layout of an ODR conflict wins
+    let definitions = |types: &[TypeInfo]| {
+        let mut definitions: BTreeMap<String, TypeInfo> = BTreeMap::new();
+        for type_info in types
+            .iter()
+            .filter(|t| t.name != "<anonymous>" && t.size.is_some())
+        {
+            let name = type_info
+                .qualified_name
+                .clone()
+                .unwrap_or_else(|| type_info.name.clone());
+            definitions.entry(name).or_insert_with(|| type_info.clone());
+        }
+        definitions
+    };
+    let (old, new) = (definitions(old), definitions(new));
+
+    let mut changes = Vec::new();
+    for (name, old_type) in &old {
+        let Some(new_type) = new.get(name) else {
+            changes.push(TypeChange {
+                name: name.clone(),
+                status: "removed".to_string(),
+                old_size: old_type.size,
+                ..Default::default()
+            });
+            continue;
+        };
+        if layout_fingerprint(old_type) == layout_fingerprint(new_type) {
+            continue;
+        }
+
+        let mut member_names: Vec<&str> = Vec::new();
+        for member in old_type.members.iter().chain(&new_type.members) {
+            if !member_names.contains(&member.name.as_str()) {
+                member_names.push(&member.name);
+            }
+        }
+        let placement = |type_info: &TypeInfo, member_name: &str| {
+            type_info
+                .members
+                .iter()
+                .find(|member| member.name == member_name)
+                .map(MemberLayout::of)
+        };
+        let members = member_names
+            .into_iter()
+            .filter_map(|member_name| {
+                let layouts = vec![
+                    placement(old_type, member_name),
+                    placement(new_type, member_name),
+                ];
+                (layouts[0] != layouts[1]).then(|| MemberDifference {
+                    name: member_name.to_string(),
+                    layouts,
+                })
+            })
+            .collect();
+        changes.push(TypeChange {
+            name: name.clone(),
+            status: "changed".to_string(),
+            old_size: old_type.size,
+            new_size: new_type.size,
+            members,
+        });
+    }
+    for (name, new_type) in new.iter().filter(|(name, _)| !old.contains_key(*name)) {
+        changes.push(TypeChange {
+            name: name.clone(),
+            status: "added".to_string(),
+            new_size: new_type.size,
+            ..Default::default()
+        });
+    }
+    changes.sort_by(|a, b| a.name.cmp(&b.name));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, file_size: u64, vm_size: u64) -> SizeEntry {
+        SizeEntry {
+            name: name.to_string(),
+            file_size,
+            vm_size,
+        }
+    }
+
+    #[test]
+    fn test_size_changes_sorted_by_magnitude() {
+        let old = [
+            entry("kept", 10, 10),
+            entry("gone", 8, 8),
+            entry("grew", 4, 4),
+        ];
+        let new = [
+            entry("kept", 10, 10),
+            entry("grew", 24, 24),
+            entry("new", 2, 0),
+        ];
+        let changes = diff_sizes(&old, &new);
+        let changes: Vec<(&str, &str, i64)> = changes
+            .iter()
+            .map(|c| (c.name.as_str(), c.status.as_str(), c.file_delta))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("grew", "resized", 20),
+                ("gone", "removed", -8),
+                ("new", "added", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_delta() {
+        assert_eq!(format_delta(128, 1024), "+128 (+12.5%)");
+        assert_eq!(format_delta(-4, 0), "-4");
+        assert_eq!(format_delta(0, 10), "+0");
+    }
+}
@@ -0,0 +1,519 @@
+//! Disassembly of function bodies for x86/x86_64, aarch64, riscv and
+//! arm/thumb, with branch targets and PC-relative addresses resolved to
+//! symbols and interleaved with source lines from the DWARF line table
+//!
+//! x86 decoding uses iced-x86; the other architectures have small decoders
+//! of their own covering the integer instructions compilers emit, and print
+//! anything else as `.inst`. Everything is pure Rust, so it also runs in
+//! the WebAssembly build.
+
+mod aarch64;
+mod arm;
+mod riscv;
+mod x86;
+
+use crate::symbolize::Symbolizer;
+use crate::values::Image;
+use crate::{demangle_symbol, SourceLocation};
+use anyhow::{Context, Result};
+use goblin::elf::header::{EM_386, EM_AARCH64, EM_ARM, EM_RISCV, EM_X86_64};
+use goblin::elf::sym::{STT_FUNC, STT_GNU_IFUNC};
+use goblin::elf::Elf;
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+
+/// A PC-relative page or upper immediate is paired with the instructions
+/// using it only within this many instructions
+const PAIRING_WINDOW: usize = 8;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DisassemblyReport {
+    pub architecture: String,
+    pub functions: Vec<FunctionDisassembly>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FunctionDisassembly {
+    pub name: String,
+    pub address: u64,
+    pub size: u64,
+    pub instructions: Vec<Instruction>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Instruction {
+    pub address: u64,
+    /// Encoding in memory order, e.g. `55 48 89 e5`
+    pub bytes: String,
+    /// Encoding length in bytes
+    pub length: u64,
+    pub mnemonic: String,
+    pub operands: String,
+    pub kind: String, // "call", "jump", "branch", "return", "indirect_call", "indirect_jump", "data", "other"
+    /// Branch target, or address computed PC-relative (`adrp`+`add`, `auipc`+`ld`, `[rip+x]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<u64>,
+    /// Symbol at the target, e.g. `memcpy@plt` or `table + 16`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Source line, where it differs from the previous instruction's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceLocation>,
+}
+
+/// One instruction as decoded by an architecture module
+#[derive(Debug, Default)]
+struct Decoded {
+    length: usize,
+    mnemonic: String,
+    operands: String,
+    kind: &'static str,
+    target: Option<u64>,
+    /// Register loaded with a PC-relative value that later instructions add to (`adrp`, `auipc`)
+    pc_relative: Option<(u8, u64)>,
+    /// Base register and displacement of an address or value the instruction computes
+    offset_from: Option<(u8, i64)>,
+    /// Register the instruction overwrites
+    writes: Option<u8>,
+    /// Immediate operand, an absolute address in non-PIC x86 code such as `mov $main,%rdi`
+    immediate: Option<u64>,
+}
+
+impl Decoded {
+    fn new(length: usize, mnemonic: &str, operands: String) -> Self {
+        Decoded {
+            length,
+            mnemonic: mnemonic.to_string(),
+            operands,
+            kind: "other",
+            ..Default::default()
+        }
+    }
+
+    fn kind(mut self, kind: &'static str) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    fn target(mut self, target: u64) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    fn writes(mut self, register: u8) -> Self {
+        self.writes = Some(register);
+        self
+    }
+
+    fn offset_from(mut self, register: u8, displacement: i64) -> Self {
+        self.offset_from = Some((register, displacement));
+        self
+    }
+
+    /// Bytes the decoder does not know, as a data directive of their size
+    fn unknown(bytes: &[u8], length: usize, little_endian: bool) -> Self {
+        let mut value = 0u64;
+        for i in 0..length.min(bytes.len()) {
+            let byte = if little_endian {
+                bytes[length - 1 - i]
+            } else {
+                bytes[i]
+            };
+            value = value << 8 | u64::from(byte);
+        }
+        let directive = match length {
+            2 => ".short",
+            _ => ".inst",
+        };
+        Decoded::new(
+            length,
+            directive,
+            format!("{value:#0width$x}", width = 2 + 2 * length),
+        )
+    }
+}
+
+/// Instruction set of a code range
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Isa {
+    X86 { bitness: u32 },
+    Aarch64,
+    Riscv,
+    Arm,
+    Thumb,
+}
+
+impl Isa {
+    fn decode(self, bytes: &[u8], address: u64) -> Decoded {
+        match self {
+            Isa::X86 { bitness } => x86::decode(bytes, address, bitness),
+            Isa::Aarch64 => aarch64::decode(bytes, address),
+            Isa::Riscv => riscv::decode(bytes, address),
+            Isa::Arm => arm::decode_arm(bytes, address),
+            Isa::Thumb => arm::decode_thumb(bytes, address),
+        }
+    }
+}
+
+/// Disassembler over the code of one ELF file
+pub struct Disassembler<'a> {
+    elf: Elf<'a>,
+    image: Image<'a>,
+    symbolizer: Symbolizer<'a>,
+    /// GOT slots the dynamic loader fills with the address of a symbol
+    got: HashMap<u64, String>,
+    /// Arm and aarch64 mapping symbols: where code (`$a`, `$t`, `$x`) and data (`$d`) start
+    mapping: BTreeMap<u64, char>,
+    /// Start addresses of function symbols, which immediates may refer to
+    function_starts: HashSet<u64>,
+}
+
+impl<'a> Disassembler<'a> {
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        let elf = match Object::parse(buffer)? {
+            Object::Elf(elf) => elf,
+            _ => anyhow::bail!("File is not a valid ELF binary"),
+        };
+        if !matches!(
+            elf.header.e_machine,
+            EM_X86_64 | EM_386 | EM_AARCH64 | EM_RISCV | EM_ARM
+        ) {
+            anyhow::bail!("Disassembly is only supported for x86, aarch64, riscv and arm");
+        }
+
+        let mut got = HashMap::new();
+        for relocation in elf
+            .pltrelocs
+            .iter()
+            .chain(elf.dynrelas.iter())
+            .chain(elf.dynrels.iter())
+        {
+            let Some(name) = elf
+                .dynsyms
+                .get(relocation.r_sym)
+                .and_then(|symbol| elf.dynstrtab.get_at(symbol.st_name))
+                .filter(|name| !name.is_empty())
+            else {
+                continue;
+            };
+            got.insert(relocation.r_offset, name.to_string());
+        }
+        let function_starts = elf
+            .syms
+            .iter()
+            .chain(elf.dynsyms.iter())
+            .filter(|symbol| symbol.st_shndx != 0 && symbol.st_value != 0)
+            .filter(|symbol| matches!(symbol.st_type(), STT_FUNC | STT_GNU_IFUNC))
+            .map(|symbol| symbol.st_value)
+            .collect();
+        let mut mapping = BTreeMap::new();
+        for symbol in elf.syms.iter() {
+            let name = elf.strtab.get_at(symbol.st_name).unwrap_or_default();
+            if let Some(kind) = name.strip_prefix('$').and_then(|kind| kind.chars().next()) {
+                if matches!(kind, 'a' | 't' | 'x' | 'd') && symbol.st_shndx != 0 {
+                    mapping.insert(symbol.st_value, kind);
+                }
+            }
+        }
+
+        Ok(Disassembler {
+            image: Image::from_elf(buffer)?,
+            symbolizer: Symbolizer::new(buffer)?,
+            elf,
+            got,
+            mapping,
+            function_starts,
+        })
+    }
+
+    /// Function symbols with a size, sorted by address; aliases keep the first name
+    pub fn functions(&self) -> Vec<(String, u64, u64)> {
+        let mut functions = Vec::new();
+        for (symtab, strtab) in [
+            (&self.elf.syms, &self.elf.strtab),
+            (&self.elf.dynsyms, &self.elf.dynstrtab),
+        ] {
+            for symbol in symtab.iter() {
+                if symbol.st_shndx == 0
+                    || symbol.st_size == 0
+                    || !matches!(symbol.st_type(), STT_FUNC | STT_GNU_IFUNC)
+                {
+                    continue;
+                }
+                let Some(name) = strtab.get_at(symbol.st_name).filter(|n| !n.is_empty()) else {
+                    continue;
+                };
+                let name = demangle_symbol(name).unwrap_or_else(|| name.to_string());
+                functions.push((name, symbol.st_value, symbol.st_size));
+            }
+        }
+        functions.sort_by_key(|&(_, address, _)| address);
+        functions.dedup_by_key(|&mut (_, address, _)| address);
+        functions
+    }
+
+    /// Source line of the instruction at `address`
+    pub fn location(&self, address: u64) -> Option<SourceLocation> {
+        self.symbolizer.location(address)
+    }
+
+    /// Instruction set at `address`; the low bit of an arm function symbol selects Thumb
+    fn isa(&self, address: u64) -> Isa {
+        match self.elf.header.e_machine {
+            EM_X86_64 => Isa::X86 { bitness: 64 },
+            EM_386 => Isa::X86 { bitness: 32 },
+            EM_AARCH64 => Isa::Aarch64,
+            EM_RISCV => Isa::Riscv,
+            _ => match self.mapping.range(..=address & !1).next_back() {
+                _ if address & 1 == 1 => Isa::Thumb,
+                Some((_, 't')) => Isa::Thumb,
+                _ => Isa::Arm,
+            },
+        }
+    }
+
+    /// Whether `address` lies in data embedded in code, such as an arm literal pool
+    fn is_data(&self, address: u64) -> Option<u64> {
+        let (_, &kind) = self.mapping.range(..=address).next_back()?;
+        if kind != 'd' {
+            return None;
+        }
+        // Up to the next code mapping symbol
+        self.mapping
+            .range(address + 1..)
+            .next()
+            .map(|(&next, _)| next)
+            .or(Some(u64::MAX))
+    }
+
+    /// Disassemble `size` bytes of the function at `address`
+    pub fn disassemble(&self, name: &str, address: u64, size: u64) -> FunctionDisassembly {
+        let isa = self.isa(address);
+        let start = match isa {
+            Isa::Thumb => address & !1,
+            _ => address,
+        };
+        let mut function = FunctionDisassembly {
+            name: name.to_string(),
+            address: start,
+            size,
+            instructions: Vec::new(),
+        };
+        let Some(code) = self.image.read(start, size) else {
+            return function;
+        };
+        let lines = self.symbolizer.line_rows(start, start + size);
+        let mut line_index = 0;
+        let mut previous_line: Option<SourceLocation> = None;
+        let mut tracked: Option<(u8, u64, usize)> = None;
+
+        let mut offset = 0usize;
+        while offset < code.len() {
+            let pc = start + offset as u64;
+            let bytes = &code[offset..];
+            let mut decoded = match self.is_data(pc) {
+                Some(end) => {
+                    let length = (end - pc).min(4).min(bytes.len() as u64) as usize;
+                    Decoded::unknown(bytes, length, self.elf.little_endian).kind("data")
+                }
+                None => isa.decode(bytes, pc),
+            };
+            if decoded.kind == "data" {
+                let directive = match decoded.length {
+                    1 => Some(".byte"),
+                    2 => Some(".short"),
+                    4 => Some(".word"),
+                    _ => None,
+                };
+                if let Some(directive) = directive {
+                    decoded.mnemonic = directive.to_string();
+                }
+            }
+            let length = decoded.length.clamp(1, bytes.len());
+
+            // Pair `adrp`/`auipc` with the instruction adding the low bits
+            if let Some((register, value, age)) = tracked {
+                if let Some((base, displacement)) = decoded.offset_from {
+                    if base == register && decoded.target.is_none() {
+                        let target = value.wrapping_add(displacement as u64);
+                        decoded.target = Some(target);
+                        decoded.kind = match decoded.kind {
+                            "indirect_call" => "call",
+                            "indirect_jump" => "jump",
+                            kind => kind,
+                        };
+                    }
+                }
+                tracked = Some((register, value, age + 1))
+                    .filter(|_| age + 1 < PAIRING_WINDOW && decoded.writes != Some(register));
+            }
+            if let Some((register, value)) = decoded.pc_relative {
+                tracked = Some((register, value, 0));
+            }
+
+            // Source line, interleaved where it changes
+            while line_index + 1 < lines.len() && lines[line_index + 1].0 <= pc {
+                line_index += 1;
+            }
+            let line = lines
+                .get(line_index)
+                .filter(|(address, _)| *address <= pc)
+                .map(|(_, location)| location.clone());
+            let source = match (&line, &previous_line) {
+                (Some(line), Some(previous))
+                    if line.file == previous.file && line.line == previous.line =>
+                {
+                    None
+                }
+                _ => line.clone(),
+            };
+            previous_line = line;
+
+            if decoded.target.is_none() {
+                decoded.target = decoded
+                    .immediate
+                    .filter(|immediate| self.function_starts.contains(immediate));
+            }
+            let symbol = decoded.target.and_then(|target| self.symbol(target));
+            function.instructions.push(Instruction {
+                address: pc,
+                bytes: code[offset..offset + length]
+                    .iter()
+                    .map(|byte| format!("{byte:02x}"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                length: length as u64,
+                mnemonic: decoded.mnemonic,
+                operands: decoded.operands,
+                kind: decoded.kind.to_string(),
+                target: decoded.target,
+                symbol,
+                source,
+            });
+            offset += length;
+        }
+        function
+    }
+
+    /// Name of the code or data at `address`: a symbol, a GOT slot, or a PLT stub
+    fn symbol(&self, address: u64) -> Option<String> {
+        if let Some(name) = self.got.get(&address) {
+            return Some(format!("{name}@got"));
+        }
+        // Thumb function symbols have the low bit set, so the symbol of the
+        // function before also covers the start of the next
+        if self.elf.header.e_machine == EM_ARM && self.function_starts.contains(&(address | 1)) {
+            return self.image.describe(address | 1);
+        }
+        if let Some(name) = self.image.describe(address) {
+            return Some(name);
+        }
+        if self.elf.header.e_machine == EM_ARM {
+            if let Some(name) = self.image.describe(address | 1) {
+                return Some(name);
+            }
+        }
+        self.plt_stub(address).map(|name| format!("{name}@plt"))
+    }
+
+    /// Symbol a PLT stub jumps to, found by decoding the stub up to its
+    /// indirect jump through a GOT slot
+    fn plt_stub(&self, address: u64) -> Option<String> {
+        let isa = self.isa(address);
+        let code = self.image.read(address, 16)?;
+        let mut tracked: Option<(u8, u64)> = None;
+        let mut offset = 0;
+        while offset < code.len() {
+            let decoded = isa.decode(&code[offset..], address + offset as u64);
+            // The load from the GOT slot is the instruction paired with the page
+            let slot = match (decoded.target, tracked, decoded.offset_from) {
+                (Some(target), _, _) if decoded.kind == "indirect_jump" => Some(target),
+                (None, Some((register, value)), Some((base, displacement))) if base == register => {
+                    Some(value.wrapping_add(displacement as u64))
+                }
+                _ => None,
+            };
+            if let Some(name) = slot.and_then(|slot| self.got.get(&slot)) {
+                return Some(name.clone());
+            }
+            if decoded.kind != "other" {
+                return None;
+            }
+            if let Some((register, value)) = decoded.pc_relative {
+                tracked = Some((register, value));
+            }
+            offset += decoded.length.max(1);
+        }
+        None
+    }
+}
+
+impl DisassemblyReport {
+    /// objdump-style listing; `source_line` supplies the text of a line when
+    /// the source file is available
+    pub fn to_text(&self, source_line: &dyn Fn(&SourceLocation) -> Option<String>) -> String {
+        let mut text = String::new();
+        for function in &self.functions {
+            let _ = writeln!(text, "{:016x} <{}>:", function.address, function.name);
+            for instruction in &function.instructions {
+                if let Some(source) = &instruction.source {
+                    let _ = writeln!(text, "; {}:{}", source.file, source.line.unwrap_or(0));
+                    if let Some(line) = source_line(source) {
+                        let _ = writeln!(text, ";     {}", line.trim_end());
+                    }
+                }
+                let mut line = format!(
+                    "{:>8x}:\t{:<24}\t{:<7} {}",
+                    instruction.address,
+                    instruction.bytes,
+                    instruction.mnemonic,
+                    instruction.operands
+                );
+                if let Some(symbol) = &instruction.symbol {
+                    let _ = write!(line, " <{symbol}>");
+                }
+                let _ = writeln!(text, "{}", line.trim_end());
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Disassemble the functions of the ELF file at `path` whose name matches
+/// one of `names`, or all functions when `names` is empty
+pub fn disassemble_files(path: &str, names: &[String]) -> Result<DisassemblyReport> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    disassemble_from_bytes(&buffer, names)
+}
+
+/// Disassemble functions from an ELF byte buffer (WebAssembly-compatible)
+pub fn disassemble_from_bytes(buffer: &[u8], names: &[String]) -> Result<DisassemblyReport> {
+    let disassembler = Disassembler::new(buffer)?;
+    let mut report = DisassemblyReport {
+        architecture: crate::analyze_elf_from_bytes_basic(buffer)?.architecture,
+        functions: Vec::new(),
+    };
+    for (name, address, size) in disassembler.functions() {
+        // Also match the demangled name without its path, e.g. `main` for `app::main`
+        let short_name = name.rsplit("::").next().unwrap_or(&name);
+        if names.is_empty() || names.iter().any(|n| *n == name || n == short_name) {
+            report
+                .functions
+                .push(disassembler.disassemble(&name, address, size));
+        }
+    }
+    if !names.is_empty() && report.functions.is_empty() {
+        anyhow::bail!("No function named {}", names.join(", "));
+    }
+    Ok(report)
+}
+
+/// Sign-extend the low `bits` bits of `value`
+fn sign_extend(value: u64, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((value << shift) as i64) >> shift
+}
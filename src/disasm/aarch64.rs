@@ -0,0 +1,848 @@
+//! AArch64 (A64): branches, address generation, integer data processing,
+//! loads and stores, and the system instructions compilers emit
+
+use super::{sign_extend, Decoded};
+
+const CONDITIONS: [&str; 16] = [
+    "eq", "ne", "hs", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "al", "nv",
+];
+const SHIFTS: [&str; 4] = ["lsl", "lsr", "asr", "ror"];
+const EXTENDS: [&str; 8] = [
+    "uxtb", "uxth", "uxtw", "uxtx", "sxtb", "sxth", "sxtw", "sxtx",
+];
+
+/// General register `n`; register 31 is the stack pointer where `sp` is set, the zero register otherwise
+fn reg(n: u32, wide: bool, sp: bool) -> String {
+    match (n, wide, sp) {
+        (31, true, true) => "sp".to_string(),
+        (31, false, true) => "wsp".to_string(),
+        (31, true, false) => "xzr".to_string(),
+        (31, false, false) => "wzr".to_string(),
+        (n, true, _) => format!("x{n}"),
+        (n, false, _) => format!("w{n}"),
+    }
+}
+
+/// SIMD and floating-point register `n` accessed as `size` bytes
+fn vreg(n: u32, size: u32) -> String {
+    let prefix = match size {
+        1 => 'b',
+        2 => 'h',
+        4 => 's',
+        8 => 'd',
+        _ => 'q',
+    };
+    format!("{prefix}{n}")
+}
+
+fn field(insn: u32, shift: u32, bits: u32) -> u32 {
+    (insn >> shift) & ((1 << bits) - 1)
+}
+
+fn address(base: u32, offset: i64) -> String {
+    match offset {
+        0 => format!("[{}]", reg(base, true, true)),
+        offset => format!("[{}, #{offset}]", reg(base, true, true)),
+    }
+}
+
+pub(super) fn decode(bytes: &[u8], pc: u64) -> Decoded {
+    let Some(word) = bytes.get(..4) else {
+        return Decoded::unknown(bytes, bytes.len(), true).kind("data");
+    };
+    let insn = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+    decode_word(insn, pc).unwrap_or_else(|| Decoded::unknown(bytes, 4, true))
+}
+
+fn decode_word(insn: u32, pc: u64) -> Option<Decoded> {
+    let rd = field(insn, 0, 5);
+    let rn = field(insn, 5, 5);
+    let rm = field(insn, 16, 5);
+    let wide = insn >> 31 == 1;
+    let new = |mnemonic: &str, operands: String| Decoded::new(4, mnemonic, operands);
+
+    // Branches
+    if insn & 0x7c00_0000 == 0x1400_0000 {
+        let target = pc.wrapping_add((sign_extend(u64::from(field(insn, 0, 26)), 26) << 2) as u64);
+        let (mnemonic, kind) = if wide { ("bl", "call") } else { ("b", "jump") };
+        return Some(
+            new(mnemonic, format!("{target:#x}"))
+                .kind(kind)
+                .target(target),
+        );
+    }
+    if insn & 0xff00_0010 == 0x5400_0000 {
+        let target = pc.wrapping_add((sign_extend(u64::from(field(insn, 5, 19)), 19) << 2) as u64);
+        let mnemonic = format!("b.{}", CONDITIONS[rd as usize & 15]);
+        return Some(
+            new(&mnemonic, format!("{target:#x}"))
+                .kind("branch")
+                .target(target),
+        );
+    }
+    if insn & 0x7e00_0000 == 0x3400_0000 {
+        let target = pc.wrapping_add((sign_extend(u64::from(field(insn, 5, 19)), 19) << 2) as u64);
+        let mnemonic = if insn & (1 << 24) != 0 { "cbnz" } else { "cbz" };
+        let operands = format!("{}, {target:#x}", reg(rd, wide, false));
+        return Some(new(mnemonic, operands).kind("branch").target(target));
+    }
+    if insn & 0x7e00_0000 == 0x3600_0000 {
+        let target = pc.wrapping_add((sign_extend(u64::from(field(insn, 5, 14)), 14) << 2) as u64);
+        let bit = (insn >> 31) << 5 | field(insn, 19, 5);
+        let mnemonic = if insn & (1 << 24) != 0 { "tbnz" } else { "tbz" };
+        let operands = format!("{}, #{bit}, {target:#x}", reg(rd, bit >= 32, false));
+        return Some(new(mnemonic, operands).kind("branch").target(target));
+    }
+    let xn = reg(rn, true, false);
+    match insn & 0xffff_fc1f {
+        0xd61f_0000 => return Some(new("br", xn).kind("indirect_jump")),
+        0xd63f_0000 => return Some(new("blr", xn).kind("indirect_call")),
+        0xd65f_0000 if rn == 30 => return Some(new("ret", String::new()).kind("return")),
+        0xd65f_0000 => return Some(new("ret", xn).kind("return")),
+        _ => {}
+    }
+    match insn {
+        0xd65f_0bff => return Some(new("retaa", String::new()).kind("return")),
+        0xd65f_0fff => return Some(new("retab", String::new()).kind("return")),
+        0xd69f_03e0 => return Some(new("eret", String::new()).kind("return")),
+        _ => {}
+    }
+    // Branches with pointer authentication
+    let key = if insn & (1 << 10) != 0 { 'b' } else { 'a' };
+    match insn & 0xffff_f800 {
+        0xd61f_0800 if rd == 31 => {
+            return Some(new(&format!("bra{key}z"), xn).kind("indirect_jump"));
+        }
+        0xd63f_0800 if rd == 31 => {
+            return Some(new(&format!("blra{key}z"), xn).kind("indirect_call"));
+        }
+        0xd71f_0800 => {
+            let operands = format!("{xn}, {}", reg(rd, true, true));
+            return Some(new(&format!("bra{key}"), operands).kind("indirect_jump"));
+        }
+        0xd73f_0800 => {
+            let operands = format!("{xn}, {}", reg(rd, true, true));
+            return Some(new(&format!("blra{key}"), operands).kind("indirect_call"));
+        }
+        _ => {}
+    }
+
+    // PC-relative addressing
+    if insn & 0x1f00_0000 == 0x1000_0000 {
+        let immediate = sign_extend(u64::from(field(insn, 5, 19) << 2 | field(insn, 29, 2)), 21);
+        let xd = reg(rd, true, false);
+        if wide {
+            let page = (pc & !0xfff).wrapping_add((immediate << 12) as u64);
+            let mut decoded = new("adrp", format!("{xd}, {page:#x}")).writes(rd as u8);
+            decoded.pc_relative = Some((rd as u8, page));
+            return Some(decoded);
+        }
+        let target = pc.wrapping_add(immediate as u64);
+        return Some(
+            new("adr", format!("{xd}, {target:#x}"))
+                .target(target)
+                .writes(rd as u8),
+        );
+    }
+    if insn & 0x3b00_0000 == 0x1800_0000 {
+        let target = pc.wrapping_add((sign_extend(u64::from(field(insn, 5, 19)), 19) << 2) as u64);
+        let opc = insn >> 30;
+        let (mnemonic, register) = match (insn & (1 << 26) != 0, opc) {
+            (false, 0) => ("ldr", reg(rd, false, false)),
+            (false, 1) => ("ldr", reg(rd, true, false)),
+            (false, 2) => ("ldrsw", reg(rd, true, false)),
+            (false, _) => ("prfm", format!("#{rd}")),
+            (true, 0) => ("ldr", vreg(rd, 4)),
+            (true, 1) => ("ldr", vreg(rd, 8)),
+            (true, _) => ("ldr", vreg(rd, 16)),
+        };
+        let operands = format!("{register}, {target:#x}");
+        return Some(new(mnemonic, operands).target(target).writes(rd as u8));
+    }
+
+    // Hints, barriers and system instructions
+    if insn & 0xffff_f01f == 0xd503_201f {
+        let mnemonic = match field(insn, 5, 7) {
+            0 => "nop",
+            1 => "yield",
+            2 => "wfe",
+            3 => "wfi",
+            4 => "sev",
+            25 => "paciasp",
+            27 => "pacibsp",
+            29 => "autiasp",
+            31 => "autibsp",
+            32 => "bti",
+            34 => "bti c",
+            36 => "bti j",
+            38 => "bti jc",
+            immediate => return Some(new("hint", format!("#{immediate}"))),
+        };
+        return Some(new(mnemonic, String::new()));
+    }
+    match insn & 0xffff_f0ff {
+        0xd503_30bf => return Some(new("dmb", barrier(field(insn, 8, 4)))),
+        0xd503_309f => return Some(new("dsb", barrier(field(insn, 8, 4)))),
+        0xd503_30df => return Some(new("isb", String::new())),
+        _ => {}
+    }
+    if insn & 0xffe0_001f == 0xd400_0001 {
+        return Some(new("svc", format!("#{:#x}", field(insn, 5, 16))));
+    }
+    if insn & 0xffe0_001f == 0xd420_0000 {
+        return Some(new("brk", format!("#{:#x}", field(insn, 5, 16))));
+    }
+    if insn & 0xffe0_0000 == 0xd440_0000 && rd == 0 {
+        return Some(new("hlt", format!("#{:#x}", field(insn, 5, 16))));
+    }
+    if insn & 0xffd0_0000 == 0xd510_0000 {
+        let register = system_register(field(insn, 5, 15));
+        let xt = reg(rd, true, false);
+        return Some(if insn & (1 << 21) != 0 {
+            new("mrs", format!("{xt}, {register}")).writes(rd as u8)
+        } else {
+            new("msr", format!("{register}, {xt}"))
+        });
+    }
+    if insn >> 16 == 0 {
+        return Some(new("udf", format!("#{insn}")).kind("other"));
+    }
+
+    data_processing(insn, rd, rn, rm, wide).or_else(|| load_store(insn, rd, rn, rm))
+}
+
+fn barrier(option: u32) -> String {
+    match option {
+        15 => "sy".to_string(),
+        14 => "st".to_string(),
+        13 => "ld".to_string(),
+        11 => "ish".to_string(),
+        10 => "ishst".to_string(),
+        9 => "ishld".to_string(),
+        7 => "nsh".to_string(),
+        3 => "osh".to_string(),
+        option => format!("#{option}"),
+    }
+}
+
+fn system_register(encoding: u32) -> String {
+    match encoding {
+        0x5e82 => "tpidr_el0".to_string(),
+        0x5a10 => "nzcv".to_string(),
+        0x5a20 => "fpcr".to_string(),
+        0x5a21 => "fpsr".to_string(),
+        0x5f01 => "cntvct_el0".to_string(),
+        0x5f00 => "cntfrq_el0".to_string(),
+        0x5801 => "ctr_el0".to_string(),
+        _ => format!(
+            "s{}_{}_c{}_c{}_{}",
+            2 + (encoding >> 14),
+            (encoding >> 11) & 7,
+            (encoding >> 7) & 15,
+            (encoding >> 3) & 15,
+            encoding & 7
+        ),
+    }
+}
+
+/// Immediate of a logical instruction (`DecodeBitMasks` in the Arm ARM)
+fn bitmask(n: u32, immr: u32, imms: u32, wide: bool) -> Option<u64> {
+    let combined = n << 6 | (!imms & 0x3f);
+    if combined == 0 {
+        return None;
+    }
+    let length = 31 - combined.leading_zeros();
+    let size = 1u32 << length;
+    let levels = size - 1;
+    let (s, r) = (imms & levels, immr & levels);
+    if s == levels {
+        return None;
+    }
+    let ones = (1u64 << (s + 1)) - 1;
+    let mask = if size == 64 {
+        u64::MAX
+    } else {
+        (1u64 << size) - 1
+    };
+    let element = ((ones >> r) | (ones << ((size - r) % size))) & mask;
+    let mut value = element;
+    let mut width = size;
+    while width < 64 {
+        value |= value << width;
+        width *= 2;
+    }
+    Some(if wide { value } else { value & 0xffff_ffff })
+}
+
+fn data_processing(insn: u32, rd: u32, rn: u32, rm: u32, wide: bool) -> Option<Decoded> {
+    let new = |mnemonic: &str, operands: String| Decoded::new(4, mnemonic, operands);
+    let set_flags = insn & (1 << 29) != 0;
+
+    // Add/subtract (immediate)
+    if insn & 0x1f80_0000 == 0x1100_0000 {
+        let shift = if insn & (1 << 22) != 0 { 12 } else { 0 };
+        let immediate = i64::from(field(insn, 10, 12)) << shift;
+        let subtract = insn & (1 << 30) != 0;
+        let dest = reg(rd, wide, !set_flags);
+        let source = reg(rn, wide, true);
+        let displacement = if subtract { -immediate } else { immediate };
+        let decoded = match (subtract, set_flags) {
+            (false, false) if immediate == 0 && (rd == 31 || rn == 31) => {
+                new("mov", format!("{dest}, {source}"))
+            }
+            (_, true) if rd == 31 => {
+                let mnemonic = if subtract { "cmp" } else { "cmn" };
+                new(mnemonic, format!("{source}, #{immediate:#x}"))
+            }
+            _ => {
+                let mnemonic = match (subtract, set_flags) {
+                    (false, false) => "add",
+                    (false, true) => "adds",
+                    (true, false) => "sub",
+                    (true, true) => "subs",
+                };
+                new(mnemonic, format!("{dest}, {source}, #{immediate:#x}"))
+            }
+        };
+        return Some(decoded.offset_from(rn as u8, displacement).writes(rd as u8));
+    }
+
+    // Logical (immediate)
+    if insn & 0x1f80_0000 == 0x1200_0000 {
+        let immediate = bitmask(
+            field(insn, 22, 1),
+            field(insn, 16, 6),
+            field(insn, 10, 6),
+            wide,
+        )?;
+        let opc = field(insn, 29, 2);
+        let dest = reg(rd, wide, opc != 3);
+        let source = reg(rn, wide, false);
+        let decoded = match opc {
+            1 if rn == 31 => new("mov", format!("{dest}, #{immediate:#x}")),
+            3 if rd == 31 => new("tst", format!("{source}, #{immediate:#x}")),
+            _ => {
+                let mnemonic = ["and", "orr", "eor", "ands"][opc as usize];
+                new(mnemonic, format!("{dest}, {source}, #{immediate:#x}"))
+            }
+        };
+        return Some(decoded.writes(rd as u8));
+    }
+
+    // Move wide (immediate)
+    if insn & 0x1f80_0000 == 0x1280_0000 {
+        let shift = field(insn, 21, 2) * 16;
+        let immediate = u64::from(field(insn, 5, 16));
+        let dest = reg(rd, wide, false);
+        let mask = if wide { u64::MAX } else { 0xffff_ffff };
+        let decoded = match field(insn, 29, 2) {
+            0 => new(
+                "mov",
+                format!("{dest}, #{:#x}", !(immediate << shift) & mask),
+            ),
+            2 => new("mov", format!("{dest}, #{:#x}", immediate << shift)),
+            3 if shift == 0 => new("movk", format!("{dest}, #{immediate:#x}")),
+            3 => new("movk", format!("{dest}, #{immediate:#x}, lsl #{shift}")),
+            _ => return None,
+        };
+        return Some(decoded.writes(rd as u8));
+    }
+
+    // Bitfield moves, mostly shifts and extensions
+    if insn & 0x1f80_0000 == 0x1300_0000 {
+        let (immr, imms) = (field(insn, 16, 6), field(insn, 10, 6));
+        let top = if wide { 63 } else { 31 };
+        let dest = reg(rd, wide, false);
+        let source = reg(rn, wide, false);
+        let decoded = match field(insn, 29, 2) {
+            0 if imms == top => new("asr", format!("{dest}, {source}, #{immr}")),
+            0 if immr == 0 && imms == 7 => {
+                new("sxtb", format!("{dest}, {}", reg(rn, false, false)))
+            }
+            0 if immr == 0 && imms == 15 => {
+                new("sxth", format!("{dest}, {}", reg(rn, false, false)))
+            }
+            0 if immr == 0 && imms == 31 => {
+                new("sxtw", format!("{dest}, {}", reg(rn, false, false)))
+            }
+            2 if imms == top => new("lsr", format!("{dest}, {source}, #{immr}")),
+            2 if imms + 1 == immr => new("lsl", format!("{dest}, {source}, #{}", top - imms)),
+            2 if immr == 0 && imms == 7 => {
+                new("uxtb", format!("{dest}, {}", reg(rn, false, false)))
+            }
+            2 if immr == 0 && imms == 15 => {
+                new("uxth", format!("{dest}, {}", reg(rn, false, false)))
+            }
+            opc => {
+                let mnemonic = ["sbfm", "bfm", "ubfm", ""][opc as usize];
+                if mnemonic.is_empty() {
+                    return None;
+                }
+                new(mnemonic, format!("{dest}, {source}, #{immr}, #{imms}"))
+            }
+        };
+        return Some(decoded.writes(rd as u8));
+    }
+
+    // Logical (shifted register)
+    if insn & 0x1f00_0000 == 0x0a00_0000 {
+        let opc = field(insn, 29, 2);
+        let negate = insn & (1 << 21) != 0;
+        let amount = field(insn, 10, 6);
+        let shift = SHIFTS[field(insn, 22, 2) as usize];
+        let (dest, first, second) = (
+            reg(rd, wide, false),
+            reg(rn, wide, false),
+            reg(rm, wide, false),
+        );
+        let shifted = match amount {
+            0 => second.clone(),
+            amount => format!("{second}, {shift} #{amount}"),
+        };
+        let decoded = match (opc, negate) {
+            (1, false) if rn == 31 && amount == 0 => new("mov", format!("{dest}, {second}")),
+            (1, true) if rn == 31 => new("mvn", format!("{dest}, {shifted}")),
+            (3, false) if rd == 31 => new("tst", format!("{first}, {shifted}")),
+            _ => {
+                let mnemonic = match (opc, negate) {
+                    (0, false) => "and",
+                    (0, true) => "bic",
+                    (1, false) => "orr",
+                    (1, true) => "orn",
+                    (2, false) => "eor",
+                    (2, true) => "eon",
+                    (3, false) => "ands",
+                    _ => "bics",
+                };
+                new(mnemonic, format!("{dest}, {first}, {shifted}"))
+            }
+        };
+        return Some(decoded.writes(rd as u8));
+    }
+
+    // Add/subtract (shifted and extended register)
+    if insn & 0x1f00_0000 == 0x0b00_0000 {
+        let subtract = insn & (1 << 30) != 0;
+        let mnemonic = match (subtract, set_flags) {
+            (false, false) => "add",
+            (false, true) => "adds",
+            (true, false) => "sub",
+            (true, true) => "subs",
+        };
+        if insn & (1 << 21) != 0 {
+            let option = field(insn, 13, 3);
+            let amount = field(insn, 10, 3);
+            let dest = reg(rd, wide, !set_flags);
+            let source = reg(rn, wide, true);
+            let index = reg(rm, wide && option & 3 == 3, false);
+            let extend = match amount {
+                0 => EXTENDS[option as usize].to_string(),
+                amount => format!("{} #{amount}", EXTENDS[option as usize]),
+            };
+            let decoded = new(mnemonic, format!("{dest}, {source}, {index}, {extend}"));
+            return Some(decoded.writes(rd as u8));
+        }
+        let amount = field(insn, 10, 6);
+        let shift = SHIFTS[field(insn, 22, 2) as usize];
+        let (dest, first, second) = (
+            reg(rd, wide, false),
+            reg(rn, wide, false),
+            reg(rm, wide, false),
+        );
+        let shifted = match amount {
+            0 => second,
+            amount => format!("{second}, {shift} #{amount}"),
+        };
+        let decoded = match (subtract, set_flags) {
+            (_, true) if rd == 31 => new(
+                if subtract { "cmp" } else { "cmn" },
+                format!("{first}, {shifted}"),
+            ),
+            (true, false) if rn == 31 => new("neg", format!("{dest}, {shifted}")),
+            _ => new(mnemonic, format!("{dest}, {first}, {shifted}")),
+        };
+        return Some(decoded.writes(rd as u8));
+    }
+
+    // Conditional select
+    if insn & 0x1fe0_0000 == 0x1a80_0000 {
+        let condition = field(insn, 12, 4) as usize;
+        let variant = field(insn, 30, 1) << 1 | field(insn, 10, 1);
+        let dest = reg(rd, wide, false);
+        let decoded = if variant == 1 && rn == 31 && rm == 31 {
+            new("cset", format!("{dest}, {}", CONDITIONS[condition ^ 1]))
+        } else if variant == 2 && rn == 31 && rm == 31 {
+            new("csetm", format!("{dest}, {}", CONDITIONS[condition ^ 1]))
+        } else {
+            let mnemonic = ["csel", "csinc", "csinv", "csneg"][variant as usize];
+            let operands = format!(
+                "{dest}, {}, {}, {}",
+                reg(rn, wide, false),
+                reg(rm, wide, false),
+                CONDITIONS[condition]
+            );
+            new(mnemonic, operands)
+        };
+        return Some(decoded.writes(rd as u8));
+    }
+
+    // Conditional compare
+    if insn & 0x3fe0_0410 == 0x3a40_0000 {
+        let mnemonic = if insn & (1 << 30) != 0 {
+            "ccmp"
+        } else {
+            "ccmn"
+        };
+        let second = if insn & (1 << 11) != 0 {
+            format!("#{rm}")
+        } else {
+            reg(rm, wide, false)
+        };
+        let operands = format!(
+            "{}, {second}, #{:#x}, {}",
+            reg(rn, wide, false),
+            field(insn, 0, 4),
+            CONDITIONS[field(insn, 12, 4) as usize]
+        );
+        return Some(new(mnemonic, operands));
+    }
+
+    // Data processing (one source)
+    if insn & 0x5fff_0000 == 0x5ac0_0000 {
+        let mnemonic = match (field(insn, 10, 6), wide) {
+            (0, _) => "rbit",
+            (1, _) => "rev16",
+            (2, true) => "rev32",
+            (2, false) | (3, true) => "rev",
+            (4, _) => "clz",
+            (5, _) => "cls",
+            _ => return None,
+        };
+        let operands = format!("{}, {}", reg(rd, wide, false), reg(rn, wide, false));
+        return Some(new(mnemonic, operands).writes(rd as u8));
+    }
+
+    // Extract, with rotate as its alias
+    if insn & 0x7fa0_0000 == 0x1380_0000 {
+        let amount = field(insn, 10, 6);
+        let (dest, first) = (reg(rd, wide, false), reg(rn, wide, false));
+        let decoded = if rn == rm {
+            new("ror", format!("{dest}, {first}, #{amount}"))
+        } else {
+            new(
+                "extr",
+                format!("{dest}, {first}, {}, #{amount}", reg(rm, wide, false)),
+            )
+        };
+        return Some(decoded.writes(rd as u8));
+    }
+
+    // Floating point: moves and conversions to and from general registers,
+    // arithmetic and compares
+    let size = if field(insn, 22, 2) == 1 { 8 } else { 4 };
+    match insn & 0x7f3f_fc00 {
+        0x1e26_0000 | 0x1e38_0000 | 0x1e39_0000 => {
+            let mnemonic = match field(insn, 16, 5) {
+                0x06 => "fmov",
+                0x18 => "fcvtzs",
+                _ => "fcvtzu",
+            };
+            let operands = format!("{}, {}", reg(rd, wide, false), vreg(rn, size));
+            return Some(new(mnemonic, operands).writes(rd as u8));
+        }
+        0x1e27_0000 | 0x1e22_0000 | 0x1e23_0000 => {
+            let mnemonic = match field(insn, 16, 5) {
+                0x07 => "fmov",
+                0x02 => "scvtf",
+                _ => "ucvtf",
+            };
+            return Some(new(
+                mnemonic,
+                format!("{}, {}", vreg(rd, size), reg(rn, wide, false)),
+            ));
+        }
+        _ => {}
+    }
+    if insn & 0xff20_0c00 == 0x1e20_0800 && field(insn, 22, 2) < 2 {
+        let mnemonic = match field(insn, 12, 4) {
+            0 => "fmul",
+            1 => "fdiv",
+            2 => "fadd",
+            3 => "fsub",
+            4 => "fmax",
+            5 => "fmin",
+            8 => "fnmul",
+            _ => return None,
+        };
+        let operands = format!("{}, {}, {}", vreg(rd, size), vreg(rn, size), vreg(rm, size));
+        return Some(new(mnemonic, operands));
+    }
+    if insn & 0xff3f_fc00 == 0x1e20_4000 && field(insn, 22, 2) < 2 {
+        return Some(new(
+            "fmov",
+            format!("{}, {}", vreg(rd, size), vreg(rn, size)),
+        ));
+    }
+    if insn & 0xff20_fc07 == 0x1e20_2000 && field(insn, 22, 2) < 2 {
+        let mnemonic = if insn & 0x10 != 0 { "fcmpe" } else { "fcmp" };
+        let second = if insn & 0x8 != 0 {
+            "#0.0".to_string()
+        } else {
+            vreg(rm, size)
+        };
+        return Some(new(mnemonic, format!("{}, {second}", vreg(rn, size))));
+    }
+
+    // Data processing (two sources)
+    if insn & 0x5fe0_0000 == 0x1ac0_0000 {
+        let mnemonic = match field(insn, 10, 6) {
+            2 => "udiv",
+            3 => "sdiv",
+            8 => "lsl",
+            9 => "lsr",
+            10 => "asr",
+            11 => "ror",
+            _ => return None,
+        };
+        let operands = format!(
+            "{}, {}, {}",
+            reg(rd, wide, false),
+            reg(rn, wide, false),
+            reg(rm, wide, false)
+        );
+        return Some(new(mnemonic, operands).writes(rd as u8));
+    }
+
+    // Data processing (three sources): multiplies
+    if insn & 0x1f00_0000 == 0x1b00_0000 {
+        let ra = field(insn, 10, 5);
+        let subtract = insn & (1 << 15) != 0;
+        let (mnemonic, long) = match (field(insn, 21, 3), subtract) {
+            (0, false) if ra == 31 => ("mul", false),
+            (0, true) if ra == 31 => ("mneg", false),
+            (0, false) => ("madd", false),
+            (0, true) => ("msub", false),
+            (1, false) if ra == 31 => ("smull", true),
+            (1, false) => ("smaddl", true),
+            (5, false) if ra == 31 => ("umull", true),
+            (5, false) => ("umaddl", true),
+            (2, false) => ("smulh", false),
+            (6, false) => ("umulh", false),
+            _ => return None,
+        };
+        let sources = format!(
+            "{}, {}",
+            reg(rn, !long && wide, false),
+            reg(rm, !long && wide, false)
+        );
+        let mut operands = format!("{}, {sources}", reg(rd, wide, false));
+        if ra != 31 && !mnemonic.ends_with("mulh") {
+            operands = format!("{operands}, {}", reg(ra, wide, false));
+        }
+        return Some(new(mnemonic, operands).writes(rd as u8));
+    }
+    None
+}
+
+/// Register name and access size of a load or store of `size_bits`
+/// (`0` byte to `3` doubleword) with `opc`; None for encodings not handled
+fn load_store_kind(
+    size_bits: u32,
+    vector: bool,
+    opc: u32,
+    rt: u32,
+) -> Option<(String, String, u32)> {
+    if vector {
+        let size = if opc & 2 != 0 { 16 } else { 1 << size_bits };
+        let mnemonic = if opc & 1 != 0 { "ldr" } else { "str" };
+        return Some((mnemonic.to_string(), vreg(rt, size), size));
+    }
+    let suffix = ["b", "h", "", ""][size_bits as usize];
+    let (mnemonic, wide) = match opc {
+        0 => ("str", size_bits == 3),
+        1 => ("ldr", size_bits == 3),
+        2 if size_bits == 3 => return None,
+        2 => ("ldrs", true),
+        _ if size_bits >= 2 => return None,
+        _ => ("ldrs", false),
+    };
+    let mnemonic = match (mnemonic, size_bits) {
+        ("ldrs", 2) => "ldrsw".to_string(),
+        (mnemonic, _) => format!("{mnemonic}{suffix}"),
+    };
+    Some((mnemonic, reg(rt, wide, false), 1 << size_bits))
+}
+
+fn load_store(insn: u32, rt: u32, rn: u32, rm: u32) -> Option<Decoded> {
+    let new = |mnemonic: &str, operands: String| Decoded::new(4, mnemonic, operands);
+    let size_bits = insn >> 30;
+    let vector = insn & (1 << 26) != 0;
+    let opc = field(insn, 22, 2);
+    let loads = |decoded: Decoded, mnemonic: &str| {
+        if mnemonic.starts_with("ld") {
+            decoded.writes(rt as u8)
+        } else {
+            decoded
+        }
+    };
+
+    // Unsigned offset
+    if insn & 0x3b00_0000 == 0x3900_0000 {
+        let (mnemonic, register, scale) = load_store_kind(size_bits, vector, opc, rt)?;
+        let offset = i64::from(field(insn, 10, 12)) * i64::from(scale);
+        let decoded = new(&mnemonic, format!("{register}, {}", address(rn, offset)))
+            .offset_from(rn as u8, offset);
+        return Some(loads(decoded, &mnemonic));
+    }
+
+    // Unscaled, post-indexed and pre-indexed offsets
+    if insn & 0x3b20_0000 == 0x3800_0000 {
+        let (mnemonic, register, _) = load_store_kind(size_bits, vector, opc, rt)?;
+        let offset = sign_extend(u64::from(field(insn, 12, 9)), 9);
+        let base = reg(rn, true, true);
+        let (mnemonic, operands) = match field(insn, 10, 2) {
+            0 => (
+                mnemonic.replacen("ld", "ldu", 1).replacen("st", "stu", 1),
+                address(rn, offset),
+            ),
+            1 => (mnemonic, format!("[{base}], #{offset}")),
+            3 => (mnemonic, format!("[{base}, #{offset}]!")),
+            _ => return None,
+        };
+        let decoded = new(&mnemonic, format!("{register}, {operands}"));
+        return Some(loads(decoded, &mnemonic));
+    }
+
+    // Register offset
+    if insn & 0x3b20_0c00 == 0x3820_0800 {
+        let (mnemonic, register, scale) = load_store_kind(size_bits, vector, opc, rt)?;
+        let option = field(insn, 13, 3);
+        let index = reg(rm, option & 1 == 1, false);
+        let amount = if insn & (1 << 12) != 0 {
+            scale.trailing_zeros()
+        } else {
+            0
+        };
+        let extend = match (option, amount) {
+            (3, 0) => String::new(),
+            (3, amount) => format!(", lsl #{amount}"),
+            (option, 0) => format!(", {}", EXTENDS[option as usize]),
+            (option, amount) => format!(", {} #{amount}", EXTENDS[option as usize]),
+        };
+        let operands = format!("{register}, [{}, {index}{extend}]", reg(rn, true, true));
+        let decoded = new(&mnemonic, operands);
+        return Some(loads(decoded, &mnemonic));
+    }
+
+    // Pairs
+    if insn & 0x3a00_0000 == 0x2800_0000 {
+        let load = insn & (1 << 22) != 0;
+        let rt2 = field(insn, 10, 5);
+        let (scale, first, second, signed) = match (vector, size_bits) {
+            (false, 0) => (4, reg(rt, false, false), reg(rt2, false, false), false),
+            (false, 1) if load => (4, reg(rt, true, false), reg(rt2, true, false), true),
+            (false, 2) => (8, reg(rt, true, false), reg(rt2, true, false), false),
+            (true, 0) => (4, vreg(rt, 4), vreg(rt2, 4), false),
+            (true, 1) => (8, vreg(rt, 8), vreg(rt2, 8), false),
+            (true, 2) => (16, vreg(rt, 16), vreg(rt2, 16), false),
+            _ => return None,
+        };
+        let offset = sign_extend(u64::from(field(insn, 15, 7)), 7) * scale;
+        let base = reg(rn, true, true);
+        let (mnemonic, operands) = match (field(insn, 23, 2), load) {
+            (0, false) => ("stnp", address(rn, offset)),
+            (0, true) => ("ldnp", address(rn, offset)),
+            (1, _) => (
+                if load { "ldp" } else { "stp" },
+                format!("[{base}], #{offset}"),
+            ),
+            (2, _) => (if load { "ldp" } else { "stp" }, address(rn, offset)),
+            _ => (
+                if load { "ldp" } else { "stp" },
+                format!("[{base}, #{offset}]!"),
+            ),
+        };
+        let mnemonic = if signed { "ldpsw" } else { mnemonic };
+        let decoded = new(mnemonic, format!("{first}, {second}, {operands}"));
+        return Some(if load {
+            decoded.writes(rt as u8)
+        } else {
+            decoded
+        });
+    }
+
+    // Exclusive and ordered accesses
+    if insn & 0x3f00_0000 == 0x0800_0000 {
+        let ordered = insn & (1 << 23) != 0;
+        let load = insn & (1 << 22) != 0;
+        let pair = insn & (1 << 21) != 0;
+        let acquire_release = insn & (1 << 15) != 0;
+        if pair {
+            return None;
+        }
+        let stem = match (ordered, load, acquire_release) {
+            (false, false, false) => "stxr",
+            (false, false, true) => "stlxr",
+            (false, true, false) => "ldxr",
+            (false, true, true) => "ldaxr",
+            (true, false, true) => "stlr",
+            (true, true, true) => "ldar",
+            _ => return None,
+        };
+        let mnemonic = format!("{stem}{}", ["b", "h", "", ""][size_bits as usize]);
+        let register = reg(rt, size_bits == 3, false);
+        let base = format!("[{}]", reg(rn, true, true));
+        let operands = if !ordered && !load {
+            format!("{}, {register}, {base}", reg(rm, false, false))
+        } else {
+            format!("{register}, {base}")
+        };
+        let decoded = new(&mnemonic, operands);
+        return Some(if load {
+            decoded.writes(rt as u8)
+        } else {
+            decoded
+        });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(insn: u32, pc: u64) -> String {
+        let decoded = decode(&insn.to_le_bytes(), pc);
+        format!("{} {}", decoded.mnemonic, decoded.operands)
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn test_decode_common_instructions() {
+        assert_eq!(text(0xa9bf7bfd, 0), "stp x29, x30, [sp, #-16]!");
+        assert_eq!(text(0x910003fd, 0), "mov x29, sp");
+        assert_eq!(text(0xa8c17bfd, 0), "ldp x29, x30, [sp], #16");
+        assert_eq!(text(0xd65f03c0, 0), "ret");
+        assert_eq!(text(0x94000004, 0x1000), "bl 0x1010");
+        assert_eq!(text(0x54000061, 0x1000), "b.ne 0x100c");
+        assert_eq!(text(0x52800540, 0), "mov w0, #0x2a");
+        assert_eq!(text(0xb9401fe0, 0), "ldr w0, [sp, #28]");
+        assert_eq!(text(0xf9400bf3, 0), "ldr x19, [sp, #16]");
+        assert_eq!(text(0x2a0003f3, 0), "mov w19, w0");
+        assert_eq!(text(0x7100041f, 0), "cmp w0, #0x1");
+        assert_eq!(text(0x1b007c00, 0), "mul w0, w0, w0");
+        assert_eq!(text(0xd53bd040, 0), "mrs x0, tpidr_el0");
+    }
+
+    #[test]
+    fn test_adrp_page() {
+        // adrp x0, 0x411000 at 0x400580
+        let decoded = decode(&0xb0000080u32.to_le_bytes(), 0x400580);
+        assert_eq!(decoded.mnemonic, "adrp");
+        assert_eq!(decoded.pc_relative, Some((0, 0x411000)));
+        // add x0, x0, #0x28
+        let decoded = decode(&0x9100a000u32.to_le_bytes(), 0x400584);
+        assert_eq!(decoded.offset_from, Some((0, 0x28)));
+    }
+}
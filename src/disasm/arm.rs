@@ -0,0 +1,572 @@
+//! 32-bit Arm: the A32 and Thumb-2 branches, literal loads, stack and
+//! integer instructions compilers emit for function prologues and calls
+
+use super::{sign_extend, Decoded};
+
+const CONDITIONS: [&str; 16] = [
+    "eq", "ne", "cs", "cc", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le", "", "",
+];
+const REGISTERS: [&str; 16] = [
+    "r0", "r1", "r2", "r3", "r4", "r5", "r6", "r7", "r8", "r9", "r10", "r11", "r12", "sp", "lr",
+    "pc",
+];
+const OPERATIONS: [&str; 16] = [
+    "and", "eor", "sub", "rsb", "add", "adc", "sbc", "rsc", "tst", "teq", "cmp", "cmn", "orr",
+    "mov", "bic", "mvn",
+];
+const SHIFTS: [&str; 4] = ["lsl", "lsr", "asr", "ror"];
+const PC: u32 = 15;
+const LR: u32 = 14;
+
+fn r(n: u32) -> &'static str {
+    REGISTERS[n as usize & 15]
+}
+
+fn bits(value: u32, shift: u32, count: u32) -> u32 {
+    (value >> shift) & ((1 << count) - 1)
+}
+
+fn register_list(list: u32) -> String {
+    let names: Vec<&str> = (0..16).filter(|n| list & (1 << n) != 0).map(r).collect();
+    format!("{{{}}}", names.join(", "))
+}
+
+/// Decode an A32 instruction at `pc`
+pub(super) fn decode_arm(bytes: &[u8], pc: u64) -> Decoded {
+    let Some(word) = bytes.get(..4) else {
+        return Decoded::unknown(bytes, bytes.len(), true).kind("data");
+    };
+    let insn = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+    decode_a32(insn, pc).unwrap_or_else(|| Decoded::unknown(bytes, 4, true))
+}
+
+fn decode_a32(insn: u32, pc: u64) -> Option<Decoded> {
+    let condition = insn >> 28;
+    let suffix = CONDITIONS[condition as usize];
+    let rn = bits(insn, 16, 4);
+    let rd = bits(insn, 12, 4);
+    let rm = bits(insn, 0, 4);
+    let new = |mnemonic: &str, operands: String| Decoded::new(4, mnemonic, operands);
+    // Reads of pc see the address of the instruction plus 8
+    let pc_value = pc.wrapping_add(8);
+
+    if condition == 0xf {
+        if insn & 0x0e00_0000 == 0x0a00_0000 {
+            let offset = sign_extend(
+                u64::from(bits(insn, 0, 24) << 2 | bits(insn, 24, 1) << 1),
+                26,
+            );
+            let target = pc_value.wrapping_add(offset as u64);
+            return Some(
+                new("blx", format!("{target:#x}"))
+                    .kind("call")
+                    .target(target),
+            );
+        }
+        return None;
+    }
+    if insn & 0x0e00_0000 == 0x0a00_0000 {
+        let offset = sign_extend(u64::from(bits(insn, 0, 24)) << 2, 26);
+        let target = pc_value.wrapping_add(offset as u64);
+        let (mnemonic, kind) = match (insn & (1 << 24) != 0, condition) {
+            (true, _) => ("bl", "call"),
+            (false, 14) => ("b", "jump"),
+            (false, _) => ("b", "branch"),
+        };
+        let mnemonic = format!("{mnemonic}{suffix}");
+        return Some(
+            new(&mnemonic, format!("{target:#x}"))
+                .kind(kind)
+                .target(target),
+        );
+    }
+    match insn & 0x0fff_fff0 {
+        0x012f_ff10 => {
+            let kind = if rm == LR { "return" } else { "indirect_jump" };
+            return Some(new(&format!("bx{suffix}"), r(rm).to_string()).kind(kind));
+        }
+        0x012f_ff30 => {
+            let decoded = new(&format!("blx{suffix}"), r(rm).to_string());
+            return Some(decoded.kind("indirect_call"));
+        }
+        _ => {}
+    }
+    if insn & 0x0fff_ffff == 0x0320_f000 || insn & 0x0fff_ffff == 0x01a0_0000 {
+        return Some(new(&format!("nop{suffix}"), String::new()));
+    }
+    if insn & 0x0f00_0000 == 0x0f00_0000 {
+        return Some(new(
+            &format!("svc{suffix}"),
+            format!("{:#x}", bits(insn, 0, 24)),
+        ));
+    }
+    if insn & 0x0ff0_00f0 == 0x07f0_00f0 {
+        let immediate = bits(insn, 8, 12) << 4 | rm;
+        return Some(new("udf", format!("#{immediate}")));
+    }
+
+    // Push and pop
+    let list = bits(insn, 0, 16);
+    if insn & 0x0fff_0000 == 0x092d_0000 {
+        return Some(new(&format!("push{suffix}"), register_list(list)));
+    }
+    if insn & 0x0fff_0000 == 0x08bd_0000 {
+        let decoded = new(&format!("pop{suffix}"), register_list(list));
+        return Some(if list & (1 << PC) != 0 {
+            decoded.kind("return")
+        } else {
+            decoded
+        });
+    }
+
+    // Multiplies
+    if insn & 0x0fe0_00f0 == 0x0000_0090 {
+        let operands = format!("{}, {}, {}", r(rn), r(rm), r(bits(insn, 8, 4)));
+        return Some(new(&format!("mul{suffix}"), operands).writes(rn as u8));
+    }
+    if insn & 0x0fe0_00f0 == 0x0020_0090 {
+        let operands = format!("{}, {}, {}, {}", r(rn), r(rm), r(bits(insn, 8, 4)), r(rd));
+        return Some(new(&format!("mla{suffix}"), operands).writes(rn as u8));
+    }
+
+    // Loads and stores with an immediate offset
+    if insn & 0x0e00_0000 == 0x0400_0000 {
+        let load = insn & (1 << 20) != 0;
+        let byte = if insn & (1 << 22) != 0 { "b" } else { "" };
+        let pre_indexed = insn & (1 << 24) != 0;
+        let write_back = insn & (1 << 21) != 0;
+        let immediate = i64::from(bits(insn, 0, 12));
+        let offset = if insn & (1 << 23) != 0 {
+            immediate
+        } else {
+            -immediate
+        };
+        let mnemonic = format!("{}{byte}{suffix}", if load { "ldr" } else { "str" });
+        if rn == PC && pre_indexed && !write_back {
+            let target = pc_value.wrapping_add(offset as u64);
+            let decoded = new(&mnemonic, format!("{}, [pc, #{offset}]", r(rd))).target(target);
+            return Some(if load {
+                decoded.writes(rd as u8)
+            } else {
+                decoded
+            });
+        }
+        let operands = match (pre_indexed, write_back) {
+            (true, false) if offset == 0 => format!("{}, [{}]", r(rd), r(rn)),
+            (true, false) => format!("{}, [{}, #{offset}]", r(rd), r(rn)),
+            (true, true) => format!("{}, [{}, #{offset}]!", r(rd), r(rn)),
+            (false, _) => format!("{}, [{}], #{offset}", r(rd), r(rn)),
+        };
+        // `ldr pc, [sp], #4` is the single-register pop
+        let kind = match (load && rd == PC, rn, pre_indexed) {
+            (true, 13, false) => "return",
+            (true, _, _) => "indirect_jump",
+            _ => "other",
+        };
+        let decoded = new(&mnemonic, operands).kind(kind);
+        return Some(if load {
+            decoded.writes(rd as u8)
+        } else {
+            decoded
+        });
+    }
+
+    // Data processing
+    let opcode = bits(insn, 21, 4);
+    let set_flags = insn & (1 << 20) != 0;
+    let compare = (8..12).contains(&opcode);
+    if compare && !set_flags {
+        // movw, movt and the miscellaneous instructions sharing their space
+        if insn & 0x0fb0_0000 == 0x0300_0000 {
+            let immediate = bits(insn, 16, 4) << 12 | bits(insn, 0, 12);
+            let mnemonic = if insn & (1 << 22) != 0 {
+                "movt"
+            } else {
+                "movw"
+            };
+            let operands = format!("{}, #{immediate:#x}", r(rd));
+            return Some(new(&format!("{mnemonic}{suffix}"), operands).writes(rd as u8));
+        }
+        return None;
+    }
+    let flags = if set_flags && !compare { "s" } else { "" };
+    let mnemonic = format!("{}{flags}{suffix}", OPERATIONS[opcode as usize]);
+    let second = if insn & 0x0e00_0000 == 0x0200_0000 {
+        let immediate = bits(insn, 0, 8).rotate_right(2 * bits(insn, 8, 4));
+        if rn == PC && matches!(opcode, 2 | 4) {
+            let offset = if opcode == 2 {
+                -i64::from(immediate)
+            } else {
+                i64::from(immediate)
+            };
+            let target = pc_value.wrapping_add(offset as u64);
+            let decoded = new(&format!("adr{suffix}"), format!("{}, {target:#x}", r(rd)));
+            return Some(decoded.target(target).writes(rd as u8));
+        }
+        format!("#{immediate}")
+    } else if insn & 0x0e00_0010 == 0 {
+        let amount = bits(insn, 7, 5);
+        let shift = bits(insn, 5, 2);
+        if opcode == 13 && amount != 0 {
+            let operands = format!("{}, {}, #{amount}", r(rd), r(rm));
+            let mnemonic = format!("{}{flags}{suffix}", SHIFTS[shift as usize]);
+            return Some(new(&mnemonic, operands).writes(rd as u8));
+        }
+        match (amount, shift) {
+            (0, 0) => r(rm).to_string(),
+            (0, 3) => format!("{}, rrx", r(rm)),
+            (0, shift) => format!("{}, {} #32", r(rm), SHIFTS[shift as usize]),
+            (amount, shift) => format!("{}, {} #{amount}", r(rm), SHIFTS[shift as usize]),
+        }
+    } else if insn & 0x0e00_0090 == 0x0000_0010 {
+        format!(
+            "{}, {} {}",
+            r(rm),
+            SHIFTS[bits(insn, 5, 2) as usize],
+            r(bits(insn, 8, 4))
+        )
+    } else {
+        return None;
+    };
+    let decoded = match opcode {
+        8..=11 => new(&mnemonic, format!("{}, {second}", r(rn))),
+        13 | 15 => new(&mnemonic, format!("{}, {second}", r(rd))),
+        _ => new(&mnemonic, format!("{}, {}, {second}", r(rd), r(rn))),
+    };
+    // Writing pc branches: `mov pc, lr` returns
+    let decoded = match (rd, opcode, second.as_str()) {
+        (PC, 13, "lr") => decoded.kind("return"),
+        (PC, 0..=7 | 12..=15, _) => decoded.kind("indirect_jump"),
+        _ => decoded,
+    };
+    Some(if compare {
+        decoded
+    } else {
+        decoded.writes(rd as u8)
+    })
+}
+
+/// Decode a Thumb instruction at `pc`, 16 or 32 bits long
+pub(super) fn decode_thumb(bytes: &[u8], pc: u64) -> Decoded {
+    let Some(half) = bytes.get(..2) else {
+        return Decoded::unknown(bytes, bytes.len(), true).kind("data");
+    };
+    let first = u32::from(u16::from_le_bytes([half[0], half[1]]));
+    if matches!(first >> 11, 0b11101..=0b11111) {
+        let Some(second) = bytes.get(2..4) else {
+            return Decoded::unknown(bytes, 2, true).kind("data");
+        };
+        let second = u32::from(u16::from_le_bytes([second[0], second[1]]));
+        return decode_t32(first, second, pc).unwrap_or_else(|| {
+            Decoded::new(4, ".inst.w", format!("{:#010x}", first << 16 | second))
+        });
+    }
+    decode_t16(first, pc).unwrap_or_else(|| Decoded::unknown(bytes, 2, true))
+}
+
+fn decode_t16(insn: u32, pc: u64) -> Option<Decoded> {
+    let new = |mnemonic: &str, operands: String| Decoded::new(2, mnemonic, operands);
+    // Reads of pc see the address of the instruction plus 4
+    let pc_value = pc.wrapping_add(4);
+    let low = bits(insn, 0, 3);
+    let middle = bits(insn, 3, 3);
+    let high = bits(insn, 8, 3);
+    let immediate8 = bits(insn, 0, 8);
+
+    if insn & 0xff00 == 0xbf00 {
+        let mask = bits(insn, 0, 4);
+        if mask == 0 {
+            let mnemonic = ["nop", "yield", "wfe", "wfi", "sev"].get(bits(insn, 4, 4) as usize)?;
+            return Some(new(mnemonic, String::new()));
+        }
+        let condition = bits(insn, 4, 4);
+        let mut mnemonic = "it".to_string();
+        for position in (mask.trailing_zeros() + 1..4).rev() {
+            let same = (mask >> position) & 1 == condition & 1;
+            mnemonic.push(if same { 't' } else { 'e' });
+        }
+        return Some(new(&mnemonic, CONDITIONS[condition as usize].to_string()));
+    }
+    if insn & 0xf800 == 0xe000 {
+        let target =
+            pc_value.wrapping_add((sign_extend(u64::from(bits(insn, 0, 11)), 11) << 1) as u64);
+        return Some(new("b", format!("{target:#x}")).kind("jump").target(target));
+    }
+    if insn & 0xf000 == 0xd000 {
+        let condition = bits(insn, 8, 4);
+        return Some(match condition {
+            14 => new("udf", format!("#{immediate8}")),
+            15 => new("svc", format!("{immediate8}")),
+            _ => {
+                let target =
+                    pc_value.wrapping_add((sign_extend(u64::from(immediate8), 8) << 1) as u64);
+                let mnemonic = format!("b{}", CONDITIONS[condition as usize]);
+                new(&mnemonic, format!("{target:#x}"))
+                    .kind("branch")
+                    .target(target)
+            }
+        });
+    }
+    if insn & 0xf500 == 0xb100 {
+        let offset = bits(insn, 9, 1) << 6 | bits(insn, 3, 5) << 1;
+        let target = pc_value.wrapping_add(u64::from(offset));
+        let mnemonic = if insn & (1 << 11) != 0 { "cbnz" } else { "cbz" };
+        let operands = format!("{}, {target:#x}", r(low));
+        return Some(new(mnemonic, operands).kind("branch").target(target));
+    }
+    let rm = bits(insn, 3, 4);
+    match insn & 0xff87 {
+        0x4700 => {
+            let kind = if rm == LR { "return" } else { "indirect_jump" };
+            return Some(new("bx", r(rm).to_string()).kind(kind));
+        }
+        0x4780 => return Some(new("blx", r(rm).to_string()).kind("indirect_call")),
+        _ => {}
+    }
+    match insn & 0xfe00 {
+        0xb400 => {
+            let list = immediate8 | bits(insn, 8, 1) << LR;
+            return Some(new("push", register_list(list)));
+        }
+        0xbc00 => {
+            let list = immediate8 | bits(insn, 8, 1) << PC;
+            let decoded = new("pop", register_list(list));
+            return Some(if list & (1 << PC) != 0 {
+                decoded.kind("return")
+            } else {
+                decoded
+            });
+        }
+        0x1800 | 0x1a00 => {
+            let mnemonic = if insn & 0x0200 != 0 { "subs" } else { "adds" };
+            let operands = format!("{}, {}, {}", r(low), r(middle), r(bits(insn, 6, 3)));
+            return Some(new(mnemonic, operands).writes(low as u8));
+        }
+        0x1c00 | 0x1e00 => {
+            let mnemonic = if insn & 0x0200 != 0 { "subs" } else { "adds" };
+            let operands = format!("{}, {}, #{}", r(low), r(middle), bits(insn, 6, 3));
+            return Some(new(mnemonic, operands).writes(low as u8));
+        }
+        _ => {}
+    }
+    match insn & 0xf800 {
+        0x4800 => {
+            let target = (pc_value & !3).wrapping_add(u64::from(immediate8 * 4));
+            let operands = format!("{}, [pc, #{}]", r(high), immediate8 * 4);
+            return Some(new("ldr", operands).target(target).writes(high as u8));
+        }
+        0xa000 => {
+            let target = (pc_value & !3).wrapping_add(u64::from(immediate8 * 4));
+            let operands = format!("{}, {target:#x}", r(high));
+            return Some(new("adr", operands).target(target).writes(high as u8));
+        }
+        0x2000 | 0x2800 | 0x3000 | 0x3800 => {
+            let mnemonic = ["movs", "cmp", "adds", "subs"][bits(insn, 11, 2) as usize];
+            let decoded = new(mnemonic, format!("{}, #{immediate8}", r(high)));
+            return Some(if mnemonic == "cmp" {
+                decoded
+            } else {
+                decoded.writes(high as u8)
+            });
+        }
+        0x9800 | 0x9000 => {
+            let mnemonic = if insn & 0x0800 != 0 { "ldr" } else { "str" };
+            let operands = format!("{}, [sp, #{}]", r(high), immediate8 * 4);
+            let decoded = new(mnemonic, operands);
+            return Some(if mnemonic == "ldr" {
+                decoded.writes(high as u8)
+            } else {
+                decoded
+            });
+        }
+        0x6800 | 0x6000 | 0x7800 | 0x7000 | 0x8800 | 0x8000 => {
+            let load = insn & 0x0800 != 0;
+            let (suffix, scale) = match insn & 0xf000 {
+                0x6000 => ("", 4),
+                0x7000 => ("b", 1),
+                _ => ("h", 2),
+            };
+            let mnemonic = format!("{}{suffix}", if load { "ldr" } else { "str" });
+            let offset = bits(insn, 6, 5) * scale;
+            let operands = match offset {
+                0 => format!("{}, [{}]", r(low), r(middle)),
+                offset => format!("{}, [{}, #{offset}]", r(low), r(middle)),
+            };
+            let decoded = new(&mnemonic, operands);
+            return Some(if load {
+                decoded.writes(low as u8)
+            } else {
+                decoded
+            });
+        }
+        0x0000 | 0x0800 | 0x1000 => {
+            let amount = bits(insn, 6, 5);
+            let shift = bits(insn, 11, 2);
+            let decoded = if shift == 0 && amount == 0 {
+                new("movs", format!("{}, {}", r(low), r(middle)))
+            } else {
+                let mnemonic = format!("{}s", SHIFTS[shift as usize]);
+                new(&mnemonic, format!("{}, {}, #{amount}", r(low), r(middle)))
+            };
+            return Some(decoded.writes(low as u8));
+        }
+        _ => {}
+    }
+    match insn & 0xff00 {
+        0xb000 => {
+            let mnemonic = if insn & 0x80 != 0 { "sub" } else { "add" };
+            return Some(new(mnemonic, format!("sp, #{}", bits(insn, 0, 7) * 4)));
+        }
+        0xb200 => {
+            let mnemonic = ["sxth", "sxtb", "uxth", "uxtb"][bits(insn, 6, 2) as usize];
+            let operands = format!("{}, {}", r(low), r(middle));
+            return Some(new(mnemonic, operands).writes(low as u8));
+        }
+        0xbe00 => return Some(new("bkpt", format!("{immediate8:#04x}"))),
+        0x4400 | 0x4500 | 0x4600 => {
+            let rd = bits(insn, 7, 1) << 3 | low;
+            let mnemonic = ["add", "cmp", "mov"][bits(insn, 8, 2) as usize];
+            let decoded = new(mnemonic, format!("{}, {}", r(rd), r(rm)));
+            return Some(match (mnemonic, rd) {
+                ("cmp", _) => decoded,
+                (_, PC) => decoded.kind("indirect_jump"),
+                _ => decoded.writes(rd as u8),
+            });
+        }
+        _ => {}
+    }
+    if insn & 0xfc00 == 0x4000 {
+        let mnemonic = [
+            "ands", "eors", "lsls", "lsrs", "asrs", "adcs", "sbcs", "rors", "tst", "negs", "cmp",
+            "cmn", "orrs", "muls", "bics", "mvns",
+        ][bits(insn, 6, 4) as usize];
+        let decoded = new(mnemonic, format!("{}, {}", r(low), r(middle)));
+        return Some(if matches!(mnemonic, "tst" | "cmp" | "cmn") {
+            decoded
+        } else {
+            decoded.writes(low as u8)
+        });
+    }
+    None
+}
+
+fn decode_t32(first: u32, second: u32, pc: u64) -> Option<Decoded> {
+    let new = |mnemonic: &str, operands: String| Decoded::new(4, mnemonic, operands);
+    let pc_value = pc.wrapping_add(4);
+
+    if first & 0xf800 == 0xf000 && second & 0x8000 != 0 {
+        let sign = bits(first, 10, 1);
+        let j1 = bits(second, 13, 1);
+        let j2 = bits(second, 11, 1);
+        let imm11 = bits(second, 0, 11);
+        if second & 0x5000 == 0 {
+            // Conditional branch, T3
+            let condition = bits(first, 6, 4);
+            if condition >= 14 {
+                return None;
+            }
+            let offset = sign << 20 | j2 << 19 | j1 << 18 | bits(first, 0, 6) << 12 | imm11 << 1;
+            let target = pc_value.wrapping_add(sign_extend(u64::from(offset), 21) as u64);
+            let mnemonic = format!("b{}.w", CONDITIONS[condition as usize]);
+            return Some(
+                new(&mnemonic, format!("{target:#x}"))
+                    .kind("branch")
+                    .target(target),
+            );
+        }
+        let i1 = !(j1 ^ sign) & 1;
+        let i2 = !(j2 ^ sign) & 1;
+        let offset = sign << 24 | i1 << 23 | i2 << 22 | bits(first, 0, 10) << 12 | imm11 << 1;
+        let offset = sign_extend(u64::from(offset), 25) as u64;
+        return Some(match bits(second, 12, 3) & 0b101 {
+            0b101 => {
+                let target = pc_value.wrapping_add(offset);
+                new("bl", format!("{target:#x}"))
+                    .kind("call")
+                    .target(target)
+            }
+            0b100 => {
+                // Switches to A32, whose code is word aligned
+                let target = (pc_value & !3).wrapping_add(offset) & !3;
+                new("blx", format!("{target:#x}"))
+                    .kind("call")
+                    .target(target)
+            }
+            _ => {
+                let target = pc_value.wrapping_add(offset);
+                new("b.w", format!("{target:#x}"))
+                    .kind("jump")
+                    .target(target)
+            }
+        });
+    }
+    if first & 0xff7f == 0xf85f {
+        let rt = bits(second, 12, 4);
+        let immediate = u64::from(bits(second, 0, 12));
+        let base = pc_value & !3;
+        let (target, offset) = if first & 0x80 != 0 {
+            (base.wrapping_add(immediate), immediate as i64)
+        } else {
+            (base.wrapping_sub(immediate), -(immediate as i64))
+        };
+        let decoded = new("ldr.w", format!("{}, [pc, #{offset}]", r(rt))).target(target);
+        return Some(if rt == PC {
+            decoded.kind("indirect_jump")
+        } else {
+            decoded.writes(rt as u8)
+        });
+    }
+    match (first, second) {
+        (0xe92d, list) => return Some(new("push.w", register_list(list))),
+        (0xe8bd, list) => {
+            let decoded = new("pop.w", register_list(list));
+            return Some(if list & (1 << PC) != 0 {
+                decoded.kind("return")
+            } else {
+                decoded
+            });
+        }
+        (0xf85d, 0xfb04) => return Some(new("pop", register_list(1 << PC)).kind("return")),
+        _ => {}
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(decoded: Decoded) -> String {
+        format!("{} {}", decoded.mnemonic, decoded.operands)
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn test_decode_arm() {
+        let arm = |insn: u32, pc| text(decode_arm(&insn.to_le_bytes(), pc));
+        assert_eq!(arm(0xe92d4800, 0), "push {r11, lr}");
+        assert_eq!(arm(0xebfffffe, 0x1000), "bl 0x1000");
+        assert_eq!(arm(0xe12fff1e, 0), "bx lr");
+        assert_eq!(arm(0xe3a00000, 0), "mov r0, #0");
+        assert_eq!(arm(0xe59f0004, 0x1000), "ldr r0, [pc, #4]");
+        assert_eq!(
+            decode_arm(&0xe59f0004u32.to_le_bytes(), 0x1000).target,
+            Some(0x100c)
+        );
+        assert_eq!(decode_arm(&0xe8bd8800u32.to_le_bytes(), 0).kind, "return");
+    }
+
+    #[test]
+    fn test_decode_thumb() {
+        let thumb = |bytes: &[u8], pc| text(decode_thumb(bytes, pc));
+        assert_eq!(thumb(&0xb580u16.to_le_bytes(), 0), "push {r7, lr}");
+        assert_eq!(thumb(&0x4770u16.to_le_bytes(), 0), "bx lr");
+        assert_eq!(thumb(&0x2000u16.to_le_bytes(), 0), "movs r0, #0");
+        // bl 0x1000 from 0x1000: f7ff fffe
+        let bl = [0xff, 0xf7, 0xfe, 0xff];
+        assert_eq!(thumb(&bl, 0x1000), "bl 0x1000");
+        assert_eq!(decode_thumb(&bl, 0x1000).length, 4);
+        assert_eq!(thumb(&0xbf08u16.to_le_bytes(), 0), "it eq");
+    }
+}
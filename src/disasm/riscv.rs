@@ -0,0 +1,603 @@
+//! RV64GC: integer, atomic and common floating-point instructions with the
+//! usual assembler aliases; compressed instructions are expanded to their
+//! 32-bit form before decoding
+
+use super::{sign_extend, Decoded};
+
+const REGISTERS: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+const FLOAT_REGISTERS: [&str; 32] = [
+    "ft0", "ft1", "ft2", "ft3", "ft4", "ft5", "ft6", "ft7", "fs0", "fs1", "fa0", "fa1", "fa2",
+    "fa3", "fa4", "fa5", "fa6", "fa7", "fs2", "fs3", "fs4", "fs5", "fs6", "fs7", "fs8", "fs9",
+    "fs10", "fs11", "ft8", "ft9", "ft10", "ft11",
+];
+
+fn x(n: u32) -> &'static str {
+    REGISTERS[n as usize & 31]
+}
+
+fn f(n: u32) -> &'static str {
+    FLOAT_REGISTERS[n as usize & 31]
+}
+
+fn bits(value: u32, shift: u32, count: u32) -> u32 {
+    (value >> shift) & ((1 << count) - 1)
+}
+
+pub(super) fn decode(bytes: &[u8], pc: u64) -> Decoded {
+    match bytes {
+        [low, high, third, fourth, ..] if low & 3 == 3 => {
+            let insn = u32::from_le_bytes([*low, *high, *third, *fourth]);
+            decode_word(insn, pc, 4).unwrap_or_else(|| Decoded::unknown(bytes, 4, true))
+        }
+        [low, high, ..] if low & 3 != 3 => {
+            let half = u16::from_le_bytes([*low, *high]);
+            expand(half)
+                .and_then(|insn| decode_word(insn, pc, 2))
+                .unwrap_or_else(|| Decoded::unknown(bytes, 2, true))
+        }
+        _ => Decoded::unknown(bytes, bytes.len(), true).kind("data"),
+    }
+}
+
+fn decode_word(insn: u32, pc: u64, length: usize) -> Option<Decoded> {
+    let rd = bits(insn, 7, 5);
+    let funct3 = bits(insn, 12, 3);
+    let rs1 = bits(insn, 15, 5);
+    let rs2 = bits(insn, 20, 5);
+    let funct7 = bits(insn, 25, 7);
+    let immediate = sign_extend(u64::from(insn >> 20), 12);
+    let store_offset = sign_extend(u64::from(funct7 << 5 | rd), 12);
+    let new = |mnemonic: &str, operands: String| Decoded::new(length, mnemonic, operands);
+
+    let decoded = match insn & 0x7f {
+        0x37 => new("lui", format!("{}, {:#x}", x(rd), insn >> 12)).writes(rd as u8),
+        0x17 => {
+            let value = pc.wrapping_add(i64::from(insn as i32 & !0xfff) as u64);
+            let mut decoded =
+                new("auipc", format!("{}, {:#x}", x(rd), insn >> 12)).writes(rd as u8);
+            decoded.pc_relative = Some((rd as u8, value));
+            decoded
+        }
+        0x6f => {
+            let offset = bits(insn, 31, 1) << 20
+                | bits(insn, 12, 8) << 12
+                | bits(insn, 20, 1) << 11
+                | bits(insn, 21, 10) << 1;
+            let target = pc.wrapping_add(sign_extend(u64::from(offset), 21) as u64);
+            match rd {
+                0 => new("j", format!("{target:#x}")).kind("jump"),
+                1 => new("jal", format!("{target:#x}")).kind("call"),
+                rd => new("jal", format!("{}, {target:#x}", x(rd))).kind("call"),
+            }
+            .target(target)
+            .writes(rd as u8)
+        }
+        0x67 if funct3 == 0 => {
+            let operand = match immediate {
+                0 => x(rs1).to_string(),
+                offset => format!("{offset}({})", x(rs1)),
+            };
+            match rd {
+                0 if rs1 == 1 && immediate == 0 => new("ret", String::new()).kind("return"),
+                0 => new("jr", operand).kind("indirect_jump"),
+                1 => new("jalr", operand).kind("indirect_call"),
+                rd => new("jalr", format!("{}, {operand}", x(rd))).kind("indirect_call"),
+            }
+            .offset_from(rs1 as u8, immediate)
+            .writes(rd as u8)
+        }
+        0x63 => {
+            let offset = bits(insn, 31, 1) << 12
+                | bits(insn, 7, 1) << 11
+                | bits(insn, 25, 6) << 5
+                | bits(insn, 8, 4) << 1;
+            let target = pc.wrapping_add(sign_extend(u64::from(offset), 13) as u64);
+            let mnemonic = ["beq", "bne", "", "", "blt", "bge", "bltu", "bgeu"][funct3 as usize];
+            let (mnemonic, operands) = match (mnemonic, rs1, rs2) {
+                ("", _, _) => return None,
+                ("beq" | "bne" | "blt" | "bge", rs1, 0) => {
+                    let alias = format!("{mnemonic}z");
+                    (alias, format!("{}, {target:#x}", x(rs1)))
+                }
+                ("blt", 0, rs2) => ("bgtz".to_string(), format!("{}, {target:#x}", x(rs2))),
+                ("bge", 0, rs2) => ("blez".to_string(), format!("{}, {target:#x}", x(rs2))),
+                (mnemonic, rs1, rs2) => (
+                    mnemonic.to_string(),
+                    format!("{}, {}, {target:#x}", x(rs1), x(rs2)),
+                ),
+            };
+            new(&mnemonic, operands).kind("branch").target(target)
+        }
+        0x03 => {
+            let mnemonic = ["lb", "lh", "lw", "ld", "lbu", "lhu", "lwu", ""][funct3 as usize];
+            if mnemonic.is_empty() {
+                return None;
+            }
+            new(mnemonic, format!("{}, {immediate}({})", x(rd), x(rs1)))
+                .offset_from(rs1 as u8, immediate)
+                .writes(rd as u8)
+        }
+        0x07 => {
+            let mnemonic = match funct3 {
+                2 => "flw",
+                3 => "fld",
+                _ => return None,
+            };
+            new(mnemonic, format!("{}, {immediate}({})", f(rd), x(rs1)))
+                .offset_from(rs1 as u8, immediate)
+        }
+        0x23 => {
+            let mnemonic = ["sb", "sh", "sw", "sd", "", "", "", ""][funct3 as usize];
+            if mnemonic.is_empty() {
+                return None;
+            }
+            new(mnemonic, format!("{}, {store_offset}({})", x(rs2), x(rs1)))
+                .offset_from(rs1 as u8, store_offset)
+        }
+        0x27 => {
+            let mnemonic = match funct3 {
+                2 => "fsw",
+                3 => "fsd",
+                _ => return None,
+            };
+            new(mnemonic, format!("{}, {store_offset}({})", f(rs2), x(rs1)))
+                .offset_from(rs1 as u8, store_offset)
+        }
+        0x13 => op_immediate(insn, rd, funct3, rs1, immediate, false, length)?,
+        0x1b => op_immediate(insn, rd, funct3, rs1, immediate, true, length)?,
+        0x33 | 0x3b => {
+            let word = insn & 0x7f == 0x3b;
+            let mnemonic = match (funct7, funct3) {
+                (0x00, 0) => "add",
+                (0x20, 0) => "sub",
+                (0x00, 1) => "sll",
+                (0x00, 2) if !word => "slt",
+                (0x00, 3) if !word => "sltu",
+                (0x00, 4) if !word => "xor",
+                (0x00, 5) => "srl",
+                (0x20, 5) => "sra",
+                (0x00, 6) if !word => "or",
+                (0x00, 7) if !word => "and",
+                (0x01, 0) => "mul",
+                (0x01, 1) if !word => "mulh",
+                (0x01, 2) if !word => "mulhsu",
+                (0x01, 3) if !word => "mulhu",
+                (0x01, 4) => "div",
+                (0x01, 5) => "divu",
+                (0x01, 6) => "rem",
+                (0x01, 7) => "remu",
+                _ => return None,
+            };
+            let suffix = if word { "w" } else { "" };
+            match (mnemonic, rs1) {
+                ("add", 0) if !word => new("mv", format!("{}, {}", x(rd), x(rs2))),
+                ("sub", 0) => new(&format!("neg{suffix}"), format!("{}, {}", x(rd), x(rs2))),
+                ("sltu", 0) => new("snez", format!("{}, {}", x(rd), x(rs2))),
+                _ => new(
+                    &format!("{mnemonic}{suffix}"),
+                    format!("{}, {}, {}", x(rd), x(rs1), x(rs2)),
+                ),
+            }
+            .writes(rd as u8)
+        }
+        0x2f => {
+            let operation = match bits(insn, 27, 5) {
+                0x00 => "amoadd",
+                0x01 => "amoswap",
+                0x02 => "lr",
+                0x03 => "sc",
+                0x04 => "amoxor",
+                0x08 => "amoor",
+                0x0c => "amoand",
+                0x10 => "amomin",
+                0x14 => "amomax",
+                0x18 => "amominu",
+                0x1c => "amomaxu",
+                _ => return None,
+            };
+            let width = match funct3 {
+                2 => "w",
+                3 => "d",
+                _ => return None,
+            };
+            let ordering = match bits(insn, 25, 2) {
+                0 => "",
+                1 => ".rl",
+                2 => ".aq",
+                _ => ".aqrl",
+            };
+            let mnemonic = format!("{operation}.{width}{ordering}");
+            let operands = if operation == "lr" {
+                format!("{}, ({})", x(rd), x(rs1))
+            } else {
+                format!("{}, {}, ({})", x(rd), x(rs2), x(rs1))
+            };
+            new(&mnemonic, operands).writes(rd as u8)
+        }
+        0x53 => float_operation(rd, funct3, rs1, rs2, funct7, length)?,
+        0x0f => match funct3 {
+            0 => new("fence", String::new()),
+            1 => new("fence.i", String::new()),
+            _ => return None,
+        },
+        0x73 => match insn {
+            0x0000_0073 => new("ecall", String::new()),
+            0x0010_0073 => new("ebreak", String::new()),
+            0x1050_0073 => new("wfi", String::new()),
+            _ => {
+                let csr = insn >> 20;
+                let mnemonic = [
+                    "", "csrrw", "csrrs", "csrrc", "", "csrrwi", "csrrsi", "csrrci",
+                ][funct3 as usize];
+                if mnemonic.is_empty() {
+                    return None;
+                }
+                let source = if funct3 >= 5 {
+                    rs1.to_string()
+                } else {
+                    x(rs1).to_string()
+                };
+                if mnemonic == "csrrs" && rs1 == 0 {
+                    new("csrr", format!("{}, {csr:#x}", x(rd)))
+                } else {
+                    new(mnemonic, format!("{}, {csr:#x}, {source}", x(rd)))
+                }
+                .writes(rd as u8)
+            }
+        },
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+fn op_immediate(
+    insn: u32,
+    rd: u32,
+    funct3: u32,
+    rs1: u32,
+    immediate: i64,
+    word: bool,
+    length: usize,
+) -> Option<Decoded> {
+    let new = |mnemonic: &str, operands: String| Decoded::new(length, mnemonic, operands);
+    let suffix = if word { "w" } else { "" };
+    let shift = if word {
+        bits(insn, 20, 5)
+    } else {
+        bits(insn, 20, 6)
+    };
+    let decoded = match (funct3, word) {
+        (0, false) if rd == 0 && rs1 == 0 && immediate == 0 => new("nop", String::new()),
+        (0, false) if rs1 == 0 => new("li", format!("{}, {immediate}", x(rd))),
+        (0, false) if immediate == 0 => new("mv", format!("{}, {}", x(rd), x(rs1))),
+        (0, true) if immediate == 0 => new("sext.w", format!("{}, {}", x(rd), x(rs1))),
+        (0, _) => new(
+            &format!("addi{suffix}"),
+            format!("{}, {}, {immediate}", x(rd), x(rs1)),
+        ),
+        (1, _) => new(
+            &format!("slli{suffix}"),
+            format!("{}, {}, {shift:#x}", x(rd), x(rs1)),
+        ),
+        (5, _) => {
+            let mnemonic = if insn & (1 << 30) != 0 {
+                "srai"
+            } else {
+                "srli"
+            };
+            new(
+                &format!("{mnemonic}{suffix}"),
+                format!("{}, {}, {shift:#x}", x(rd), x(rs1)),
+            )
+        }
+        (3, false) if immediate == 1 => new("seqz", format!("{}, {}", x(rd), x(rs1))),
+        (4, false) if immediate == -1 => new("not", format!("{}, {}", x(rd), x(rs1))),
+        (2 | 3 | 4 | 6 | 7, false) => {
+            let mnemonic = ["", "", "slti", "sltiu", "xori", "", "ori", "andi"][funct3 as usize];
+            new(mnemonic, format!("{}, {}, {immediate}", x(rd), x(rs1)))
+        }
+        _ => return None,
+    };
+    let decoded = if funct3 == 0 && !word {
+        decoded.offset_from(rs1 as u8, immediate)
+    } else {
+        decoded
+    };
+    Some(decoded.writes(rd as u8))
+}
+
+/// Single- and double-precision arithmetic, moves, compares and conversions
+fn float_operation(
+    rd: u32,
+    funct3: u32,
+    rs1: u32,
+    rs2: u32,
+    funct7: u32,
+    length: usize,
+) -> Option<Decoded> {
+    let new = |mnemonic: &str, operands: String| Decoded::new(length, mnemonic, operands);
+    let precision = match funct7 & 3 {
+        0 => "s",
+        1 => "d",
+        _ => return None,
+    };
+    let integer = ["w", "wu", "l", "lu"].get(rs2 as usize).copied();
+    let decoded = match (funct7 >> 2, funct3) {
+        (0x00..=0x03, _) => {
+            let mnemonic = ["fadd", "fsub", "fmul", "fdiv"][(funct7 >> 2) as usize];
+            new(
+                &format!("{mnemonic}.{precision}"),
+                format!("{}, {}, {}", f(rd), f(rs1), f(rs2)),
+            )
+        }
+        (0x04, 0..=2) if rs1 == rs2 => {
+            let mnemonic = ["fmv", "fneg", "fabs"][funct3 as usize];
+            new(
+                &format!("{mnemonic}.{precision}"),
+                format!("{}, {}", f(rd), f(rs1)),
+            )
+        }
+        (0x04, 0..=2) => {
+            let mnemonic = ["fsgnj", "fsgnjn", "fsgnjx"][funct3 as usize];
+            new(
+                &format!("{mnemonic}.{precision}"),
+                format!("{}, {}, {}", f(rd), f(rs1), f(rs2)),
+            )
+        }
+        (0x05, 0 | 1) => {
+            let mnemonic = ["fmin", "fmax"][funct3 as usize];
+            new(
+                &format!("{mnemonic}.{precision}"),
+                format!("{}, {}, {}", f(rd), f(rs1), f(rs2)),
+            )
+        }
+        (0x08, _) => {
+            let source = if precision == "s" { "d" } else { "s" };
+            new(
+                &format!("fcvt.{precision}.{source}"),
+                format!("{}, {}", f(rd), f(rs1)),
+            )
+        }
+        (0x0b, _) if rs2 == 0 => new(
+            &format!("fsqrt.{precision}"),
+            format!("{}, {}", f(rd), f(rs1)),
+        ),
+        (0x14, 0..=2) => {
+            let mnemonic = ["fle", "flt", "feq"][funct3 as usize];
+            new(
+                &format!("{mnemonic}.{precision}"),
+                format!("{}, {}, {}", x(rd), f(rs1), f(rs2)),
+            )
+            .writes(rd as u8)
+        }
+        (0x18, _) => new(
+            &format!("fcvt.{}.{precision}", integer?),
+            format!("{}, {}", x(rd), f(rs1)),
+        )
+        .writes(rd as u8),
+        (0x1a, _) => new(
+            &format!("fcvt.{precision}.{}", integer?),
+            format!("{}, {}", f(rd), x(rs1)),
+        ),
+        (0x1c, 0) if rs2 == 0 => {
+            let target = if precision == "s" { "w" } else { "d" };
+            new(&format!("fmv.x.{target}"), format!("{}, {}", x(rd), f(rs1))).writes(rd as u8)
+        }
+        (0x1c, 1) if rs2 == 0 => new(
+            &format!("fclass.{precision}"),
+            format!("{}, {}", x(rd), f(rs1)),
+        )
+        .writes(rd as u8),
+        (0x1e, 0) if rs2 == 0 => {
+            let source = if precision == "s" { "w" } else { "d" };
+            new(&format!("fmv.{source}.x"), format!("{}, {}", f(rd), x(rs1)))
+        }
+        _ => return None,
+    };
+    Some(decoded)
+}
+
+fn i_type(opcode: u32, rd: u32, funct3: u32, rs1: u32, immediate: i32) -> u32 {
+    (immediate as u32 & 0xfff) << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn s_type(opcode: u32, funct3: u32, rs1: u32, rs2: u32, immediate: i32) -> u32 {
+    let immediate = immediate as u32;
+    (immediate >> 5 & 0x7f) << 25
+        | rs2 << 20
+        | rs1 << 15
+        | funct3 << 12
+        | (immediate & 0x1f) << 7
+        | opcode
+}
+
+fn r_type(opcode: u32, rd: u32, funct3: u32, rs1: u32, rs2: u32, funct7: u32) -> u32 {
+    funct7 << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn b_type(funct3: u32, rs1: u32, rs2: u32, offset: i32) -> u32 {
+    let offset = offset as u32;
+    bits(offset, 12, 1) << 31
+        | bits(offset, 5, 6) << 25
+        | rs2 << 20
+        | rs1 << 15
+        | funct3 << 12
+        | bits(offset, 1, 4) << 8
+        | bits(offset, 11, 1) << 7
+        | 0x63
+}
+
+fn j_type(rd: u32, offset: i32) -> u32 {
+    let offset = offset as u32;
+    bits(offset, 20, 1) << 31
+        | bits(offset, 1, 10) << 21
+        | bits(offset, 11, 1) << 20
+        | bits(offset, 12, 8) << 12
+        | rd << 7
+        | 0x6f
+}
+
+/// 32-bit equivalent of a compressed instruction; None for reserved encodings
+fn expand(half: u16) -> Option<u32> {
+    let c = u32::from(half);
+    let funct3 = bits(c, 13, 3);
+    let rd = bits(c, 7, 5);
+    let rs2 = bits(c, 2, 5);
+    // Registers x8-x15 of the three-bit fields
+    let rd_short = 8 + bits(c, 2, 3);
+    let rs1_short = 8 + bits(c, 7, 3);
+    let immediate6 = sign_extend(u64::from(bits(c, 12, 1) << 5 | bits(c, 2, 5)), 6) as i32;
+    let shift = (bits(c, 12, 1) << 5 | bits(c, 2, 5)) as i32;
+    // Offsets of doubleword and word loads and stores
+    let offset_d = (bits(c, 10, 3) << 3 | bits(c, 5, 2) << 6) as i32;
+    let offset_w = (bits(c, 10, 3) << 3 | bits(c, 6, 1) << 2 | bits(c, 5, 1) << 6) as i32;
+
+    let insn = match (c & 3, funct3) {
+        (0, 0) => {
+            let offset =
+                bits(c, 11, 2) << 4 | bits(c, 7, 4) << 6 | bits(c, 6, 1) << 2 | bits(c, 5, 1) << 3;
+            if offset == 0 {
+                return None;
+            }
+            i_type(0x13, rd_short, 0, 2, offset as i32)
+        }
+        (0, 1) => i_type(0x07, rd_short, 3, rs1_short, offset_d),
+        (0, 2) => i_type(0x03, rd_short, 2, rs1_short, offset_w),
+        (0, 3) => i_type(0x03, rd_short, 3, rs1_short, offset_d),
+        (0, 5) => s_type(0x27, 3, rs1_short, rd_short, offset_d),
+        (0, 6) => s_type(0x23, 2, rs1_short, rd_short, offset_w),
+        (0, 7) => s_type(0x23, 3, rs1_short, rd_short, offset_d),
+        (1, 0) => i_type(0x13, rd, 0, rd, immediate6),
+        (1, 1) if rd != 0 => i_type(0x1b, rd, 0, rd, immediate6),
+        (1, 2) => i_type(0x13, rd, 0, 0, immediate6),
+        (1, 3) if rd == 2 => {
+            let offset = bits(c, 12, 1) << 9
+                | bits(c, 6, 1) << 4
+                | bits(c, 5, 1) << 6
+                | bits(c, 3, 2) << 7
+                | bits(c, 2, 1) << 5;
+            i_type(0x13, 2, 0, 2, sign_extend(u64::from(offset), 10) as i32)
+        }
+        (1, 3) if immediate6 != 0 => (immediate6 as u32) << 12 | rd << 7 | 0x37,
+        (1, 4) => match bits(c, 10, 2) {
+            0 => i_type(0x13, rs1_short, 5, rs1_short, shift),
+            1 => i_type(0x13, rs1_short, 5, rs1_short, 0x400 | shift),
+            2 => i_type(0x13, rs1_short, 7, rs1_short, immediate6),
+            _ => {
+                let (funct3, funct7, opcode) = match (bits(c, 12, 1), bits(c, 5, 2)) {
+                    (0, 0) => (0, 0x20, 0x33),
+                    (0, 1) => (4, 0, 0x33),
+                    (0, 2) => (6, 0, 0x33),
+                    (0, 3) => (7, 0, 0x33),
+                    (1, 0) => (0, 0x20, 0x3b),
+                    (1, 1) => (0, 0, 0x3b),
+                    _ => return None,
+                };
+                r_type(opcode, rs1_short, funct3, rs1_short, rd_short, funct7)
+            }
+        },
+        (1, 5) => {
+            let offset = bits(c, 12, 1) << 11
+                | bits(c, 11, 1) << 4
+                | bits(c, 9, 2) << 8
+                | bits(c, 8, 1) << 10
+                | bits(c, 7, 1) << 6
+                | bits(c, 6, 1) << 7
+                | bits(c, 3, 3) << 1
+                | bits(c, 2, 1) << 5;
+            j_type(0, sign_extend(u64::from(offset), 12) as i32)
+        }
+        (1, 6 | 7) => {
+            let offset = bits(c, 12, 1) << 8
+                | bits(c, 10, 2) << 3
+                | bits(c, 5, 2) << 6
+                | bits(c, 3, 2) << 1
+                | bits(c, 2, 1) << 5;
+            b_type(
+                funct3 - 6,
+                rs1_short,
+                0,
+                sign_extend(u64::from(offset), 9) as i32,
+            )
+        }
+        (2, 0) => i_type(0x13, rd, 1, rd, shift),
+        (2, 1) => {
+            let offset = bits(c, 12, 1) << 5 | bits(c, 5, 2) << 3 | bits(c, 2, 3) << 6;
+            i_type(0x07, rd, 3, 2, offset as i32)
+        }
+        (2, 2) if rd != 0 => {
+            let offset = bits(c, 12, 1) << 5 | bits(c, 4, 3) << 2 | bits(c, 2, 2) << 6;
+            i_type(0x03, rd, 2, 2, offset as i32)
+        }
+        (2, 3) if rd != 0 => {
+            let offset = bits(c, 12, 1) << 5 | bits(c, 5, 2) << 3 | bits(c, 2, 3) << 6;
+            i_type(0x03, rd, 3, 2, offset as i32)
+        }
+        (2, 4) => match (bits(c, 12, 1), rd, rs2) {
+            (0, 0, _) => return None,
+            (0, rs1, 0) => i_type(0x67, 0, 0, rs1, 0),
+            (0, rd, rs2) => r_type(0x33, rd, 0, 0, rs2, 0),
+            (_, 0, 0) => 0x0010_0073,
+            (_, rs1, 0) => i_type(0x67, 1, 0, rs1, 0),
+            (_, rd, rs2) => r_type(0x33, rd, 0, rd, rs2, 0),
+        },
+        (2, 5) => {
+            let offset = bits(c, 10, 3) << 3 | bits(c, 7, 3) << 6;
+            s_type(0x27, 3, 2, rs2, offset as i32)
+        }
+        (2, 6) => {
+            let offset = bits(c, 9, 4) << 2 | bits(c, 7, 2) << 6;
+            s_type(0x23, 2, 2, rs2, offset as i32)
+        }
+        (2, 7) => {
+            let offset = bits(c, 10, 3) << 3 | bits(c, 7, 3) << 6;
+            s_type(0x23, 3, 2, rs2, offset as i32)
+        }
+        _ => return None,
+    };
+    Some(insn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(bytes: &[u8], pc: u64) -> String {
+        let decoded = decode(bytes, pc);
+        format!("{} {}", decoded.mnemonic, decoded.operands)
+            .trim()
+            .to_string()
+    }
+
+    #[test]
+    fn test_decode_common_instructions() {
+        assert_eq!(text(&0x00008067u32.to_le_bytes(), 0), "ret");
+        assert_eq!(text(&0xfe010113u32.to_le_bytes(), 0), "addi sp, sp, -32");
+        assert_eq!(text(&0x00113c23u32.to_le_bytes(), 0), "sd ra, 24(sp)");
+        assert_eq!(text(&0x02a00513u32.to_le_bytes(), 0), "li a0, 42");
+        assert_eq!(text(&0x010000efu32.to_le_bytes(), 0x1000), "jal 0x1010");
+        assert_eq!(
+            text(&0x00050463u32.to_le_bytes(), 0x1000),
+            "beqz a0, 0x1008"
+        );
+        assert_eq!(text(&0x02b50533u32.to_le_bytes(), 0), "mul a0, a0, a1");
+        assert_eq!(text(&0x0005051bu32.to_le_bytes(), 0), "sext.w a0, a0");
+    }
+
+    #[test]
+    fn test_decode_compressed_instructions() {
+        assert_eq!(text(&0x8082u16.to_le_bytes(), 0), "ret");
+        assert_eq!(decode(&0x8082u16.to_le_bytes(), 0).length, 2);
+        assert_eq!(text(&0x1141u16.to_le_bytes(), 0), "addi sp, sp, -16");
+        assert_eq!(text(&0xe406u16.to_le_bytes(), 0), "sd ra, 8(sp)");
+        assert_eq!(text(&0x60a2u16.to_le_bytes(), 0), "ld ra, 8(sp)");
+        assert_eq!(text(&0x4501u16.to_le_bytes(), 0), "li a0, 0");
+        assert_eq!(text(&0x852eu16.to_le_bytes(), 0), "mv a0, a1");
+        assert_eq!(text(&0x0001u16.to_le_bytes(), 0), "nop");
+        assert_eq!(text(&0xa001u16.to_le_bytes(), 0x1000), "j 0x1000");
+    }
+}
@@ -0,0 +1,54 @@
+//! x86 and x86_64 through iced-x86, in AT&T syntax like objdump
+
+use super::Decoded;
+use iced_x86::{
+    Decoder, DecoderOptions, FlowControl, Formatter, GasFormatter, Instruction, OpKind,
+};
+
+pub(super) fn decode(bytes: &[u8], address: u64, bitness: u32) -> Decoded {
+    let mut decoder = Decoder::with_ip(bitness, bytes, address, DecoderOptions::NONE);
+    let mut instruction = Instruction::default();
+    decoder.decode_out(&mut instruction);
+    if instruction.is_invalid() {
+        return Decoded::unknown(bytes, 1, true).kind("data");
+    }
+
+    let mut formatter = GasFormatter::new();
+    formatter.options_mut().set_branch_leading_zeros(false);
+    formatter
+        .options_mut()
+        .set_space_after_operand_separator(false);
+    formatter.options_mut().set_uppercase_hex(false);
+    formatter.options_mut().set_show_branch_size(false);
+    let mut mnemonic = String::new();
+    formatter.format_mnemonic(&instruction, &mut mnemonic);
+    let mut operands = String::new();
+    formatter.format_all_operands(&instruction, &mut operands);
+
+    let kind = match instruction.flow_control() {
+        FlowControl::Call => "call",
+        FlowControl::IndirectCall => "indirect_call",
+        FlowControl::UnconditionalBranch => "jump",
+        FlowControl::IndirectBranch => "indirect_jump",
+        FlowControl::ConditionalBranch => "branch",
+        FlowControl::Return => "return",
+        _ => "other",
+    };
+    let mut decoded = Decoded::new(instruction.len(), &mnemonic, operands).kind(kind);
+    if matches!(
+        instruction.op0_kind(),
+        OpKind::NearBranch16 | OpKind::NearBranch32 | OpKind::NearBranch64
+    ) {
+        decoded = decoded.target(instruction.near_branch_target());
+    } else if instruction.is_ip_rel_memory_operand() {
+        decoded = decoded.target(instruction.ip_rel_memory_address());
+    }
+    decoded.immediate =
+        (0..instruction.op_count()).find_map(|operand| match instruction.op_kind(operand) {
+            OpKind::Immediate32 | OpKind::Immediate32to64 | OpKind::Immediate64 => {
+                Some(instruction.immediate(operand))
+            }
+            _ => None,
+        });
+    decoded
+}
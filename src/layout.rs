@@ -0,0 +1,821 @@
+//! Struct layout analysis in the spirit of `pahole`: holes between members,
+//! trailing padding, cacheline placement and a padding-minimising member order
+
+use crate::{ElfInfo, TypeInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub const DEFAULT_CACHELINE_SIZE: u64 = 64;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StructLayout {
+    pub name: String,
+    pub kind: String, // "struct" or "class"
+    pub size: u64,
+    pub alignment: u64,
+    pub cacheline_size: u64,
+    /// Number of cachelines the type spans when it starts on a cacheline boundary
+    pub cachelines: u64,
+    /// Base class subobjects and data members in offset order
+    pub members: Vec<FieldLayout>,
+    pub holes: Vec<Hole>,
+    pub trailing_padding: u64,
+    /// Bytes in holes plus trailing padding
+    pub wasted_bytes: u64,
+    /// Member names in the order that minimises padding
+    pub suggested_order: Vec<String>,
+    pub suggested_size: u64,
+    /// `size - suggested_size`; 0 when the current order is already optimal
+    pub savings: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FieldLayout {
+    pub name: String,
+    pub type_name: String,
+    pub offset: u64,
+    pub size: u64,
+    pub alignment: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_size: Option<u64>,
+    /// Index of the cacheline holding the first byte
+    pub cacheline: u64,
+    pub crosses_cacheline: bool,
+    /// Base class subobject rather than a data member
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_base: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Hole {
+    /// Member the hole follows; None for a hole before the first member
+    pub after: Option<String>,
+    pub offset: u64,
+    /// Whole unused bytes
+    pub size: u64,
+    /// Unused bits around bit-fields, on top of `size`
+    pub bits: u64,
+}
+
+/// Structs ranked by the padding they cost across all global instances
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PaddingReport {
+    /// Padding bytes held by global instances, summed over all structs
+    pub total_padding: u64,
+    /// Bytes reordering every struct would save across global instances
+    pub total_savings: u64,
+    pub structs: Vec<PaddingRank>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PaddingRank {
+    pub name: String,
+    pub size: u64,
+    /// Holes plus trailing padding of one instance
+    pub padding: u64,
+    /// Bytes one instance would shrink by with the suggested member order
+    pub savings: u64,
+    /// Instances in writable, allocated sections (`.data`, `.bss`, ...),
+    /// counting array elements and structs embedded by value
+    pub instances: u64,
+    pub total_padding: u64,
+    pub total_savings: u64,
+    /// Global variables holding the instances
+    pub variables: Vec<String>,
+}
+
+/// Rank structs by `padding * instances`, then by their own padding and size
+pub fn padding_report(elf_info: &ElfInfo) -> PaddingReport {
+    let mut ranks: HashMap<(String, u64), PaddingRank> = HashMap::new();
+    let mut instances = Vec::new();
+    for variable in &elf_info.variables {
+        let in_ram = variable
+            .address
+            .and_then(|address| elf_info.section_containing(address))
+            .is_some_and(|section| section.write);
+        if in_ram {
+            collect_instances(
+                &variable.type_info,
+                None,
+                1,
+                &mut |type_info, name, count| {
+                    instances.push((type_info.clone(), name.to_string(), count, &variable.name));
+                },
+            );
+        }
+    }
+    for (type_info, name, count, variable) in &instances {
+        if let Some(rank) = padding_rank(&mut ranks, elf_info, type_info, name) {
+            rank.instances = rank.instances.saturating_add(*count);
+            if !rank.variables.contains(variable) {
+                rank.variables.push((*variable).clone());
+            }
+        }
+    }
+    // Structs without global instances still rank by their own padding
+    for type_info in &elf_info.types {
+        if type_info.name != "<anonymous>" {
+            padding_rank(&mut ranks, elf_info, type_info, &layout_name(type_info));
+        }
+    }
+
+    let mut structs: Vec<PaddingRank> = ranks
+        .into_values()
+        .map(|mut rank| {
+            rank.total_padding = rank.padding.saturating_mul(rank.instances);
+            rank.total_savings = rank.savings.saturating_mul(rank.instances);
+            rank
+        })
+        .collect();
+    structs.sort_by(|a, b| {
+        b.total_padding
+            .cmp(&a.total_padding)
+            .then(b.padding.cmp(&a.padding))
+            .then(b.size.cmp(&a.size))
+            .then(a.name.cmp(&b.name))
+    });
+
+    PaddingReport {
+        total_padding: structs
+            .iter()
+            .fold(0, |total, rank| total.saturating_add(rank.total_padding)),
+        total_savings: structs
+            .iter()
+            .fold(0, |total, rank| total.saturating_add(rank.total_savings)),
+        structs,
+    }
+}
+
+/// Entry for `type_info` under `name`, analysing its layout on first use
+fn padding_rank<'a>(
+    ranks: &'a mut HashMap<(String, u64), PaddingRank>,
+    elf_info: &ElfInfo,
+    type_info: &TypeInfo,
+    name: &str,
+) -> Option<&'a mut PaddingRank> {
+    let key = (name.to_string(), type_info.size?);
+    if !ranks.contains_key(&key) {
+        let layout = analyze_layout(type_info, &elf_info.types, DEFAULT_CACHELINE_SIZE)?;
+        ranks.insert(
+            key.clone(),
+            PaddingRank {
+                name: name.to_string(),
+                size: layout.size,
+                padding: layout.wasted_bytes,
+                savings: layout.savings,
+                ..Default::default()
+            },
+        );
+    }
+    ranks.get_mut(&key)
+}
+
+/// Call `visit` for every struct or class stored by value in `type_info`,
+/// with the number of instances (array elements multiply). Anonymous structs
+/// are named after the typedef that introduces them
+fn collect_instances(
+    type_info: &TypeInfo,
+    typedef_name: Option<&str>,
+    count: u64,
+    visit: &mut dyn FnMut(&TypeInfo, &str, u64),
+) {
+    if count == 0 {
+        return;
+    }
+    match type_info.kind.as_str() {
+        "typedef" => {
+            if let Some(target) = &type_info.target {
+                collect_instances(target, Some(&type_info.name), count, visit);
+            }
+        }
+        "const" | "volatile" | "restrict" | "atomic" => {
+            if let Some(target) = &type_info.target {
+                collect_instances(target, typedef_name, count, visit);
+            }
+        }
+        "array" => {
+            if let Some(array) = &type_info.array {
+                let elements = array
+                    .dimensions
+                    .iter()
+                    .try_fold(1u64, |elements, dimension| {
+                        elements.checked_mul(dimension.count.unwrap_or(0))
+                    })
+                    .unwrap_or(u64::MAX);
+                collect_instances(
+                    &array.element_type,
+                    None,
+                    count.saturating_mul(elements),
+                    visit,
+                );
+            }
+        }
+        "struct" | "class" | "union" => {
+            if type_info.kind != "union" {
+                let name = match typedef_name {
+                    Some(typedef_name) if type_info.name == "<anonymous>" => {
+                        typedef_name.to_string()
+                    }
+                    _ => layout_name(type_info),
+                };
+                visit(type_info, &name, count);
+            }
+            for member in &type_info.members {
+                collect_instances(&member.type_info, None, count, visit);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn layout_name(type_info: &TypeInfo) -> String {
+    type_info
+        .qualified_name
+        .clone()
+        .unwrap_or_else(|| type_info.name.clone())
+}
+
+/// Layouts of every complete struct and class in `elf_info.types`
+pub fn analyze_layouts(elf_info: &ElfInfo, cacheline_size: u64) -> Vec<StructLayout> {
+    elf_info
+        .types
+        .iter()
+        .filter_map(|type_info| analyze_layout(type_info, &elf_info.types, cacheline_size))
+        .collect()
+}
+
+/// Analyse one struct or class; `types` is used to size base class subobjects.
+/// Returns None for other kinds and for declarations without a size
+pub fn analyze_layout(
+    type_info: &TypeInfo,
+    types: &[TypeInfo],
+    cacheline_size: u64,
+) -> Option<StructLayout> {
+    if !matches!(type_info.kind.as_str(), "struct" | "class") {
+        return None;
+    }
+    let size = type_info.size?;
+    let cacheline_size = cacheline_size.max(1);
+
+    let mut members = base_layouts(type_info, types);
+    members.extend(type_info.members.iter().map(|member| {
+        let member_size = member
+            .bit_size
+            .map_or(member.type_info.size.unwrap_or(0), |bits| {
+                (member.bit_offset.unwrap_or(member.offset * 8) % 8 + bits).div_ceil(8)
+            });
+        FieldLayout {
+            name: member.name.clone(),
+            type_name: member.type_info.name.clone(),
+            offset: member.offset,
+            size: member_size,
+            alignment: member
+                .alignment
+                .unwrap_or_else(|| type_alignment(&member.type_info)),
+            bit_offset: member.bit_offset,
+            bit_size: member.bit_size,
+            ..Default::default()
+        }
+    }));
+    members.sort_by_key(|field| (start_bit(field), !field.is_base));
+    for field in &mut members {
+        field.cacheline = field.offset / cacheline_size;
+        field.crosses_cacheline =
+            field.size > 0 && (field.offset + field.size - 1) / cacheline_size != field.cacheline;
+    }
+
+    // Walk the members in bits so that bit-field gaps are accounted for
+    let mut holes = Vec::new();
+    let mut cursor = 0;
+    let mut previous: Option<&FieldLayout> = None;
+    for field in &members {
+        let start = start_bit(field);
+        if start > cursor {
+            holes.push(hole_between(previous, cursor, start));
+        }
+        cursor = cursor.max(end_bit(field));
+        previous = Some(field);
+    }
+    let used_bytes = cursor.div_ceil(8);
+    if cursor % 8 != 0 && used_bytes <= size {
+        // Unused high bits of the last bit-field storage byte
+        holes.push(Hole {
+            after: previous.map(|field| field.name.clone()),
+            offset: cursor / 8,
+            size: 0,
+            bits: 8 - cursor % 8,
+        });
+    }
+    let trailing_padding = size.saturating_sub(used_bytes);
+    let wasted_bytes = holes.iter().map(|hole| hole.size).sum::<u64>() + trailing_padding;
+
+    let alignment = type_alignment(type_info);
+    let (suggested_order, suggested_size) = suggest_order(&members, alignment);
+    let savings = size.saturating_sub(suggested_size);
+    let (suggested_order, suggested_size) = if savings > 0 {
+        (suggested_order, suggested_size)
+    } else {
+        (
+            members
+                .iter()
+                .filter(|field| !field.is_base)
+                .map(|field| field.name.clone())
+                .collect(),
+            size,
+        )
+    };
+
+    Some(StructLayout {
+        name: layout_name(type_info),
+        kind: type_info.kind.clone(),
+        size,
+        alignment,
+        cacheline_size,
+        cachelines: size.div_ceil(cacheline_size),
+        members,
+        holes,
+        trailing_padding,
+        wasted_bytes,
+        suggested_order,
+        suggested_size,
+        savings,
+    })
+}
+
+/// Non-virtual base class subobjects, sized from their definitions in `types`
+fn base_layouts(type_info: &TypeInfo, types: &[TypeInfo]) -> Vec<FieldLayout> {
+    let Some(class) = &type_info.class else {
+        return Vec::new();
+    };
+    class
+        .bases
+        .iter()
+        .filter_map(|base| {
+            let offset = base.offset?;
+            let definition = types.iter().find(|candidate| {
+                matches!(candidate.kind.as_str(), "struct" | "class")
+                    && (candidate.qualified_name.as_ref() == Some(&base.name)
+                        || candidate.name == base.name)
+                    && candidate.size.is_some()
+            });
+            // Empty bases occupy no storage (empty base optimisation)
+            let size = definition
+                .filter(|definition| {
+                    !definition.members.is_empty()
+                        || definition
+                            .class
+                            .as_ref()
+                            .is_some_and(|class| !class.bases.is_empty())
+                })
+                .and_then(|definition| definition.size)
+                .unwrap_or(0);
+            Some(FieldLayout {
+                name: base.name.clone(),
+                type_name: base.name.clone(),
+                offset,
+                size,
+                alignment: definition.map_or(1, type_alignment),
+                is_base: true,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+fn start_bit(field: &FieldLayout) -> u64 {
+    field.bit_offset.unwrap_or(field.offset * 8)
+}
+
+fn end_bit(field: &FieldLayout) -> u64 {
+    match field.bit_size {
+        Some(bits) => start_bit(field) + bits,
+        None => (field.offset + field.size) * 8,
+    }
+}
+
+fn hole_between(previous: Option<&FieldLayout>, from_bit: u64, to_bit: u64) -> Hole {
+    let first_byte = from_bit.div_ceil(8);
+    let last_byte = to_bit / 8;
+    let size = last_byte.saturating_sub(first_byte);
+    Hole {
+        after: previous.map(|field| field.name.clone()),
+        offset: if size > 0 { first_byte } else { from_bit / 8 },
+        size,
+        bits: (to_bit - from_bit) - size * 8,
+    }
+}
+
+/// Alignment from `DW_AT_alignment`, or the natural alignment implied by the type
+pub fn type_alignment(type_info: &TypeInfo) -> u64 {
+    if let Some(alignment) = type_info.alignment {
+        return alignment.max(1);
+    }
+    match type_info.kind.as_str() {
+        "typedef" | "const" | "volatile" | "restrict" | "atomic" => {
+            type_info.target.as_deref().map_or(1, type_alignment)
+        }
+        "array" => type_info
+            .array
+            .as_ref()
+            .map_or(1, |array| type_alignment(&array.element_type)),
+        "struct" | "class" | "union" if !type_info.members.is_empty() => type_info
+            .members
+            .iter()
+            .map(|member| {
+                member
+                    .alignment
+                    .unwrap_or_else(|| type_alignment(&member.type_info))
+            })
+            .max()
+            .unwrap_or(1),
+        _ => match type_info.size {
+            // Scalars are aligned to their size, up to 16 bytes
+            Some(size) if size > 0 => 1 << size.trailing_zeros().min(4),
+            _ => 1,
+        },
+    }
+}
+
+/// Order members by decreasing alignment (then size), keeping base classes and
+/// the vtable pointer first, and return that order with its resulting size
+fn suggest_order(members: &[FieldLayout], alignment: u64) -> (Vec<String>, u64) {
+    // Bit-fields sharing storage move together as one unit
+    let mut units: Vec<(Vec<&FieldLayout>, u64, u64)> = Vec::new();
+    for field in members {
+        if let Some((group, group_size, group_alignment)) = units.last_mut() {
+            let last = group[group.len() - 1];
+            if field.bit_size.is_some()
+                && last.bit_size.is_some()
+                && start_bit(field) < (group[0].offset + *group_size) * 8
+            {
+                group.push(field);
+                *group_size = (*group_size).max(end_bit(field).div_ceil(8) - group[0].offset);
+                *group_alignment = (*group_alignment).max(field.alignment);
+                continue;
+            }
+        }
+        let unit_size = if field.bit_size.is_some() {
+            // Claim the whole storage unit of the declared type
+            field.size.max(field.alignment)
+        } else {
+            field.size
+        };
+        units.push((vec![field], unit_size, field.alignment));
+    }
+
+    let pinned = units
+        .iter()
+        .take_while(|(group, _, _)| group[0].is_base || group[0].name.starts_with("_vptr"))
+        .count();
+    let mut movable: Vec<_> = units.split_off(pinned);
+    movable.sort_by(|(_, a_size, a_alignment), (_, b_size, b_alignment)| {
+        b_alignment.cmp(a_alignment).then(b_size.cmp(a_size))
+    });
+
+    let mut cursor = units
+        .iter()
+        .map(|(group, size, _)| group[0].offset + size)
+        .max()
+        .unwrap_or(0);
+    // Place each unit in the first alignment gap it fits, else at the end
+    let mut gaps: Vec<(u64, u64)> = Vec::new();
+    let mut placed: Vec<(u64, &Vec<&FieldLayout>)> = Vec::new();
+    for (group, unit_size, unit_alignment) in &movable {
+        let unit_alignment = (*unit_alignment).max(1);
+        let fit = gaps
+            .iter()
+            .position(|&(start, end)| start.next_multiple_of(unit_alignment) + unit_size <= end);
+        let offset = match fit {
+            Some(index) => {
+                let (start, end) = gaps.remove(index);
+                let offset = start.next_multiple_of(unit_alignment);
+                if offset > start {
+                    gaps.insert(index, (start, offset));
+                }
+                if offset + unit_size < end {
+                    gaps.push((offset + unit_size, end));
+                }
+                offset
+            }
+            None => {
+                let offset = cursor.next_multiple_of(unit_alignment);
+                if offset > cursor {
+                    gaps.push((cursor, offset));
+                }
+                cursor = offset + unit_size;
+                offset
+            }
+        };
+        placed.push((offset, group));
+    }
+    placed.sort_by_key(|(offset, _)| *offset);
+    let order = placed
+        .iter()
+        .flat_map(|(_, group)| group.iter().map(|field| field.name.clone()));
+
+    let pinned_members = units
+        .iter()
+        .flat_map(|(group, _, _)| group.iter())
+        .filter(|field| !field.is_base)
+        .map(|field| field.name.clone());
+    let order = pinned_members.chain(order).collect();
+    (order, cursor.next_multiple_of(alignment.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ArrayDimension, ArrayInfo, BaseClassInfo, ClassInfo, MemberInfo, SectionInfo, VariableInfo,
+    };
+
+    fn scalar(name: &str, size: u64) -> TypeInfo {
+        TypeInfo {
+            name: name.to_string(),
+            size: Some(size),
+            kind: "basic".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn member(name: &str, offset: u64, type_info: TypeInfo) -> MemberInfo {
+        MemberInfo {
+            name: name.to_string(),
+            offset,
+            type_info,
+            ..Default::default()
+        }
+    }
+
+    fn structure(name: &str, size: u64, members: Vec<MemberInfo>) -> TypeInfo {
+        TypeInfo {
+            name: name.to_string(),
+            size: Some(size),
+            kind: "struct".to_string(),
+            members,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_holes_and_reordering() {
+        // struct padded { char a; int b; char c; long d; short e; };
+        let padded = structure(
+            "padded",
+            32,
+            vec![
+                member("a", 0, scalar("char", 1)),
+                member("b", 4, scalar("int", 4)),
+                member("c", 8, scalar("char", 1)),
+                member("d", 16, scalar("long", 8)),
+                member("e", 24, scalar("short", 2)),
+            ],
+        );
+
+        let layout = analyze_layout(&padded, &[], DEFAULT_CACHELINE_SIZE).unwrap();
+        let holes: Vec<(Option<&str>, u64, u64)> = layout
+            .holes
+            .iter()
+            .map(|hole| (hole.after.as_deref(), hole.offset, hole.size))
+            .collect();
+        assert_eq!(holes, vec![(Some("a"), 1, 3), (Some("c"), 9, 7)]);
+        assert_eq!(layout.trailing_padding, 6);
+        assert_eq!(layout.wasted_bytes, 16);
+        assert_eq!(layout.alignment, 8);
+
+        assert_eq!(layout.suggested_order, vec!["d", "b", "e", "a", "c"]);
+        assert_eq!(layout.suggested_size, 16);
+        assert_eq!(layout.savings, 16);
+    }
+
+    #[test]
+    fn test_packed_struct_keeps_its_order() {
+        let packed = structure(
+            "packed",
+            16,
+            vec![
+                member("d", 0, scalar("long", 8)),
+                member("b", 8, scalar("int", 4)),
+                member("e", 12, scalar("short", 2)),
+                member("a", 14, scalar("char", 1)),
+                member("c", 15, scalar("char", 1)),
+            ],
+        );
+
+        let layout = analyze_layout(&packed, &[], DEFAULT_CACHELINE_SIZE).unwrap();
+        assert!(layout.holes.is_empty());
+        assert_eq!(layout.wasted_bytes, 0);
+        assert_eq!(layout.savings, 0);
+        assert_eq!(layout.suggested_order, vec!["d", "b", "e", "a", "c"]);
+    }
+
+    #[test]
+    fn test_bit_field_holes() {
+        // struct flags { unsigned int x : 3; unsigned int y : 4; char z; long w; };
+        let bit_field = |name: &str, bit_offset: u64, bit_size: u64| MemberInfo {
+            bit_offset: Some(bit_offset),
+            bit_size: Some(bit_size),
+            ..member(name, bit_offset / 8, scalar("unsigned int", 4))
+        };
+        let flags = structure(
+            "flags",
+            16,
+            vec![
+                bit_field("x", 0, 3),
+                bit_field("y", 3, 4),
+                member("z", 1, scalar("char", 1)),
+                member("w", 8, scalar("long", 8)),
+            ],
+        );
+
+        let layout = analyze_layout(&flags, &[], DEFAULT_CACHELINE_SIZE).unwrap();
+        let holes: Vec<(Option<&str>, u64, u64, u64)> = layout
+            .holes
+            .iter()
+            .map(|hole| (hole.after.as_deref(), hole.offset, hole.size, hole.bits))
+            .collect();
+        assert_eq!(holes, vec![(Some("y"), 0, 0, 1), (Some("z"), 2, 6, 0)]);
+        assert_eq!(layout.wasted_bytes, 6);
+        assert_eq!(layout.savings, 0);
+    }
+
+    #[test]
+    fn test_cachelines() {
+        let array = TypeInfo {
+            name: "double[9]".to_string(),
+            size: Some(72),
+            kind: "array".to_string(),
+            array: Some(crate::ArrayInfo {
+                element_type: Box::new(scalar("double", 8)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let big = structure(
+            "big",
+            88,
+            vec![
+                member("tag", 0, scalar("char", 1)),
+                member("values", 8, array),
+                member("tail", 80, scalar("int", 4)),
+            ],
+        );
+
+        let layout = analyze_layout(&big, &[], 32).unwrap();
+        assert_eq!(layout.cacheline_size, 32);
+        assert_eq!(layout.cachelines, 3);
+        let placement: Vec<(&str, u64, bool)> = layout
+            .members
+            .iter()
+            .map(|field| {
+                (
+                    field.name.as_str(),
+                    field.cacheline,
+                    field.crosses_cacheline,
+                )
+            })
+            .collect();
+        assert_eq!(
+            placement,
+            vec![("tag", 0, false), ("values", 0, true), ("tail", 2, false)]
+        );
+    }
+
+    #[test]
+    fn test_base_classes_stay_first() {
+        let base = structure("Base", 4, vec![member("id", 0, scalar("int", 4))]);
+        let mut derived = structure(
+            "Derived",
+            24,
+            vec![
+                member("flag", 4, scalar("bool", 1)),
+                member("value", 8, scalar("long", 8)),
+                member("other", 16, scalar("bool", 1)),
+            ],
+        );
+        derived.kind = "class".to_string();
+        derived.class = Some(ClassInfo {
+            bases: vec![BaseClassInfo {
+                name: "Base".to_string(),
+                offset: Some(0),
+                is_virtual: false,
+                accessibility: "public".to_string(),
+            }],
+            ..Default::default()
+        });
+
+        let layout = analyze_layout(&derived, &[base], DEFAULT_CACHELINE_SIZE).unwrap();
+        assert!(layout.members[0].is_base);
+        assert_eq!(layout.members[0].size, 4);
+        // No hole is reported for the base subobject itself
+        assert_eq!(layout.holes[0].after.as_deref(), Some("flag"));
+        assert_eq!(layout.suggested_order, vec!["flag", "other", "value"]);
+        assert_eq!(layout.suggested_size, 16);
+    }
+
+    #[test]
+    fn test_only_structs_are_analyzed() {
+        assert!(analyze_layout(&scalar("int", 4), &[], DEFAULT_CACHELINE_SIZE).is_none());
+        let declaration = TypeInfo {
+            name: "opaque".to_string(),
+            kind: "struct".to_string(),
+            ..Default::default()
+        };
+        assert!(analyze_layout(&declaration, &[], DEFAULT_CACHELINE_SIZE).is_none());
+    }
+
+    #[test]
+    fn test_padding_report_ranks_by_instances() {
+        // struct padded { char a; long b; }: 7 bytes of padding per instance
+        let padded = structure(
+            "padded",
+            16,
+            vec![
+                member("a", 0, scalar("char", 1)),
+                member("b", 8, scalar("long", 8)),
+            ],
+        );
+        // struct wide { char a; long b; char c; }: 14 bytes of padding; reordered
+        // as { long b; char a; char c; } it takes 16 bytes, saving 8
+        let wide = structure(
+            "wide",
+            24,
+            vec![
+                member("a", 0, scalar("char", 1)),
+                member("b", 8, scalar("long", 8)),
+                member("c", 16, scalar("char", 1)),
+            ],
+        );
+        let padded_array = TypeInfo {
+            name: "padded[10]".to_string(),
+            kind: "array".to_string(),
+            size: Some(160),
+            array: Some(ArrayInfo {
+                element_type: Box::new(padded.clone()),
+                dimensions: vec![ArrayDimension {
+                    count: Some(10),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let global = |name: &str, address: u64, type_info: &TypeInfo| VariableInfo {
+            name: name.to_string(),
+            address: Some(address),
+            type_info: type_info.clone(),
+            scope: "global".to_string(),
+            ..Default::default()
+        };
+        let section = |name: &str, address: u64, write: bool| SectionInfo {
+            name: name.to_string(),
+            kind: "progbits".to_string(),
+            address,
+            size: 0x1000,
+            alloc: true,
+            write,
+            ..Default::default()
+        };
+
+        let elf_info = ElfInfo {
+            architecture: "x86_64".to_string(),
+            entry_point: 0,
+            sections: vec![".rodata".to_string(), ".bss".to_string()],
+            section_headers: vec![
+                section(".rodata", 0x1000, false),
+                section(".bss", 0x2000, true),
+            ],
+            file_type: "executable".to_string(),
+            endianness: "little_endian".to_string(),
+            functions: Vec::new(),
+            variables: vec![
+                global("table", 0x2000, &padded_array),
+                global("one", 0x20a0, &wide),
+                // Read-only instances do not cost RAM
+                global("constant", 0x1000, &wide),
+            ],
+            types: vec![padded, wide],
+            type_conflicts: Vec::new(),
+        };
+
+        let report = padding_report(&elf_info);
+        let ranks: Vec<(&str, u64, u64, u64)> = report
+            .structs
+            .iter()
+            .map(|rank| {
+                (
+                    rank.name.as_str(),
+                    rank.padding,
+                    rank.instances,
+                    rank.total_padding,
+                )
+            })
+            .collect();
+        assert_eq!(ranks, vec![("padded", 7, 10, 70), ("wide", 14, 1, 14)]);
+        assert_eq!(report.structs[0].variables, vec!["table"]);
+        assert_eq!(report.structs[1].total_savings, 8);
+        assert_eq!(report.total_padding, 84);
+    }
+}
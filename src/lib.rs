@@ -3,8 +3,28 @@ use goblin::elf::Elf;
 use goblin::Object;
 use object::{Object as ObjectTrait, ObjectSection};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::rc::Rc;
+
+pub mod abi;
+pub mod c_header;
+pub mod callgraph;
+pub mod cfi;
+pub mod checksec;
+pub mod core_dump;
+pub mod dead_code;
+pub mod diff;
+pub mod disasm;
+pub mod layout;
+pub mod policy;
+pub mod rust_bindings;
+pub mod size;
+pub mod stack;
+pub mod symbolize;
+pub mod unwind;
+pub mod values;
 
 // WebAssembly support
 #[cfg(target_arch = "wasm32")]
@@ -22,37 +42,215 @@ macro_rules! console_log {
     ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct FunctionInfo {
     pub name: String,
+    /// Name including enclosing namespaces, modules and types, e.g. `core::fmt::Write::write_str`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linkage_name: Option<String>,
     pub address: u64,
     pub size: Option<u64>,
     pub parameters: Vec<VariableInfo>,
     pub return_type: Option<TypeInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decl_location: Option<SourceLocation>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct VariableInfo {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linkage_name: Option<String>,
     pub address: Option<u64>,
     pub offset: Option<i64>,
     pub type_info: TypeInfo,
-    pub scope: String, // "global", "local", "parameter"
+    pub scope: String, // "global", "local", "parameter", "static_member"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decl_location: Option<SourceLocation>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct TypeInfo {
     pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
     pub size: Option<u64>,
-    pub kind: String, // "basic", "struct", "enum", "union", "pointer", "array"
+    /// Explicit `DW_AT_alignment` (alignas, `#[repr(align)]`; rustc emits it on every type)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<u64>,
+    pub kind: String, // "basic", "struct", "class", "enum", "union", "pointer", "reference", "array", "subroutine", "typedef", "const", "volatile", "restrict", "atomic", "slice", "str", "trait_object"
     pub members: Vec<MemberInfo>,
+    /// Referenced type for pointers, references, typedefs and qualifiers; the
+    /// element type of Rust slices and the `dyn Trait` of Rust trait objects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<Box<TypeInfo>>,
+    /// Element type and dimensions, present when `kind` is "array"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub array: Option<ArrayInfo>,
+    /// Return and parameter types, present when `kind` is "subroutine"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subroutine: Option<SubroutineInfo>,
+    /// Base classes, methods and static members of C++ aggregates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<ClassInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub template_parameters: Vec<TemplateParameter>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub enumerators: Vec<EnumeratorInfo>,
+    /// Variants of a Rust enum, present when `kind` is "enum" and the type has a `DW_TAG_variant_part`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant_part: Option<Box<VariantPartInfo>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decl_location: Option<SourceLocation>,
+    /// Units defining this layout; only set on the top-level `ElfInfo.types` entries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub units: Vec<String>,
+    /// Another layout is defined under the same qualified name (a One Definition Rule violation)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub odr_conflict: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl TypeInfo {
+    /// Follow typedefs and qualifiers down to the type that defines the layout
+    pub fn underlying(&self) -> &TypeInfo {
+        let mut current = self;
+        while matches!(
+            current.kind.as_str(),
+            "typedef" | "const" | "volatile" | "restrict" | "atomic"
+        ) {
+            match &current.target {
+                Some(target) => current = target,
+                None => break,
+            }
+        }
+        current
+    }
+}
+
+/// Where an entity is declared, from `DW_AT_decl_file`, `DW_AT_decl_line` and `DW_AT_decl_column`
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SourceLocation {
+    /// Path joined with the directory entry and the unit's `DW_AT_comp_dir`
+    pub file: String,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ArrayInfo {
+    pub element_type: Box<TypeInfo>,
+    /// One entry per `DW_TAG_subrange_type`, outermost dimension first
+    pub dimensions: Vec<ArrayDimension>,
+    pub byte_stride: Option<u64>,
+    pub bit_stride: Option<u64>,
+    /// Trailing struct member without an element count (`T data[]` or `T data[0]`)
+    pub flexible: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SubroutineInfo {
+    /// None for functions returning void
+    pub return_type: Option<Box<TypeInfo>>,
+    pub parameters: Vec<TypeInfo>,
+    /// Declared with a trailing `...` (`DW_TAG_unspecified_parameters`)
+    pub variadic: bool,
+    pub prototyped: bool,
+    pub calling_convention: String, // "normal", "program", "nocall", "pass_by_reference", "pass_by_value" or a vendor value
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ClassInfo {
+    pub bases: Vec<BaseClassInfo>,
+    pub methods: Vec<MethodInfo>,
+    pub static_members: Vec<VariableInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct BaseClassInfo {
+    pub name: String,
+    /// None for virtual bases, which are located through the vtable at run time
+    pub offset: Option<u64>,
+    pub is_virtual: bool,
+    pub accessibility: String, // "public", "protected", "private"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MethodInfo {
+    pub name: String,
+    pub linkage_name: Option<String>,
+    pub accessibility: String,
+    pub signature: SubroutineInfo,
+    pub is_const: bool,
+    pub is_static: bool,
+    /// Compiler-generated (implicit constructors, destructors and operators)
+    pub is_artificial: bool,
+    pub virtuality: String, // "none", "virtual", "pure_virtual"
+    pub vtable_slot: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TemplateParameter {
+    pub name: String,
+    pub kind: String, // "type", "value", "template", "pack"
+    pub type_info: Option<TypeInfo>,
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EnumeratorInfo {
+    pub name: String,
+    pub value: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VariantPartInfo {
+    /// Member holding the tag; None when the variant is implied by the layout alone
+    pub discriminant: Option<MemberInfo>,
+    pub variants: Vec<VariantInfo>,
+    /// Tag stored in otherwise invalid values of a payload field (e.g. null for `Option<&T>`)
+    pub niche: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VariantInfo {
+    pub name: String,
+    /// None for the variant selected when no other discriminant value matches
+    pub discriminant_value: Option<u64>,
+    pub offset: u64,
+    pub payload: TypeInfo,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ArrayDimension {
+    pub lower_bound: i64,
+    pub upper_bound: Option<i64>,
+    pub count: Option<u64>,
+    /// Bound computed at run time (variable length arrays), as a DWARF expression or variable
+    pub dynamic_bound: Option<String>,
+    pub byte_stride: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct MemberInfo {
     pub name: String,
     pub offset: u64,
     pub type_info: TypeInfo,
+    /// "public", "protected" or "private"; only reported for C++
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessibility: Option<String>,
+    /// Width of a bit-field member
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_size: Option<u64>,
+    /// Position of a bit-field member in bits from the start of the enclosing type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_offset: Option<u64>,
+    /// Explicit `DW_AT_alignment` of the member itself (`_Alignas` on a field)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alignment: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -60,11 +258,84 @@ pub struct ElfInfo {
     pub architecture: String,
     pub entry_point: u64,
     pub sections: Vec<String>,
+    /// Address, file range and flags of each entry in `sections`
+    #[serde(default)]
+    pub section_headers: Vec<SectionInfo>,
     pub file_type: String,
     pub endianness: String,
     pub functions: Vec<FunctionInfo>,
     pub variables: Vec<VariableInfo>,
     pub types: Vec<TypeInfo>,
+    /// Types defined with different layouts in different units
+    #[serde(default)]
+    pub type_conflicts: Vec<TypeConflict>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SectionInfo {
+    pub name: String,
+    pub kind: String, // "progbits", "nobits", "symtab", "strtab", "rela", "dynamic", "note", ...
+    pub address: u64,
+    pub offset: u64,
+    pub size: u64,
+    /// Occupies memory at run time (SHF_ALLOC)
+    pub alloc: bool,
+    pub write: bool,
+    pub exec: bool,
+}
+
+impl ElfInfo {
+    /// Allocated section whose address range contains `address`
+    pub fn section_containing(&self, address: u64) -> Option<&SectionInfo> {
+        self.section_headers.iter().find(|section| {
+            section.alloc
+                && section.size > 0
+                // `.tbss` only describes the TLS template and overlaps the sections after it
+                && !(section.kind == "nobits" && section.name.starts_with(".tbss"))
+                && address >= section.address
+                && address - section.address < section.size
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TypeConflict {
+    pub name: String,
+    /// One entry per distinct layout
+    pub layouts: Vec<ConflictingLayout>,
+    /// Members whose presence, offset, type or size is not the same in every layout
+    pub differences: Vec<MemberDifference>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ConflictingLayout {
+    pub kind: String,
+    pub size: Option<u64>,
+    pub units: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MemberDifference {
+    pub name: String,
+    /// Parallel to `TypeConflict.layouts`; None where the layout lacks the member
+    pub layouts: Vec<Option<MemberLayout>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct MemberLayout {
+    pub offset: u64,
+    pub type_name: String,
+    pub size: Option<u64>,
+}
+
+impl MemberLayout {
+    pub fn of(member: &MemberInfo) -> Self {
+        MemberLayout {
+            offset: member.offset,
+            type_name: member.type_info.name.clone(),
+            size: member.type_info.size,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,6 +355,7 @@ pub fn analyze_elf_with_dwarf(file_path: &str) -> Result<ElfInfo> {
                 elf_info.functions = dwarf_info.0;
                 elf_info.variables = dwarf_info.1;
                 elf_info.types = dwarf_info.2;
+                elf_info.type_conflicts = find_type_conflicts(&elf_info.types);
             }
             Ok(elf_info)
         }
@@ -100,6 +372,7 @@ pub fn analyze_elf_from_bytes_with_dwarf(buffer: &[u8]) -> Result<ElfInfo> {
                 elf_info.functions = dwarf_info.0;
                 elf_info.variables = dwarf_info.1;
                 elf_info.types = dwarf_info.2;
+                elf_info.type_conflicts = find_type_conflicts(&elf_info.types);
             }
             Ok(elf_info)
         }
@@ -111,21 +384,7 @@ fn extract_dwarf_info(
     buffer: &[u8],
 ) -> Result<(Vec<FunctionInfo>, Vec<VariableInfo>, Vec<TypeInfo>)> {
     let object_file = object::File::parse(buffer)?;
-
-    // Helper function to load DWARF sections
-    let load_section = |id: gimli::SectionId| -> Result<std::borrow::Cow<[u8]>> {
-        if let Some(section) = object_file.section_by_name(id.name()) {
-            match section.uncompressed_data() {
-                Ok(data) => Ok(data),
-                Err(_) => Ok(std::borrow::Cow::Borrowed(&[])),
-            }
-        } else {
-            Ok(std::borrow::Cow::Borrowed(&[]))
-        }
-    };
-
-    // Load DWARF sections
-    let dwarf_sections = gimli::DwarfSections::load(load_section)?;
+    let dwarf_sections = load_dwarf_sections(&object_file)?;
 
     let dwarf =
         dwarf_sections.borrow(|section| gimli::EndianSlice::new(section, gimli::LittleEndian));
@@ -133,33 +392,65 @@ fn extract_dwarf_info(
     let mut functions = Vec::new();
     let mut variables = Vec::new();
     let mut types = Vec::new();
-    let mut type_cache: HashMap<gimli::UnitOffset, TypeInfo> = HashMap::new();
 
-    // Iterate through compilation units
-    let mut compilation_units = dwarf.units();
-    while let Some(header) = compilation_units.next()? {
+    // Compilation units, DWARF 5 type units (.debug_info) and DWARF 4 type units (.debug_types)
+    let mut headers = Vec::new();
+    let mut info_units = dwarf.units();
+    while let Some(header) = info_units.next()? {
+        headers.push(header);
+    }
+    let mut type_units = dwarf.type_units();
+    while let Some(header) = type_units.next()? {
+        headers.push(header);
+    }
+    let type_units = Rc::new(TypeUnits::new(&headers));
+
+    for header in headers {
         let unit = dwarf.unit(header)?;
+        let unit_name = match header.type_() {
+            gimli::UnitType::Type { type_signature, .. }
+            | gimli::UnitType::SplitType { type_signature, .. } => {
+                format!("type unit 0x{:016x}", type_signature.0)
+            }
+            _ => unit
+                .name
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "<unknown>".to_string()),
+        };
+        // DIE references are unit-relative offsets, so the cache is per unit
+        let mut cache = UnitCache {
+            scopes: collect_scopes(&dwarf, &unit)?,
+            files: collect_file_names(&dwarf, &unit)?,
+            type_units: type_units.clone(),
+            ..Default::default()
+        };
 
         // Iterate through DIEs (Debug Information Entries)
         let mut entries = unit.entries();
         while let Some((_, entry)) = entries.next_dfs()? {
             match entry.tag() {
                 gimli::DW_TAG_subprogram => {
-                    if let Ok(function_info) = extract_function_info(&dwarf, &unit, entry) {
+                    if let Ok(function_info) =
+                        extract_function_info(&dwarf, &unit, entry, &mut cache)
+                    {
                         functions.push(function_info);
                     }
                 }
                 gimli::DW_TAG_variable => {
-                    if let Ok(variable_info) = extract_variable_info(&dwarf, &unit, entry, "global")
+                    if let Ok(variable_info) =
+                        extract_variable_info(&dwarf, &unit, entry, "global", &mut cache)
                     {
                         variables.push(variable_info);
                     }
                 }
                 gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_class_type
                 | gimli::DW_TAG_union_type
                 | gimli::DW_TAG_enumeration_type => {
-                    if let Ok(type_info) = extract_type_info(&dwarf, &unit, entry, &mut type_cache)
+                    if let Ok(mut type_info) =
+                        resolve_type(&dwarf, &unit, entry.offset(), &mut cache, false)
                     {
+                        type_info.units = vec![unit_name.clone()];
                         types.push(type_info);
                     }
                 }
@@ -168,13 +459,528 @@ fn extract_dwarf_info(
         }
     }
 
-    Ok((functions, variables, types))
+    Ok((functions, variables, deduplicate_types(types)))
+}
+
+/// Load (and decompress) the DWARF sections of `object_file`; missing sections are empty
+pub(crate) fn load_dwarf_sections<'data>(
+    object_file: &object::File<'data>,
+) -> Result<gimli::DwarfSections<std::borrow::Cow<'data, [u8]>>> {
+    // Helper function to load DWARF sections
+    let load_section = |id: gimli::SectionId| -> Result<std::borrow::Cow<'data, [u8]>> {
+        if let Some(section) = object_file.section_by_name(id.name()) {
+            match section.uncompressed_data() {
+                Ok(data) => Ok(data),
+                Err(_) => Ok(std::borrow::Cow::Borrowed(&[])),
+            }
+        } else {
+            Ok(std::borrow::Cow::Borrowed(&[]))
+        }
+    };
+
+    gimli::DwarfSections::load(load_section)
+}
+
+/// Merge identical layouts defined in several units, dropping forward
+/// declarations of completed types and flagging names with conflicting layouts
+fn deduplicate_types(types: Vec<TypeInfo>) -> Vec<TypeInfo> {
+    let type_key = |type_info: &TypeInfo| {
+        type_info
+            .qualified_name
+            .clone()
+            .unwrap_or_else(|| type_info.name.clone())
+    };
+    let is_declaration = |type_info: &TypeInfo| {
+        type_info.size.is_none()
+            && type_info.members.is_empty()
+            && type_info.enumerators.is_empty()
+            && type_info.variant_part.is_none()
+    };
+
+    let complete: HashSet<String> = types
+        .iter()
+        .filter(|type_info| !is_declaration(type_info))
+        .map(type_key)
+        .collect();
+
+    let mut merged: Vec<TypeInfo> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for type_info in types {
+        let name = type_key(&type_info);
+        if is_declaration(&type_info) && complete.contains(&name) {
+            continue;
+        }
+        match index.entry((name, layout_fingerprint(&type_info))) {
+            std::collections::hash_map::Entry::Occupied(existing) => {
+                let existing = &mut merged[*existing.get()];
+                for unit in type_info.units {
+                    if !existing.units.contains(&unit) {
+                        existing.units.push(unit);
+                    }
+                }
+            }
+            std::collections::hash_map::Entry::Vacant(slot) => {
+                slot.insert(merged.len());
+                merged.push(type_info);
+            }
+        }
+    }
+
+    let mut layouts: HashMap<String, usize> = HashMap::new();
+    for type_info in &merged {
+        *layouts.entry(type_key(type_info)).or_default() += 1;
+    }
+    for type_info in &mut merged {
+        if type_info.name != "<anonymous>" && layouts[&type_key(type_info)] > 1 {
+            type_info.odr_conflict = true;
+        }
+    }
+    merged
+}
+
+/// Group the types flagged as ODR conflicts by name and describe how their layouts differ
+pub fn find_type_conflicts(types: &[TypeInfo]) -> Vec<TypeConflict> {
+    let mut conflicts: Vec<TypeConflict> = Vec::new();
+    let mut by_name: HashMap<&str, Vec<&TypeInfo>> = HashMap::new();
+    for type_info in types.iter().filter(|t| t.odr_conflict) {
+        let name = type_info
+            .qualified_name
+            .as_deref()
+            .unwrap_or(&type_info.name);
+        let layouts = by_name.entry(name).or_default();
+        if layouts.is_empty() {
+            conflicts.push(TypeConflict {
+                name: name.to_string(),
+                ..Default::default()
+            });
+        }
+        layouts.push(type_info);
+    }
+
+    for conflict in &mut conflicts {
+        let layouts = &by_name[conflict.name.as_str()];
+        conflict.layouts = layouts
+            .iter()
+            .map(|layout| ConflictingLayout {
+                kind: layout.kind.clone(),
+                size: layout.size,
+                units: layout.units.clone(),
+            })
+            .collect();
+
+        // Member names in order of first appearance across the layouts
+        let mut member_names: Vec<&str> = Vec::new();
+        for layout in layouts {
+            for member in &layout.members {
+                if !member_names.contains(&member.name.as_str()) {
+                    member_names.push(&member.name);
+                }
+            }
+        }
+        for member_name in member_names {
+            let placements: Vec<Option<MemberLayout>> = layouts
+                .iter()
+                .map(|layout| {
+                    layout
+                        .members
+                        .iter()
+                        .find(|member| member.name == member_name)
+                        .map(MemberLayout::of)
+                })
+                .collect();
+            if placements
+                .iter()
+                .any(|placement| placement != &placements[0])
+            {
+                conflict.differences.push(MemberDifference {
+                    name: member_name.to_string(),
+                    layouts: placements,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Summary of everything that determines a type's layout: kind, size and
+/// the name, offset, type and size of each member, enumerator or variant
+pub(crate) fn layout_fingerprint(type_info: &TypeInfo) -> String {
+    let mut fingerprint = format!("{}:{:?}", type_info.kind, type_info.size);
+    for member in &type_info.members {
+        fingerprint.push_str(&format!(
+            ";{}@{}:{:?}:{}:{:?}",
+            member.name,
+            member.offset,
+            member.bit_offset,
+            member.type_info.name,
+            member.type_info.size
+        ));
+    }
+    for enumerator in &type_info.enumerators {
+        fingerprint.push_str(&format!(";{}={}", enumerator.name, enumerator.value));
+    }
+    if let Some(variant_part) = &type_info.variant_part {
+        for variant in &variant_part.variants {
+            fingerprint.push_str(&format!(
+                ";{}={:?}@{}:{}",
+                variant.name,
+                variant.discriminant_value,
+                variant.offset,
+                layout_fingerprint(&variant.payload)
+            ));
+        }
+    }
+    if let Some(class) = &type_info.class {
+        for base in &class.bases {
+            fingerprint.push_str(&format!(";base {}@{:?}", base.name, base.offset));
+        }
+    }
+    fingerprint
+}
+
+/// Per-unit lookup tables built while walking a compilation unit
+#[derive(Default)]
+struct UnitCache {
+    /// Fully decoded types keyed by DIE offset
+    types: HashMap<gimli::UnitOffset, TypeInfo>,
+    /// Enclosing namespace, module and type path of each DIE, e.g. `core::fmt`
+    scopes: HashMap<gimli::UnitOffset, String>,
+    /// Full paths of the line program's file table, keyed by `DW_AT_decl_file` index
+    files: HashMap<u64, String>,
+    type_units: Rc<TypeUnits>,
+}
+
+/// Type units shared by every unit, for resolving `DW_FORM_ref_sig8` references
+#[derive(Default)]
+struct TypeUnits {
+    /// Section offset of each type unit, keyed by type signature
+    units: HashMap<u64, gimli::UnitSectionOffset>,
+    /// Fully decoded types keyed by type signature
+    types: RefCell<HashMap<u64, TypeInfo>>,
+    /// Shallow decodes keyed by type signature, for references behind pointers
+    shallow_types: RefCell<HashMap<u64, TypeInfo>>,
+    /// Signatures being fully decoded, resolved shallowly when met again
+    in_progress: RefCell<HashSet<u64>>,
+}
+
+impl TypeUnits {
+    fn new(headers: &[gimli::UnitHeader<gimli::EndianSlice<gimli::LittleEndian>>]) -> Self {
+        let units = headers
+            .iter()
+            .filter_map(|header| match header.type_() {
+                gimli::UnitType::Type { type_signature, .. }
+                | gimli::UnitType::SplitType { type_signature, .. } => {
+                    Some((type_signature.0, header.offset()))
+                }
+                _ => None,
+            })
+            .collect();
+        TypeUnits {
+            units,
+            ..Default::default()
+        }
+    }
+}
+
+fn load_unit_header<'input>(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<'input, gimli::LittleEndian>>,
+    offset: gimli::UnitSectionOffset,
+) -> Result<gimli::UnitHeader<gimli::EndianSlice<'input, gimli::LittleEndian>>> {
+    match offset {
+        gimli::UnitSectionOffset::DebugInfoOffset(offset) => {
+            Ok(dwarf.debug_info.header_from_offset(offset)?)
+        }
+        gimli::UnitSectionOffset::DebugTypesOffset(offset) => {
+            // `.debug_types` can only be walked from its start, so parse the
+            // header from a slice and restore its section-relative offset
+            let section = gimli::Section::reader(&dwarf.debug_types).range_from(offset.0..);
+            let header = gimli::DebugTypes::from(section)
+                .units()
+                .next()?
+                .context("Missing type unit header")?;
+            let entries = header.range_from(gimli::UnitOffset(header.header_size())..)?;
+            Ok(gimli::UnitHeader::new(
+                header.encoding(),
+                header.unit_length(),
+                header.type_(),
+                header.debug_abbrev_offset(),
+                gimli::UnitSectionOffset::DebugTypesOffset(offset),
+                entries,
+            ))
+        }
+    }
+}
+
+/// Decode the type a type unit defines, given its 8-byte signature
+fn resolve_signature_type(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    signature: gimli::DebugTypeSignature,
+    cache: &UnitCache,
+    shallow: bool,
+) -> Result<Option<TypeInfo>> {
+    let type_units = cache.type_units.clone();
+    if let Some(cached) = type_units.types.borrow().get(&signature.0) {
+        return Ok(Some(cached.clone()));
+    }
+    let shallow = shallow || type_units.in_progress.borrow().contains(&signature.0);
+    if shallow {
+        if let Some(cached) = type_units.shallow_types.borrow().get(&signature.0) {
+            return Ok(Some(cached.clone()));
+        }
+    }
+    let Some(offset) = type_units.units.get(&signature.0).copied() else {
+        return Ok(None);
+    };
+    let header = load_unit_header(dwarf, offset)?;
+    let type_offset = match header.type_() {
+        gimli::UnitType::Type { type_offset, .. }
+        | gimli::UnitType::SplitType { type_offset, .. } => type_offset,
+        _ => return Ok(None),
+    };
+
+    let unit = dwarf.unit(header)?;
+    let mut unit_cache = UnitCache {
+        scopes: collect_scopes(dwarf, &unit)?,
+        files: collect_file_names(dwarf, &unit)?,
+        type_units: type_units.clone(),
+        ..Default::default()
+    };
+
+    if shallow {
+        let type_info = resolve_type(dwarf, &unit, type_offset, &mut unit_cache, true)?;
+        type_units
+            .shallow_types
+            .borrow_mut()
+            .insert(signature.0, type_info.clone());
+        return Ok(Some(type_info));
+    }
+    type_units.in_progress.borrow_mut().insert(signature.0);
+    let type_info = resolve_type(dwarf, &unit, type_offset, &mut unit_cache, false);
+    type_units.in_progress.borrow_mut().remove(&signature.0);
+    let type_info = type_info?;
+    type_units
+        .types
+        .borrow_mut()
+        .insert(signature.0, type_info.clone());
+    Ok(Some(type_info))
+}
+
+pub(crate) fn collect_file_names(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<HashMap<u64, String>> {
+    let mut files = HashMap::new();
+    let Some(program) = &unit.line_program else {
+        return Ok(files);
+    };
+    let header = program.header();
+    let comp_dir = unit
+        .comp_dir
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    // DWARF 5 numbers files from 0, earlier versions from 1; `file()` accounts for both
+    let count = header.file_names().len() as u64;
+    for index in 0..=count {
+        let Some(file) = header.file(index) else {
+            continue;
+        };
+        // A malformed entry only loses its own name, not the unit's analysis
+        let mut path = comp_dir.clone();
+        if let Some(directory) = file.directory(header) {
+            let Ok(directory) = dwarf.attr_string(unit, directory) else {
+                continue;
+            };
+            path = join_path(&path, &directory.to_string_lossy());
+        }
+        let Ok(name) = dwarf.attr_string(unit, file.path_name()) else {
+            continue;
+        };
+        files.insert(index, join_path(&path, &name.to_string_lossy()));
+    }
+    Ok(files)
+}
+
+/// Join DWARF path components with `/`, letting absolute components replace the base
+fn join_path(base: &str, path: &str) -> String {
+    let is_absolute = path.starts_with(['/', '\\']) || path.as_bytes().get(1) == Some(&b':');
+    if base.is_empty() || is_absolute {
+        path.to_string()
+    } else if path.is_empty() {
+        base.to_string()
+    } else {
+        format!("{}/{path}", base.trim_end_matches('/'))
+    }
+}
+
+/// Declaration coordinates of an entry, falling back to the declaration of a definition
+fn get_decl_location(
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &UnitCache,
+) -> Result<Option<SourceLocation>> {
+    let declaration;
+    let mut source = entry;
+    if entry.attr_value(gimli::DW_AT_decl_file)?.is_none() {
+        let offset = declaration_offset(unit, entry)?;
+        if offset == entry.offset() {
+            return Ok(None);
+        }
+        declaration = unit.entry(offset)?;
+        source = &declaration;
+    }
+
+    let file = match source.attr_value(gimli::DW_AT_decl_file)? {
+        Some(gimli::AttributeValue::FileIndex(index)) => index,
+        Some(gimli::AttributeValue::Udata(index)) => index,
+        _ => return Ok(None),
+    };
+    let Some(file) = cache.files.get(&file) else {
+        return Ok(None);
+    };
+
+    let coordinate = |attr_name| -> Result<Option<u64>> {
+        Ok(match source.attr_value(attr_name)? {
+            Some(gimli::AttributeValue::Udata(value)) => Some(value),
+            Some(gimli::AttributeValue::Data1(value)) => Some(value.into()),
+            Some(gimli::AttributeValue::Data2(value)) => Some(value.into()),
+            Some(gimli::AttributeValue::Data4(value)) => Some(value.into()),
+            Some(gimli::AttributeValue::Data8(value)) => Some(value),
+            _ => None,
+        })
+    };
+
+    Ok(Some(SourceLocation {
+        file: file.clone(),
+        line: coordinate(gimli::DW_AT_decl_line)?,
+        column: coordinate(gimli::DW_AT_decl_column)?,
+    }))
+}
+
+fn collect_scopes(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<HashMap<gimli::UnitOffset, String>> {
+    let mut scopes = HashMap::new();
+    // Path inside each ancestor of the current entry, indexed by depth
+    let mut paths: Vec<String> = Vec::new();
+    let mut depth: isize = 0;
+
+    let mut entries = unit.entries();
+    while let Some((delta, entry)) = entries.next_dfs()? {
+        depth += delta;
+        paths.truncate(depth.max(0) as usize);
+        let enclosing = paths.last().cloned().unwrap_or_default();
+
+        let scope_name = match entry.tag() {
+            gimli::DW_TAG_namespace => Some(
+                get_die_name(dwarf, unit, entry)?
+                    .unwrap_or_else(|| "(anonymous namespace)".to_string()),
+            ),
+            gimli::DW_TAG_module
+            | gimli::DW_TAG_class_type
+            | gimli::DW_TAG_structure_type
+            | gimli::DW_TAG_union_type
+            | gimli::DW_TAG_interface_type => get_die_name(dwarf, unit, entry)?,
+            _ => None,
+        };
+
+        let inner = match scope_name {
+            Some(name) if enclosing.is_empty() => name,
+            Some(name) => format!("{enclosing}::{name}"),
+            // Function-local statics and types are scoped by their function,
+            // whose own scope is that of its declaration
+            None if entry.tag() == gimli::DW_TAG_subprogram => {
+                let name =
+                    get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<unknown>".to_string());
+                match get_die_linkage_name(dwarf, unit, entry)?
+                    .as_deref()
+                    .and_then(demangle_symbol)
+                {
+                    Some(demangled) => demangled,
+                    None => match scopes.get(&declaration_offset(unit, entry)?) {
+                        Some(scope) => format!("{scope}::{name}"),
+                        None if enclosing.is_empty() => name,
+                        None => format!("{enclosing}::{name}"),
+                    },
+                }
+            }
+            None => enclosing.clone(),
+        };
+        if !enclosing.is_empty() {
+            scopes.insert(entry.offset(), enclosing);
+        }
+        paths.push(inner);
+    }
+
+    Ok(scopes)
+}
+
+/// Qualified name of an entity, preferring its demangled linkage name
+fn get_qualified_name(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &UnitCache,
+    name: &str,
+) -> Result<String> {
+    if let Some(demangled) = get_die_linkage_name(dwarf, unit, entry)?
+        .as_deref()
+        .and_then(demangle_symbol)
+    {
+        return Ok(demangled);
+    }
+
+    // Out-of-line definitions live at unit level; their scope is the declaration's
+    let declaration = declaration_offset(unit, entry)?;
+    Ok(match cache.scopes.get(&declaration) {
+        Some(scope) => format!("{scope}::{name}"),
+        None => name.to_string(),
+    })
+}
+
+/// Follow `DW_AT_specification` and `DW_AT_abstract_origin` to the declaring DIE
+fn declaration_offset(
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<gimli::UnitOffset> {
+    let mut offset = entry.offset();
+    // Bounded to guard against malformed reference cycles
+    for _ in 0..8 {
+        let current = unit.entry(offset)?;
+        let next = match current.attr_value(gimli::DW_AT_specification)? {
+            Some(gimli::AttributeValue::UnitRef(next)) => next,
+            _ => match current.attr_value(gimli::DW_AT_abstract_origin)? {
+                Some(gimli::AttributeValue::UnitRef(next)) => next,
+                _ => break,
+            },
+        };
+        offset = next;
+    }
+    Ok(offset)
+}
+
+/// Demangle a Rust or Itanium C++ symbol without its hash or parameter list
+pub fn demangle_symbol(symbol: &str) -> Option<String> {
+    if let Ok(demangled) = rustc_demangle::try_demangle(symbol) {
+        return Some(format!("{demangled:#}"));
+    }
+    if symbol.starts_with("_Z") {
+        let options = cpp_demangle::DemangleOptions::new()
+            .no_params()
+            .no_return_type();
+        if let Ok(demangled) = cpp_demangle::Symbol::new(symbol).ok()?.demangle(&options) {
+            return Some(demangled);
+        }
+    }
+    None
 }
 
 fn extract_function_info(
     dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
     unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
     entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &mut UnitCache,
 ) -> Result<FunctionInfo> {
     let name = get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<unknown>".to_string());
 
@@ -196,36 +1002,34 @@ fn extract_function_info(
 
     let mut parameters = Vec::new();
 
-    // Extract parameters
-    let mut child_entries = unit.entries_at_offset(entry.offset())?;
-    child_entries.next_dfs()?; // Skip the current entry
-
-    while let Some((_, child_entry)) = child_entries.next_dfs()? {
-        if child_entry.tag() == gimli::DW_TAG_formal_parameter {
-            if let Ok(param_info) = extract_variable_info(dwarf, unit, child_entry, "parameter") {
+    // Extract parameters (direct children only; nested scopes belong to other entries)
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    let root = tree.root()?;
+    let mut children = root.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() == gimli::DW_TAG_formal_parameter {
+            if let Ok(param_info) =
+                extract_variable_info(dwarf, unit, child.entry(), "parameter", cache)
+            {
                 parameters.push(param_info);
             }
         }
     }
 
-    let return_type = entry
-        .attr_value(gimli::DW_AT_type)?
-        .and_then(|attr| match attr {
-            gimli::AttributeValue::UnitRef(_offset) => Some(TypeInfo {
-                name: "<return_type>".to_string(),
-                size: None,
-                kind: "unknown".to_string(),
-                members: Vec::new(),
-            }),
-            _ => None,
-        });
+    let return_type = resolve_type_attr(dwarf, unit, entry, cache, false)?;
+    let qualified_name = Some(get_qualified_name(dwarf, unit, entry, cache, &name)?);
+    let linkage_name = get_die_linkage_name(dwarf, unit, entry)?;
+    let decl_location = get_decl_location(unit, entry, cache)?;
 
     Ok(FunctionInfo {
         name,
+        qualified_name,
+        linkage_name,
         address,
         size,
         parameters,
         return_type,
+        decl_location,
     })
 }
 
@@ -234,6 +1038,7 @@ fn extract_variable_info(
     unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
     entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
     scope: &str,
+    cache: &mut UnitCache,
 ) -> Result<VariableInfo> {
     let name = get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<unknown>".to_string());
 
@@ -261,832 +1066,4135 @@ fn extract_variable_info(
             _ => None,
         });
 
-    let type_info = entry
-        .attr_value(gimli::DW_AT_type)?
-        .map(|_| TypeInfo {
-            name: "unknown".to_string(),
-            size: None,
-            kind: "basic".to_string(),
-            members: Vec::new(),
-        })
-        .unwrap_or_else(|| TypeInfo {
-            name: "void".to_string(),
-            size: None,
-            kind: "basic".to_string(),
-            members: Vec::new(),
-        });
+    let type_info = resolve_type_attr(dwarf, unit, entry, cache, false)?.unwrap_or_else(void_type);
+
+    let qualified_name = Some(get_qualified_name(dwarf, unit, entry, cache, &name)?);
+    let linkage_name = get_die_linkage_name(dwarf, unit, entry)?;
+    let decl_location = get_decl_location(unit, entry, cache)?;
 
     Ok(VariableInfo {
         name,
+        qualified_name,
+        linkage_name,
         address,
         offset: None,
         type_info,
         scope: scope.to_string(),
+        decl_location,
     })
 }
 
-fn extract_type_info(
+fn void_type() -> TypeInfo {
+    TypeInfo {
+        name: "void".to_string(),
+        kind: "basic".to_string(),
+        ..Default::default()
+    }
+}
+
+/// Resolve the type referenced by an entry's `DW_AT_type` attribute
+fn resolve_type_attr(
     dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
     unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
     entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
-    type_cache: &mut HashMap<gimli::UnitOffset, TypeInfo>,
-) -> Result<TypeInfo> {
-    let name = get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<anonymous>".to_string());
+    cache: &mut UnitCache,
+    shallow: bool,
+) -> Result<Option<TypeInfo>> {
+    let resolved = match entry.attr_value(gimli::DW_AT_type)? {
+        Some(gimli::AttributeValue::UnitRef(offset)) => {
+            Some(resolve_type(dwarf, unit, offset, cache, shallow)?)
+        }
+        Some(gimli::AttributeValue::DebugInfoRef(offset)) => {
+            match offset.to_unit_offset(&unit.header) {
+                Some(offset) => Some(resolve_type(dwarf, unit, offset, cache, shallow)?),
+                None => None,
+            }
+        }
+        Some(gimli::AttributeValue::DebugTypesRef(signature)) => {
+            resolve_signature_type(dwarf, signature, cache, shallow)?
+        }
+        Some(_) => None,
+        None => return Ok(None),
+    };
 
-    let size = entry
-        .attr_value(gimli::DW_AT_byte_size)?
-        .and_then(|attr| match attr {
-            gimli::AttributeValue::Udata(size) => Some(size),
-            _ => None,
-        });
+    Ok(Some(resolved.unwrap_or_else(|| TypeInfo {
+        name: "unknown".to_string(),
+        kind: "basic".to_string(),
+        ..Default::default()
+    })))
+}
 
-    let kind = match entry.tag() {
-        gimli::DW_TAG_structure_type => "struct",
-        gimli::DW_TAG_union_type => "union",
-        gimli::DW_TAG_enumeration_type => "enum",
-        _ => "unknown",
+/// Decode the type DIE at `offset`.
+///
+/// A shallow decode skips aggregate members; it is used behind pointers so
+/// that self-referential types terminate. Full decodes are cached per unit.
+fn resolve_type(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    offset: gimli::UnitOffset,
+    cache: &mut UnitCache,
+    shallow: bool,
+) -> Result<TypeInfo> {
+    if !shallow {
+        if let Some(cached) = cache.types.get(&offset) {
+            return Ok(cached.clone());
+        }
     }
-    .to_string();
 
-    let mut members = Vec::new();
+    let entry = unit.entry(offset)?;
+    let type_info = match extract_type_info(dwarf, unit, &entry, cache, shallow) {
+        Ok(type_info) => type_info,
+        Err(e) => {
+            // Drop the member-less entry an aggregate registers before its members
+            if !shallow {
+                cache.types.remove(&offset);
+            }
+            return Err(e);
+        }
+    };
+    if !shallow {
+        cache.types.insert(offset, type_info.clone());
+    }
+    Ok(type_info)
+}
+
+fn extract_type_info(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &mut UnitCache,
+    shallow: bool,
+) -> Result<TypeInfo> {
+    // Declaration stub completed by a type unit (-fdebug-types-section)
+    if let Some(gimli::AttributeValue::DebugTypesRef(signature)) =
+        entry.attr_value(gimli::DW_AT_signature)?
+    {
+        if let Some(type_info) = resolve_signature_type(dwarf, signature, cache, shallow)? {
+            return Ok(type_info);
+        }
+    }
+
+    let die_name = get_die_name(dwarf, unit, entry)?;
+    let scoped_name = die_name.as_ref().map(|name| {
+        let qualified = match cache.scopes.get(&entry.offset()) {
+            Some(scope) => format!("{scope}::{name}"),
+            None => name.clone(),
+        };
+        (name.clone(), qualified)
+    });
+
+    let mut size = entry
+        .attr_value(gimli::DW_AT_byte_size)?
+        .and_then(|attr| match attr {
+            gimli::AttributeValue::Udata(size) => Some(size),
+            _ => None,
+        });
+    let alignment = entry
+        .attr_value(gimli::DW_AT_alignment)?
+        .and_then(|attr| attr.udata_value());
+
+    let mut kind = match entry.tag() {
+        gimli::DW_TAG_base_type | gimli::DW_TAG_unspecified_type => "basic",
+        gimli::DW_TAG_structure_type => "struct",
+        gimli::DW_TAG_class_type => "class",
+        gimli::DW_TAG_union_type => "union",
+        gimli::DW_TAG_enumeration_type => "enum",
+        gimli::DW_TAG_pointer_type | gimli::DW_TAG_ptr_to_member_type => "pointer",
+        gimli::DW_TAG_reference_type | gimli::DW_TAG_rvalue_reference_type => "reference",
+        gimli::DW_TAG_array_type => "array",
+        gimli::DW_TAG_subroutine_type => "subroutine",
+        gimli::DW_TAG_typedef => "typedef",
+        gimli::DW_TAG_const_type => "const",
+        gimli::DW_TAG_volatile_type => "volatile",
+        gimli::DW_TAG_restrict_type => "restrict",
+        gimli::DW_TAG_atomic_type => "atomic",
+        _ => "unknown",
+    }
+    .to_string();
+
+    let mut target = None;
+    let mut array = None;
+    let mut subroutine = None;
+    let mut class = None;
+    let mut template_parameters = Vec::new();
+    let mut enumerators = Vec::new();
+    let mut variant_part = None;
+    let mut members = Vec::new();
+
+    match kind.as_str() {
+        "pointer" | "reference" => {
+            // Never expand aggregates behind an indirection
+            target = resolve_type_attr(dwarf, unit, entry, cache, true)?;
+            if size.is_none() {
+                size = Some(u64::from(unit.encoding().address_size));
+            }
+        }
+        "typedef" | "const" | "volatile" | "restrict" | "atomic" => {
+            target = resolve_type_attr(dwarf, unit, entry, cache, shallow)?;
+            if size.is_none() {
+                size = target.as_ref().and_then(|t| t.size);
+            }
+        }
+        "array" => {
+            let array_info = extract_array_info(dwarf, unit, entry, cache, shallow)?;
+            if size.is_none() {
+                size = array_byte_size(&array_info);
+            }
+            array = Some(array_info);
+        }
+        "subroutine" => {
+            subroutine = Some(extract_subroutine_info(dwarf, unit, entry, cache)?);
+        }
+        "enum" if !shallow => {
+            let mut tree = unit.entries_tree(Some(entry.offset()))?;
+            let root = tree.root()?;
+            let mut children = root.children();
+            while let Some(child) = children.next()? {
+                if child.entry().tag() == gimli::DW_TAG_enumerator {
+                    enumerators.push(extract_enumerator_info(dwarf, unit, child.entry())?);
+                }
+            }
+        }
+        "struct" | "class" | "union" if !shallow => {
+            // Register a member-less entry first so that a by-value cycle
+            // (e.g. a static member of the enclosing type) resolves shallowly
+            cache.types.insert(
+                entry.offset(),
+                TypeInfo {
+                    name: die_name
+                        .clone()
+                        .unwrap_or_else(|| "<anonymous>".to_string()),
+                    qualified_name: scoped_name.as_ref().map(|(_, qualified)| qualified.clone()),
+                    size,
+                    kind: kind.clone(),
+                    ..Default::default()
+                },
+            );
+
+            let default_accessibility = if !is_cplusplus(unit)? {
+                None
+            } else if kind == "class" {
+                Some("private")
+            } else {
+                Some("public")
+            };
+            let mut class_info = ClassInfo::default();
+
+            let mut tree = unit.entries_tree(Some(entry.offset()))?;
+            let root = tree.root()?;
+            let mut children = root.children();
+            while let Some(child) = children.next()? {
+                let child_entry = child.entry();
+                match child_entry.tag() {
+                    // DWARF 4 describes static data members as external member declarations
+                    gimli::DW_TAG_member
+                        if has_flag(child_entry, gimli::DW_AT_external)?
+                            && has_flag(child_entry, gimli::DW_AT_declaration)? =>
+                    {
+                        if let Ok(static_member) =
+                            extract_variable_info(dwarf, unit, child_entry, "static_member", cache)
+                        {
+                            class_info.static_members.push(static_member);
+                        }
+                    }
+                    gimli::DW_TAG_member => {
+                        if let Ok(member_info) = extract_member_info(
+                            dwarf,
+                            unit,
+                            child_entry,
+                            cache,
+                            default_accessibility,
+                        ) {
+                            members.push(member_info);
+                        }
+                    }
+                    gimli::DW_TAG_variable => {
+                        if let Ok(static_member) =
+                            extract_variable_info(dwarf, unit, child_entry, "static_member", cache)
+                        {
+                            class_info.static_members.push(static_member);
+                        }
+                    }
+                    gimli::DW_TAG_inheritance => class_info.bases.push(extract_base_class_info(
+                        dwarf,
+                        unit,
+                        child_entry,
+                        cache,
+                        default_accessibility.unwrap_or("public"),
+                    )?),
+                    gimli::DW_TAG_subprogram => class_info.methods.push(extract_method_info(
+                        dwarf,
+                        unit,
+                        child_entry,
+                        cache,
+                        default_accessibility.unwrap_or("public"),
+                    )?),
+                    gimli::DW_TAG_variant_part => {
+                        variant_part = Some(Box::new(extract_variant_part_info(
+                            dwarf,
+                            unit,
+                            child_entry,
+                            cache,
+                        )?));
+                    }
+                    _ => {
+                        if let Some(parameter) =
+                            extract_template_parameter(dwarf, unit, child_entry, cache)?
+                        {
+                            template_parameters.push(parameter);
+                        }
+                    }
+                }
+            }
 
-    // Extract members for struct/union
-    if entry.tag() == gimli::DW_TAG_structure_type || entry.tag() == gimli::DW_TAG_union_type {
-        let mut child_entries = unit.entries_at_offset(entry.offset())?;
-        child_entries.next_dfs()?; // Skip the current entry
+            if kind == "class"
+                || !class_info.bases.is_empty()
+                || !class_info.methods.is_empty()
+                || !class_info.static_members.is_empty()
+            {
+                class = Some(class_info);
+            }
 
-        while let Some((_, child_entry)) = child_entries.next_dfs()? {
-            if child_entry.tag() == gimli::DW_TAG_member {
-                if let Ok(member_info) = extract_member_info(dwarf, unit, child_entry, type_cache) {
-                    members.push(member_info);
+            if kind == "struct" {
+                if let Some(last) = members.last_mut() {
+                    mark_flexible_array(&mut last.type_info);
                 }
             }
+
+            // Rust enums are structures wrapping a single variant part
+            if variant_part.is_some() {
+                kind = "enum".to_string();
+            } else if let Some((fat_kind, pointee)) =
+                rust_fat_pointer_kind(dwarf, unit, entry, die_name.as_deref(), cache)?
+            {
+                kind = fat_kind.to_string();
+                target = pointee;
+            }
+        }
+        "struct" => {
+            // Shallow decode: classify without expanding variants or members
+            if has_child(unit, entry, gimli::DW_TAG_variant_part)? {
+                kind = "enum".to_string();
+            } else if let Some((fat_kind, pointee)) =
+                rust_fat_pointer_kind(dwarf, unit, entry, die_name.as_deref(), cache)?
+            {
+                kind = fat_kind.to_string();
+                target = pointee;
+            }
         }
+        _ => {}
     }
 
+    let (name, qualified_name) = match scoped_name {
+        Some(names) => names,
+        None => (
+            derived_type_name(
+                entry.tag(),
+                target.as_ref(),
+                array.as_ref(),
+                subroutine.as_ref(),
+                false,
+            ),
+            derived_type_name(
+                entry.tag(),
+                target.as_ref(),
+                array.as_ref(),
+                subroutine.as_ref(),
+                true,
+            ),
+        ),
+    };
+
     Ok(TypeInfo {
         name,
+        qualified_name: Some(qualified_name),
         size,
+        alignment,
         kind,
         members,
+        target: target.map(Box::new),
+        array,
+        subroutine,
+        class,
+        template_parameters,
+        enumerators,
+        variant_part,
+        decl_location: get_decl_location(unit, entry, cache)?,
+        ..Default::default()
     })
 }
 
-fn extract_member_info(
+/// Name of an unnamed type, built from the names of the types it refers to
+fn derived_type_name(
+    tag: gimli::DwTag,
+    target: Option<&TypeInfo>,
+    array: Option<&ArrayInfo>,
+    subroutine: Option<&SubroutineInfo>,
+    qualified: bool,
+) -> String {
+    let type_name = |type_info: &TypeInfo| match &type_info.qualified_name {
+        Some(qualified_name) if qualified => qualified_name.clone(),
+        _ => type_info.name.clone(),
+    };
+    let target_name = target.map_or_else(|| "void".to_string(), type_name);
+    let target_subroutine = target.and_then(|t| t.subroutine.as_ref());
+
+    match tag {
+        gimli::DW_TAG_pointer_type => match target_subroutine {
+            Some(signature) => subroutine_signature(signature, "(*)"),
+            None => format!("{target_name} *"),
+        },
+        gimli::DW_TAG_ptr_to_member_type => format!("{target_name} ::*"),
+        gimli::DW_TAG_reference_type => match target_subroutine {
+            Some(signature) => subroutine_signature(signature, "(&)"),
+            None => format!("{target_name} &"),
+        },
+        gimli::DW_TAG_rvalue_reference_type => format!("{target_name} &&"),
+        gimli::DW_TAG_const_type => format!("const {target_name}"),
+        gimli::DW_TAG_volatile_type => format!("volatile {target_name}"),
+        gimli::DW_TAG_restrict_type => format!("{target_name} restrict"),
+        gimli::DW_TAG_atomic_type => format!("_Atomic {target_name}"),
+        gimli::DW_TAG_array_type => array.map_or_else(String::new, |array| {
+            array_type_name(array, &type_name(&array.element_type))
+        }),
+        gimli::DW_TAG_subroutine_type => {
+            subroutine.map_or_else(String::new, |signature| subroutine_signature(signature, ""))
+        }
+        _ => "<anonymous>".to_string(),
+    }
+}
+
+fn extract_base_class_info(
     dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
     unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
     entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
-    _type_cache: &mut HashMap<gimli::UnitOffset, TypeInfo>,
-) -> Result<MemberInfo> {
-    let name = get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<unknown>".to_string());
+    cache: &mut UnitCache,
+    default_accessibility: &str,
+) -> Result<BaseClassInfo> {
+    let name = resolve_type_attr(dwarf, unit, entry, cache, true)?
+        .map_or_else(|| "<unknown>".to_string(), |t| t.name);
 
+    // Virtual bases carry a location expression instead of a constant offset
     let offset = entry
         .attr_value(gimli::DW_AT_data_member_location)?
-        .and_then(|attr| match attr {
-            gimli::AttributeValue::Udata(offset) => Some(offset),
-            _ => None,
-        })
-        .unwrap_or(0);
+        .and_then(|attr| attr.udata_value());
 
-    let type_info = TypeInfo {
-        name: "unknown".to_string(),
-        size: None,
-        kind: "basic".to_string(),
-        members: Vec::new(),
-    };
+    let is_virtual = matches!(
+        entry.attr_value(gimli::DW_AT_virtuality)?,
+        Some(gimli::AttributeValue::Virtuality(virtuality))
+            if virtuality != gimli::DW_VIRTUALITY_none
+    );
 
-    Ok(MemberInfo {
+    Ok(BaseClassInfo {
         name,
         offset,
-        type_info,
+        is_virtual,
+        accessibility: get_accessibility(entry)?
+            .unwrap_or_else(|| default_accessibility.to_string()),
     })
 }
 
-fn get_die_name(
+fn extract_method_info(
     dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
-    _unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
     entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
-) -> Result<Option<String>> {
-    if let Some(attr) = entry.attr_value(gimli::DW_AT_name)? {
-        match attr {
-            gimli::AttributeValue::DebugStrRef(offset) => {
-                let name = dwarf.debug_str.get_str(offset)?;
-                Ok(Some(name.to_string_lossy().into_owned()))
+    cache: &mut UnitCache,
+    default_accessibility: &str,
+) -> Result<MethodInfo> {
+    let name = get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<unknown>".to_string());
+    let linkage_name = get_die_linkage_name(dwarf, unit, entry)?;
+    let signature = extract_subroutine_info(dwarf, unit, entry, cache)?;
+
+    // Non-static methods take an artificial `this` pointer as their first parameter
+    let mut this_type = None;
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    let root = tree.root()?;
+    let mut children = root.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() == gimli::DW_TAG_formal_parameter {
+            if has_flag(child.entry(), gimli::DW_AT_artificial)? {
+                this_type = resolve_type_attr(dwarf, unit, child.entry(), cache, true)?;
             }
-            gimli::AttributeValue::String(name) => Ok(Some(name.to_string_lossy().into_owned())),
-            _ => Ok(None),
+            break;
         }
-    } else {
-        Ok(None)
     }
-}
-
-/// Parse an ELF file and extract basic information (backward compatibility)
-pub fn analyze_elf(file_path: &str) -> Result<ElfInfo> {
-    analyze_elf_with_dwarf(file_path)
-}
 
-/// Parse an ELF file and extract basic information only (no DWARF)
-pub fn analyze_elf_basic(file_path: &str) -> Result<ElfInfo> {
-    let buffer =
-        fs::read(file_path).with_context(|| format!("Failed to read ELF file: {file_path}"))?;
+    let is_const = this_type
+        .as_ref()
+        .and_then(|t| t.target.as_ref())
+        .is_some_and(|t| t.kind == "const");
 
-    match Object::parse(&buffer)? {
-        Object::Elf(elf) => Ok(extract_elf_info(&elf)),
-        _ => anyhow::bail!("File is not a valid ELF binary"),
+    let virtuality = match entry.attr_value(gimli::DW_AT_virtuality)? {
+        Some(gimli::AttributeValue::Virtuality(gimli::DW_VIRTUALITY_virtual)) => "virtual",
+        Some(gimli::AttributeValue::Virtuality(gimli::DW_VIRTUALITY_pure_virtual)) => {
+            "pure_virtual"
+        }
+        _ => "none",
     }
+    .to_string();
+
+    let vtable_slot = match entry.attr_value(gimli::DW_AT_vtable_elem_location)? {
+        Some(gimli::AttributeValue::Exprloc(expression)) => {
+            match expression.operations(unit.encoding()).next() {
+                Ok(Some(gimli::Operation::UnsignedConstant { value })) => Some(value),
+                _ => None,
+            }
+        }
+        Some(attr) => attr.udata_value(),
+        None => None,
+    };
+
+    Ok(MethodInfo {
+        name,
+        linkage_name,
+        accessibility: get_accessibility(entry)?
+            .unwrap_or_else(|| default_accessibility.to_string()),
+        signature,
+        is_const,
+        is_static: this_type.is_none() && entry.attr_value(gimli::DW_AT_object_pointer)?.is_none(),
+        is_artificial: has_flag(entry, gimli::DW_AT_artificial)?,
+        virtuality,
+        vtable_slot,
+    })
 }
 
-/// Parse an ELF file from byte buffer and extract basic information only (no DWARF)
-pub fn analyze_elf_from_bytes_basic(buffer: &[u8]) -> Result<ElfInfo> {
-    match Object::parse(buffer)? {
-        Object::Elf(elf) => Ok(extract_elf_info(&elf)),
-        _ => anyhow::bail!("File is not a valid ELF binary"),
-    }
+fn extract_template_parameter(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &mut UnitCache,
+) -> Result<Option<TemplateParameter>> {
+    let kind = match entry.tag() {
+        gimli::DW_TAG_template_type_parameter => "type",
+        gimli::DW_TAG_template_value_parameter => "value",
+        gimli::DW_TAG_GNU_template_template_param => "template",
+        gimli::DW_TAG_GNU_template_parameter_pack => "pack",
+        _ => return Ok(None),
+    };
+
+    let value = match entry.attr_value(gimli::DW_AT_const_value)? {
+        Some(gimli::AttributeValue::Data1(value)) => Some(value.to_string()),
+        Some(gimli::AttributeValue::Data2(value)) => Some(value.to_string()),
+        Some(gimli::AttributeValue::Data4(value)) => Some(value.to_string()),
+        Some(gimli::AttributeValue::Data8(value)) => Some(value.to_string()),
+        Some(gimli::AttributeValue::Sdata(value)) => Some(value.to_string()),
+        Some(gimli::AttributeValue::Udata(value)) => Some(value.to_string()),
+        Some(attr) => dwarf
+            .attr_string(unit, attr)
+            .ok()
+            .map(|value| value.to_string_lossy().into_owned()),
+        None => None,
+    };
+
+    Ok(Some(TemplateParameter {
+        name: get_die_name(dwarf, unit, entry)?.unwrap_or_default(),
+        kind: kind.to_string(),
+        type_info: resolve_type_attr(dwarf, unit, entry, cache, true)?,
+        value,
+    }))
 }
 
-/// Parse an ELF file from byte buffer and extract basic information (WebAssembly-compatible)
-pub fn analyze_elf_from_bytes(buffer: &[u8]) -> Result<ElfInfo> {
-    analyze_elf_from_bytes_with_dwarf(buffer)
+fn get_accessibility(
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<Option<String>> {
+    Ok(match entry.attr_value(gimli::DW_AT_accessibility)? {
+        Some(gimli::AttributeValue::Accessibility(access)) => Some(
+            match access {
+                gimli::DW_ACCESS_public => "public",
+                gimli::DW_ACCESS_protected => "protected",
+                gimli::DW_ACCESS_private => "private",
+                _ => "unknown",
+            }
+            .to_string(),
+        ),
+        _ => None,
+    })
 }
 
-fn extract_elf_info(elf: &Elf) -> ElfInfo {
-    use goblin::elf::header::*;
+fn has_flag(
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    attr_name: gimli::DwAt,
+) -> Result<bool> {
+    Ok(matches!(
+        entry.attr_value(attr_name)?,
+        Some(gimli::AttributeValue::Flag(true))
+    ))
+}
 
-    let architecture = match elf.header.e_machine {
-        EM_X86_64 => "x86_64",
-        EM_386 => "i386",
-        EM_AARCH64 => "aarch64",
-        EM_ARM => "arm",
-        EM_RISCV => "riscv",
-        _ => "unknown",
+fn has_child(
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    tag: gimli::DwTag,
+) -> Result<bool> {
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    let root = tree.root()?;
+    let mut children = root.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() == tag {
+            return Ok(true);
+        }
     }
-    .to_string();
+    Ok(false)
+}
 
-    let file_type = match elf.header.e_type {
-        ET_EXEC => "executable",
-        ET_DYN => "shared_object",
-        ET_REL => "relocatable",
-        ET_CORE => "core_dump",
-        _ => "unknown",
+fn extract_subroutine_info(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &mut UnitCache,
+) -> Result<SubroutineInfo> {
+    // A signature only needs type names, so aggregates are never expanded here
+    let return_type = resolve_type_attr(dwarf, unit, entry, cache, true)?.map(Box::new);
+
+    let prototyped = has_flag(entry, gimli::DW_AT_prototyped)?;
+
+    let calling_convention = match entry.attr_value(gimli::DW_AT_calling_convention)? {
+        Some(gimli::AttributeValue::CallingConvention(convention)) => convention
+            .static_string()
+            .map(|name| name.trim_start_matches("DW_CC_").to_string())
+            .unwrap_or_else(|| format!("0x{:x}", convention.0)),
+        _ => "normal".to_string(),
+    };
+
+    let mut parameters = Vec::new();
+    let mut unspecified_parameters = false;
+
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    let root = tree.root()?;
+    let mut children = root.children();
+    while let Some(child) = children.next()? {
+        match child.entry().tag() {
+            gimli::DW_TAG_formal_parameter if has_flag(child.entry(), gimli::DW_AT_artificial)? => {
+            }
+            gimli::DW_TAG_formal_parameter => parameters.push(
+                resolve_type_attr(dwarf, unit, child.entry(), cache, true)?
+                    .unwrap_or_else(void_type),
+            ),
+            gimli::DW_TAG_unspecified_parameters => unspecified_parameters = true,
+            _ => {}
+        }
     }
-    .to_string();
 
-    let endianness = if elf.little_endian {
-        "little_endian".to_string()
-    } else {
-        "big_endian".to_string()
-    };
+    // C also uses DW_TAG_unspecified_parameters for unprototyped `f()` declarations
+    let is_c = matches!(
+        unit_language(unit)?,
+        Some(
+            gimli::DW_LANG_C
+                | gimli::DW_LANG_C89
+                | gimli::DW_LANG_C99
+                | gimli::DW_LANG_C11
+                | gimli::DW_LANG_C17
+        )
+    );
+
+    Ok(SubroutineInfo {
+        return_type,
+        parameters,
+        variadic: unspecified_parameters && (prototyped || !is_c),
+        prototyped,
+        calling_convention,
+    })
+}
 
-    let sections = elf
-        .section_headers
+/// Render a C-style signature, e.g. `int (*)(const char *, ...)` for the declarator `(*)`
+fn subroutine_signature(subroutine: &SubroutineInfo, declarator: &str) -> String {
+    let return_name = subroutine
+        .return_type
+        .as_ref()
+        .map_or("void", |t| t.name.as_str());
+
+    let mut parameters: Vec<&str> = subroutine
+        .parameters
         .iter()
-        .map(|section| {
-            elf.shdr_strtab
-                .get_at(section.sh_name)
-                .unwrap_or("unnamed")
-                .to_string()
-        })
+        .map(|p| p.name.as_str())
         .collect();
+    if subroutine.variadic {
+        parameters.push("...");
+    }
+    let parameter_list = if parameters.is_empty() && subroutine.prototyped {
+        "void".to_string()
+    } else {
+        parameters.join(", ")
+    };
 
-    ElfInfo {
-        architecture,
-        entry_point: elf.entry,
-        sections,
-        file_type,
-        endianness,
-        functions: Vec::new(),
-        variables: Vec::new(),
-        types: Vec::new(),
+    if declarator.is_empty() {
+        format!("{return_name} ({parameter_list})")
+    } else {
+        format!("{return_name} {declarator}({parameter_list})")
     }
 }
 
-/// Analyze ELF files
-pub fn analyze_files(elf_path: Option<&str>) -> Result<AnalysisResult> {
-    let elf_info = if let Some(path) = elf_path {
-        Some(analyze_elf(path)?)
-    } else {
-        None
-    };
+fn extract_array_info(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &mut UnitCache,
+    shallow: bool,
+) -> Result<ArrayInfo> {
+    let element_type =
+        resolve_type_attr(dwarf, unit, entry, cache, shallow)?.unwrap_or_else(void_type);
+
+    let byte_stride = entry
+        .attr_value(gimli::DW_AT_byte_stride)?
+        .and_then(|attr| attr.udata_value());
+    let bit_stride = entry
+        .attr_value(gimli::DW_AT_bit_stride)?
+        .and_then(|attr| attr.udata_value());
+
+    let default_lower_bound = default_lower_bound(unit)?;
+    let mut dimensions = Vec::new();
+
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    let root = tree.root()?;
+    let mut children = root.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() == gimli::DW_TAG_subrange_type {
+            dimensions.push(extract_subrange_info(
+                dwarf,
+                unit,
+                child.entry(),
+                default_lower_bound,
+            )?);
+        }
+    }
 
-    Ok(AnalysisResult { elf_info })
+    Ok(ArrayInfo {
+        element_type: Box::new(element_type),
+        dimensions,
+        byte_stride,
+        bit_stride,
+        flexible: false,
+    })
 }
 
-/// Analyze ELF files from byte buffers (WebAssembly-compatible)
-pub fn analyze_files_from_bytes(elf_data: Option<&[u8]>) -> Result<AnalysisResult> {
-    let elf_info = if let Some(data) = elf_data {
-        Some(analyze_elf_from_bytes(data)?)
-    } else {
-        None
+fn extract_subrange_info(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    default_lower_bound: i64,
+) -> Result<ArrayDimension> {
+    let mut dynamic = Vec::new();
+
+    let lower_bound = match extract_bound(dwarf, unit, entry, gimli::DW_AT_lower_bound)? {
+        Some(ArrayBound::Constant(value)) => value,
+        Some(ArrayBound::Dynamic(expression)) => {
+            dynamic.push(format!("lower_bound = {expression}"));
+            default_lower_bound
+        }
+        None => default_lower_bound,
     };
 
-    Ok(AnalysisResult { elf_info })
+    let mut upper_bound = None;
+    let mut count = None;
+
+    match extract_bound(dwarf, unit, entry, gimli::DW_AT_count)? {
+        Some(ArrayBound::Constant(value)) => count = u64::try_from(value).ok(),
+        Some(ArrayBound::Dynamic(expression)) => dynamic.push(format!("count = {expression}")),
+        None => match extract_bound(dwarf, unit, entry, gimli::DW_AT_upper_bound)? {
+            Some(ArrayBound::Constant(value)) => {
+                upper_bound = Some(value);
+                // An upper bound below the lower bound (GNU `T x[0]`) means no elements
+                count = value
+                    .checked_sub(lower_bound)
+                    .and_then(|span| span.checked_add(1))
+                    .map(|count| u64::try_from(count).unwrap_or(0));
+            }
+            Some(ArrayBound::Dynamic(expression)) => {
+                dynamic.push(format!("upper_bound = {expression}"))
+            }
+            None => {}
+        },
+    }
+
+    if let (Some(count), None) = (count, upper_bound) {
+        upper_bound = i64::try_from(count)
+            .ok()
+            .and_then(|count| lower_bound.checked_add(count))
+            .and_then(|end| end.checked_sub(1));
+    }
+
+    let byte_stride = entry
+        .attr_value(gimli::DW_AT_byte_stride)?
+        .and_then(|attr| attr.udata_value());
+
+    Ok(ArrayDimension {
+        lower_bound,
+        upper_bound,
+        count,
+        dynamic_bound: if dynamic.is_empty() {
+            None
+        } else {
+            Some(dynamic.join(", "))
+        },
+        byte_stride,
+    })
 }
 
-/// Convert analysis result to prettified JSON
-pub fn to_json(result: &AnalysisResult) -> Result<String> {
-    serde_json::to_string_pretty(result).context("Failed to serialize result to JSON")
+enum ArrayBound {
+    Constant(i64),
+    Dynamic(String),
 }
 
-// WebAssembly bindings
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen(start)]
-pub fn main() {
-    console_error_panic_hook::set_once();
+fn extract_bound(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    attr_name: gimli::DwAt,
+) -> Result<Option<ArrayBound>> {
+    let bound = match entry.attr_value(attr_name)? {
+        // Bounds are left unnormalized by gimli; only 8-byte forms can carry a
+        // negative value in practice (GNU emits -1 as DW_FORM_data8)
+        Some(gimli::AttributeValue::Data1(value)) => Some(ArrayBound::Constant(value.into())),
+        Some(gimli::AttributeValue::Data2(value)) => Some(ArrayBound::Constant(value.into())),
+        Some(gimli::AttributeValue::Data4(value)) => Some(ArrayBound::Constant(value.into())),
+        Some(gimli::AttributeValue::Data8(value)) => Some(ArrayBound::Constant(value as i64)),
+        Some(gimli::AttributeValue::Udata(value)) => Some(ArrayBound::Constant(value as i64)),
+        Some(gimli::AttributeValue::Sdata(value)) => Some(ArrayBound::Constant(value)),
+        Some(gimli::AttributeValue::Exprloc(expression)) => {
+            Some(ArrayBound::Dynamic(format_expression(unit, expression)))
+        }
+        Some(gimli::AttributeValue::UnitRef(offset)) => {
+            // Bound stored in another DIE, usually an artificial variable
+            let bound_entry = unit.entry(offset)?;
+            let description = match get_die_name(dwarf, unit, &bound_entry)? {
+                Some(name) => name,
+                None => match bound_entry.attr_value(gimli::DW_AT_location)? {
+                    Some(gimli::AttributeValue::Exprloc(expression)) => {
+                        format_expression(unit, expression)
+                    }
+                    _ => format!("<0x{:x}>", offset.0),
+                },
+            };
+            Some(ArrayBound::Dynamic(description))
+        }
+        _ => None,
+    };
+    Ok(bound)
 }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn analyze_elf_wasm(data: &[u8]) -> String {
-    match analyze_elf_from_bytes_with_dwarf(data) {
-        Ok(result) => match serde_json::to_string_pretty(&result) {
-            Ok(json) => json,
-            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
-        },
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+fn unit_language(
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<Option<gimli::DwLang>> {
+    let mut entries = unit.entries();
+    if let Some((_, root)) = entries.next_dfs()? {
+        if let Some(gimli::AttributeValue::Language(language)) =
+            root.attr_value(gimli::DW_AT_language)?
+        {
+            return Ok(Some(language));
+        }
     }
+    Ok(None)
 }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn analyze_elf_basic_wasm(data: &[u8]) -> String {
-    match analyze_elf_from_bytes_basic(data) {
-        Ok(result) => match serde_json::to_string_pretty(&result) {
-            Ok(json) => json,
-            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
-        },
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
-    }
+fn is_cplusplus(unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>) -> Result<bool> {
+    Ok(matches!(
+        unit_language(unit)?,
+        Some(
+            gimli::DW_LANG_C_plus_plus
+                | gimli::DW_LANG_C_plus_plus_03
+                | gimli::DW_LANG_C_plus_plus_11
+                | gimli::DW_LANG_C_plus_plus_14
+                | gimli::DW_LANG_C_plus_plus_17
+                | gimli::DW_LANG_C_plus_plus_20
+        )
+    ))
 }
 
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn analyze_files_wasm(elf_data: Option<Box<[u8]>>) -> String {
-    let elf_slice = elf_data.as_deref();
-
-    match analyze_files_from_bytes(elf_slice) {
-        Ok(result) => match to_json(&result) {
-            Ok(json) => json,
-            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
-        },
-        Err(e) => format!("{{\"error\": \"{}\"}}", e),
-    }
+/// Lower bound assumed when `DW_AT_lower_bound` is absent (DWARF 5, section 5.13)
+fn default_lower_bound(unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>) -> Result<i64> {
+    Ok(match unit_language(unit)? {
+        Some(
+            gimli::DW_LANG_Ada83
+            | gimli::DW_LANG_Ada95
+            | gimli::DW_LANG_Ada2005
+            | gimli::DW_LANG_Ada2012
+            | gimli::DW_LANG_Cobol74
+            | gimli::DW_LANG_Cobol85
+            | gimli::DW_LANG_Fortran77
+            | gimli::DW_LANG_Fortran90
+            | gimli::DW_LANG_Fortran95
+            | gimli::DW_LANG_Fortran03
+            | gimli::DW_LANG_Fortran08
+            | gimli::DW_LANG_Fortran18
+            | gimli::DW_LANG_Modula2
+            | gimli::DW_LANG_Modula3
+            | gimli::DW_LANG_Pascal83
+            | gimli::DW_LANG_PLI,
+        ) => 1,
+        _ => 0,
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
-    use tempfile::NamedTempFile;
-
-    fn create_test_elf_file() -> NamedTempFile {
-        let mut file = NamedTempFile::new().expect("Failed to create temp file");
-
-        // Create a minimal valid ELF header for x86_64
-        let elf_header = [
-            0x7f, 0x45, 0x4c, 0x46, // ELF magic
-            0x02, // 64-bit
-            0x01, // Little endian
-            0x01, // ELF version
-            0x00, // System V ABI
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Padding
-            0x02, 0x00, // ET_EXEC (executable file)
-            0x3e, 0x00, // EM_X86_64
-            0x01, 0x00, 0x00, 0x00, // Version
-            0x00, 0x10, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, // Entry point
-            0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Program header offset
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Section header offset
-            0x00, 0x00, 0x00, 0x00, // Flags
-            0x40, 0x00, // ELF header size
-            0x38, 0x00, // Program header size
-            0x00, 0x00, // Program header count
-            0x40, 0x00, // Section header size
-            0x00, 0x00, // Section header count
-            0x00, 0x00, // Section header string table index
-        ];
-
-        file.write_all(&elf_header)
-            .expect("Failed to write ELF header");
-        file
+/// Total array size for arrays without `DW_AT_byte_size`
+fn array_byte_size(array: &ArrayInfo) -> Option<u64> {
+    let mut elements: u64 = 1;
+    for dimension in &array.dimensions {
+        elements = elements.checked_mul(dimension.count?)?;
     }
 
-    fn create_invalid_file() -> NamedTempFile {
-        let mut file = NamedTempFile::new().expect("Failed to create temp file");
-        file.write_all(b"not an elf file")
-            .expect("Failed to write invalid data");
-        file
+    if let Some(bit_stride) = array.bit_stride {
+        return Some(elements.checked_mul(bit_stride)?.div_ceil(8));
     }
+    let stride = array.byte_stride.or(array.element_type.size)?;
+    elements.checked_mul(stride)
+}
 
-    #[test]
-    fn test_analysis_result_serialization() {
-        let result = AnalysisResult { elf_info: None };
-
-        let json = to_json(&result).unwrap();
-        assert!(json.contains("elf_info"));
-        assert!(json.contains("null"));
+fn array_type_name(array: &ArrayInfo, element_name: &str) -> String {
+    let mut name = element_name.to_string();
+    for dimension in &array.dimensions {
+        match (dimension.count, dimension.lower_bound) {
+            (Some(count), 0) => name.push_str(&format!("[{count}]")),
+            (Some(_), lower) => name.push_str(&format!(
+                "[{lower}..{}]",
+                dimension.upper_bound.unwrap_or(lower)
+            )),
+            (None, _) => name.push_str("[]"),
+        }
     }
+    name
+}
 
-    #[test]
-    fn test_analysis_result_with_data_serialization() {
-        let elf_info = ElfInfo {
-            architecture: "x86_64".to_string(),
-            entry_point: 0x401000,
-            sections: vec![".text".to_string(), ".data".to_string()],
-            file_type: "executable".to_string(),
-            endianness: "little_endian".to_string(),
-            functions: Vec::new(),
-            variables: Vec::new(),
-            types: Vec::new(),
+/// Flag a trailing struct member as a flexible array member if its outermost
+/// dimension has no elements or no known count
+fn mark_flexible_array(type_info: &mut TypeInfo) {
+    if type_info.kind != "array" {
+        return;
+    }
+    if let Some(array) = type_info.array.as_mut() {
+        let unbounded = match array.dimensions.first() {
+            Some(dimension) => {
+                dimension.dynamic_bound.is_none() && dimension.count.unwrap_or(0) == 0
+            }
+            None => true,
         };
+        if unbounded {
+            array.flexible = true;
+            type_info.size = Some(0);
+        }
+    }
+}
 
-        let result = AnalysisResult {
-            elf_info: Some(elf_info),
-        };
+fn extract_enumerator_info(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<EnumeratorInfo> {
+    let name = get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<unknown>".to_string());
+    // Negative values are emitted as DW_FORM_sdata, so fixed-size forms are unsigned
+    let value = match entry.attr_value(gimli::DW_AT_const_value)? {
+        Some(gimli::AttributeValue::Data1(value)) => value.into(),
+        Some(gimli::AttributeValue::Data2(value)) => value.into(),
+        Some(gimli::AttributeValue::Data4(value)) => value.into(),
+        Some(gimli::AttributeValue::Data8(value)) => value as i64,
+        Some(gimli::AttributeValue::Udata(value)) => value as i64,
+        Some(gimli::AttributeValue::Sdata(value)) => value,
+        _ => 0,
+    };
+    Ok(EnumeratorInfo { name, value })
+}
 
-        let json = to_json(&result).unwrap();
+/// Decode a `DW_TAG_variant_part`: the tag member referenced by `DW_AT_discr`
+/// and one `DW_TAG_variant` per enum variant, each wrapping a single payload member
+fn extract_variant_part_info(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &mut UnitCache,
+) -> Result<VariantPartInfo> {
+    let discriminant = match entry.attr_value(gimli::DW_AT_discr)? {
+        Some(gimli::AttributeValue::UnitRef(offset)) => Some(extract_member_info(
+            dwarf,
+            unit,
+            &unit.entry(offset)?,
+            cache,
+            None,
+        )?),
+        _ => None,
+    };
 
-        assert!(json.contains("x86_64"));
-        assert!(json.contains("4198400")); // 0x401000 in decimal
-        assert!(json.contains(".text"));
-        assert!(json.contains("executable"));
-        assert!(json.contains("little_endian"));
+    let mut variants = Vec::new();
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    let root = tree.root()?;
+    let mut children = root.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() != gimli::DW_TAG_variant {
+            continue;
+        }
+        let discriminant_value = match child.entry().attr_value(gimli::DW_AT_discr_value)? {
+            Some(gimli::AttributeValue::Data1(value)) => Some(value.into()),
+            Some(gimli::AttributeValue::Data2(value)) => Some(value.into()),
+            Some(gimli::AttributeValue::Data4(value)) => Some(value.into()),
+            Some(gimli::AttributeValue::Data8(value)) => Some(value),
+            Some(gimli::AttributeValue::Udata(value)) => Some(value),
+            Some(gimli::AttributeValue::Sdata(value)) => Some(value as u64),
+            _ => None,
+        };
+
+        let mut payloads = child.children();
+        while let Some(payload) = payloads.next()? {
+            if payload.entry().tag() != gimli::DW_TAG_member {
+                continue;
+            }
+            let member = extract_member_info(dwarf, unit, payload.entry(), cache, None)?;
+            variants.push(VariantInfo {
+                name: member.name,
+                discriminant_value,
+                offset: member.offset,
+                payload: member.type_info,
+            });
+        }
     }
 
-    #[test]
-    fn test_analyze_elf_success() {
-        let temp_file = create_test_elf_file();
-        let file_path = temp_file.path().to_str().unwrap();
+    let niche = discriminant.as_ref().is_some_and(|tag| {
+        variants
+            .iter()
+            .any(|variant| overlaps_payload(tag, variant))
+    });
 
-        let result = analyze_elf(file_path).unwrap();
+    Ok(VariantPartInfo {
+        discriminant,
+        variants,
+        niche,
+    })
+}
 
-        assert_eq!(result.architecture, "x86_64");
-        assert_eq!(result.entry_point, 0x401000);
-        assert_eq!(result.file_type, "executable");
-        assert_eq!(result.endianness, "little_endian");
-        assert!(result.sections.is_empty()); // No sections in minimal ELF
-    }
+/// Whether the tag shares its bytes with a payload field, as in niche-filling layouts
+fn overlaps_payload(tag: &MemberInfo, variant: &VariantInfo) -> bool {
+    let tag_start = tag.offset;
+    let tag_end = tag_start.saturating_add(tag.type_info.size.unwrap_or(0));
+    variant.payload.members.iter().any(|field| {
+        let field_start = variant.offset.saturating_add(field.offset);
+        let field_end = field_start.saturating_add(field.type_info.size.unwrap_or(0));
+        field_start < tag_end && tag_start < field_end
+    })
+}
 
-    #[test]
-    fn test_analyze_elf_file_not_found() {
-        let result = analyze_elf("/nonexistent/path");
-        assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Failed to read ELF file"));
+/// Recognise the fat pointers rustc describes as plain structures: slices and
+/// `str` as `{ data_ptr, length }`, trait objects as `{ pointer, vtable }`.
+/// Returns the kind and the pointee (element type or `dyn Trait`)
+fn rust_fat_pointer_kind(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    name: Option<&str>,
+    cache: &mut UnitCache,
+) -> Result<Option<(&'static str, Option<TypeInfo>)>> {
+    if unit_language(unit)? != Some(gimli::DW_LANG_Rust) {
+        return Ok(None);
     }
 
-    #[test]
-    fn test_analyze_elf_invalid_file() {
-        let temp_file = create_invalid_file();
-        let file_path = temp_file.path().to_str().unwrap();
+    let mut fields = Vec::new();
+    let mut tree = unit.entries_tree(Some(entry.offset()))?;
+    let root = tree.root()?;
+    let mut children = root.children();
+    while let Some(child) = children.next()? {
+        if child.entry().tag() != gimli::DW_TAG_member || fields.len() == 2 {
+            return Ok(None);
+        }
+        let field_name = get_die_name(dwarf, unit, child.entry())?.unwrap_or_default();
+        fields.push((field_name, child.entry().offset()));
+    }
 
-        let result = analyze_elf(file_path);
-        assert!(result.is_err());
+    let kind = match fields
+        .iter()
+        .map(|(field_name, _)| field_name.as_str())
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["data_ptr", "length"] => {
+            // `&str`, `&mut str`, `*const str`, `Box<str>` all end in a bare `str`
+            let pointee_name = name
+                .unwrap_or_default()
+                .rsplit(['&', ' ', '<'])
+                .next()
+                .unwrap_or_default();
+            if pointee_name.trim_end_matches('>') == "str" {
+                "str"
+            } else {
+                "slice"
+            }
+        }
+        ["pointer", "vtable"] => "trait_object",
+        _ => return Ok(None),
+    };
 
-        let error_msg = result.unwrap_err().to_string();
+    let pointer = unit.entry(fields[0].1)?;
+    let pointee = resolve_type_attr(dwarf, unit, &pointer, cache, true)?
+        .and_then(|pointer_type| pointer_type.target.map(|target| *target));
+    Ok(Some((kind, pointee)))
+}
 
-        // More flexible error checking - any parsing error is acceptable
-        assert!(
-            error_msg.contains("File is not a valid ELF binary")
-                || error_msg.contains("Bad magic")
-                || error_msg.contains("Malformed")
-                || error_msg.contains("too small")
-        );
+fn format_expression(
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    expression: gimli::Expression<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> String {
+    let mut parts = Vec::new();
+    let mut operations = expression.operations(unit.encoding());
+    while let Ok(Some(operation)) = operations.next() {
+        parts.push(match operation {
+            gimli::Operation::Address { address } => format!("DW_OP_addr 0x{address:x}"),
+            gimli::Operation::Deref { .. } => "DW_OP_deref".to_string(),
+            gimli::Operation::FrameOffset { offset } => format!("DW_OP_fbreg {offset}"),
+            gimli::Operation::Register { register } => format!("DW_OP_reg{}", register.0),
+            gimli::Operation::RegisterOffset {
+                register, offset, ..
+            } => format!("DW_OP_breg{} {offset}", register.0),
+            gimli::Operation::UnsignedConstant { value } => format!("DW_OP_constu {value}"),
+            gimli::Operation::SignedConstant { value } => format!("DW_OP_consts {value}"),
+            gimli::Operation::PlusConstant { value } => format!("DW_OP_plus_uconst {value}"),
+            gimli::Operation::Plus => "DW_OP_plus".to_string(),
+            gimli::Operation::Minus => "DW_OP_minus".to_string(),
+            gimli::Operation::Mul => "DW_OP_mul".to_string(),
+            gimli::Operation::CallFrameCFA => "DW_OP_call_frame_cfa".to_string(),
+            gimli::Operation::StackValue => "DW_OP_stack_value".to_string(),
+            other => format!("{other:?}"),
+        });
     }
+    parts.join("; ")
+}
 
-    #[test]
-    fn test_analyze_files_elf_valid() {
-        let elf_file = create_test_elf_file();
-        let elf_path = elf_file.path().to_str().unwrap();
+fn extract_member_info(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    cache: &mut UnitCache,
+    default_accessibility: Option<&str>,
+) -> Result<MemberInfo> {
+    let name = get_die_name(dwarf, unit, entry)?.unwrap_or_else(|| "<unknown>".to_string());
 
-        let result = analyze_files(Some(elf_path)).unwrap();
+    let mut offset = entry
+        .attr_value(gimli::DW_AT_data_member_location)?
+        .and_then(|attr| match attr {
+            gimli::AttributeValue::Udata(offset) => Some(offset),
+            _ => None,
+        })
+        .unwrap_or(0);
 
-        assert!(result.elf_info.is_some());
+    let type_info = resolve_type_attr(dwarf, unit, entry, cache, false)?.unwrap_or_else(void_type);
+
+    let accessibility = get_accessibility(entry)?.or(default_accessibility.map(str::to_string));
+
+    let bit_size = entry
+        .attr_value(gimli::DW_AT_bit_size)?
+        .and_then(|attr| attr.udata_value());
+    let bit_offset = match (
+        entry
+            .attr_value(gimli::DW_AT_data_bit_offset)?
+            .and_then(|attr| attr.udata_value()),
+        entry
+            .attr_value(gimli::DW_AT_bit_offset)?
+            .and_then(|attr| attr.udata_value()),
+    ) {
+        // Relative to the member's byte location when a producer emits both
+        (Some(data_bit_offset), _) => {
+            let bit_offset = offset
+                .checked_mul(8)
+                .and_then(|bits| bits.checked_add(data_bit_offset));
+            if let Some(bit_offset) = bit_offset {
+                offset = bit_offset / 8;
+            }
+            bit_offset
+        }
+        // DWARF 2/3 count from the most significant bit of the storage unit
+        (None, Some(msb_offset)) => {
+            let storage_bits = entry
+                .attr_value(gimli::DW_AT_byte_size)?
+                .and_then(|attr| attr.udata_value())
+                .or(type_info.size)
+                .unwrap_or(0)
+                .checked_mul(8);
+            let msb_end = msb_offset.checked_add(bit_size.unwrap_or(0));
+            storage_bits
+                .zip(msb_end)
+                .and_then(|(storage_bits, msb_end)| storage_bits.checked_sub(msb_end))
+                .and_then(|lsb_offset| offset.checked_mul(8)?.checked_add(lsb_offset))
+        }
+        (None, None) => None,
+    };
 
-        let elf_info = result.elf_info.unwrap();
-        assert_eq!(elf_info.architecture, "x86_64");
-    }
+    let alignment = entry
+        .attr_value(gimli::DW_AT_alignment)?
+        .and_then(|attr| attr.udata_value());
 
-    #[test]
-    fn test_analyze_files_only_elf() {
-        let elf_file = create_test_elf_file();
-        let elf_path = elf_file.path().to_str().unwrap();
+    Ok(MemberInfo {
+        name,
+        offset,
+        type_info,
+        accessibility,
+        bit_size,
+        bit_offset,
+        alignment,
+    })
+}
 
-        let result = analyze_files(Some(elf_path)).unwrap();
+pub(crate) fn get_die_name(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<Option<String>> {
+    get_declared_string(dwarf, unit, entry, gimli::DW_AT_name)
+}
 
-        assert!(result.elf_info.is_some());
+pub(crate) fn get_die_linkage_name(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+) -> Result<Option<String>> {
+    match get_declared_string(dwarf, unit, entry, gimli::DW_AT_linkage_name)? {
+        Some(name) => Ok(Some(name)),
+        None => get_declared_string(dwarf, unit, entry, gimli::DW_AT_MIPS_linkage_name),
     }
+}
 
-    #[test]
-    fn test_analyze_files_none() {
-        let result = analyze_files(None).unwrap();
-
-        assert!(result.elf_info.is_none());
+/// Read a string attribute, falling back to the declaration of a definition
+/// (out-of-line definitions and inlined instances carry no name of their own)
+fn get_declared_string(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    attr_name: gimli::DwAt,
+) -> Result<Option<String>> {
+    if let Some(value) = get_die_string(dwarf, unit, entry, attr_name)? {
+        return Ok(Some(value));
+    }
+    let declaration = declaration_offset(unit, entry)?;
+    if declaration == entry.offset() {
+        return Ok(None);
     }
+    get_die_string(dwarf, unit, &unit.entry(declaration)?, attr_name)
+}
 
-    #[test]
-    fn test_analyze_files_elf_error() {
-        let result = analyze_files(Some("/nonexistent/elf"));
-        assert!(result.is_err());
+fn get_die_string(
+    dwarf: &gimli::Dwarf<gimli::EndianSlice<gimli::LittleEndian>>,
+    unit: &gimli::Unit<gimli::EndianSlice<gimli::LittleEndian>>,
+    entry: &gimli::DebuggingInformationEntry<gimli::EndianSlice<gimli::LittleEndian>>,
+    attr_name: gimli::DwAt,
+) -> Result<Option<String>> {
+    match entry.attr_value(attr_name)? {
+        Some(attr) => match dwarf.attr_string(unit, attr) {
+            Ok(value) => Ok(Some(value.to_string_lossy().into_owned())),
+            Err(_) => Ok(None),
+        },
+        None => Ok(None),
     }
+}
 
-    #[test]
-    fn test_extract_elf_info_different_architectures() {
-        // Since we can't easily mock goblin::elf::Elf, we'll test via the public interface
-        // by creating different architecture ELF files and testing the extract logic indirectly
+/// Parse an ELF file and extract basic information (backward compatibility)
+pub fn analyze_elf(file_path: &str) -> Result<ElfInfo> {
+    analyze_elf_with_dwarf(file_path)
+}
 
-        // This test verifies that our architecture mapping logic works
-        // We've already tested this through the integration tests above with real ELF files
+/// Parse an ELF file and extract basic information only (no DWARF)
+pub fn analyze_elf_basic(file_path: &str) -> Result<ElfInfo> {
+    let buffer =
+        fs::read(file_path).with_context(|| format!("Failed to read ELF file: {file_path}"))?;
 
-        // Test the architecture string mapping directly
-        use goblin::elf::header::*;
+    match Object::parse(&buffer)? {
+        Object::Elf(elf) => Ok(extract_elf_info(&elf)),
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    }
+}
 
-        let test_cases = vec![
-            (EM_X86_64, "x86_64"),
-            (EM_386, "i386"),
-            (EM_AARCH64, "aarch64"),
-            (EM_ARM, "arm"),
-            (EM_RISCV, "riscv"),
-            (999, "unknown"), // Unknown architecture
-        ];
-
-        for (machine_type, expected_arch) in test_cases {
-            let arch_str = match machine_type {
-                EM_X86_64 => "x86_64",
-                EM_386 => "i386",
-                EM_AARCH64 => "aarch64",
-                EM_ARM => "arm",
-                EM_RISCV => "riscv",
-                _ => "unknown",
-            };
-            assert_eq!(arch_str, expected_arch);
-        }
+/// Parse an ELF file from byte buffer and extract basic information only (no DWARF)
+pub fn analyze_elf_from_bytes_basic(buffer: &[u8]) -> Result<ElfInfo> {
+    match Object::parse(buffer)? {
+        Object::Elf(elf) => Ok(extract_elf_info(&elf)),
+        _ => anyhow::bail!("File is not a valid ELF binary"),
     }
+}
 
-    #[test]
-    fn test_to_json_error_handling() {
-        // Test with a result that should serialize properly
-        let result = AnalysisResult { elf_info: None };
+/// Parse an ELF file from byte buffer and extract basic information (WebAssembly-compatible)
+pub fn analyze_elf_from_bytes(buffer: &[u8]) -> Result<ElfInfo> {
+    analyze_elf_from_bytes_with_dwarf(buffer)
+}
 
-        let json_result = to_json(&result);
-        assert!(json_result.is_ok());
+fn extract_elf_info(elf: &Elf) -> ElfInfo {
+    use goblin::elf::header::*;
 
-        let json = json_result.unwrap();
-        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+    let architecture = match elf.header.e_machine {
+        EM_X86_64 => "x86_64",
+        EM_386 => "i386",
+        EM_AARCH64 => "aarch64",
+        EM_ARM => "arm",
+        EM_RISCV => "riscv",
+        _ => "unknown",
     }
+    .to_string();
 
-    #[test]
-    fn test_elf_info_creation() {
-        let elf = ElfInfo {
-            architecture: "arm".to_string(),
-            entry_point: 0x8000,
-            sections: vec![".init".to_string(), ".fini".to_string()],
-            file_type: "shared_object".to_string(),
-            endianness: "big_endian".to_string(),
-            functions: Vec::new(),
-            variables: Vec::new(),
-            types: Vec::new(),
-        };
-
-        assert_eq!(elf.architecture, "arm");
-        assert_eq!(elf.entry_point, 0x8000);
-        assert_eq!(elf.sections.len(), 2);
-        assert_eq!(elf.file_type, "shared_object");
-        assert_eq!(elf.endianness, "big_endian");
+    let file_type = match elf.header.e_type {
+        ET_EXEC => "executable",
+        ET_DYN => "shared_object",
+        ET_REL => "relocatable",
+        ET_CORE => "core_dump",
+        _ => "unknown",
     }
+    .to_string();
 
-    #[test]
-    fn test_analyze_elf_from_bytes_success() {
-        let temp_file = create_test_elf_file();
-        let buffer = std::fs::read(temp_file.path()).unwrap();
+    let endianness = if elf.little_endian {
+        "little_endian".to_string()
+    } else {
+        "big_endian".to_string()
+    };
 
-        let result = analyze_elf_from_bytes(&buffer).unwrap();
+    let sections = elf
+        .section_headers
+        .iter()
+        .map(|section| {
+            elf.shdr_strtab
+                .get_at(section.sh_name)
+                .unwrap_or("unnamed")
+                .to_string()
+        })
+        .collect::<Vec<String>>();
 
-        assert_eq!(result.architecture, "x86_64");
-        assert_eq!(result.entry_point, 0x401000);
-        assert_eq!(result.file_type, "executable");
-        assert_eq!(result.endianness, "little_endian");
-        assert!(result.sections.is_empty()); // No sections in minimal ELF
-    }
+    let section_headers = elf
+        .section_headers
+        .iter()
+        .zip(&sections)
+        .map(|(section, name)| SectionInfo {
+            name: name.clone(),
+            kind: goblin::elf::section_header::sht_to_str(section.sh_type)
+                .trim_start_matches("SHT_")
+                .to_lowercase(),
+            address: section.sh_addr,
+            offset: section.sh_offset,
+            size: section.sh_size,
+            alloc: section.is_alloc(),
+            write: section.is_writable(),
+            exec: section.is_executable(),
+        })
+        .collect();
 
-    #[test]
-    fn test_analyze_elf_from_bytes_invalid() {
-        let invalid_data = vec![0x00, 0x01, 0x02, 0x03]; // Not a valid ELF file
-        let result = analyze_elf_from_bytes(&invalid_data);
-        assert!(result.is_err());
+    ElfInfo {
+        architecture,
+        entry_point: elf.entry,
+        sections,
+        section_headers,
+        file_type,
+        endianness,
+        functions: Vec::new(),
+        variables: Vec::new(),
+        types: Vec::new(),
+        type_conflicts: Vec::new(),
     }
+}
 
-    #[test]
-    fn test_analyze_files_from_bytes_elf() {
-        let elf_file = create_test_elf_file();
-        let elf_buffer = std::fs::read(elf_file.path()).unwrap();
+/// Analyze ELF files
+pub fn analyze_files(elf_path: Option<&str>) -> Result<AnalysisResult> {
+    let elf_info = if let Some(path) = elf_path {
+        Some(analyze_elf(path)?)
+    } else {
+        None
+    };
 
-        let result = analyze_files_from_bytes(Some(&elf_buffer)).unwrap();
+    Ok(AnalysisResult { elf_info })
+}
 
-        assert!(result.elf_info.is_some());
+/// Analyze ELF files from byte buffers (WebAssembly-compatible)
+pub fn analyze_files_from_bytes(elf_data: Option<&[u8]>) -> Result<AnalysisResult> {
+    let elf_info = if let Some(data) = elf_data {
+        Some(analyze_elf_from_bytes(data)?)
+    } else {
+        None
+    };
 
-        let elf_info = result.elf_info.unwrap();
-        assert_eq!(elf_info.architecture, "x86_64");
-    }
+    Ok(AnalysisResult { elf_info })
+}
 
-    #[test]
-    fn test_analyze_files_from_bytes_elf_only() {
-        let elf_file = create_test_elf_file();
-        let elf_buffer = std::fs::read(elf_file.path()).unwrap();
+/// Convert an analysis result or report to prettified JSON
+pub fn to_json<T: Serialize>(result: &T) -> Result<String> {
+    serde_json::to_string_pretty(result).context("Failed to serialize result to JSON")
+}
 
-        let result = analyze_files_from_bytes(Some(&elf_buffer)).unwrap();
+// WebAssembly bindings
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(start)]
+pub fn main() {
+    console_error_panic_hook::set_once();
+}
 
-        assert!(result.elf_info.is_some());
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn analyze_elf_wasm(data: &[u8]) -> String {
+    match analyze_elf_from_bytes_with_dwarf(data) {
+        Ok(result) => match serde_json::to_string_pretty(&result) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
     }
+}
 
-    #[test]
-    fn test_analyze_files_from_bytes_none() {
-        let result = analyze_files_from_bytes(None).unwrap();
-
-        assert!(result.elf_info.is_none());
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn analyze_elf_basic_wasm(data: &[u8]) -> String {
+    match analyze_elf_from_bytes_basic(data) {
+        Ok(result) => match serde_json::to_string_pretty(&result) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
     }
+}
 
-    #[test]
-    fn test_dwarf_parsing_with_debug_binary() {
-        // Create a simple C program with debug info
-        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
-        let source_path = temp_dir.path().join("test.c");
-        let binary_path = temp_dir.path().join("test");
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn analyze_files_wasm(elf_data: Option<Box<[u8]>>) -> String {
+    let elf_slice = elf_data.as_deref();
 
-        let c_source = r#"
-struct Point {
-    int x;
-    int y;
-};
+    match analyze_files_from_bytes(elf_slice) {
+        Ok(result) => match to_json(&result) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-enum Color {
-    RED,
-    GREEN,
-    BLUE
-};
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn analyze_layouts_wasm(data: &[u8], cacheline_size: u32) -> String {
+    match analyze_elf_from_bytes_with_dwarf(data) {
+        Ok(elf_info) => {
+            let layouts = layout::analyze_layouts(&elf_info, cacheline_size.into());
+            match to_json(&layouts) {
+                Ok(json) => json,
+                Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+            }
+        }
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-int global_var = 42;
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn padding_report_wasm(data: &[u8]) -> String {
+    match analyze_elf_from_bytes_with_dwarf(data) {
+        Ok(elf_info) => match to_json(&layout::padding_report(&elf_info)) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-int add(int a, int b) {
-    return a + b;
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn analyze_sizes_wasm(data: &[u8]) -> String {
+    match size::analyze_sizes_from_bytes(data) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
 }
 
-int main() {
-    struct Point p = {1, 2};
-    return add(p.x, p.y);
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn diff_wasm(old: &[u8], new: &[u8]) -> String {
+    match diff::diff_from_bytes(old, new) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
 }
-"#;
 
-        std::fs::write(&source_path, c_source).expect("Failed to write C source");
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn check_abi_wasm(old: &[u8], new: &[u8]) -> String {
+    match abi::check_abi_from_bytes(old, new) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-        // Compile with debug info
-        let output = std::process::Command::new("gcc")
-            .args(["-g", "-O0", "-o"])
-            .arg(&binary_path)
-            .arg(&source_path)
-            .output();
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn generate_c_header_wasm(data: &[u8]) -> String {
+    match analyze_elf_from_bytes_with_dwarf(data) {
+        Ok(elf_info) => c_header::generate_c_header(&elf_info, &[]),
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-        if let Ok(output) = output {
-            if output.status.success() {
-                // Test the enhanced analysis
-                if let Ok(result) = analyze_elf_with_dwarf(binary_path.to_str().unwrap()) {
-                    // Should have detected debug information
-                    assert_eq!(result.architecture, "x86_64");
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn generate_rust_bindings_wasm(data: &[u8]) -> String {
+    match analyze_elf_from_bytes_with_dwarf(data) {
+        Ok(elf_info) => rust_bindings::generate_rust_bindings(&elf_info, &[]),
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-                    // Should have extracted some functions
-                    let function_names: Vec<&str> =
-                        result.functions.iter().map(|f| f.name.as_str()).collect();
-                    assert!(function_names.contains(&"main") || function_names.contains(&"add"));
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn read_initial_values_wasm(data: &[u8]) -> String {
+    match values::read_initial_values_from_bytes(data) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-                    // Should have extracted some types
-                    let type_names: Vec<&str> =
-                        result.types.iter().map(|t| t.name.as_str()).collect();
-                    // Note: types might not always be detected depending on compilation
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn analyze_core_wasm(core: &[u8], executable: &[u8]) -> String {
+    match core_dump::analyze_core_from_bytes(core, executable) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
 
-                    println!("Functions found: {function_names:?}");
-                    println!("Types found: {type_names:?}");
-                }
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn backtrace_wasm(
+    executable: &[u8],
+    memory: &[u8],
+    address: u64,
+    load_bias: u64,
+    registers: &str,
+) -> String {
+    // Registers as comma-separated NAME=VALUE pairs, e.g. "rip=0x401136,rsp=0x7ffc1000"
+    let backtrace = registers
+        .split(',')
+        .filter(|register| !register.trim().is_empty())
+        .map(unwind::parse_register)
+        .collect::<Result<Vec<_>>>()
+        .and_then(|registers| {
+            unwind::backtrace_from_bytes(executable, memory, address, load_bias, &registers)
+        });
+    match backtrace {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn cfi_coverage_wasm(data: &[u8]) -> String {
+    match cfi::cfi_coverage_from_bytes(data) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn disassemble_wasm(data: &[u8], function: &str) -> String {
+    let names: Vec<String> = match function {
+        "" => Vec::new(),
+        name => vec![name.to_string()],
+    };
+    match disasm::disassemble_from_bytes(data, &names) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn call_graph_wasm(data: &[u8]) -> String {
+    match callgraph::call_graph_from_bytes(data) {
+        Ok(graph) => match to_json(&graph) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn stack_usage_wasm(data: &[u8], stack_usage: &str) -> String {
+    let stack_usage: Vec<String> = match stack_usage {
+        "" => Vec::new(),
+        text => vec![text.to_string()],
+    };
+    match stack::stack_usage_from_bytes(data, &[], &stack_usage) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn dead_code_wasm(data: &[u8]) -> String {
+    match dead_code::dead_code_from_bytes(data) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn checksec_wasm(data: &[u8]) -> String {
+    match checksec::checksec_from_bytes(data) {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn check_policy_wasm(data: &[u8], policy: &str) -> String {
+    let report = policy::Policy::from_toml(policy)
+        .and_then(|policy| policy::check_policy_from_bytes(data, &policy));
+    match report {
+        Ok(report) => match to_json(&report) {
+            Ok(json) => json,
+            Err(e) => format!("{{\"error\": \"Failed to serialize result: {}\"}}", e),
+        },
+        Err(e) => format!("{{\"error\": \"{}\"}}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_test_elf_file() -> NamedTempFile {
+        let mut file = NamedTempFile::new().expect("Failed to create temp file");
+
+        // Create a minimal valid ELF header for x86_64
+        let elf_header = [
+            0x7f, 0x45, 0x4c, 0x46, // ELF magic
+            0x02, // 64-bit
+            0x01, // Little endian
+            0x01, // ELF version
+            0x00, // System V ABI
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Padding
+            0x02, 0x00, // ET_EXEC (executable file)
+            0x3e, 0x00, // EM_X86_64
+            0x01, 0x00, 0x00, 0x00, // Version
+            0x00, 0x10, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, // Entry point
+            0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Program header offset
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Section header offset
+            0x00, 0x00, 0x00, 0x00, // Flags
+            0x40, 0x00, // ELF header size
+            0x38, 0x00, // Program header size
+            0x00, 0x00, // Program header count
+            0x40, 0x00, // Section header size
+            0x00, 0x00, // Section header count
+            0x00, 0x00, // Section header string table index
+        ];
+
+        file.write_all(&elf_header)
+            .expect("Failed to write ELF header");
+        file
+    }
+
+    fn create_invalid_file() -> NamedTempFile {
+        let mut file = NamedTempFile::new().expect("Failed to create temp file");
+        file.write_all(b"not an elf file")
+            .expect("Failed to write invalid data");
+        file
+    }
+
+    /// Compile source with debug info, or return None when the compiler is unavailable
+    fn compile_with_debug_info(
+        compiler: &str,
+        source_name: &str,
+        source: &str,
+    ) -> Option<(tempfile::TempDir, String)> {
+        compile_sources_with_debug_info(compiler, &[], &[(source_name, source)])
+    }
+
+    /// Compile and link several translation units with extra compiler flags
+    fn compile_sources_with_debug_info(
+        compiler: &str,
+        flags: &[&str],
+        sources: &[(&str, &str)],
+    ) -> Option<(tempfile::TempDir, String)> {
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let binary_path = temp_dir.path().join("test");
+        let mut source_paths = Vec::new();
+        for (source_name, source) in sources {
+            let source_path = temp_dir.path().join(source_name);
+            std::fs::write(&source_path, source).expect("Failed to write source");
+            source_paths.push(source_path);
+        }
+
+        let optimization = if compiler == "rustc" {
+            "-Copt-level=0"
+        } else {
+            "-O0"
+        };
+        let output = std::process::Command::new(compiler)
+            .args(["-g", optimization])
+            .args(flags)
+            .arg("-o")
+            .arg(&binary_path)
+            .args(&source_paths)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let binary_path = binary_path.to_str().unwrap().to_string();
+        Some((temp_dir, binary_path))
+    }
+
+    /// Thumb code of a Cortex-M firmware image: `main` at 0x8000 calls
+    /// `helper` at 0x8008, which uses 16 bytes of stack. The function
+    /// symbols and the entry point have the Thumb bit set
+    const THUMB_CODE: [u8; 16] = [
+        0x80, 0xb5, // push {r7, lr}
+        0x00, 0xf0, 0x01, 0xf8, // bl 0x8008
+        0x80, 0xbd, // pop {r7, pc}
+        0x84, 0xb0, // sub sp, #16
+        0x04, 0xb0, // add sp, #16
+        0x70, 0x47, // bx lr
+        0x00, 0xbf, // nop
+    ];
+
+    /// ELF32 arm executable of `code` loaded at 0x8000, with symbols of
+    /// (name, value, size): global functions, and local mapping symbols
+    /// such as `$d` listed first
+    fn arm_elf(code: &[u8], entry: u32, functions: &[(&str, u32, u32)]) -> Vec<u8> {
+        let mut strtab = vec![0u8];
+        let mut symtab = vec![0u8; 16];
+        for (name, value, size) in functions {
+            symtab.extend((strtab.len() as u32).to_le_bytes());
+            symtab.extend(value.to_le_bytes());
+            symtab.extend(size.to_le_bytes());
+            // STB_LOCAL STT_NOTYPE or STB_GLOBAL STT_FUNC, in section 1
+            let info = if name.starts_with('$') { 0x00 } else { 0x12 };
+            symtab.extend([info, 0, 1, 0]);
+            strtab.extend(name.as_bytes());
+            strtab.push(0);
+        }
+        let locals = functions
+            .iter()
+            .filter(|(name, _, _)| name.starts_with('$'))
+            .count() as u32;
+        let shstrtab = b"\0.text\0.symtab\0.strtab\0.shstrtab\0";
+        let mut elf = vec![0u8; 52];
+        let mut sections = vec![[0u32; 10]];
+        for (name, kind, flags, address, data, link, info, entsize) in [
+            (1, 1, 6, 0x8000, code, 0, 0, 0),
+            (7, 2, 0, 0, &symtab[..], 3, 1 + locals, 16),
+            (15, 3, 0, 0, &strtab[..], 0, 0, 0),
+            (23, 3, 0, 0, &shstrtab[..], 0, 0, 0),
+        ] {
+            elf.resize(elf.len().next_multiple_of(4), 0);
+            let offset = elf.len() as u32;
+            elf.extend(data);
+            let size = data.len() as u32;
+            sections.push([
+                name, kind, flags, address, offset, size, link, info, 4, entsize,
+            ]);
+        }
+        elf.resize(elf.len().next_multiple_of(4), 0);
+        let section_offset = elf.len() as u32;
+        for section in &sections {
+            for field in section {
+                elf.extend(field.to_le_bytes());
             }
         }
-        // Test should not fail if gcc is not available - this is an optional enhancement test
+        let mut header = vec![0x7f, b'E', b'L', b'F', 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        header.extend(2u16.to_le_bytes()); // ET_EXEC
+        header.extend(40u16.to_le_bytes()); // EM_ARM
+        header.extend(1u32.to_le_bytes());
+        header.extend(entry.to_le_bytes());
+        header.extend(0u32.to_le_bytes()); // No program headers
+        header.extend(section_offset.to_le_bytes());
+        header.extend(0x0500_0000u32.to_le_bytes()); // EABI version 5
+        for half in [52u16, 32, 0, 40, sections.len() as u16, 4] {
+            header.extend(half.to_le_bytes());
+        }
+        elf[..52].copy_from_slice(&header);
+        elf
     }
 
     #[test]
-    fn test_dwarf_data_structures() {
-        // Test the new data structures for serialization
-        let function_info = FunctionInfo {
-            name: "test_function".to_string(),
-            address: 0x1000,
-            size: Some(100),
-            parameters: vec![VariableInfo {
-                name: "param1".to_string(),
-                address: None,
-                offset: Some(8),
-                type_info: TypeInfo {
-                    name: "int".to_string(),
-                    size: Some(4),
-                    kind: "basic".to_string(),
-                    members: Vec::new(),
-                },
-                scope: "parameter".to_string(),
-            }],
-            return_type: Some(TypeInfo {
-                name: "int".to_string(),
-                size: Some(4),
-                kind: "basic".to_string(),
-                members: Vec::new(),
-            }),
+    fn test_analysis_result_serialization() {
+        let result = AnalysisResult { elf_info: None };
+
+        let json = to_json(&result).unwrap();
+        assert!(json.contains("elf_info"));
+        assert!(json.contains("null"));
+    }
+
+    #[test]
+    fn test_analysis_result_with_data_serialization() {
+        let elf_info = ElfInfo {
+            architecture: "x86_64".to_string(),
+            entry_point: 0x401000,
+            sections: vec![".text".to_string(), ".data".to_string()],
+            section_headers: Vec::new(),
+            file_type: "executable".to_string(),
+            endianness: "little_endian".to_string(),
+            functions: Vec::new(),
+            variables: Vec::new(),
+            types: Vec::new(),
+            type_conflicts: Vec::new(),
+        };
+
+        let result = AnalysisResult {
+            elf_info: Some(elf_info),
+        };
+
+        let json = to_json(&result).unwrap();
+
+        assert!(json.contains("x86_64"));
+        assert!(json.contains("4198400")); // 0x401000 in decimal
+        assert!(json.contains(".text"));
+        assert!(json.contains("executable"));
+        assert!(json.contains("little_endian"));
+    }
+
+    #[test]
+    fn test_analyze_elf_success() {
+        let temp_file = create_test_elf_file();
+        let file_path = temp_file.path().to_str().unwrap();
+
+        let result = analyze_elf(file_path).unwrap();
+
+        assert_eq!(result.architecture, "x86_64");
+        assert_eq!(result.entry_point, 0x401000);
+        assert_eq!(result.file_type, "executable");
+        assert_eq!(result.endianness, "little_endian");
+        assert!(result.sections.is_empty()); // No sections in minimal ELF
+    }
+
+    #[test]
+    fn test_analyze_elf_file_not_found() {
+        let result = analyze_elf("/nonexistent/path");
+        assert!(result.is_err());
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Failed to read ELF file"));
+    }
+
+    #[test]
+    fn test_analyze_elf_invalid_file() {
+        let temp_file = create_invalid_file();
+        let file_path = temp_file.path().to_str().unwrap();
+
+        let result = analyze_elf(file_path);
+        assert!(result.is_err());
+
+        let error_msg = result.unwrap_err().to_string();
+
+        // More flexible error checking - any parsing error is acceptable
+        assert!(
+            error_msg.contains("File is not a valid ELF binary")
+                || error_msg.contains("Bad magic")
+                || error_msg.contains("Malformed")
+                || error_msg.contains("too small")
+        );
+    }
+
+    #[test]
+    fn test_analyze_files_elf_valid() {
+        let elf_file = create_test_elf_file();
+        let elf_path = elf_file.path().to_str().unwrap();
+
+        let result = analyze_files(Some(elf_path)).unwrap();
+
+        assert!(result.elf_info.is_some());
+
+        let elf_info = result.elf_info.unwrap();
+        assert_eq!(elf_info.architecture, "x86_64");
+    }
+
+    #[test]
+    fn test_analyze_files_only_elf() {
+        let elf_file = create_test_elf_file();
+        let elf_path = elf_file.path().to_str().unwrap();
+
+        let result = analyze_files(Some(elf_path)).unwrap();
+
+        assert!(result.elf_info.is_some());
+    }
+
+    #[test]
+    fn test_analyze_files_none() {
+        let result = analyze_files(None).unwrap();
+
+        assert!(result.elf_info.is_none());
+    }
+
+    #[test]
+    fn test_analyze_files_elf_error() {
+        let result = analyze_files(Some("/nonexistent/elf"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_extract_elf_info_different_architectures() {
+        // Since we can't easily mock goblin::elf::Elf, we'll test via the public interface
+        // by creating different architecture ELF files and testing the extract logic indirectly
+
+        // This test verifies that our architecture mapping logic works
+        // We've already tested this through the integration tests above with real ELF files
+
+        // Test the architecture string mapping directly
+        use goblin::elf::header::*;
+
+        let test_cases = vec![
+            (EM_X86_64, "x86_64"),
+            (EM_386, "i386"),
+            (EM_AARCH64, "aarch64"),
+            (EM_ARM, "arm"),
+            (EM_RISCV, "riscv"),
+            (999, "unknown"), // Unknown architecture
+        ];
+
+        for (machine_type, expected_arch) in test_cases {
+            let arch_str = match machine_type {
+                EM_X86_64 => "x86_64",
+                EM_386 => "i386",
+                EM_AARCH64 => "aarch64",
+                EM_ARM => "arm",
+                EM_RISCV => "riscv",
+                _ => "unknown",
+            };
+            assert_eq!(arch_str, expected_arch);
+        }
+    }
+
+    #[test]
+    fn test_to_json_error_handling() {
+        // Test with a result that should serialize properly
+        let result = AnalysisResult { elf_info: None };
+
+        let json_result = to_json(&result);
+        assert!(json_result.is_ok());
+
+        let json = json_result.unwrap();
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_ok());
+    }
+
+    #[test]
+    fn test_elf_info_creation() {
+        let elf = ElfInfo {
+            architecture: "arm".to_string(),
+            entry_point: 0x8000,
+            sections: vec![".init".to_string(), ".fini".to_string()],
+            section_headers: Vec::new(),
+            file_type: "shared_object".to_string(),
+            endianness: "big_endian".to_string(),
+            functions: Vec::new(),
+            variables: Vec::new(),
+            types: Vec::new(),
+            type_conflicts: Vec::new(),
+        };
+
+        assert_eq!(elf.architecture, "arm");
+        assert_eq!(elf.entry_point, 0x8000);
+        assert_eq!(elf.sections.len(), 2);
+        assert_eq!(elf.file_type, "shared_object");
+        assert_eq!(elf.endianness, "big_endian");
+    }
+
+    #[test]
+    fn test_analyze_elf_from_bytes_success() {
+        let temp_file = create_test_elf_file();
+        let buffer = std::fs::read(temp_file.path()).unwrap();
+
+        let result = analyze_elf_from_bytes(&buffer).unwrap();
+
+        assert_eq!(result.architecture, "x86_64");
+        assert_eq!(result.entry_point, 0x401000);
+        assert_eq!(result.file_type, "executable");
+        assert_eq!(result.endianness, "little_endian");
+        assert!(result.sections.is_empty()); // No sections in minimal ELF
+    }
+
+    #[test]
+    fn test_analyze_elf_from_bytes_invalid() {
+        let invalid_data = vec![0x00, 0x01, 0x02, 0x03]; // Not a valid ELF file
+        let result = analyze_elf_from_bytes(&invalid_data);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_analyze_files_from_bytes_elf() {
+        let elf_file = create_test_elf_file();
+        let elf_buffer = std::fs::read(elf_file.path()).unwrap();
+
+        let result = analyze_files_from_bytes(Some(&elf_buffer)).unwrap();
+
+        assert!(result.elf_info.is_some());
+
+        let elf_info = result.elf_info.unwrap();
+        assert_eq!(elf_info.architecture, "x86_64");
+    }
+
+    #[test]
+    fn test_analyze_files_from_bytes_elf_only() {
+        let elf_file = create_test_elf_file();
+        let elf_buffer = std::fs::read(elf_file.path()).unwrap();
+
+        let result = analyze_files_from_bytes(Some(&elf_buffer)).unwrap();
+
+        assert!(result.elf_info.is_some());
+    }
+
+    #[test]
+    fn test_analyze_files_from_bytes_none() {
+        let result = analyze_files_from_bytes(None).unwrap();
+
+        assert!(result.elf_info.is_none());
+    }
+
+    #[test]
+    fn test_dwarf_parsing_with_debug_binary() {
+        // Create a simple C program with debug info
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let source_path = temp_dir.path().join("test.c");
+        let binary_path = temp_dir.path().join("test");
+
+        let c_source = r#"
+struct Point {
+    int x;
+    int y;
+};
+
+enum Color {
+    RED,
+    GREEN,
+    BLUE
+};
+
+int global_var = 42;
+
+int add(int a, int b) {
+    return a + b;
+}
+
+int main() {
+    struct Point p = {1, 2};
+    return add(p.x, p.y);
+}
+"#;
+
+        std::fs::write(&source_path, c_source).expect("Failed to write C source");
+
+        // Compile with debug info
+        let output = std::process::Command::new("gcc")
+            .args(["-g", "-O0", "-o"])
+            .arg(&binary_path)
+            .arg(&source_path)
+            .output();
+
+        if let Ok(output) = output {
+            if output.status.success() {
+                // Test the enhanced analysis
+                if let Ok(result) = analyze_elf_with_dwarf(binary_path.to_str().unwrap()) {
+                    // Should have detected debug information
+                    assert_eq!(result.architecture, "x86_64");
+
+                    // Should have extracted some functions
+                    let function_names: Vec<&str> =
+                        result.functions.iter().map(|f| f.name.as_str()).collect();
+                    assert!(function_names.contains(&"main") || function_names.contains(&"add"));
+
+                    // Should have extracted some types
+                    let type_names: Vec<&str> =
+                        result.types.iter().map(|t| t.name.as_str()).collect();
+                    // Note: types might not always be detected depending on compilation
+
+                    println!("Functions found: {function_names:?}");
+                    println!("Types found: {type_names:?}");
+                }
+            }
+        }
+        // Test should not fail if gcc is not available - this is an optional enhancement test
+    }
+
+    #[test]
+    fn test_dwarf_data_structures() {
+        // Test the new data structures for serialization
+        let function_info = FunctionInfo {
+            name: "test_function".to_string(),
+            address: 0x1000,
+            size: Some(100),
+            parameters: vec![VariableInfo {
+                name: "param1".to_string(),
+                address: None,
+                offset: Some(8),
+                type_info: TypeInfo {
+                    name: "int".to_string(),
+                    size: Some(4),
+                    kind: "basic".to_string(),
+                    members: Vec::new(),
+                    ..Default::default()
+                },
+                scope: "parameter".to_string(),
+                ..Default::default()
+            }],
+            return_type: Some(TypeInfo {
+                name: "int".to_string(),
+                size: Some(4),
+                kind: "basic".to_string(),
+                members: Vec::new(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let type_info = TypeInfo {
+            name: "TestStruct".to_string(),
+            size: Some(16),
+            kind: "struct".to_string(),
+            members: vec![
+                MemberInfo {
+                    name: "field1".to_string(),
+                    offset: 0,
+                    type_info: TypeInfo {
+                        name: "int".to_string(),
+                        size: Some(4),
+                        kind: "basic".to_string(),
+                        members: Vec::new(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                MemberInfo {
+                    name: "field2".to_string(),
+                    offset: 8,
+                    type_info: TypeInfo {
+                        name: "double".to_string(),
+                        size: Some(8),
+                        kind: "basic".to_string(),
+                        members: Vec::new(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let variable_info = VariableInfo {
+            name: "global_var".to_string(),
+            address: Some(0x2000),
+            offset: None,
+            type_info: type_info.clone(),
+            scope: "global".to_string(),
+            ..Default::default()
+        };
+
+        // Test serialization
+        assert!(serde_json::to_string(&function_info).is_ok());
+        assert!(serde_json::to_string(&type_info).is_ok());
+        assert!(serde_json::to_string(&variable_info).is_ok());
+
+        // Test field access
+        assert_eq!(function_info.name, "test_function");
+        assert_eq!(function_info.address, 0x1000);
+        assert_eq!(function_info.parameters.len(), 1);
+        assert_eq!(function_info.parameters[0].name, "param1");
+
+        assert_eq!(type_info.name, "TestStruct");
+        assert_eq!(type_info.members.len(), 2);
+        assert_eq!(type_info.members[0].offset, 0);
+        assert_eq!(type_info.members[1].offset, 8);
+
+        assert_eq!(variable_info.scope, "global");
+        assert_eq!(variable_info.address, Some(0x2000));
+    }
+
+    #[test]
+    fn test_enhanced_elf_info_json_output() {
+        // Test that the enhanced ElfInfo structure produces valid, jq-compatible JSON
+        let enhanced_elf = ElfInfo {
+            architecture: "x86_64".to_string(),
+            entry_point: 0x1000,
+            sections: vec![".text".to_string(), ".data".to_string()],
+            section_headers: Vec::new(),
+            file_type: "executable".to_string(),
+            endianness: "little_endian".to_string(),
+            functions: vec![FunctionInfo {
+                name: "main".to_string(),
+                address: 0x1100,
+                size: Some(50),
+                parameters: vec![VariableInfo {
+                    name: "argc".to_string(),
+                    address: None,
+                    offset: Some(8),
+                    type_info: TypeInfo {
+                        name: "int".to_string(),
+                        size: Some(4),
+                        kind: "basic".to_string(),
+                        members: Vec::new(),
+                        ..Default::default()
+                    },
+                    scope: "parameter".to_string(),
+                    ..Default::default()
+                }],
+                return_type: Some(TypeInfo {
+                    name: "int".to_string(),
+                    size: Some(4),
+                    kind: "basic".to_string(),
+                    members: Vec::new(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            variables: vec![VariableInfo {
+                name: "global_counter".to_string(),
+                address: Some(0x2000),
+                offset: None,
+                type_info: TypeInfo {
+                    name: "int".to_string(),
+                    size: Some(4),
+                    kind: "basic".to_string(),
+                    members: Vec::new(),
+                    ..Default::default()
+                },
+                scope: "global".to_string(),
+                ..Default::default()
+            }],
+            types: vec![TypeInfo {
+                name: "Point".to_string(),
+                size: Some(8),
+                kind: "struct".to_string(),
+                members: vec![
+                    MemberInfo {
+                        name: "x".to_string(),
+                        offset: 0,
+                        type_info: TypeInfo {
+                            name: "int".to_string(),
+                            size: Some(4),
+                            kind: "basic".to_string(),
+                            members: Vec::new(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    MemberInfo {
+                        name: "y".to_string(),
+                        offset: 4,
+                        type_info: TypeInfo {
+                            name: "int".to_string(),
+                            size: Some(4),
+                            kind: "basic".to_string(),
+                            members: Vec::new(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            type_conflicts: Vec::new(),
+        };
+
+        let analysis_result = AnalysisResult {
+            elf_info: Some(enhanced_elf),
+        };
+
+        // Test JSON serialization
+        let json = to_json(&analysis_result).expect("Should serialize to JSON");
+
+        // Verify it's valid JSON
+        let parsed: serde_json::Value = serde_json::from_str(&json).expect("Should be valid JSON");
+
+        // Test jq-like access patterns (verify structure is accessible)
+        assert!(parsed["elf_info"]["functions"].is_array());
+        assert!(parsed["elf_info"]["variables"].is_array());
+        assert!(parsed["elf_info"]["types"].is_array());
+
+        // Verify function information
+        let functions = &parsed["elf_info"]["functions"];
+        assert_eq!(functions[0]["name"], "main");
+        assert_eq!(functions[0]["address"], 0x1100);
+        assert_eq!(functions[0]["parameters"][0]["name"], "argc");
+
+        // Verify type information
+        let types = &parsed["elf_info"]["types"];
+        assert_eq!(types[0]["name"], "Point");
+        assert_eq!(types[0]["kind"], "struct");
+        assert_eq!(types[0]["members"][0]["name"], "x");
+        assert_eq!(types[0]["members"][0]["offset"], 0);
+        assert_eq!(types[0]["members"][1]["offset"], 4);
+
+        // Verify variable information
+        let variables = &parsed["elf_info"]["variables"];
+        assert_eq!(variables[0]["name"], "global_counter");
+        assert_eq!(variables[0]["address"], 0x2000);
+        assert_eq!(variables[0]["scope"], "global");
+    }
+
+    #[test]
+    fn test_analyze_demo_binaries_from_bytes() {
+        // Test basic analysis of demo binaries from byte data (without expensive DWARF parsing)
+        // Using smaller binaries to avoid slow test execution
+        let demo_paths = [
+            "demo-binaries/bin/x86_64/hello",
+            "demo-binaries/bin/aarch64/fibonacci",
+            "demo-binaries/bin/riscv64/hello", // Changed from counter (3.7MB) to hello (544KB) for faster test
+        ];
+
+        for path in demo_paths {
+            if std::path::Path::new(path).exists() {
+                let data = std::fs::read(path).unwrap();
+                // Use basic analysis to avoid expensive DWARF parsing in tests
+                let result = analyze_elf_from_bytes_basic(&data).unwrap();
+                // Note: file_type can be either "executable" or "shared_object" depending on linking
+                assert!(["executable", "shared_object"].contains(&result.file_type.as_str()));
+                assert_eq!(result.endianness, "little_endian");
+                assert!(result.entry_point > 0);
+
+                // Architecture should be valid
+                assert!(["x86_64", "aarch64", "riscv"].contains(&result.architecture.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_dwarf_array_types() {
+        let c_source = r#"
+struct Packet {
+    int len;
+    char name[16];
+    int grid[3][4];
+    unsigned char data[];
+};
+
+struct Legacy {
+    int n;
+    int items[0];
+};
+
+struct Packet packet;
+struct Legacy legacy;
+
+int sum(int n) {
+    int values[n];
+    values[0] = n;
+    return values[0];
+}
+
+int main() {
+    return sum(3);
+}
+"#;
+
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+        let packet = result.types.iter().find(|t| t.name == "Packet").unwrap();
+        let member = |name: &str| {
+            packet
+                .members
+                .iter()
+                .find(|m| m.name == name)
+                .unwrap()
+                .type_info
+                .clone()
+        };
+
+        let name = member("name");
+        assert_eq!(name.kind, "array");
+        assert_eq!(name.name, "char[16]");
+        assert_eq!(name.size, Some(16));
+        let name_array = name.array.unwrap();
+        assert_eq!(name_array.element_type.name, "char");
+        assert_eq!(name_array.dimensions[0].count, Some(16));
+        assert_eq!(name_array.dimensions[0].upper_bound, Some(15));
+        assert!(!name_array.flexible);
+
+        let grid = member("grid");
+        assert_eq!(grid.name, "int[3][4]");
+        assert_eq!(grid.size, Some(48));
+        let grid_counts: Vec<Option<u64>> = grid
+            .array
+            .unwrap()
+            .dimensions
+            .iter()
+            .map(|d| d.count)
+            .collect();
+        assert_eq!(grid_counts, vec![Some(3), Some(4)]);
+
+        let data = member("data");
+        assert!(data.array.unwrap().flexible);
+
+        let legacy = result.types.iter().find(|t| t.name == "Legacy").unwrap();
+        let items = &legacy.members.last().unwrap().type_info;
+        assert!(items.array.as_ref().unwrap().flexible);
+
+        let values = result
+            .variables
+            .iter()
+            .find(|v| v.name == "values")
+            .unwrap();
+        let values_array = values.type_info.array.as_ref().unwrap();
+        assert_eq!(values_array.dimensions[0].count, None);
+        assert!(values_array.dimensions[0].dynamic_bound.is_some());
+        assert_eq!(values.type_info.size, None);
+    }
+
+    #[test]
+    fn test_dwarf_subroutine_types() {
+        let c_source = r#"
+typedef int (*binop_t)(int, int);
+
+struct ops {
+    binop_t apply;
+    int (*log)(const char *, ...);
+    void (*reset)(void);
+};
+
+struct ops table;
+
+int main() {
+    return 0;
+}
+"#;
+
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+        let ops = result.types.iter().find(|t| t.name == "ops").unwrap();
+
+        let apply = &ops.members[0].type_info;
+        assert_eq!(apply.kind, "typedef");
+        let apply_pointer = apply.target.as_ref().unwrap();
+        assert_eq!(apply_pointer.name, "int (*)(int, int)");
+        assert_eq!(apply_pointer.size, Some(8));
+        let apply_signature = apply_pointer.target.as_ref().unwrap();
+        assert_eq!(apply_signature.kind, "subroutine");
+        let subroutine = apply_signature.subroutine.as_ref().unwrap();
+        assert_eq!(subroutine.return_type.as_ref().unwrap().name, "int");
+        assert_eq!(subroutine.parameters.len(), 2);
+        assert!(!subroutine.variadic);
+        assert_eq!(subroutine.calling_convention, "normal");
+
+        let log = &ops.members[1].type_info;
+        assert_eq!(log.name, "int (*)(const char *, ...)");
+        let log_signature = log.target.as_ref().unwrap().subroutine.as_ref().unwrap();
+        assert!(log_signature.variadic);
+        assert_eq!(log_signature.parameters[0].name, "const char *");
+
+        let reset = &ops.members[2].type_info;
+        assert_eq!(reset.name, "void (*)(void)");
+    }
+
+    #[test]
+    fn test_dwarf_cplusplus_class_model() {
+        let cpp_source = r#"
+struct Shape {
+    virtual ~Shape() {}
+    virtual int area() const = 0;
+    int id;
+};
+
+struct Tagged {
+    int tag;
+};
+
+template <typename T, int N>
+class Buffer : public Shape, protected virtual Tagged {
+public:
+    int area() const override { return N; }
+    static int instances;
+    static Buffer make() { return Buffer(); }
+    void push(T value) { data[0] = value; }
+
+private:
+    T data[N];
+};
+
+template <typename T, int N>
+int Buffer<T, N>::instances = 0;
+
+Buffer<short, 4> buffer;
+
+int main() {
+    return buffer.area() + Buffer<short, 4>::instances;
+}
+"#;
+
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("g++", "test.cpp", cpp_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+        let buffer = result
+            .types
+            .iter()
+            .find(|t| t.name.starts_with("Buffer<") && t.class.is_some())
+            .unwrap();
+        assert_eq!(buffer.kind, "class");
+        let class = buffer.class.as_ref().unwrap();
+
+        assert_eq!(class.bases.len(), 2);
+        assert_eq!(class.bases[0].name, "Shape");
+        assert_eq!(class.bases[0].offset, Some(0));
+        assert_eq!(class.bases[0].accessibility, "public");
+        assert!(class.bases[1].is_virtual);
+        assert_eq!(class.bases[1].offset, None);
+        assert_eq!(class.bases[1].accessibility, "protected");
+
+        let method = |name: &str| class.methods.iter().find(|m| m.name == name).unwrap();
+        let area = method("area");
+        assert!(area.is_const);
+        assert_eq!(area.virtuality, "virtual");
+        assert!(area.vtable_slot.is_some());
+        assert!(method("make").is_static);
+        let push = method("push");
+        assert!(!push.is_static && !push.is_const);
+        assert_eq!(push.signature.parameters.len(), 1);
+
+        assert!(class.static_members.iter().any(|v| v.name == "instances"));
+        let data = buffer.members.iter().find(|m| m.name == "data").unwrap();
+        assert_eq!(data.accessibility.as_deref(), Some("private"));
+
+        let parameters: Vec<(&str, &str)> = buffer
+            .template_parameters
+            .iter()
+            .map(|p| (p.name.as_str(), p.kind.as_str()))
+            .collect();
+        assert_eq!(parameters, vec![("T", "type"), ("N", "value")]);
+        assert_eq!(buffer.template_parameters[1].value.as_deref(), Some("4"));
+
+        let shape = result.types.iter().find(|t| t.name == "Shape").unwrap();
+        let shape_area = shape
+            .class
+            .as_ref()
+            .unwrap()
+            .methods
+            .iter()
+            .find(|m| m.name == "area")
+            .unwrap();
+        // GCC reports pure virtuals as DW_VIRTUALITY_virtual
+        assert_ne!(shape_area.virtuality, "none");
+        assert_eq!(shape_area.vtable_slot, area.vtable_slot);
+    }
+
+    #[test]
+    fn test_dwarf_qualified_names() {
+        let cpp_source = r#"
+namespace net {
+int init() { return 1; }
+struct Config {
+    int port;
+};
+Config current;
+namespace detail {
+struct Socket {
+    int fd;
+    int open(int flags);
+};
+int Socket::open(int flags) { return fd + flags; }
+}
+}
+
+namespace storage {
+int init() { return 2; }
+}
+
+namespace {
+int hidden(int value) { return value; }
+}
+
+int main() {
+    net::detail::Socket socket{1};
+    return net::init() + storage::init() + socket.open(0) + hidden(3) + net::current.port;
+}
+"#;
+
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("g++", "test.cpp", cpp_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+        let defined: Vec<&FunctionInfo> =
+            result.functions.iter().filter(|f| f.address != 0).collect();
+        let qualified = |name: &str| {
+            defined
+                .iter()
+                .filter(|f| f.name == name)
+                .filter_map(|f| f.qualified_name.as_deref())
+                .collect::<Vec<&str>>()
+        };
+
+        let mut init_names = qualified("init");
+        init_names.sort();
+        assert_eq!(init_names, vec!["net::init", "storage::init"]);
+        assert_eq!(qualified("open"), vec!["net::detail::Socket::open"]);
+        assert_eq!(qualified("hidden"), vec!["(anonymous namespace)::hidden"]);
+        assert_eq!(qualified("main"), vec!["main"]);
+
+        let open = defined.iter().find(|f| f.name == "open").unwrap();
+        assert_eq!(
+            open.linkage_name.as_deref(),
+            Some("_ZN3net6detail6Socket4openEi")
+        );
+
+        // Parameters belong to their own function only
+        let hidden = defined.iter().find(|f| f.name == "hidden").unwrap();
+        assert_eq!(hidden.parameters.len(), 1);
+        assert!(defined
+            .iter()
+            .filter(|f| f.name == "init")
+            .all(|f| f.parameters.is_empty()));
+
+        let current = result
+            .variables
+            .iter()
+            .find(|v| v.name == "current")
+            .unwrap();
+        assert_eq!(current.qualified_name.as_deref(), Some("net::current"));
+        assert_eq!(
+            current.type_info.qualified_name.as_deref(),
+            Some("net::Config")
+        );
+
+        let socket = result.types.iter().find(|t| t.name == "Socket").unwrap();
+        assert_eq!(
+            socket.qualified_name.as_deref(),
+            Some("net::detail::Socket")
+        );
+    }
+
+    #[test]
+    fn test_dwarf_function_local_static_scope() {
+        let c_source = r#"
+int counter = 1;
+
+int foo(void) {
+    static int counter;
+    return ++counter;
+}
+
+int main(void) {
+    return foo() + counter;
+}
+"#;
+
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+        let mut counters: Vec<&str> = result
+            .variables
+            .iter()
+            .filter(|v| v.name == "counter")
+            .filter_map(|v| v.qualified_name.as_deref())
+            .collect();
+        counters.sort();
+        assert_eq!(counters, vec!["counter", "foo::counter"]);
+    }
+
+    #[test]
+    fn test_demangle_symbol() {
+        assert_eq!(
+            demangle_symbol("_ZN4core3fmt5Write9write_str17h0123456789abcdefE").as_deref(),
+            Some("core::fmt::Write::write_str")
+        );
+        assert_eq!(
+            demangle_symbol("_ZN3net6detail6Socket4openEi").as_deref(),
+            Some("net::detail::Socket::open")
+        );
+        assert_eq!(demangle_symbol("main"), None);
+    }
+
+    #[test]
+    fn test_dwarf_rust_types() {
+        let rust_source = r#"
+use std::fmt::Debug;
+pub enum Shape { Circle(f64), Rect { w: u32, h: u32 }, Empty }
+pub enum Level { Low = 1, High = 10 }
+pub struct Wrapper<T> { pub inner: T, pub count: usize }
+#[inline(never)]
+fn take(s: &Shape, o: Option<&u32>, name: &str, data: &[u16], d: &dyn Debug, w: Wrapper<u8>, l: Level) -> usize {
+    let x = match s { Shape::Circle(_) => 1, Shape::Rect { w, .. } => *w as usize, Shape::Empty => 0 };
+    x + o.map(|v| *v as usize).unwrap_or(0) + name.len() + data.len() + format!("{d:?}").len() + w.count + l as usize
+}
+fn main() {
+    let v = 5u32;
+    let shape = Shape::Rect { w: 1, h: 2 };
+    println!("{}", take(&shape, Some(&v), "hi", &[1, 2], &3, Wrapper { inner: 1u8, count: 2 }, Level::High));
+}
+"#;
+
+        let Some((_temp_dir, binary_path)) =
+            compile_with_debug_info("rustc", "test.rs", rust_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+        let take = result
+            .functions
+            .iter()
+            .find(|f| f.qualified_name.as_deref() == Some("test::take"))
+            .unwrap();
+        let parameter = |name: &str| {
+            &take
+                .parameters
+                .iter()
+                .find(|p| p.name == name)
+                .unwrap()
+                .type_info
+        };
+
+        // Tagged enum: discriminant member plus one payload per variant
+        let shape = result
+            .types
+            .iter()
+            .find(|t| t.qualified_name.as_deref() == Some("test::Shape"))
+            .unwrap();
+        assert_eq!(shape.kind, "enum");
+        let variant_part = shape.variant_part.as_ref().unwrap();
+        assert!(!variant_part.niche);
+        let variants: Vec<(&str, Option<u64>)> = variant_part
+            .variants
+            .iter()
+            .map(|v| (v.name.as_str(), v.discriminant_value))
+            .collect();
+        assert_eq!(
+            variants,
+            vec![("Circle", Some(0)), ("Rect", Some(1)), ("Empty", Some(2))]
+        );
+        let rect = &variant_part.variants[1].payload;
+        let fields: Vec<&str> = rect.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(fields, vec!["w", "h"]);
+        assert_eq!(parameter("s").target.as_ref().unwrap().kind, "enum");
+
+        // Option<&u32> keeps None in the null value of the reference
+        let option = parameter("o");
+        assert_eq!(option.kind, "enum");
+        let niche = option.variant_part.as_ref().unwrap();
+        assert!(niche.niche);
+        let some = niche.variants.iter().find(|v| v.name == "Some").unwrap();
+        assert_eq!(some.discriminant_value, None);
+
+        // Fat pointers
+        let name = parameter("name");
+        assert_eq!(name.kind, "str");
+        assert_eq!(name.target.as_ref().unwrap().name, "u8");
+        let data = parameter("data");
+        assert_eq!(data.kind, "slice");
+        assert_eq!(data.target.as_ref().unwrap().name, "u16");
+        let debug = parameter("d");
+        assert_eq!(debug.kind, "trait_object");
+        assert_eq!(debug.target.as_ref().unwrap().name, "dyn core::fmt::Debug");
+
+        // Generic parameters and C-like enums
+        let wrapper = parameter("w");
+        assert_eq!(wrapper.template_parameters.len(), 1);
+        assert_eq!(wrapper.template_parameters[0].name, "T");
+        assert_eq!(
+            wrapper.template_parameters[0]
+                .type_info
+                .as_ref()
+                .unwrap()
+                .name,
+            "u8"
+        );
+        let level = parameter("l");
+        let enumerators: Vec<(&str, i64)> = level
+            .enumerators
+            .iter()
+            .map(|e| (e.name.as_str(), e.value))
+            .collect();
+        assert_eq!(enumerators, vec![("Low", 1), ("High", 10)]);
+    }
+
+    #[test]
+    fn test_dwarf_decl_locations() {
+        let c_source = r#"struct point {
+    int x;
+    int y;
+};
+
+struct point origin;
+
+int area(struct point *p) {
+    return p->x * p->y;
+}
+
+int main(void) { return area(&origin); }
+"#;
+
+        let Some((temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+        let source_path = temp_dir.path().join("test.c");
+        let source_path = source_path.to_str().unwrap();
+
+        let area = result.functions.iter().find(|f| f.name == "area").unwrap();
+        let location = area.decl_location.as_ref().unwrap();
+        assert_eq!(location.file, source_path);
+        assert_eq!(location.line, Some(8));
+        assert_eq!(location.column, Some(5));
+        let parameter = area.parameters[0].decl_location.as_ref().unwrap();
+        assert_eq!(parameter.line, Some(8));
+        assert_eq!(parameter.column, Some(24));
+
+        let origin = result
+            .variables
+            .iter()
+            .find(|v| v.name == "origin")
+            .unwrap();
+        assert_eq!(origin.decl_location.as_ref().unwrap().line, Some(6));
+
+        let point = result.types.iter().find(|t| t.name == "point").unwrap();
+        let location = point.decl_location.as_ref().unwrap();
+        assert_eq!(location.file, source_path);
+        assert_eq!(location.line, Some(1));
+
+        // Base types have no declaration
+        assert!(point.members[0].type_info.decl_location.is_none());
+    }
+
+    #[test]
+    fn test_dwarf_type_deduplication() {
+        let shared = "struct config { int port; char mode; };\nstruct opaque;\n";
+        let first = format!(
+            "{shared}struct packet {{ int id; int len; }};\n\
+             struct config first_config;\nstruct packet first_packet;\n\
+             struct opaque *first_handle;\n\
+             int main(void) {{ return first_config.port + first_packet.id; }}\n"
+        );
+        let second = format!(
+            "{shared}struct packet {{ int id; long len; }};\n\
+             struct config second_config;\nstruct packet second_packet;\n\
+             struct opaque {{ int fd; }} second_handle;\n"
+        );
+
+        let Some((_temp_dir, binary_path)) = compile_sources_with_debug_info(
+            "gcc",
+            &[],
+            &[("first.c", &first), ("second.c", &second)],
+        ) else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+        let named = |name: &str| {
+            result
+                .types
+                .iter()
+                .filter(|t| t.name == name)
+                .collect::<Vec<&TypeInfo>>()
+        };
+
+        // Identical layouts merge, keeping every defining unit
+        let config = named("config");
+        assert_eq!(config.len(), 1);
+        assert!(!config[0].odr_conflict);
+        let mut units: Vec<&str> = config[0]
+            .units
+            .iter()
+            .map(|unit| unit.rsplit('/').next().unwrap())
+            .collect();
+        units.sort();
+        assert_eq!(units, vec!["first.c", "second.c"]);
+
+        // Differing layouts under one name are both kept and flagged
+        let packet = named("packet");
+        assert_eq!(packet.len(), 2);
+        assert!(packet.iter().all(|t| t.odr_conflict));
+        let mut sizes: Vec<Option<u64>> = packet.iter().map(|t| t.size).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![Some(8), Some(16)]);
+
+        // A forward declaration gives way to the definition
+        let opaque = named("opaque");
+        assert_eq!(opaque.len(), 1);
+        assert_eq!(opaque[0].size, Some(4));
+    }
+
+    #[test]
+    fn test_dwarf_type_units() {
+        let header =
+            "struct inner { long value; };\nstruct config { int port; struct inner in; };\n";
+        let first = format!(
+            "{header}struct config first_config;\nint port_of(struct config *c) {{ return c->port; }}\n"
+        );
+        let second = format!(
+            "{header}int port_of(struct config *c);\nstruct config second_config;\n\
+             int main() {{ return port_of(&second_config); }}\n"
+        );
+
+        for version in ["-gdwarf-4", "-gdwarf-5"] {
+            let Some((_temp_dir, binary_path)) = compile_sources_with_debug_info(
+                "g++",
+                &[version, "-fdebug-types-section"],
+                &[("first.cpp", &first), ("second.cpp", &second)],
+            ) else {
+                return;
+            };
+            let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+            let configs: Vec<&TypeInfo> =
+                result.types.iter().filter(|t| t.name == "config").collect();
+            assert_eq!(configs.len(), 1, "{version}");
+            let members: Vec<(&str, &str, Option<u64>)> = configs[0]
+                .members
+                .iter()
+                .map(|m| (m.name.as_str(), m.type_info.name.as_str(), m.type_info.size))
+                .collect();
+            assert_eq!(
+                members,
+                vec![("port", "int", Some(4)), ("in", "inner", Some(8))],
+                "{version}"
+            );
+
+            // Variables refer to the type unit through DW_FORM_ref_sig8
+            let variable = result
+                .variables
+                .iter()
+                .find(|v| v.name == "second_config")
+                .unwrap();
+            assert_eq!(variable.type_info.name, "config", "{version}");
+            assert_eq!(variable.type_info.members.len(), 2, "{version}");
+        }
+    }
+
+    #[test]
+    fn test_type_conflict_report() {
+        let first = "struct packet { int id; int len; char flags; };\n\
+                     struct packet first_packet;\n\
+                     int main(void) { return first_packet.id; }\n";
+        let second = "struct packet { int id; long len; };\nstruct packet second_packet;\n";
+
+        let Some((_temp_dir, binary_path)) = compile_sources_with_debug_info(
+            "gcc",
+            &[],
+            &[("first.c", first), ("second.c", second)],
+        ) else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+
+        assert_eq!(result.type_conflicts.len(), 1);
+        let conflict = &result.type_conflicts[0];
+        assert_eq!(conflict.name, "packet");
+
+        let layouts: Vec<(Option<u64>, &str)> = conflict
+            .layouts
+            .iter()
+            .map(|layout| {
+                let unit = layout.units[0].rsplit('/').next().unwrap();
+                (layout.size, unit)
+            })
+            .collect();
+        assert_eq!(layouts, vec![(Some(12), "first.c"), (Some(16), "second.c")]);
+
+        // `id` matches in both layouts and is not reported
+        let names: Vec<&str> = conflict
+            .differences
+            .iter()
+            .map(|d| d.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["len", "flags"]);
+
+        let len = &conflict.differences[0].layouts;
+        let placement = |index: usize| {
+            let member = len[index].as_ref().unwrap();
+            (member.offset, member.type_name.as_str(), member.size)
+        };
+        assert_eq!(placement(0), (4, "int", Some(4)));
+        assert_eq!(placement(1), (8, "long int", Some(8)));
+
+        let flags = &conflict.differences[1].layouts;
+        assert!(flags[0].is_some());
+        assert!(flags[1].is_none());
+    }
+
+    #[test]
+    fn test_dwarf_bit_fields_and_alignment() {
+        let c_source = r#"
+struct flags {
+    unsigned int ready : 1;
+    unsigned int mode : 3;
+    char tag;
+    _Alignas(16) long counter;
+};
+struct flags current;
+int main(void) { return current.mode; }
+"#;
+
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let result = analyze_elf_with_dwarf(&binary_path).unwrap();
+        let flags = result.types.iter().find(|t| t.name == "flags").unwrap();
+
+        let members: Vec<(&str, u64, Option<u64>, Option<u64>)> = flags
+            .members
+            .iter()
+            .map(|m| (m.name.as_str(), m.offset, m.bit_offset, m.bit_size))
+            .collect();
+        assert_eq!(
+            members,
+            vec![
+                ("ready", 0, Some(0), Some(1)),
+                ("mode", 0, Some(1), Some(3)),
+                ("tag", 1, None, None),
+                ("counter", 16, None, None),
+            ]
+        );
+        assert_eq!(flags.size, Some(32));
+
+        assert_eq!(flags.alignment, Some(16));
+        assert_eq!(flags.members[3].alignment, Some(16));
+
+        let layout = layout::analyze_layout(flags, &result.types, 64).unwrap();
+        assert_eq!(layout.alignment, 16);
+        assert_eq!(layout.members[3].alignment, 16);
+        assert_eq!(layout.holes.last().unwrap().size, 14);
+    }
+
+    #[test]
+    fn test_size_attribution_adds_up() {
+        let main_source = r#"
+static char buffer[4096];
+int helper(int x);
+int main(void) { buffer[0] = 1; return helper(buffer[0]); }
+"#;
+        let helper_source = "int helper(int x) { return x * 3 + 1; }\n";
+
+        let Some((_temp_dir, binary_path)) = compile_sources_with_debug_info(
+            "gcc",
+            &[],
+            &[("main.c", main_source), ("helper.c", helper_source)],
+        ) else {
+            return;
+        };
+        let report = size::analyze_sizes(&binary_path).unwrap();
+        assert_eq!(
+            report.file_size,
+            std::fs::metadata(&binary_path).unwrap().len()
+        );
+
+        for entries in [
+            &report.sections,
+            &report.segments,
+            &report.symbols,
+            &report.compile_units,
+            &report.crates,
+        ] {
+            let file_size: u64 = entries.iter().map(|e| e.file_size).sum();
+            let vm_size: u64 = entries.iter().map(|e| e.vm_size).sum();
+            assert_eq!(file_size, report.file_size);
+            assert_eq!(vm_size, report.vm_size);
+        }
+
+        let find = |entries: &[size::SizeEntry], name: &str| -> size::SizeEntry {
+            entries
+                .iter()
+                .find(|e| e.name.ends_with(name))
+                .cloned()
+                .unwrap()
+        };
+        // .bss occupies memory but no file bytes
+        let buffer = find(&report.symbols, "buffer");
+        assert_eq!((buffer.file_size, buffer.vm_size), (0, 4096));
+        assert!(find(&report.sections, ".bss").vm_size >= 4096);
+        let helper = find(&report.symbols, "helper");
+        assert!(helper.file_size > 0 && helper.file_size == helper.vm_size);
+        assert!(find(&report.compile_units, "helper.c").vm_size >= helper.vm_size);
+        assert!(find(&report.crates, size::NON_RUST).vm_size > 0);
+
+        let mut truncated = report.clone();
+        truncated.truncate(2);
+        assert_eq!(truncated.symbols.len(), 3);
+        let vm_size: u64 = truncated.symbols.iter().map(|e| e.vm_size).sum();
+        assert_eq!(vm_size, report.vm_size);
+    }
+
+    #[test]
+    fn test_diff_builds() {
+        let old_source = r#"
+struct packet { int id; char flag; };
+struct packet pk;
+int process(int a) { return a + pk.id; }
+int removed_fn(void) { return 1; }
+int main(void) { return process(1) + removed_fn(); }
+"#;
+        let new_source = r#"
+struct packet { long id; char flag; short extra; };
+struct packet pk;
+static char table[256];
+long process(int a, char b) { return a + pk.id + b + table[a]; }
+int main(void) { return process(1, 2); }
+"#;
+        let Some((_old_dir, old_path)) = compile_with_debug_info("gcc", "old.c", old_source) else {
+            return;
+        };
+        let Some((_new_dir, new_path)) = compile_with_debug_info("gcc", "new.c", new_source) else {
+            return;
+        };
+        let report = diff::diff_files(&old_path, &new_path).unwrap();
+
+        let old_size = std::fs::metadata(&old_path).unwrap().len() as i64;
+        let new_size = std::fs::metadata(&new_path).unwrap().len() as i64;
+        assert_eq!(report.summary.file_delta, new_size - old_size);
+        // Symbol deltas include the unattributed bucket, so they add up to the total
+        let vm_delta: i64 = report.symbols.iter().map(|c| c.vm_delta).sum();
+        assert_eq!(vm_delta, report.summary.vm_delta);
+
+        let symbol = |name: &str| report.symbols.iter().find(|c| c.name == name).unwrap();
+        assert_eq!(symbol("table").status, "added");
+        assert_eq!(symbol("table").vm_delta, 256);
+        assert_eq!(symbol("removed_fn").status, "removed");
+        assert_eq!(symbol("pk").vm_delta, 8);
+
+        let process = report
+            .functions
+            .iter()
+            .find(|c| c.name == "process")
+            .unwrap();
+        assert_eq!(process.status, "changed");
+        assert_eq!(process.old_signature.as_deref(), Some("int process(int)"));
+        assert_eq!(
+            process.new_signature.as_deref(),
+            Some("long int process(int, char)")
+        );
+
+        let packet = report.types.iter().find(|c| c.name == "packet").unwrap();
+        assert_eq!((packet.old_size, packet.new_size), (Some(8), Some(16)));
+        let members: Vec<&str> = packet.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(members, vec!["id", "flag", "extra"]);
+        assert_eq!(packet.members[2].layouts[0], None);
+
+        let markdown = report.to_markdown(5);
+        assert!(markdown.contains("| `table` | added | +256 | +0 |"));
+        assert!(markdown.contains("`int process(int)` → `long int process(int, char)`"));
+    }
+
+    #[test]
+    fn test_abi_check_shared_libraries() {
+        let v1 = r#"
+struct config { int flags; char mode; };
+int counter = 1;
+int lib_init(struct config *c) { return c->flags; }
+void lib_old(void) {}
+"#;
+        // Compatible: a member added into padding and a new function
+        let v2 = r#"
+struct config { int flags; char mode; char extra; };
+int counter = 1;
+int lib_init(struct config *c) { return c->flags; }
+void lib_old(void) {}
+void lib_new(void) {}
+"#;
+        // Breaking: a member grows, moving the next one, and a parameter is added
+        let v3 = r#"
+struct config { long flags; char mode; };
+int counter = 1;
+int lib_init(struct config *c, int x) { return c->flags + x; }
+void lib_old(void) {}
+"#;
+        let flags = ["-shared", "-fPIC"];
+        let build = |source| compile_sources_with_debug_info("gcc", &flags, &[("lib.c", source)]);
+        let (Some((_dir1, lib1)), Some((_dir2, lib2)), Some((_dir3, lib3))) =
+            (build(v1), build(v2), build(v3))
+        else {
+            return;
+        };
+
+        let compatible = abi::check_abi_files(&lib1, &lib2).unwrap();
+        assert!(compatible.compatible, "{:?}", compatible.changes);
+        let descriptions: Vec<&str> = compatible
+            .changes
+            .iter()
+            .map(|c| c.description.as_str())
+            .collect();
+        assert!(descriptions.contains(&"exported function added"));
+        assert!(descriptions.contains(&"member `extra` added at offset 5"));
+
+        let breaking = abi::check_abi_files(&lib1, &lib3).unwrap();
+        assert!(!breaking.compatible);
+        let changes: Vec<(&str, &str)> = breaking
+            .changes
+            .iter()
+            .map(|c| (c.name.as_str(), c.description.as_str()))
+            .collect();
+        assert!(changes.contains(&("lib_init", "parameter count changed from 1 to 2")));
+        // `config` is only reachable through a pointer parameter
+        assert!(changes.contains(&("config", "size changed from 8 to 16 bytes")));
+        assert!(changes.contains(&("config", "member `mode` moved from offset 4 to 8")));
+
+        let removed = abi::check_abi_files(&lib2, &lib1).unwrap();
+        assert!(removed
+            .changes
+            .iter()
+            .any(|c| c.name == "lib_new" && c.severity == "breaking"));
+    }
+
+    #[test]
+    fn test_generated_c_header_compiles() {
+        let c_source = r#"
+#include <stdint.h>
+typedef struct node node_t;
+struct node { node_t *next; int value; };
+typedef struct { char x; int y; } anon_t;
+enum color { RED, GREEN = 5, BLUE };
+union value { int i; double d; char bytes[12]; };
+struct flags { unsigned ready : 1; unsigned mode : 3; char tag; _Alignas(16) long counter; };
+struct __attribute__((packed)) wire { char kind; uint32_t len; uint16_t crc; };
+struct ops { int (*open)(const char *path, int flags); void (*close)(void *); };
+struct matrix { float m[3][4]; struct node *rows[2]; anon_t a; };
+struct outer { struct { int a; char b; } inner; union { int i; float f; }; enum color c; };
+struct node head; anon_t anon; union value val; struct flags fl; struct wire w;
+struct ops ops; struct matrix mx; struct outer out;
+int process(struct node *n, const anon_t *a, enum color c) { return n->value + a->y + c; }
+int main(void) { return process(&head, &anon, RED); }
+"#;
+        let Some((temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let elf_info = analyze_elf_with_dwarf(&binary_path).unwrap();
+        let header = c_header::generate_c_header(&elf_info, &[]);
+
+        assert!(header.contains("struct node {\n    node_t *next;\n    int value;\n"));
+        assert!(header.contains("typedef struct {\n    char x;\n    unsigned char __pad0[3];"));
+        assert!(header.contains("    unsigned int mode : 3;\n"));
+        assert!(header.contains("    _Alignas(16) long int counter;\n"));
+        assert!(header.contains("} __attribute__((packed));"));
+        assert!(header.contains("    int (*open)(const char *, int);\n"));
+        assert!(header.contains("    float m[3][4];\n"));
+        assert!(header.contains("int process(struct node *n, const anon_t *a, enum color c);"));
+        assert!(header.contains(
+            "_Static_assert(offsetof(struct flags, counter) == 16, \"offsetof(struct flags, counter)\");"
+        ));
+
+        // The static asserts check every size and offset against the original layout
+        let header_path = temp_dir.path().join("generated.h");
+        std::fs::write(&header_path, &header).unwrap();
+        let check_path = temp_dir.path().join("check.c");
+        std::fs::write(&check_path, "#include \"generated.h\"\n").unwrap();
+        let output = std::process::Command::new("gcc")
+            .args(["-std=c11", "-Wall", "-fsyntax-only"])
+            .arg(&check_path)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}\n{header}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        // Only the requested type and what it depends on
+        let header = c_header::generate_c_header(&elf_info, &["matrix".to_string()]);
+        assert!(header.contains("struct matrix {"));
+        assert!(header.contains("typedef struct {"));
+        assert!(!header.contains("struct flags {"));
+        assert!(!header.contains("int process("));
+    }
+
+    #[test]
+    fn test_generated_rust_bindings_compile() {
+        let c_source = r#"
+#include <stdint.h>
+enum mode { MODE_OFF = -1, MODE_IDLE = 2, MODE_RUN };
+struct regs {
+    uint8_t id;
+    unsigned ready : 1;
+    int level : 5;
+    enum mode mode : 4;
+    _Alignas(8) uint32_t status;
+    union { uint32_t word; uint8_t bytes[4]; } data;
+    struct regs *next;
+    void (*irq)(int line, void *context);
+};
+struct __attribute__((packed)) frame { uint8_t type; uint32_t length; uint16_t crc; };
+typedef struct { struct frame frames[4]; struct regs regs; } shared_t;
+shared_t shared;
+int main(void) { return shared.regs.id; }
+"#;
+        let Some((temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let elf_info = analyze_elf_with_dwarf(&binary_path).unwrap();
+        let bindings = rust_bindings::generate_rust_bindings(&elf_info, &["shared_t".to_string()]);
+
+        assert!(bindings.contains("#[repr(C, packed)]\n#[derive(Clone, Copy)]\npub struct frame {"));
+        assert!(bindings.contains("pub struct mode(pub i32);"));
+        assert!(bindings.contains("    pub const MODE_RUN: mode = mode(3);"));
+        assert!(bindings.contains("    pub fn level(&self) -> i32 {"));
+        assert!(bindings.contains("    pub fn set_mode(&mut self, value: mode) {"));
+        assert!(bindings.contains("pub union regs_data {"));
+        assert!(bindings.contains("    pub next: *mut regs,"));
+        assert!(bindings
+            .contains("    pub irq: Option<unsafe extern \"C\" fn(i32, *mut core::ffi::c_void)>,"));
+        assert!(bindings.contains("const _: () = assert!(core::mem::size_of::<shared_t>() == 64);"));
+        assert!(
+            bindings.contains("const _: () = assert!(core::mem::offset_of!(regs, status) == 8);")
+        );
+        assert!(!bindings.contains("fn main"));
+
+        // The const asserts check every size and offset against the original layout
+        let bindings_path = temp_dir.path().join("bindings.rs");
+        std::fs::write(&bindings_path, &bindings).unwrap();
+        let output = std::process::Command::new("rustc")
+            .args(["--edition", "2021", "--crate-type", "lib", "--out-dir"])
+            .arg(temp_dir.path())
+            .arg(&bindings_path)
+            .output();
+        let Ok(output) = output else {
+            return;
+        };
+        assert!(
+            output.status.success(),
+            "{}\n{bindings}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    #[test]
+    fn test_read_initial_values() {
+        let c_source = r#"
+enum color { RED, GREEN = 5, BLUE };
+enum perm { READ = 1, WRITE = 2, EXEC = 4 };
+struct point { int x, y; };
+struct config {
+    const char *name;
+    struct point origin;
+    enum color color;
+    enum perm perms;
+    int level : 5;
+    double ratio;
+    char label[8];
+    int (*handler)(int);
+    struct point *target;
+};
+int twice(int x) { return 2 * x; }
+struct point points[3] = {{1, 2}, {3, 4}, {5, 6}};
+struct config cfg = { "main", {10, -20}, BLUE, READ | EXEC, -7, 0.25, "abc", twice, &points[1] };
+const long big = -1234567890123L;
+int counter;
+int main(void) { return counter + cfg.color + (int)big; }
+"#;
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let report = values::read_initial_values(&binary_path).unwrap();
+        let find = |name: &str| {
+            report
+                .variables
+                .iter()
+                .find(|v| v.name == name)
+                .unwrap_or_else(|| panic!("{name} not reported"))
+        };
+        let field = |value: &values::Value, name: &str| {
+            value
+                .fields
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| f.value.clone())
+                .unwrap()
+        };
+
+        let counter = find("counter");
+        assert!(counter.zero_initialized);
+        assert_eq!(counter.section, ".bss");
+        assert_eq!(counter.value.text, "0");
+
+        let big = find("big");
+        assert_eq!(big.section, ".rodata");
+        assert_eq!(big.value.text, "-1234567890123");
+
+        let points = find("points");
+        assert_eq!(points.value.elements.len(), 3);
+        assert_eq!(field(&points.value.elements[2], "y").text, "6");
+
+        let cfg = &find("cfg").value;
+        assert!(!find("cfg").zero_initialized);
+        assert_eq!(field(cfg, "name").text, "\"main\"");
+        assert_eq!(field(&field(cfg, "origin"), "y").text, "-20");
+        assert_eq!(field(cfg, "color").text, "BLUE");
+        assert_eq!(field(cfg, "perms").text, "READ | EXEC");
+        assert_eq!(field(cfg, "level").text, "-7");
+        assert_eq!(field(cfg, "ratio").text, "0.25");
+        assert_eq!(field(cfg, "label").kind, "string");
+        assert_eq!(field(cfg, "label").text, "\"abc\"");
+        // Resolved through relative relocations in a position-independent executable
+        assert_eq!(field(cfg, "handler").text, "twice");
+        assert_eq!(field(cfg, "target").text, "&points + 8");
+        assert_eq!(field(cfg, "target").symbol.as_deref(), Some("points"));
+    }
+
+    /// Run the compiled test program and return its core dump; where the
+    /// core ends up depends on kernel.core_pattern, so None unless it lands
+    /// next to the program
+    fn dump_core(temp_dir: &tempfile::TempDir) -> Option<String> {
+        std::process::Command::new("sh")
+            .args(["-c", "ulimit -c unlimited; ./test"])
+            .current_dir(temp_dir.path())
+            .output()
+            .ok()?;
+        std::fs::read_dir(temp_dir.path())
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .find(|entry| entry.file_name().to_string_lossy().starts_with("core"))
+            .map(|entry| entry.path().to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn test_analyze_core_dump() {
+        let c_source = r#"
+#include <pthread.h>
+#include <unistd.h>
+struct state { int counter; const char *label; double ratio; };
+struct state state = { 1, "boot", 0.5 };
+int ticks;
+static void *worker(void *arg) { (void)arg; for (;;) pause(); return 0; }
+void crash(int *p) { state.counter = 42; ticks = 7; state.label = "crashing"; *p = 1; }
+int main(void) { pthread_t t; pthread_create(&t, 0, worker, 0); usleep(100000); crash(0); return 0; }
+"#;
+        let Some((temp_dir, binary_path)) =
+            compile_sources_with_debug_info("gcc", &["-pthread"], &[("test.c", c_source)])
+        else {
+            return;
+        };
+        let Some(core_path) = dump_core(&temp_dir) else {
+            return;
         };
 
-        let type_info = TypeInfo {
-            name: "TestStruct".to_string(),
-            size: Some(16),
-            kind: "struct".to_string(),
-            members: vec![
-                MemberInfo {
-                    name: "field1".to_string(),
-                    offset: 0,
-                    type_info: TypeInfo {
-                        name: "int".to_string(),
-                        size: Some(4),
-                        kind: "basic".to_string(),
-                        members: Vec::new(),
-                    },
-                },
-                MemberInfo {
-                    name: "field2".to_string(),
-                    offset: 8,
-                    type_info: TypeInfo {
-                        name: "double".to_string(),
-                        size: Some(8),
-                        kind: "basic".to_string(),
-                        members: Vec::new(),
-                    },
-                },
-            ],
+        let report = core_dump::analyze_core_files(&core_path, &binary_path).unwrap();
+        assert_eq!(report.signal_name, "SIGSEGV");
+        assert_eq!(report.threads.len(), 2);
+        assert!(report.executable.as_deref().unwrap().ends_with("/test"));
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        let crashed = &report.threads[0];
+        assert!(crashed.function.as_deref().unwrap().starts_with("crash"));
+        assert_eq!(report.process.as_ref().unwrap().name, "test");
+        assert!(report.auxv.iter().any(|entry| entry.key == "AT_ENTRY"));
+
+        let value = |name: &str| {
+            report
+                .variables
+                .iter()
+                .find(|v| v.name == name)
+                .map(|v| v.value.clone())
+                .unwrap_or_else(|| panic!("{name} not reported"))
         };
+        // Values at the time of the crash, not the initializers
+        assert_eq!(value("ticks").text, "7");
+        let state = value("state");
+        assert_eq!(state.fields[0].value.text, "42");
+        assert_eq!(state.fields[1].value.text, "\"crashing\"");
+    }
 
-        let variable_info = VariableInfo {
-            name: "global_var".to_string(),
-            address: Some(0x2000),
-            offset: None,
-            type_info: type_info.clone(),
-            scope: "global".to_string(),
+    #[test]
+    fn test_unwind_core_dump_with_inlined_frames() {
+        let c_source = r#"
+int *volatile target;
+static inline __attribute__((always_inline)) void poke(int *p, int v) { *p = v; }
+static inline __attribute__((always_inline)) void store(int *p) { poke(p, 7); }
+__attribute__((noinline)) void crash(int *p) { store(p); __asm__ volatile(""); }
+__attribute__((noinline)) int middle(int n) { crash(target); return n + 1; }
+int main(int argc, char **argv) { (void)argv; return middle(argc) + 1; }
+"#;
+        // Optimized without frame pointers, so only the CFI can unwind it
+        let Some((temp_dir, binary_path)) = compile_sources_with_debug_info(
+            "gcc",
+            &["-O2", "-fomit-frame-pointer"],
+            &[("test.c", c_source)],
+        ) else {
+            return;
+        };
+        let Some(core_path) = dump_core(&temp_dir) else {
+            return;
         };
 
-        // Test serialization
-        assert!(serde_json::to_string(&function_info).is_ok());
-        assert!(serde_json::to_string(&type_info).is_ok());
-        assert!(serde_json::to_string(&variable_info).is_ok());
+        let report = core_dump::analyze_core_files(&core_path, &binary_path).unwrap();
+        let backtrace = report.threads[0].backtrace.as_ref().unwrap();
+        let functions: Vec<(&str, usize, bool)> = backtrace
+            .frames
+            .iter()
+            .filter_map(|frame| Some((frame.function.as_deref()?, frame.index, frame.inlined)))
+            .collect();
+        assert_eq!(
+            functions,
+            [
+                ("poke", 0, true),
+                ("store", 0, true),
+                ("crash", 0, false),
+                ("middle", 1, false),
+                ("main", 2, false),
+            ]
+        );
+        // Inlined frames report the call site in their caller
+        let store = &backtrace.frames[1];
+        assert_eq!(store.location.as_ref().unwrap().line, Some(4));
+        let crash = &backtrace.frames[2];
+        assert_eq!(crash.location.as_ref().unwrap().line, Some(5));
+        assert!(crash.location.as_ref().unwrap().file.ends_with("test.c"));
+        assert_eq!(backtrace.frames[3].method, "cfi");
+    }
 
-        // Test field access
-        assert_eq!(function_info.name, "test_function");
-        assert_eq!(function_info.address, 0x1000);
-        assert_eq!(function_info.parameters.len(), 1);
-        assert_eq!(function_info.parameters[0].name, "param1");
+    #[test]
+    fn test_unwind_tables_demo_binaries() {
+        for (path, cfa_register) in [
+            ("demo-binaries/bin/x86_64/hello", "rsp"),
+            ("demo-binaries/bin/aarch64/fibonacci", "sp"),
+            ("demo-binaries/bin/riscv64/hello", "sp"),
+        ] {
+            let Ok(buffer) = std::fs::read(path) else {
+                continue;
+            };
+            let tables = unwind::unwind_tables(&buffer).unwrap();
+            assert!(!tables.is_empty(), "{path} has no FDEs");
+            assert!(tables.windows(2).all(|pair| pair[0].start <= pair[1].start));
+            // Every function starts with the CFA at the caller's stack pointer
+            let first_rows: Vec<&str> = tables
+                .iter()
+                .filter_map(|table| table.rows.first())
+                .map(|row| row.cfa.as_str())
+                .collect();
+            let expected = format!(
+                "{cfa_register}+{}",
+                if cfa_register == "rsp" { 8 } else { 0 }
+            );
+            assert!(
+                first_rows.iter().filter(|&&cfa| cfa == expected).count() > first_rows.len() / 2,
+                "{path}: unexpected initial CFA rules"
+            );
+        }
+    }
 
-        assert_eq!(type_info.name, "TestStruct");
-        assert_eq!(type_info.members.len(), 2);
-        assert_eq!(type_info.members[0].offset, 0);
-        assert_eq!(type_info.members[1].offset, 8);
+    #[test]
+    fn test_cfi_coverage() {
+        let c_source = r#"
+__asm__(".text\n"
+        ".globl bare\n.type bare, @function\nbare:\n ret\n.size bare, .-bare\n"
+        ".globl padded\n.type padded, @function\npadded:\n.cfi_startproc\n ret\n.cfi_endproc\n"
+        " nop\n nop\n.size padded, .-padded\n");
+void bare(void);
+void padded(void);
+int twice(int x) { return 2 * x; }
+int main(void) { bare(); padded(); return twice(1); }
+"#;
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
+        };
+        let report = cfi::cfi_coverage_files(&binary_path).unwrap();
+        let find = |name: &str| {
+            report
+                .functions
+                .iter()
+                .find(|f| f.name == name)
+                .unwrap_or_else(|| panic!("{name} not reported"))
+        };
 
-        assert_eq!(variable_info.scope, "global");
-        assert_eq!(variable_info.address, Some(0x2000));
+        // Hand-written assembly without .cfi_startproc
+        assert!(!find("bare").covered());
+        assert!(find("bare").rows.is_empty());
+
+        // The symbol's .size includes padding the FDE does not cover
+        let padded = find("padded");
+        assert!(padded.covered());
+        assert_eq!(padded.mismatches.len(), 1, "{:?}", padded.mismatches);
+
+        let twice = find("twice");
+        assert_eq!(twice.sections, [".eh_frame"]);
+        assert_eq!(twice.dwarf_low_pc, Some(twice.address));
+        assert!(twice.mismatches.is_empty(), "{:?}", twice.mismatches);
+        assert!(twice.rows.len() > 1);
+        assert!(report.uncovered >= 1);
+        assert!(report.mismatched >= 1);
     }
 
     #[test]
-    fn test_enhanced_elf_info_json_output() {
-        // Test that the enhanced ElfInfo structure produces valid, jq-compatible JSON
-        let enhanced_elf = ElfInfo {
-            architecture: "x86_64".to_string(),
-            entry_point: 0x1000,
-            sections: vec![".text".to_string(), ".data".to_string()],
-            file_type: "executable".to_string(),
-            endianness: "little_endian".to_string(),
-            functions: vec![FunctionInfo {
-                name: "main".to_string(),
-                address: 0x1100,
-                size: Some(50),
-                parameters: vec![VariableInfo {
-                    name: "argc".to_string(),
-                    address: None,
-                    offset: Some(8),
-                    type_info: TypeInfo {
-                        name: "int".to_string(),
-                        size: Some(4),
-                        kind: "basic".to_string(),
-                        members: Vec::new(),
-                    },
-                    scope: "parameter".to_string(),
-                }],
-                return_type: Some(TypeInfo {
-                    name: "int".to_string(),
-                    size: Some(4),
-                    kind: "basic".to_string(),
-                    members: Vec::new(),
-                }),
-            }],
-            variables: vec![VariableInfo {
-                name: "global_counter".to_string(),
-                address: Some(0x2000),
-                offset: None,
-                type_info: TypeInfo {
-                    name: "int".to_string(),
-                    size: Some(4),
-                    kind: "basic".to_string(),
-                    members: Vec::new(),
-                },
-                scope: "global".to_string(),
-            }],
-            types: vec![TypeInfo {
-                name: "Point".to_string(),
-                size: Some(8),
-                kind: "struct".to_string(),
-                members: vec![
-                    MemberInfo {
-                        name: "x".to_string(),
-                        offset: 0,
-                        type_info: TypeInfo {
-                            name: "int".to_string(),
-                            size: Some(4),
-                            kind: "basic".to_string(),
-                            members: Vec::new(),
-                        },
-                    },
-                    MemberInfo {
-                        name: "y".to_string(),
-                        offset: 4,
-                        type_info: TypeInfo {
-                            name: "int".to_string(),
-                            size: Some(4),
-                            kind: "basic".to_string(),
-                            members: Vec::new(),
-                        },
-                    },
-                ],
-            }],
+    fn test_disassemble_function() {
+        let c_source = r#"
+#include <stdio.h>
+int counter = 5;
+static int __attribute__((noinline)) helper(int x) { return x * counter + 1; }
+int main(int argc, char **argv) {
+    int v = helper(argc);
+    printf("%d\n", v);
+    return v;
+}
+"#;
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
         };
+        let names = ["main".to_string(), "helper".to_string()];
+        let report = disasm::disassemble_files(&binary_path, &names).unwrap();
+        assert_eq!(report.functions.len(), 2);
+        let main = report.functions.iter().find(|f| f.name == "main").unwrap();
+        let helper = report
+            .functions
+            .iter()
+            .find(|f| f.name == "helper")
+            .unwrap();
+        assert_eq!(main.instructions[0].address, main.address);
+        assert_eq!(
+            main.instructions.last().unwrap().kind,
+            "return",
+            "{:?}",
+            main.instructions.last()
+        );
 
-        let analysis_result = AnalysisResult {
-            elf_info: Some(enhanced_elf),
+        // Direct call to the static function, and the PLT stub of printf
+        let call = main
+            .instructions
+            .iter()
+            .find(|i| i.kind == "call" && i.target == Some(helper.address))
+            .expect("call to helper");
+        assert_eq!(call.symbol.as_deref(), Some("helper"));
+        assert!(main.instructions.iter().any(|i| i
+            .symbol
+            .as_deref()
+            .is_some_and(|s| s.starts_with("printf@"))));
+
+        // RIP-relative load of the global
+        assert!(helper
+            .instructions
+            .iter()
+            .any(|i| i.symbol.as_deref() == Some("counter")));
+
+        // Source lines interleaved where they change
+        let lines: Vec<u64> = main
+            .instructions
+            .iter()
+            .filter_map(|i| i.source.as_ref()?.line)
+            .collect();
+        assert!(lines.contains(&6) && lines.contains(&7), "{lines:?}");
+        assert!(report.to_text(&|_| None).contains("<helper>"));
+
+        assert!(disasm::disassemble_files(&binary_path, &["missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_disassemble_demo_binaries() {
+        let names = ["fibonacci".to_string(), "main".to_string()];
+        for (path, prologue, call, compressed) in [
+            ("demo-binaries/bin/aarch64/fibonacci", "stp", "bl", false),
+            ("demo-binaries/bin/riscv64/fibonacci", "addi", "jal", true),
+        ] {
+            let report = disasm::disassemble_files(path, &names).unwrap();
+            let fibonacci = &report.functions[0];
+            let main = &report.functions[1];
+            assert_eq!(fibonacci.instructions[0].mnemonic, prologue, "{path}");
+            assert_eq!(fibonacci.instructions.last().unwrap().kind, "return");
+            assert!(fibonacci
+                .instructions
+                .iter()
+                .all(|i| i.kind != "data" && !i.mnemonic.starts_with(".inst")));
+
+            // The recursive calls and the call from main resolve to fibonacci
+            let calls = |function: &disasm::FunctionDisassembly| {
+                function
+                    .instructions
+                    .iter()
+                    .filter(|i| i.mnemonic == call && i.target == Some(fibonacci.address))
+                    .inspect(|i| {
+                        assert_eq!(i.kind, "call");
+                        assert_eq!(i.symbol.as_deref(), Some("fibonacci"));
+                    })
+                    .count()
+            };
+            assert_eq!(calls(fibonacci), 2, "{path}");
+            assert_eq!(calls(main), 1, "{path}");
+            // The loop in main branches back into itself
+            assert!(main.instructions.iter().any(|i| {
+                i.kind == "branch" && i.target.is_some_and(|t| t > main.address && t < i.address)
+            }));
+            // The format string is found through adrp+add or auipc+addi
+            assert!(main
+                .instructions
+                .iter()
+                .any(|i| i.kind == "other" && i.target.is_some()));
+            assert_eq!(
+                fibonacci.instructions.iter().any(|i| i.length == 2),
+                compressed,
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_disassemble_thumb() {
+        // `table` returns, then holds a halfword of data before more code
+        let mut code = THUMB_CODE.to_vec();
+        code.extend([0x70, 0x47, 0x34, 0x12, 0x00, 0xbf]);
+        let elf = arm_elf(
+            &code,
+            0x8001,
+            &[
+                ("$t", 0x8000, 0),
+                ("$d", 0x8012, 0),
+                ("$t", 0x8014, 0),
+                ("main", 0x8001, 8),
+                ("helper", 0x8009, 8),
+                ("table", 0x8011, 6),
+            ],
+        );
+        let names = ["main".to_string(), "table".to_string()];
+        let report = disasm::disassemble_from_bytes(&elf, &names).unwrap();
+        let main = &report.functions[0];
+        assert_eq!(main.address, 0x8000);
+        let mnemonics: Vec<&str> = main
+            .instructions
+            .iter()
+            .map(|i| i.mnemonic.as_str())
+            .collect();
+        assert_eq!(mnemonics, ["push", "bl", "pop"]);
+        let call = &main.instructions[1];
+        assert_eq!((call.kind.as_str(), call.length), ("call", 4));
+        assert_eq!(call.target, Some(0x8008));
+        assert_eq!(call.symbol.as_deref(), Some("helper"));
+        assert_eq!(main.instructions[2].kind, "return");
+
+        let table = &report.functions[1];
+        let listing: Vec<(&str, &str)> = table
+            .instructions
+            .iter()
+            .map(|i| (i.mnemonic.as_str(), i.operands.as_str()))
+            .collect();
+        assert_eq!(listing, [("bx", "lr"), (".short", "0x1234"), ("nop", "")]);
+    }
+
+    #[test]
+    fn test_call_graph() {
+        let c_source = r#"
+#include <stdio.h>
+static int __attribute__((noinline)) helper(int x) { return x * 3 + 1; }
+int __attribute__((noinline)) apply(int (*f)(int), int x) { return f(x) + 1; }
+int __attribute__((noinline)) unused(int x) { return helper(x) - 1; }
+int main(int argc, char **argv) {
+    int v = helper(argc) + apply(helper, argc);
+    printf("%d\n", v);
+    return v;
+}
+"#;
+        let Some((_temp_dir, binary_path)) = compile_with_debug_info("gcc", "test.c", c_source)
+        else {
+            return;
         };
+        let graph = callgraph::call_graph_files(&binary_path).unwrap();
+        let edge = graph
+            .edges
+            .iter()
+            .find(|e| e.caller == "main" && e.callee == "helper")
+            .expect("main calls helper");
+        assert_eq!(edge.kind, "call");
+        assert!(!edge.sites.is_empty());
+        assert!(graph.callees("main").contains(&"apply"));
+        assert!(graph
+            .callees("main")
+            .iter()
+            .any(|callee| callee.starts_with("printf")));
+
+        // The call through the function pointer has no static target
+        let indirect = graph
+            .indirect_calls
+            .iter()
+            .find(|c| c.caller == "apply")
+            .expect("indirect call in apply");
+        assert!(indirect.location.is_some());
+
+        // main is reached from the entry point through its address
+        let entry = graph.entry.clone().expect("entry function");
+        let reachable = graph.reachable_from(&[entry.as_str()]);
+        assert!(reachable.contains("main") && reachable.contains("helper"));
+        assert!(!reachable.contains("unused"));
+
+        let callers = graph.callers_of(&["helper"]);
+        assert!(callers.contains("main") && callers.contains("unused"));
+        assert!(!callers.contains("apply"));
+        assert!(graph.to_dot().contains("\"main\" -> \"helper\""));
+    }
 
-        // Test JSON serialization
-        let json = to_json(&analysis_result).expect("Should serialize to JSON");
+    #[test]
+    fn test_call_graph_thumb() {
+        let elf = arm_elf(
+            &THUMB_CODE,
+            0x8001,
+            &[("main", 0x8001, 8), ("helper", 0x8009, 8)],
+        );
+        let graph = callgraph::call_graph_from_bytes(&elf).unwrap();
+        assert_eq!(graph.entry.as_deref(), Some("main"));
+        // The bl target is the even address of the function symbol
+        let edge = graph
+            .edges
+            .iter()
+            .find(|e| e.caller == "main" && e.callee == "helper")
+            .expect("main calls helper");
+        assert_eq!(edge.kind, "call");
+        assert_eq!(edge.sites, [0x8002]);
+        let helper = graph.functions.iter().find(|f| f.name == "helper").unwrap();
+        assert_eq!(helper.address, Some(0x8008));
+    }
 
-        // Verify it's valid JSON
-        let parsed: serde_json::Value = serde_json::from_str(&json).expect("Should be valid JSON");
+    #[test]
+    fn test_stack_usage() {
+        let c_source = r#"
+#include <stdio.h>
+struct node { struct node *l, *r; };
+static int __attribute__((noinline)) leaf(int x) { return x * 3 + 1; }
+int __attribute__((noinline)) big(int x) {
+    volatile char buf[256];
+    buf[x & 255] = 1;
+    return leaf(buf[x & 127]) + 1;
+}
+int __attribute__((noinline)) count(struct node *n) {
+    return n ? count(n->l) + count(n->r) + 1 : 0;
+}
+int __attribute__((noinline)) apply(int (*f)(int), int x) { return f(x) + 1; }
+int main(int argc, char **argv) {
+    printf("%d\n", big(argc) + count(0) + apply(leaf, argc));
+    return 0;
+}
+"#;
+        let Some((temp_dir, binary_path)) = compile_sources_with_debug_info(
+            "gcc",
+            &["-O2", "-fstack-usage"],
+            &[("test.c", c_source)],
+        ) else {
+            return;
+        };
+        let report = stack::stack_usage_files(&binary_path, &["main".to_string()], &[]).unwrap();
+        let frame = |report: &stack::StackReport, name: &str| {
+            report
+                .functions
+                .iter()
+                .find(|f| f.name == name)
+                .cloned()
+                .unwrap()
+        };
+        let big = frame(&report, "big");
+        assert_eq!(big.source, "cfi");
+        assert!(big.frame_size.unwrap() >= 128, "{big:?}");
+        assert!(frame(&report, "count").recursive);
+        assert_eq!(frame(&report, "apply").indirect_calls, 1);
+
+        let [root] = &report.roots[..] else {
+            panic!("{:?}", report.roots);
+        };
+        assert_eq!(root.path[..3], ["main", "big", "leaf"]);
+        let main = frame(&report, "main").frame_size.unwrap();
+        let leaf = frame(&report, "leaf").frame_size.unwrap();
+        assert_eq!(root.worst_case, main + big.frame_size.unwrap() + leaf);
+        assert!(!root.bounded);
+        let reasons: Vec<(&str, &str)> = root
+            .unknowns
+            .iter()
+            .map(|u| (u.function.as_str(), u.reason.as_str()))
+            .collect();
+        assert!(reasons.contains(&("count", "recursion")), "{reasons:?}");
+        assert!(reasons.contains(&("apply", "indirect_call")), "{reasons:?}");
+        assert!(reasons.contains(&("printf", "external")), "{reasons:?}");
+
+        // GCC writes the .su file next to the output, named after both
+        let su_files: Vec<String> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok()?.path().to_str().map(str::to_string))
+            .filter(|path| path.ends_with(".su"))
+            .collect();
+        if !su_files.is_empty() {
+            let report = stack::stack_usage_files(&binary_path, &[], &su_files).unwrap();
+            let from_su = frame(&report, "big");
+            assert_eq!(from_su.source, "stack_usage");
+            assert_eq!(from_su.frame_size, big.frame_size);
+            assert!(report.roots.iter().any(|root| root.name == "main"));
+        }
+        assert!(stack::stack_usage_files(&binary_path, &["missing".to_string()], &[]).is_err());
+    }
 
-        // Test jq-like access patterns (verify structure is accessible)
-        assert!(parsed["elf_info"]["functions"].is_array());
-        assert!(parsed["elf_info"]["variables"].is_array());
-        assert!(parsed["elf_info"]["types"].is_array());
+    #[test]
+    fn test_stack_usage_thumb() {
+        let elf = arm_elf(
+            &THUMB_CODE,
+            0x8001,
+            &[("main", 0x8001, 8), ("helper", 0x8009, 8)],
+        );
+        let su = "fw.c:3:5:main\t8\tstatic\nfw.c:9:12:helper\t16\tstatic\n".to_string();
+        let report = stack::stack_usage_from_bytes(&elf, &[], &[su]).unwrap();
+        let [root] = &report.roots[..] else {
+            panic!("{:?}", report.roots);
+        };
+        // helper is called from main, not a root of its own
+        assert_eq!(root.path, ["main", "helper"]);
+        assert_eq!(root.worst_case, 24);
+        assert!(root.bounded, "{:?}", root.unknowns);
+
+        // Two static helpers in different files, with no DWARF to tell them apart
+        let su = "fw.c:3:5:main\t8\tstatic\n\
+                  a.c:9:12:helper\t16\tstatic\n\
+                  b.c:4:12:helper\t400\tstatic\n"
+            .to_string();
+        let report = stack::stack_usage_from_bytes(&elf, &[], &[su]).unwrap();
+        let helper = report
+            .functions
+            .iter()
+            .find(|f| f.name == "helper")
+            .unwrap();
+        assert_eq!(helper.frame_size, None);
+        assert!(!report.roots[0].bounded);
+        assert_eq!(report.roots[0].unknowns[0].reason, "unknown_frame");
+    }
 
-        // Verify function information
-        let functions = &parsed["elf_info"]["functions"];
-        assert_eq!(functions[0]["name"], "main");
-        assert_eq!(functions[0]["address"], 0x1100);
-        assert_eq!(functions[0]["parameters"][0]["name"], "argc");
+    #[test]
+    fn test_dead_code() {
+        let c_source = r#"
+#include <stdio.h>
+static int __attribute__((noinline)) helper(int x) { return x * 3 + 1; }
+static int __attribute__((noinline)) only_dead(int x) { return x - 7; }
+int __attribute__((noinline)) unused(int x) { return only_dead(x) + 2; }
+static int __attribute__((noinline)) via_table(int x) { return x + 5; }
+int (*table[])(int) = { via_table };
+__attribute__((constructor)) static void init(void) { puts("init"); }
+int main(int argc, char **argv) {
+    printf("%d\n", helper(argc) + table[0](argc));
+    return 0;
+}
+"#;
+        let Some((_temp_dir, binary_path)) =
+            compile_sources_with_debug_info("gcc", &["-O2"], &[("test.c", c_source)])
+        else {
+            return;
+        };
+        let report = dead_code::dead_code_files(&binary_path).unwrap();
+        let dead: Vec<&str> = report.dead.iter().map(|f| f.name.as_str()).collect();
+        assert!(
+            dead.contains(&"unused") && dead.contains(&"only_dead"),
+            "{dead:?}"
+        );
+        for live in ["main", "helper", "via_table", "init"] {
+            assert!(!dead.contains(&live), "{live} in {dead:?}");
+        }
+        let only_dead = report.dead.iter().find(|f| f.name == "only_dead").unwrap();
+        assert_eq!(only_dead.referenced_by, ["unused"]);
+        assert_eq!(
+            report.reclaimable,
+            report.dead.iter().map(|f| f.size).sum::<u64>()
+        );
 
-        // Verify type information
-        let types = &parsed["elf_info"]["types"];
-        assert_eq!(types[0]["name"], "Point");
-        assert_eq!(types[0]["kind"], "struct");
-        assert_eq!(types[0]["members"][0]["name"], "x");
-        assert_eq!(types[0]["members"][0]["offset"], 0);
-        assert_eq!(types[0]["members"][1]["offset"], 4);
+        let reasons = |name: &str| {
+            report
+                .roots
+                .iter()
+                .find(|root| root.name == name)
+                .map(|root| root.reasons.clone())
+                .unwrap_or_default()
+        };
+        assert!(reasons("init").contains(&"init_fini".to_string()));
+        assert!(reasons("_start").contains(&"entry".to_string()));
+        // A relocation in a PIE, the plain pointer otherwise
+        assert!(reasons("via_table")
+            .iter()
+            .any(|reason| reason == "relocation" || reason == "data_pointer"));
+    }
 
-        // Verify variable information
-        let variables = &parsed["elf_info"]["variables"];
-        assert_eq!(variables[0]["name"], "global_counter");
-        assert_eq!(variables[0]["address"], 0x2000);
-        assert_eq!(variables[0]["scope"], "global");
+    #[test]
+    fn test_dead_code_thumb() {
+        // `unused` after the fixture is only `bx lr`
+        let mut code = THUMB_CODE.to_vec();
+        code.extend([0x70, 0x47, 0x00, 0xbf]);
+        let elf = arm_elf(
+            &code,
+            0x8001,
+            &[
+                ("main", 0x8001, 8),
+                ("helper", 0x8009, 8),
+                ("unused", 0x8011, 4),
+            ],
+        );
+        let report = dead_code::dead_code_from_bytes(&elf).unwrap();
+        let dead: Vec<&str> = report.dead.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(dead, ["unused"]);
+        assert_eq!(report.dead[0].address, 0x8010);
+        assert_eq!(report.reclaimable, 4);
+        assert_eq!(report.roots[0].name, "main");
+        assert_eq!(report.roots[0].reasons, ["entry"]);
     }
 
     #[test]
-    fn test_analyze_demo_binaries_from_bytes() {
-        // Test basic analysis of demo binaries from byte data (without expensive DWARF parsing)
-        // Using smaller binaries to avoid slow test execution
-        let demo_paths = [
-            "demo-binaries/bin/x86_64/hello",
-            "demo-binaries/bin/aarch64/fibonacci",
-            "demo-binaries/bin/riscv64/hello", // Changed from counter (3.7MB) to hello (544KB) for faster test
+    fn test_checksec() {
+        let c_source = r#"
+#include <stdio.h>
+#include <string.h>
+__attribute__((section(".rwx,\"awx\",@progbits#"))) int patched(int x) { return x + 1; }
+int main(int argc, char **argv) {
+    char buffer[16];
+    strcpy(buffer, argv[0]);
+    printf("%s %d\n", buffer, patched(argc));
+    return 0;
+}
+"#;
+        let hardened = [
+            "-O2",
+            "-fPIE",
+            "-pie",
+            "-fstack-protector-all",
+            "-D_FORTIFY_SOURCE=2",
+            "-Wl,-z,relro,-z,now,-z,noexecstack",
         ];
+        let Some((_temp_dir, binary_path)) =
+            compile_sources_with_debug_info("gcc", &hardened, &[("test.c", c_source)])
+        else {
+            return;
+        };
+        let report = checksec::checksec_files(&binary_path).unwrap();
+        for name in ["pie", "relro", "nx", "canary", "fortify", "rpath"] {
+            assert_eq!(report.status(name), Some("pass"), "{name}: {report:?}");
+        }
+        assert_eq!(
+            report.passed + report.warnings + report.failed,
+            report.checks.len()
+        );
 
-        for path in demo_paths {
-            if std::path::Path::new(path).exists() {
-                let data = std::fs::read(path).unwrap();
-                // Use basic analysis to avoid expensive DWARF parsing in tests
-                let result = analyze_elf_from_bytes_basic(&data).unwrap();
-                // Note: file_type can be either "executable" or "shared_object" depending on linking
-                assert!(["executable", "shared_object"].contains(&result.file_type.as_str()));
-                assert_eq!(result.endianness, "little_endian");
-                assert!(result.entry_point > 0);
+        // memcpy into a buffer of unknown size cannot be fortified
+        let partial_source = c_source.replace(
+            "int main(",
+            "void __attribute__((noinline)) copy(char *d, const char *s, size_t n) { memcpy(d, s, n); }\nint main(",
+        );
+        let Some((_temp_dir, binary_path)) =
+            compile_sources_with_debug_info("gcc", &hardened, &[("test.c", &partial_source)])
+        else {
+            return;
+        };
+        let report = checksec::checksec_files(&binary_path).unwrap();
+        let fortify = report.checks.iter().find(|c| c.name == "fortify").unwrap();
+        assert_eq!(fortify.status, "warn", "{fortify:?}");
+        assert!(
+            fortify.detail.contains("unfortified: memcpy"),
+            "{fortify:?}"
+        );
 
-                // Architecture should be valid
-                assert!(["x86_64", "aarch64", "riscv"].contains(&result.architecture.as_str()));
-            }
+        let weak = [
+            "-fno-stack-protector",
+            "-no-pie",
+            "-Wl,-z,norelro,-z,execstack,-rpath,lib",
+        ];
+        let Some((_temp_dir, binary_path)) =
+            compile_sources_with_debug_info("gcc", &weak, &[("test.c", c_source)])
+        else {
+            return;
+        };
+        let report = checksec::checksec_files(&binary_path).unwrap();
+        for name in ["pie", "relro", "nx", "rpath", "wx_segments"] {
+            assert_eq!(report.status(name), Some("fail"), "{name}: {report:?}");
         }
+        assert_eq!(report.status("fortify"), Some("warn"));
+        assert_eq!(report.status("canary"), Some("warn"));
+    }
+
+    #[test]
+    fn test_check_policy() {
+        let c_source = r#"
+#include <stdio.h>
+#include <stdlib.h>
+int __attribute__((noinline)) deep(int x) {
+    volatile char buffer[512];
+    buffer[x & 511] = 1;
+    return buffer[(x + 1) & 511];
+}
+int main(int argc, char **argv) {
+    char *p = malloc(16);
+    printf("%p %d\n", p, deep(argc));
+    return 0;
+}
+"#;
+        let Some((temp_dir, binary_path)) = compile_sources_with_debug_info(
+            "gcc",
+            &["-O2", "-Wl,--build-id=none,-z,now"],
+            &[("test.c", c_source)],
+        ) else {
+            return;
+        };
+        let policy = policy::Policy::from_toml(
+            r#"
+max_text_size = 16
+forbidden_symbols = ["malloc", "free", "str*"]
+require_build_id = true
+required_hardening = ["nx", "relro"]
+max_stack_depth = 256
+stack_roots = ["main"]
+"#,
+        )
+        .unwrap();
+        let buffer = std::fs::read(&binary_path).unwrap();
+        let report = policy::check_policy_from_bytes(&buffer, &policy).unwrap();
+        assert!(!report.passed);
+        assert_eq!(report.rules.len(), 5);
+        let violated: Vec<(&str, &str)> = report
+            .violations
+            .iter()
+            .map(|v| (v.rule.as_str(), v.subject.as_str()))
+            .collect();
+        assert!(
+            violated.contains(&("max_text_size", ".text")),
+            "{violated:?}"
+        );
+        assert!(violated.contains(&("forbidden_symbols", "malloc")));
+        assert!(!violated.iter().any(|&(_, subject)| subject == "free"));
+        assert!(violated.contains(&("require_build_id", ".note.gnu.build-id")));
+        assert!(!violated
+            .iter()
+            .any(|&(rule, _)| rule == "required_hardening"));
+        let stack = report
+            .violations
+            .iter()
+            .find(|v| v.rule == "max_stack_depth")
+            .expect("stack violation");
+        assert_eq!(stack.subject, "main");
+        assert!(stack.actual.unwrap() > 256 && stack.limit == Some(256));
+
+        // The same limits relaxed pass, read from a file
+        let policy_path = temp_dir.path().join("policy.toml");
+        std::fs::write(
+            &policy_path,
+            "max_text_size = 0x100000\nforbidden_symbols = [\"free\"]\nmax_stack_depth = 65536\n",
+        )
+        .unwrap();
+        let report =
+            policy::check_policy_files(&binary_path, policy_path.to_str().unwrap()).unwrap();
+        assert!(report.passed, "{:?}", report.violations);
     }
 }
@@ -1,46 +1,424 @@
 use anyhow::Result;
-use clap::Parser;
-use himmel::{analyze_files, to_json};
+use clap::{Parser, Subcommand};
+use himmel::abi::check_abi_files;
+use himmel::c_header::generate_c_header;
+use himmel::callgraph::call_graph_files;
+use himmel::cfi::cfi_coverage_files;
+use himmel::checksec::checksec_files;
+use himmel::core_dump::analyze_core_files;
+use himmel::dead_code::dead_code_files;
+use himmel::diff::diff_files;
+use himmel::disasm::disassemble_files;
+use himmel::layout::{analyze_layouts, padding_report, DEFAULT_CACHELINE_SIZE};
+use himmel::policy::check_policy_files;
+use himmel::rust_bindings::generate_rust_bindings;
+use himmel::size::analyze_sizes;
+use himmel::stack::stack_usage_files;
+use himmel::unwind::{backtrace_files, parse_number, parse_register};
+use himmel::values::read_initial_values;
+use himmel::{analyze_elf, analyze_files, to_json, SourceLocation};
+use std::collections::HashMap;
 
 #[derive(Parser)]
 #[command(name = "himmel")]
 #[command(about = "A CLI tool for analyzing ELF files using DWARF info")]
 #[command(version = "0.1.0")]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Path to ELF file to analyze
     #[arg(long, value_name = "FILE")]
     elf: Option<String>,
 
-    /// Output format (currently only JSON is supported)
-    #[arg(long, default_value = "json")]
+    /// Output format: json, markdown for `diff`, text for `disasm`, or dot for `callgraph`
+    #[arg(long, default_value = "json", global = true)]
     format: String,
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+#[derive(Subcommand)]
+enum Command {
+    /// Show struct holes, padding and cacheline placement with a suggested member order
+    Layout {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
 
-    // Validate that ELF file is provided
-    if args.elf.is_none() {
-        eprintln!("Error: ELF file (--elf) must be provided");
-        std::process::exit(1);
+        /// Cacheline size in bytes
+        #[arg(long, default_value_t = DEFAULT_CACHELINE_SIZE)]
+        cacheline_size: u64,
+
+        /// Only report the type with this name or qualified name
+        #[arg(long = "type", value_name = "NAME")]
+        type_name: Option<String>,
+    },
+    /// Rank structs by padding bytes multiplied by their global instances in .data/.bss
+    Padding {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Only report the N highest-ranked structs
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+    },
+    /// Attribute file and VM bytes to sections, segments, symbols, compilation units and crates
+    Size {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Keep the N largest entries of each breakdown and fold the rest together
+        #[arg(long, value_name = "N")]
+        top: Option<usize>,
+    },
+    /// Compare two builds: section and symbol sizes, function signatures and struct layouts
+    Diff {
+        /// Baseline ELF file
+        old: String,
+
+        /// ELF file to compare against the baseline
+        new: String,
+
+        /// Number of section and symbol changes listed in markdown output
+        #[arg(long, value_name = "N", default_value_t = 20)]
+        top: usize,
+    },
+    /// Check two versions of a shared library for ABI compatibility; exits with 2 on breaking changes
+    AbiCheck {
+        /// Previously released library
+        old: String,
+
+        /// Library to check against the previous release
+        new: String,
+    },
+    /// Generate a C header with static asserts from the DWARF types
+    Header {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Only emit these types and their dependencies (repeatable)
+        #[arg(long = "type", value_name = "NAME")]
+        type_names: Vec<String>,
+    },
+    /// Generate `#[repr(C)]` Rust bindings with layout asserts from the DWARF types
+    Bindings {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Only emit these types and their dependencies (repeatable)
+        #[arg(long = "type", value_name = "NAME")]
+        type_names: Vec<String>,
+    },
+    /// Decode the initial values of global variables from .data/.rodata (.bss reads as zero)
+    Values {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Only report variables with this name or qualified name (repeatable)
+        #[arg(long = "name", value_name = "NAME")]
+        names: Vec<String>,
+    },
+    /// Analyze a core dump: signal, threads and registers, mappings, auxv and global values
+    Core {
+        /// Path to the core dump
+        #[arg(long, value_name = "FILE")]
+        core: String,
+
+        /// Path to the executable that produced the core dump
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+    },
+    /// Unwind a raw memory snapshot of a process using the CFI of its executable
+    Backtrace {
+        /// Path to the executable of the process
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Raw memory dump, usually of the stack
+        #[arg(long, value_name = "FILE")]
+        memory: String,
+
+        /// Address the memory dump was taken at
+        #[arg(long, value_name = "ADDRESS", value_parser = parse_number_arg)]
+        address: u64,
+
+        /// Register value as NAME=VALUE, e.g. rsp=0x7ffc1000; pc and sp are required (repeatable)
+        #[arg(long = "reg", value_name = "NAME=VALUE")]
+        registers: Vec<String>,
+
+        /// Offset a position-independent executable was loaded at
+        #[arg(long, value_name = "OFFSET", default_value = "0", value_parser = parse_number_arg)]
+        load_bias: u64,
+    },
+    /// Report which functions have CFI, their unwind rules, and FDEs that disagree with symbols or DWARF
+    Cfi {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Only list functions without CFI or with mismatched FDE ranges
+        #[arg(long)]
+        issues: bool,
+    },
+    /// Disassemble functions with branch targets resolved to symbols and source lines interleaved
+    Disasm {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Function to disassemble; all functions when omitted (repeatable)
+        #[arg(long = "function", value_name = "NAME")]
+        names: Vec<String>,
+    },
+    /// Build the static call graph from decoded calls and DWARF call sites
+    Callgraph {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Only keep the functions that call NAME, directly or through others
+        #[arg(long, value_name = "NAME")]
+        callers: Option<String>,
+
+        /// Only keep the functions reachable from NAME, or from the entry point without NAME
+        #[arg(long, value_name = "NAME", num_args = 0..=1, default_missing_value = "")]
+        reachable: Option<String>,
+    },
+    /// Compute worst-case stack usage from frame sizes and the call graph
+    Stack {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Function to start from; every function nothing calls when omitted (repeatable)
+        #[arg(long = "root", value_name = "NAME")]
+        roots: Vec<String>,
+
+        /// Frame sizes from a `.su` file written by GCC -fstack-usage (repeatable)
+        #[arg(long = "stack-usage", value_name = "FILE")]
+        stack_usage: Vec<String>,
+    },
+    /// Find functions nothing references and the bytes removing them would reclaim
+    DeadCode {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+    },
+    /// Audit hardening: PIE, RELRO, NX, canaries, FORTIFY, CET/BTI/PAC, RPATH, W+X segments
+    Checksec {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+    },
+    /// Check limits declared in a TOML policy; exits with status 2 on violations
+    Check {
+        /// Path to ELF file to analyze
+        #[arg(long, value_name = "FILE")]
+        elf: String,
+
+        /// Policy file with the limits to enforce
+        #[arg(long, value_name = "FILE")]
+        policy: String,
+    },
+}
+
+fn parse_number_arg(text: &str) -> Result<u64, String> {
+    parse_number(text).map_err(|e| e.to_string())
+}
+
+/// Reads source lines for the text listing, caching each file
+fn source_line_reader() -> impl Fn(&SourceLocation) -> Option<String> {
+    let files = std::cell::RefCell::new(HashMap::<String, Option<Vec<String>>>::new());
+    move |location| {
+        let line = usize::try_from(location.line?).ok()?.checked_sub(1)?;
+        let mut files = files.borrow_mut();
+        let lines = files.entry(location.file.clone()).or_insert_with(|| {
+            std::fs::read_to_string(&location.file)
+                .ok()
+                .map(|text| text.lines().map(str::to_string).collect())
+        });
+        lines.as_ref()?.get(line).cloned()
     }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
 
     // Validate format
-    if args.format != "json" {
-        eprintln!("Error: Only 'json' format is currently supported");
+    let other_format = match args.command {
+        Some(Command::Diff { .. }) => "markdown",
+        Some(Command::Disasm { .. }) => "text",
+        Some(Command::Callgraph { .. }) => "dot",
+        _ => "json",
+    };
+    if args.format != "json" && args.format != other_format {
+        eprintln!(
+            "Error: Only 'json' format is supported (and 'markdown' for diff, 'text' for disasm, 'dot' for callgraph)"
+        );
         std::process::exit(1);
     }
 
-    match analyze_files(args.elf.as_deref()) {
-        Ok(result) => {
-            let json_output = to_json(&result)?;
-            println!("{json_output}");
+    let mut exit_code = 0;
+    let output = match args.command {
+        Some(Command::Layout {
+            elf,
+            cacheline_size,
+            type_name,
+        }) => analyze_elf(&elf).and_then(|elf_info| {
+            let mut layouts = analyze_layouts(&elf_info, cacheline_size);
+            if let Some(type_name) = type_name {
+                layouts.retain(|layout| {
+                    layout.name == type_name || layout.name.rsplit("::").next() == Some(&type_name)
+                });
+            }
+            to_json(&layouts)
+        }),
+        Some(Command::Padding { elf, top }) => analyze_elf(&elf).and_then(|elf_info| {
+            let mut report = padding_report(&elf_info);
+            if let Some(top) = top {
+                report.structs.truncate(top);
+            }
+            to_json(&report)
+        }),
+        Some(Command::Size { elf, top }) => analyze_sizes(&elf).and_then(|mut report| {
+            if let Some(top) = top {
+                report.truncate(top);
+            }
+            to_json(&report)
+        }),
+        Some(Command::Diff { old, new, top }) => diff_files(&old, &new).and_then(|report| {
+            if args.format == "markdown" {
+                Ok(report.to_markdown(top))
+            } else {
+                to_json(&report)
+            }
+        }),
+        Some(Command::AbiCheck { old, new }) => check_abi_files(&old, &new).and_then(|report| {
+            if !report.compatible {
+                exit_code = 2;
+            }
+            to_json(&report)
+        }),
+        Some(Command::Header { elf, type_names }) => {
+            analyze_elf(&elf).map(|elf_info| generate_c_header(&elf_info, &type_names))
+        }
+        Some(Command::Bindings { elf, type_names }) => {
+            analyze_elf(&elf).map(|elf_info| generate_rust_bindings(&elf_info, &type_names))
+        }
+        Some(Command::Values { elf, names }) => read_initial_values(&elf).and_then(|mut report| {
+            if !names.is_empty() {
+                report.variables.retain(|v| {
+                    names.contains(&v.name)
+                        || v.qualified_name.as_ref().is_some_and(|q| names.contains(q))
+                });
+            }
+            to_json(&report)
+        }),
+        Some(Command::Core { core, elf }) => {
+            analyze_core_files(&core, &elf).and_then(|r| to_json(&r))
+        }
+        Some(Command::Backtrace {
+            elf,
+            memory,
+            address,
+            registers,
+            load_bias,
+        }) => registers
+            .iter()
+            .map(|register| parse_register(register))
+            .collect::<Result<Vec<_>>>()
+            .and_then(|registers| backtrace_files(&elf, &memory, address, load_bias, &registers))
+            .and_then(|r| to_json(&r)),
+        Some(Command::Cfi { elf, issues }) => cfi_coverage_files(&elf).and_then(|mut report| {
+            if issues {
+                report.retain_issues();
+            }
+            to_json(&report)
+        }),
+        Some(Command::Disasm { elf, names }) => {
+            disassemble_files(&elf, &names).and_then(|report| {
+                if args.format == "text" {
+                    Ok(report.to_text(&source_line_reader()))
+                } else {
+                    to_json(&report)
+                }
+            })
         }
+        Some(Command::Callgraph {
+            elf,
+            callers,
+            reachable,
+        }) => call_graph_files(&elf).and_then(|mut graph| {
+            if let Some(name) = callers {
+                let targets = graph.resolve(&name);
+                if targets.is_empty() {
+                    anyhow::bail!("No function named {name}");
+                }
+                let keep = graph.callers_of(&targets);
+                graph.retain(&keep);
+            }
+            if let Some(name) = reachable {
+                let name = match name.as_str() {
+                    "" => graph
+                        .entry
+                        .clone()
+                        .ok_or_else(|| anyhow::anyhow!("The entry point is not in a function"))?,
+                    _ => name,
+                };
+                let roots = graph.resolve(&name);
+                if roots.is_empty() {
+                    anyhow::bail!("No function named {name}");
+                }
+                let keep = graph.reachable_from(&roots);
+                graph.retain(&keep);
+            }
+            if args.format == "dot" {
+                Ok(graph.to_dot())
+            } else {
+                to_json(&graph)
+            }
+        }),
+        Some(Command::Stack {
+            elf,
+            roots,
+            stack_usage,
+        }) => stack_usage_files(&elf, &roots, &stack_usage).and_then(|report| to_json(&report)),
+        Some(Command::DeadCode { elf }) => {
+            dead_code_files(&elf).and_then(|report| to_json(&report))
+        }
+        Some(Command::Checksec { elf }) => checksec_files(&elf).and_then(|report| to_json(&report)),
+        Some(Command::Check { elf, policy }) => {
+            check_policy_files(&elf, &policy).and_then(|report| {
+                if !report.passed {
+                    exit_code = 2;
+                }
+                to_json(&report)
+            })
+        }
+        None => {
+            // Validate that ELF file is provided
+            if args.elf.is_none() {
+                eprintln!("Error: ELF file (--elf) must be provided");
+                std::process::exit(1);
+            }
+            analyze_files(args.elf.as_deref()).and_then(|result| to_json(&result))
+        }
+    };
+
+    match output {
+        Ok(json_output) => println!("{json_output}"),
         Err(e) => {
             eprintln!("Error: {e}");
             std::process::exit(1);
         }
     }
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
 
     Ok(())
 }
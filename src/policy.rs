@@ -0,0 +1,328 @@
+//! Policy check: limits declared in a TOML file, such as
+//!
+//! ```toml
+//! max_text_size = 65536
+//! forbidden_symbols = ["malloc", "free", "_Unwind_*"]
+//! require_build_id = true
+//! required_hardening = ["relro", "nx", "canary"]
+//! max_stack_depth = 4096
+//! stack_roots = ["Reset_Handler"]
+//! ```
+//!
+//! checked against an ELF file, with every violation reported. Each limit
+//! is optional; the hardening names are those of the checksec report.
+
+use crate::checksec::{checksec_from_bytes, symbol_names};
+use crate::demangle_symbol;
+use crate::stack::stack_usage_from_bytes;
+use anyhow::{Context, Result};
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const NT_GNU_BUILD_ID: u32 = 3;
+
+const HARDENING_CHECKS: &[&str] = &[
+    "pie",
+    "relro",
+    "nx",
+    "canary",
+    "fortify",
+    "cet",
+    "bti",
+    "pac",
+    "rpath",
+    "wx_segments",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    /// Largest allowed size of `.text` in bytes
+    pub max_text_size: Option<u64>,
+    /// Symbols that must be neither defined nor imported; `*` matches any
+    /// run of characters, and demangled names are matched too
+    #[serde(default)]
+    pub forbidden_symbols: Vec<String>,
+    /// A GNU build-id note must be present
+    #[serde(default)]
+    pub require_build_id: bool,
+    /// Checksec checks that must pass
+    #[serde(default)]
+    pub required_hardening: Vec<String>,
+    /// Largest allowed worst-case stack depth in bytes
+    pub max_stack_depth: Option<u64>,
+    /// Functions the stack depth is measured from; every function nothing
+    /// calls when empty
+    #[serde(default)]
+    pub stack_roots: Vec<String>,
+    /// A root whose worst case is not a true bound, because of recursion,
+    /// indirect calls or frames of unknown size, is a violation too
+    #[serde(default)]
+    pub require_bounded_stack: bool,
+}
+
+impl Policy {
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let policy: Policy = toml::from_str(text)?;
+        for name in &policy.required_hardening {
+            if !HARDENING_CHECKS.contains(&name.as_str()) {
+                anyhow::bail!(
+                    "Unknown hardening check '{name}' (expected one of {})",
+                    HARDENING_CHECKS.join(", ")
+                );
+            }
+        }
+        Ok(policy)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PolicyReport {
+    /// No rule was violated
+    pub passed: bool,
+    /// Rules the policy sets, whether violated or not
+    pub rules: Vec<String>,
+    pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Violation {
+    pub rule: String, // "max_text_size", "forbidden_symbols", "require_build_id", "required_hardening", "max_stack_depth", "require_bounded_stack"
+    /// What violates it: a section, symbol, hardening check or stack root
+    pub subject: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+}
+
+impl PolicyReport {
+    fn violation(&mut self, rule: &str, subject: &str, message: String) -> &mut Violation {
+        self.violations.push(Violation {
+            rule: rule.to_string(),
+            subject: subject.to_string(),
+            message,
+            ..Default::default()
+        });
+        self.violations.last_mut().unwrap()
+    }
+}
+
+/// Check the ELF file at `path` against the TOML policy at `policy_path`
+pub fn check_policy_files(path: &str, policy_path: &str) -> Result<PolicyReport> {
+    let text = fs::read_to_string(policy_path)
+        .with_context(|| format!("Failed to read policy file: {policy_path}"))?;
+    let policy = Policy::from_toml(&text)
+        .map_err(|e| anyhow::anyhow!("Invalid policy file {policy_path}: {e}"))?;
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    check_policy_from_bytes(&buffer, &policy)
+}
+
+/// Check an ELF byte buffer against a policy (WebAssembly-compatible)
+pub fn check_policy_from_bytes(buffer: &[u8], policy: &Policy) -> Result<PolicyReport> {
+    let elf = match Object::parse(buffer)? {
+        Object::Elf(elf) => elf,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+    let mut report = PolicyReport::default();
+
+    if let Some(limit) = policy.max_text_size {
+        report.rules.push("max_text_size".to_string());
+        let text = elf
+            .section_headers
+            .iter()
+            .find(|section| elf.shdr_strtab.get_at(section.sh_name) == Some(".text"));
+        match text {
+            Some(section) if section.sh_size > limit => {
+                let size = section.sh_size;
+                let violation = report.violation(
+                    "max_text_size",
+                    ".text",
+                    format!(
+                        ".text is {size} bytes, {} over the limit of {limit}",
+                        size - limit
+                    ),
+                );
+                violation.actual = Some(size);
+                violation.limit = Some(limit);
+            }
+            Some(_) => {}
+            None => {
+                report.violation(
+                    "max_text_size",
+                    ".text",
+                    "No .text section to measure".to_string(),
+                );
+            }
+        }
+    }
+
+    if !policy.forbidden_symbols.is_empty() {
+        report.rules.push("forbidden_symbols".to_string());
+        let symbols = symbol_names(&elf);
+        for (name, how) in symbols
+            .defined
+            .iter()
+            .map(|name| (name, "defined"))
+            .chain(symbols.imported.iter().map(|name| (name, "imported")))
+        {
+            let demangled = demangle_symbol(name);
+            let Some(pattern) = policy.forbidden_symbols.iter().find(|pattern| {
+                glob_match(pattern, name)
+                    || demangled
+                        .as_deref()
+                        .is_some_and(|demangled| glob_match(pattern, demangled))
+            }) else {
+                continue;
+            };
+            let shown = demangled.as_deref().unwrap_or(name);
+            report.violation(
+                "forbidden_symbols",
+                shown,
+                format!("{shown} is {how}, forbidden by '{pattern}'"),
+            );
+        }
+    }
+
+    if policy.require_build_id {
+        report.rules.push("require_build_id".to_string());
+        let has_build_id = elf
+            .iter_note_headers(buffer)
+            .or_else(|| elf.iter_note_sections(buffer, None))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .any(|note| note.name == "GNU" && note.n_type == NT_GNU_BUILD_ID);
+        if !has_build_id {
+            report.violation(
+                "require_build_id",
+                ".note.gnu.build-id",
+                "No GNU build-id note: link with --build-id".to_string(),
+            );
+        }
+    }
+
+    if !policy.required_hardening.is_empty() {
+        report.rules.push("required_hardening".to_string());
+        let checksec = checksec_from_bytes(buffer)?;
+        for name in &policy.required_hardening {
+            match checksec.checks.iter().find(|check| &check.name == name) {
+                Some(check) if check.status == "pass" => {}
+                Some(check) => {
+                    report.violation(
+                        "required_hardening",
+                        name,
+                        format!("{name} is {}: {}", check.status, check.detail),
+                    );
+                }
+                None => {
+                    report.violation(
+                        "required_hardening",
+                        name,
+                        format!("{name} cannot be checked on this architecture"),
+                    );
+                }
+            }
+        }
+    }
+
+    if policy.max_stack_depth.is_some() || policy.require_bounded_stack {
+        let stack = stack_usage_from_bytes(buffer, &policy.stack_roots, &[])?;
+        if let Some(limit) = policy.max_stack_depth {
+            report.rules.push("max_stack_depth".to_string());
+            for root in stack.roots.iter().filter(|root| root.worst_case > limit) {
+                let violation = report.violation(
+                    "max_stack_depth",
+                    &root.name,
+                    format!(
+                        "{} bytes deep through {}, over the limit of {limit}",
+                        root.worst_case,
+                        root.path.join(" -> ")
+                    ),
+                );
+                violation.actual = Some(root.worst_case);
+                violation.limit = Some(limit);
+            }
+        }
+        if policy.require_bounded_stack {
+            report.rules.push("require_bounded_stack".to_string());
+            for root in stack.roots.iter().filter(|root| !root.bounded) {
+                let unknowns: Vec<String> = root
+                    .unknowns
+                    .iter()
+                    .map(|unknown| format!("{} ({})", unknown.function, unknown.reason))
+                    .collect();
+                report.violation(
+                    "require_bounded_stack",
+                    &root.name,
+                    format!("Stack depth is not bounded: {}", unknowns.join(", ")),
+                );
+            }
+        }
+    }
+
+    report.passed = report.violations.is_empty();
+    Ok(report)
+}
+
+/// Whether `text` matches `pattern`, where `*` stands for any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((first, rest)) = pattern.split_once('*') else {
+        return pattern == text;
+    };
+    let Some(mut remaining) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.len() >= last.len() && remaining.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("malloc", "malloc"));
+        assert!(!glob_match("malloc", "malloc_trim"));
+        assert!(glob_match("malloc*", "malloc_trim"));
+        assert!(glob_match("_Unwind_*", "_Unwind_Resume"));
+        assert!(glob_match("*printf", "vsnprintf"));
+        assert!(glob_match("operator new*", "operator new(unsigned long)"));
+        assert!(glob_match("a*b*c", "a-b-b-c"));
+        assert!(!glob_match("a*b*c", "a-c-b"));
+        assert!(!glob_match("ab*ba", "aba"));
+    }
+
+    #[test]
+    fn test_policy_from_toml() {
+        let policy = Policy::from_toml(
+            r#"
+max_text_size = 0x10000
+forbidden_symbols = ["malloc", "free"]
+require_build_id = true
+required_hardening = ["relro", "nx"]
+max_stack_depth = 2048
+"#,
+        )
+        .unwrap();
+        assert_eq!(policy.max_text_size, Some(0x10000));
+        assert_eq!(policy.forbidden_symbols, ["malloc", "free"]);
+        assert!(policy.require_build_id && !policy.require_bounded_stack);
+        assert_eq!(policy.max_stack_depth, Some(2048));
+        assert!(policy.stack_roots.is_empty());
+
+        assert!(Policy::from_toml("max_text = 10").is_err());
+        let error = Policy::from_toml("required_hardening = [\"aslr\"]").unwrap_err();
+        assert!(error.to_string().contains("aslr"));
+    }
+}
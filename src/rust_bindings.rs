@@ -0,0 +1,685 @@
+//! Rust `#[repr(C)]` bindings from DWARF types: structs and unions with
+//! explicit padding fields and bitfield accessors, C enums as newtypes over
+//! their underlying integer, and const asserts that check every size and
+//! field offset against DWARF
+
+use crate::c_header::{
+    c_identifier, is_aggregate, is_anonymous_member, is_zero_sized, type_key, TypeIndex,
+};
+use crate::layout::type_alignment;
+use crate::{ElfInfo, MemberInfo, TypeInfo};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Bitfield runs are read through a `u128`
+const MAX_BITFIELD_STORAGE: u64 = 16;
+
+/// Emit Rust definitions for the named types in `elf_info` (all of them when
+/// `type_names` is empty) and every type they depend on
+pub fn generate_rust_bindings(elf_info: &ElfInfo, type_names: &[String]) -> String {
+    let mut writer = BindingWriter::new(elf_info);
+
+    for type_info in writer.index.roots(type_names) {
+        let context = rust_identifier(type_key(type_info));
+        writer.spell(type_info, &context, true);
+    }
+
+    let mut out = String::from(
+        "// Generated by himmel from DWARF debug information\n\
+         #![allow(non_camel_case_types, non_snake_case, non_upper_case_globals, dead_code)]\n",
+    );
+    for name in writer.opaque.difference(&writer.emitted) {
+        // Only used behind pointers, like a forward declaration in C
+        out.push_str(&format!(
+            "\n#[repr(C)]\npub struct {name} {{\n    _unused: [u8; 0],\n}}\n"
+        ));
+    }
+    for item in &writer.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+    if !writer.asserts.is_empty() {
+        out.push_str("\n// Layout checks\n");
+        for assertion in &writer.asserts {
+            out.push_str(&format!("const _: () = assert!({assertion});\n"));
+        }
+    }
+    out
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+const PRIMITIVES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32",
+    "f64", "bool", "char", "str",
+];
+
+/// A C identifier that is also valid in Rust: keywords become raw identifiers
+fn rust_identifier(name: &str) -> String {
+    let identifier = c_identifier(name);
+    match identifier.as_str() {
+        // Cannot be raw identifiers
+        "self" | "Self" | "super" | "crate" | "_" => format!("{identifier}_"),
+        keyword if KEYWORDS.contains(&keyword) => format!("r#{identifier}"),
+        _ => identifier,
+    }
+}
+
+/// Rust spelling of a DWARF base type; `char` signedness follows the target
+fn rust_base_type(type_info: &TypeInfo, char_signed: bool) -> String {
+    let name = type_info.name.as_str();
+    let size = type_info.size.unwrap_or(0);
+    let bits = size * 8;
+    let mapped = match name {
+        "()" => "()",
+        _ if PRIMITIVES.contains(&name) && name != "char" && name != "str" => name,
+        // Rust `char` is a 32-bit Unicode scalar value; not every bit pattern is valid
+        "char" if size == 4 => "u32",
+        "_Bool" => "bool",
+        "signed char" => "i8",
+        "unsigned char" | "char8_t" => "u8",
+        "char" | "wchar_t" if char_signed => return format!("i{bits}"),
+        "char" | "wchar_t" => return format!("u{bits}"),
+        _ if name.contains("complex") => {
+            return match size {
+                8 => "[f32; 2]".to_string(),
+                16 => "[f64; 2]".to_string(),
+                _ => format!("[u8; {size}]"),
+            };
+        }
+        _ if name.contains("float") || name.contains("double") || name.starts_with("_Float") => {
+            match size {
+                4 => "f32",
+                8 => "f64",
+                // `long double`, `_Float16` and `__float128` have no Rust equivalent
+                _ => return format!("[u8; {size}]"),
+            }
+        }
+        _ if matches!(size, 1 | 2 | 4 | 8 | 16) => {
+            let unsigned = name.contains("unsigned") || name.starts_with("char");
+            return format!("{}{bits}", if unsigned { 'u' } else { 'i' });
+        }
+        _ if size == 0 => "()",
+        _ => return format!("[u8; {size}]"),
+    };
+    mapped.to_string()
+}
+
+/// A struct field in generated order: a member, or a run of bitfields
+/// sharing one byte array
+enum Field<'a> {
+    Member {
+        offset: u64,
+        member: &'a MemberInfo,
+    },
+    Bitfields {
+        offset: u64,
+        end_bit: u64,
+        members: Vec<(u64, &'a MemberInfo)>,
+    },
+}
+
+struct BindingWriter<'a> {
+    index: TypeIndex<'a>,
+    big_endian: bool,
+    char_signed: bool,
+    /// Names given to anonymous aggregates, keyed by their definition
+    anonymous: HashMap<*const TypeInfo, String>,
+    emitted: BTreeSet<String>,
+    in_progress: HashSet<String>,
+    /// Aggregates referenced behind pointers, defined opaquely unless used by value
+    opaque: BTreeSet<String>,
+    items: Vec<String>,
+    asserts: Vec<String>,
+}
+
+impl<'a> BindingWriter<'a> {
+    fn new(elf_info: &'a ElfInfo) -> Self {
+        BindingWriter {
+            index: TypeIndex::new(elf_info),
+            big_endian: elf_info.endianness == "big_endian",
+            char_signed: matches!(elf_info.architecture.as_str(), "x86_64" | "i386"),
+            anonymous: HashMap::new(),
+            emitted: BTreeSet::new(),
+            in_progress: HashSet::new(),
+            opaque: BTreeSet::new(),
+            items: Vec::new(),
+            asserts: Vec::new(),
+        }
+    }
+
+    /// Rust type for `type_info`, emitting whatever it refers to: complete
+    /// definitions for aggregates used by value, opaque ones behind pointers.
+    /// Anonymous aggregates are named after `context`
+    fn spell(&mut self, type_info: &'a TypeInfo, context: &str, by_value: bool) -> String {
+        match type_info.kind.as_str() {
+            "typedef" => self.emit_typedef(type_info, by_value),
+            "const" | "volatile" | "restrict" | "atomic" => match type_info.target.as_deref() {
+                Some(target) => self.spell(target, context, by_value),
+                None => "core::ffi::c_void".to_string(),
+            },
+            "pointer" | "reference" => {
+                let Some(target) = type_info.target.as_deref() else {
+                    return "*mut core::ffi::c_void".to_string();
+                };
+                if target.underlying().kind == "subroutine" {
+                    return format!("Option<{}>", self.spell(target, context, false));
+                }
+                let mutability = if target.kind == "const" {
+                    "const"
+                } else {
+                    "mut"
+                };
+                format!("*{mutability} {}", self.spell(target, context, false))
+            }
+            "array" => {
+                let Some(array) = &type_info.array else {
+                    return format!("[u8; {}]", type_info.size.unwrap_or(0));
+                };
+                let mut spelled = self.spell(&array.element_type, context, true);
+                for dimension in array.dimensions.iter().rev() {
+                    let count = match dimension.count {
+                        Some(count) if !array.flexible => count,
+                        _ => 0,
+                    };
+                    spelled = format!("[{spelled}; {count}]");
+                }
+                spelled
+            }
+            "subroutine" => {
+                let Some(subroutine) = &type_info.subroutine else {
+                    return "unsafe extern \"C\" fn()".to_string();
+                };
+                let mut parameters: Vec<String> = subroutine
+                    .parameters
+                    .iter()
+                    .map(|parameter| self.spell(parameter, context, false))
+                    .collect();
+                if subroutine.variadic && !parameters.is_empty() {
+                    parameters.push("...".to_string());
+                }
+                let mut spelled = format!("unsafe extern \"C\" fn({})", parameters.join(", "));
+                if let Some(return_type) = subroutine.return_type.as_deref() {
+                    let return_type = self.spell(return_type, context, false);
+                    if return_type != "()" && return_type != "core::ffi::c_void" {
+                        spelled.push_str(&format!(" -> {return_type}"));
+                    }
+                }
+                spelled
+            }
+            "basic" => rust_base_type(type_info, self.char_signed),
+            _ if is_aggregate(type_info) => {
+                let definition = self.index.definition(type_info);
+                if definition.name == "<anonymous>" {
+                    let name = self.anonymous_name(definition, context);
+                    self.emit_aggregate(definition, &name);
+                    return name;
+                }
+                let name = rust_identifier(type_key(definition));
+                if (by_value || definition.kind == "enum") && definition.size.is_some() {
+                    self.emit_aggregate(definition, &name);
+                } else {
+                    self.opaque.insert(name.clone());
+                }
+                name
+            }
+            _ => match type_info.size {
+                Some(size) => format!("[u8; {size}]"),
+                None => "core::ffi::c_void".to_string(),
+            },
+        }
+    }
+
+    fn anonymous_name(&mut self, definition: &TypeInfo, context: &str) -> String {
+        let key = definition as *const TypeInfo;
+        if let Some(name) = self.anonymous.get(&key) {
+            return name.clone();
+        }
+        let mut name = context.trim_start_matches("r#").to_string();
+        let mut suffix = 1;
+        while self.anonymous.values().any(|taken| *taken == name) || self.index.get(&name).is_some()
+        {
+            suffix += 1;
+            name = format!("{}_{suffix}", context.trim_start_matches("r#"));
+        }
+        self.anonymous.insert(key, name.clone());
+        name
+    }
+
+    fn emit_typedef(&mut self, typedef: &'a TypeInfo, by_value: bool) -> String {
+        let name = rust_identifier(type_key(typedef));
+        let Some(target) = typedef.target.as_deref() else {
+            return "core::ffi::c_void".to_string();
+        };
+        if PRIMITIVES.contains(&name.as_str()) {
+            // `typedef unsigned char u8;` would shadow the primitive with itself
+            return self.spell(target, &name, by_value);
+        }
+        let definition = self.index.definition(target.underlying());
+        if is_aggregate(definition) && definition.name == "<anonymous>" {
+            // `typedef struct { ... } name_t;` names the struct itself
+            self.anonymous
+                .entry(definition as *const TypeInfo)
+                .or_insert_with(|| name.clone());
+            return self.spell(target, &name, by_value);
+        }
+        if self.in_progress.contains(&name) {
+            return name;
+        }
+        self.in_progress.insert(name.clone());
+        let spelled = self.spell(target, &name, by_value);
+        self.in_progress.remove(&name);
+        if spelled == name {
+            return name;
+        }
+        if self.emitted.insert(name.clone()) {
+            self.items.push(format!("pub type {name} = {spelled};\n"));
+        }
+        name
+    }
+
+    fn emit_aggregate(&mut self, type_info: &'a TypeInfo, name: &str) {
+        if self.emitted.contains(name) || self.in_progress.contains(name) {
+            return;
+        }
+        self.in_progress.insert(name.to_string());
+        let item = if type_info.kind == "enum" && type_info.variant_part.is_none() {
+            self.enumeration(type_info, name)
+        } else if type_info.variant_part.is_some()
+            || type_info.members.is_empty() && type_info.size.unwrap_or(0) > 0
+        {
+            self.opaque_struct(type_info, name)
+        } else if type_info.kind == "union" {
+            self.union(type_info, name)
+        } else {
+            self.structure(type_info, name)
+        };
+        self.in_progress.remove(name);
+        self.emitted.insert(name.to_string());
+        self.items.push(item);
+    }
+
+    /// C enums can hold any value of their underlying type, which a Rust
+    /// enum cannot, so they become newtypes with associated constants
+    fn enumeration(&mut self, type_info: &TypeInfo, name: &str) -> String {
+        let size = type_info.size.unwrap_or(4);
+        let signed = type_info.enumerators.iter().any(|e| e.value < 0);
+        let repr = format!("{}{}", if signed { 'i' } else { 'u' }, size * 8);
+        let mut item = format!(
+            "#[repr(transparent)]\n#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]\n\
+             pub struct {name}(pub {repr});\n"
+        );
+        if !type_info.enumerators.is_empty() {
+            item.push_str(&format!("\nimpl {name} {{\n"));
+            let mut names = HashSet::new();
+            for enumerator in &type_info.enumerators {
+                let constant = rust_identifier(&enumerator.name);
+                if !names.insert(constant.clone()) {
+                    continue;
+                }
+                let value = if signed {
+                    match enumerator.value {
+                        i64::MIN => "i64::MIN".to_string(),
+                        value => value.to_string(),
+                    }
+                } else if size < 8 {
+                    (enumerator.value as u64 & ((1u64 << (size * 8)) - 1)).to_string()
+                } else {
+                    (enumerator.value as u64).to_string()
+                };
+                item.push_str(&format!(
+                    "    pub const {constant}: {name} = {name}({value});\n"
+                ));
+            }
+            item.push_str("}\n");
+        }
+        self.assert_size(name, type_info);
+        item
+    }
+
+    fn opaque_struct(&mut self, type_info: &TypeInfo, name: &str) -> String {
+        let size = type_info.size.unwrap_or(0);
+        let alignment = type_alignment(type_info);
+        let repr = if alignment > 1 {
+            format!("C, align({alignment})")
+        } else {
+            "C".to_string()
+        };
+        self.assert_size(name, type_info);
+        self.assert_alignment(name, alignment);
+        format!(
+            "#[repr({repr})]\n#[derive(Clone, Copy)]\npub struct {name} {{\n    pub __opaque: [u8; {size}],\n}}\n"
+        )
+    }
+
+    fn union(&mut self, type_info: &'a TypeInfo, name: &str) -> String {
+        let size = type_info.size.unwrap_or(0);
+        let mut lines = Vec::new();
+        let mut names = HashSet::new();
+        let mut natural = 1;
+        for (index, member) in type_info.members.iter().enumerate() {
+            if is_zero_sized(&member.type_info) {
+                continue;
+            }
+            let field = self.field_name(member, index, &mut names);
+            let context = format!(
+                "{name}_{}",
+                field.trim_start_matches("r#").trim_start_matches("__")
+            );
+            let spelled = self.spell(&member.type_info, &context, true);
+            natural = natural.max(type_alignment(&member.type_info));
+            lines.push(format!("    pub {field}: {spelled},"));
+        }
+        let largest = type_info
+            .members
+            .iter()
+            .filter_map(|member| member.type_info.size)
+            .max()
+            .unwrap_or(0);
+        if largest < size {
+            lines.push(format!("    pub __size: [u8; {size}],"));
+        }
+
+        let alignment = type_alignment(type_info);
+        let repr = if alignment > natural {
+            format!("C, align({alignment})")
+        } else {
+            "C".to_string()
+        };
+        self.assert_size(name, type_info);
+        self.assert_alignment(name, alignment);
+        format!(
+            "#[repr({repr})]\n#[derive(Clone, Copy)]\npub union {name} {{\n{}\n}}\n",
+            lines.join("\n")
+        )
+    }
+
+    fn structure(&mut self, type_info: &'a TypeInfo, name: &str) -> String {
+        let size = type_info.size.unwrap_or(0);
+
+        // Base class members are flattened in, as the C++ ABI may place
+        // derived members in the tail padding of a base
+        let mut members = Vec::new();
+        self.index.collect_members(type_info, 0, &mut members);
+        let mut entries: Vec<(u64, &'a MemberInfo)> = members
+            .into_iter()
+            .filter(|(_, member)| !is_zero_sized(&member.type_info))
+            .filter(|(_, member)| member.bit_size != Some(0))
+            .map(|(base_bits, member)| {
+                (
+                    base_bits + member.bit_offset.unwrap_or(member.offset * 8),
+                    member,
+                )
+            })
+            .collect();
+        entries.sort_by_key(|(start, _)| *start);
+
+        let mut fields: Vec<Field> = Vec::new();
+        for (start, member) in entries {
+            let Some(bits) = member.bit_size else {
+                fields.push(Field::Member {
+                    offset: start / 8,
+                    member,
+                });
+                continue;
+            };
+            if let Some(Field::Bitfields {
+                offset,
+                end_bit,
+                members,
+            }) = fields.last_mut()
+            {
+                let end = (*end_bit).max(start + bits);
+                if end.div_ceil(8) - *offset <= MAX_BITFIELD_STORAGE {
+                    *end_bit = end;
+                    members.push((start, member));
+                    continue;
+                }
+            }
+            fields.push(Field::Bitfields {
+                offset: start / 8,
+                end_bit: start + bits,
+                members: vec![(start, member)],
+            });
+        }
+
+        let mut lines = Vec::new();
+        let mut accessors = Vec::new();
+        let mut names = HashSet::new();
+        let mut offsets = Vec::new();
+        let mut cursor = 0;
+        let mut padding = 0;
+        let mut natural = 1;
+        let mut alignment = 1;
+        let mut packed = false;
+        for (index, field) in fields.iter().enumerate() {
+            let (offset, end) = match field {
+                Field::Member { offset, member } => {
+                    (*offset, offset + member.type_info.size.unwrap_or(0))
+                }
+                Field::Bitfields {
+                    offset, end_bit, ..
+                } => (*offset, end_bit.div_ceil(8)),
+            };
+            if offset > cursor {
+                lines.push(format!(
+                    "    pub __pad{padding}: [u8; {}],",
+                    offset - cursor
+                ));
+                padding += 1;
+            }
+            match field {
+                Field::Member { offset, member } => {
+                    let field_name = self.field_name(member, index, &mut names);
+                    let context = format!(
+                        "{name}_{}",
+                        field_name.trim_start_matches("r#").trim_start_matches("__")
+                    );
+                    let spelled = self.spell(&member.type_info, &context, true);
+                    let field_alignment = type_alignment(&member.type_info);
+                    natural = natural.max(field_alignment);
+                    alignment = alignment.max(member.alignment.unwrap_or(field_alignment));
+                    packed |= offset % field_alignment != 0;
+                    lines.push(format!("    pub {field_name}: {spelled},"));
+                    offsets.push((field_name, *offset));
+                }
+                Field::Bitfields {
+                    offset, members, ..
+                } => {
+                    let storage = format!("__bitfield{}", accessors.len());
+                    lines.push(format!("    pub {storage}: [u8; {}],", end - offset));
+                    let mut accessor = Vec::new();
+                    for (start, member) in members {
+                        if is_anonymous_member(member) {
+                            continue;
+                        }
+                        let method = self.field_name(member, index, &mut names);
+                        accessor.push(self.bitfield_accessor(
+                            member,
+                            &method,
+                            &storage,
+                            start - offset * 8,
+                            end - offset,
+                        ));
+                    }
+                    accessors.push(accessor.join("\n"));
+                }
+            }
+            cursor = cursor.max(end);
+        }
+        if size > cursor {
+            lines.push(format!("    pub __pad{padding}: [u8; {}],", size - cursor));
+        }
+        packed |= !size.is_multiple_of(natural);
+
+        // Members of base classes count towards the alignment too
+        let alignment = type_info.alignment.unwrap_or(alignment);
+        let repr = if packed {
+            "C, packed"
+        } else if alignment > natural {
+            &format!("C, align({alignment})")
+        } else {
+            "C"
+        };
+        let mut item = format!(
+            "#[repr({repr})]\n#[derive(Clone, Copy)]\npub struct {name} {{\n{}\n}}\n",
+            lines.join("\n")
+        );
+        let accessors: Vec<String> = accessors.into_iter().filter(|a| !a.is_empty()).collect();
+        if !accessors.is_empty() {
+            item.push_str(&format!("\nimpl {name} {{\n{}}}\n", accessors.join("\n")));
+        }
+
+        self.assert_size(name, type_info);
+        if !packed {
+            // A packed struct cannot also carry `align` in Rust
+            self.assert_alignment(name, alignment);
+        }
+        for (field_name, offset) in offsets {
+            self.asserts.push(format!(
+                "core::mem::offset_of!({name}, {field_name}) == {offset}"
+            ));
+        }
+        item
+    }
+
+    /// Getter and setter for a bitfield `start` bits into a byte array of
+    /// `length` bytes, in the bit order of the target
+    fn bitfield_accessor(
+        &mut self,
+        member: &'a MemberInfo,
+        method: &str,
+        storage: &str,
+        start: u64,
+        length: u64,
+    ) -> String {
+        let bits = member.bit_size.unwrap_or(0);
+        let mask = format!("{:#x}u128", (1u128 << bits) - 1);
+        let shift = if self.big_endian {
+            MAX_BITFIELD_STORAGE * 8 - start - bits
+        } else {
+            start
+        };
+        let order = if self.big_endian { "be" } else { "le" };
+        let (shift_right, shift_left) = match shift {
+            0 => (String::new(), String::new()),
+            shift => (format!(" >> {shift}"), format!(" << {shift}")),
+        };
+        let unused = MAX_BITFIELD_STORAGE * 8 - bits;
+
+        let underlying = member.type_info.underlying();
+        let definition = self.index.definition(underlying);
+        let (value_type, integer) =
+            if definition.kind == "enum" && definition.variant_part.is_none() {
+                let name = self.spell(&member.type_info, method, true);
+                let size = definition.size.unwrap_or(4);
+                let signed = definition.enumerators.iter().any(|e| e.value < 0);
+                (
+                    name,
+                    format!("{}{}", if signed { 'i' } else { 'u' }, size * 8),
+                )
+            } else {
+                let spelled = match underlying.kind.as_str() {
+                    "basic" => rust_base_type(underlying, self.char_signed),
+                    _ => "u64".to_string(),
+                };
+                (spelled.clone(), spelled)
+            };
+        let is_enum = value_type != integer;
+
+        let value = if integer == "bool" {
+            "raw != 0".to_string()
+        } else if integer.starts_with('i') {
+            format!("(((raw << {unused}) as i128) >> {unused}) as {integer}")
+        } else {
+            format!("raw as {integer}")
+        };
+        let value = if is_enum {
+            format!("{value_type}({value})")
+        } else {
+            value
+        };
+        let input = if is_enum { "value.0" } else { "value" };
+        let setter = format!("set_{}", method.trim_start_matches("r#"));
+        format!(
+            "    pub fn {method}(&self) -> {value_type} {{\n        \
+                 let mut bytes = [0u8; 16];\n        \
+                 bytes[..{length}].copy_from_slice(&self.{storage});\n        \
+                 let raw = (u128::from_{order}_bytes(bytes){shift_right}) & {mask};\n        \
+                 {value}\n    \
+             }}\n\n    \
+             pub fn {setter}(&mut self, value: {value_type}) {{\n        \
+                 let mut bytes = [0u8; 16];\n        \
+                 bytes[..{length}].copy_from_slice(&self.{storage});\n        \
+                 let mut raw = u128::from_{order}_bytes(bytes);\n        \
+                 raw &= !({mask}{shift_left});\n        \
+                 raw |= ({input} as u128 & {mask}){shift_left};\n        \
+                 self.{storage}.copy_from_slice(&raw.to_{order}_bytes()[..{length}]);\n    \
+             }}\n"
+        )
+    }
+
+    /// Field name for `member`, unique within its aggregate; anonymous members
+    /// are numbered by position
+    fn field_name(&self, member: &MemberInfo, index: usize, names: &mut HashSet<String>) -> String {
+        let mut name = if is_anonymous_member(member) {
+            format!("__anon{index}")
+        } else {
+            rust_identifier(&member.name)
+        };
+        if !names.insert(name.clone()) {
+            name = format!("{}_{}", name.trim_start_matches("r#"), member.offset);
+            names.insert(name.clone());
+        }
+        name
+    }
+
+    fn assert_size(&mut self, name: &str, type_info: &TypeInfo) {
+        if let Some(size) = type_info.size {
+            self.asserts
+                .push(format!("core::mem::size_of::<{name}>() == {size}"));
+        }
+    }
+
+    fn assert_alignment(&mut self, name: &str, alignment: u64) {
+        self.asserts
+            .push(format!("core::mem::align_of::<{name}>() == {alignment}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_identifiers() {
+        assert_eq!(rust_identifier("type"), "r#type");
+        assert_eq!(rust_identifier("self"), "self_");
+        assert_eq!(rust_identifier("ns::Type"), "ns__Type");
+        assert_eq!(rust_identifier("value"), "value");
+    }
+
+    #[test]
+    fn test_base_types() {
+        let base = |name: &str, size: u64| TypeInfo {
+            name: name.to_string(),
+            size: Some(size),
+            kind: "basic".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(rust_base_type(&base("unsigned int", 4), true), "u32");
+        assert_eq!(rust_base_type(&base("long int", 8), true), "i64");
+        assert_eq!(rust_base_type(&base("char", 1), true), "i8");
+        assert_eq!(rust_base_type(&base("char", 1), false), "u8");
+        assert_eq!(rust_base_type(&base("_Bool", 1), true), "bool");
+        assert_eq!(rust_base_type(&base("double", 8), true), "f64");
+        assert_eq!(rust_base_type(&base("long double", 16), true), "[u8; 16]");
+        assert_eq!(rust_base_type(&base("char", 4), true), "u32");
+        assert_eq!(rust_base_type(&base("usize", 8), true), "usize");
+    }
+}
@@ -0,0 +1,475 @@
+//! Binary size attribution in the spirit of `bloaty`: file and VM bytes broken
+//! down by section, segment, symbol, compilation unit and Rust crate
+//!
+//! Every breakdown covers the whole file and the whole loaded image, so the
+//! entries of each list always add up to `file_size` and `vm_size`. Bytes are
+//! attributed to the first entry claiming them; whatever nothing claims
+//! (alignment gaps, stripped code, debug sections outside any segment, ...)
+//! lands in [`UNATTRIBUTED`].
+
+use crate::{demangle_symbol, load_dwarf_sections};
+use anyhow::{Context, Result};
+use goblin::elf::program_header::{PF_R, PF_W, PF_X, PT_LOAD};
+use goblin::elf::section_header::SHT_NOBITS;
+use goblin::elf::sym::{STT_FILE, STT_SECTION};
+use goblin::elf::Elf;
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/// Bucket for bytes no entry of a breakdown accounts for
+pub const UNATTRIBUTED: &str = "[unattributed]";
+/// Crate bucket for symbols that are not Rust-mangled (C, C++, assembly)
+pub const NON_RUST: &str = "[non-rust]";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SizeReport {
+    /// Length of the file
+    pub file_size: u64,
+    /// Bytes mapped by `PT_LOAD` segments (0 for relocatable objects)
+    pub vm_size: u64,
+    pub sections: Vec<SizeEntry>,
+    pub segments: Vec<SizeEntry>,
+    pub symbols: Vec<SizeEntry>,
+    /// Code attributed through `DW_AT_ranges`/`DW_AT_low_pc` of each unit
+    pub compile_units: Vec<SizeEntry>,
+    /// Rust symbols grouped by the first component of their demangled path
+    pub crates: Vec<SizeEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SizeEntry {
+    pub name: String,
+    pub file_size: u64,
+    pub vm_size: u64,
+}
+
+impl SizeReport {
+    /// Keep the `count` largest entries of each breakdown and fold the rest
+    /// into a single `[N others]` entry, so totals still add up
+    pub fn truncate(&mut self, count: usize) {
+        for entries in [
+            &mut self.sections,
+            &mut self.segments,
+            &mut self.symbols,
+            &mut self.compile_units,
+            &mut self.crates,
+        ] {
+            if entries.len() > count {
+                let rest: Vec<SizeEntry> = entries.drain(count..).collect();
+                entries.push(SizeEntry {
+                    name: format!("[{} others]", rest.len()),
+                    file_size: rest.iter().map(|entry| entry.file_size).sum(),
+                    vm_size: rest.iter().map(|entry| entry.vm_size).sum(),
+                });
+            }
+        }
+    }
+}
+
+/// Attribute the sizes of the ELF file at `file_path`
+pub fn analyze_sizes(file_path: &str) -> Result<SizeReport> {
+    let buffer =
+        fs::read(file_path).with_context(|| format!("Failed to read ELF file: {file_path}"))?;
+    analyze_sizes_from_bytes(&buffer)
+}
+
+/// Attribute the sizes of an ELF file in memory (WebAssembly-compatible)
+pub fn analyze_sizes_from_bytes(buffer: &[u8]) -> Result<SizeReport> {
+    let elf = match Object::parse(buffer)? {
+        Object::Elf(elf) => elf,
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    };
+
+    let file_domain = vec![(0, buffer.len() as u64)];
+    let vm_domain = merge_ranges(
+        elf.program_headers
+            .iter()
+            .filter(|segment| segment.p_type == PT_LOAD)
+            .map(|segment| {
+                (
+                    segment.p_vaddr,
+                    segment.p_vaddr.saturating_add(segment.p_memsz),
+                )
+            })
+            .collect(),
+    );
+    let new_breakdown = || Breakdown::new(&file_domain, &vm_domain);
+
+    let mut sections = new_breakdown();
+    let header = &elf.header;
+    let program_headers = u64::from(header.e_phentsize) * u64::from(header.e_phnum);
+    let section_headers = u64::from(header.e_shentsize) * u64::from(header.e_shnum);
+    for (name, offset, size) in [
+        ("[ELF header]", 0, u64::from(header.e_ehsize)),
+        ("[program headers]", header.e_phoff, program_headers),
+        ("[section headers]", header.e_shoff, section_headers),
+    ] {
+        sections.claim_file(name, offset, size);
+        // The headers are usually mapped at the start of the first segment
+        for (address, size) in vm_ranges(&elf, offset, size) {
+            sections.claim_vm(name, address, size);
+        }
+    }
+    for section in elf
+        .section_headers
+        .iter()
+        .filter(|section| section.sh_size > 0)
+    {
+        let name = elf.shdr_strtab.get_at(section.sh_name).unwrap_or("unnamed");
+        if section.sh_type != SHT_NOBITS {
+            sections.claim_file(name, section.sh_offset, section.sh_size);
+        }
+        if section.is_alloc() {
+            sections.claim_vm(name, section.sh_addr, section.sh_size);
+        }
+    }
+
+    let mut segments = new_breakdown();
+    for (index, segment) in elf.program_headers.iter().enumerate() {
+        if segment.p_type != PT_LOAD {
+            continue;
+        }
+        let flags: String = [(PF_R, 'R'), (PF_W, 'W'), (PF_X, 'X')]
+            .iter()
+            .map(|&(flag, c)| if segment.p_flags & flag != 0 { c } else { '-' })
+            .collect();
+        let name = format!("LOAD #{index} [{flags}]");
+        segments.claim_file(&name, segment.p_offset, segment.p_filesz);
+        segments.claim_vm(&name, segment.p_vaddr, segment.p_memsz);
+    }
+
+    let mut symbols = new_breakdown();
+    let mut crates = new_breakdown();
+    let (symtab, strtab) = if elf.syms.is_empty() {
+        (&elf.dynsyms, &elf.dynstrtab)
+    } else {
+        (&elf.syms, &elf.strtab)
+    };
+    for symbol in symtab.iter() {
+        if symbol.st_size == 0
+            || symbol.st_shndx == 0
+            || symbol.st_shndx >= elf.section_headers.len()
+            || matches!(symbol.st_type(), STT_SECTION | STT_FILE)
+        {
+            continue;
+        }
+        let Some(raw_name) = strtab.get_at(symbol.st_name) else {
+            continue;
+        };
+        let name = demangle_symbol(raw_name).unwrap_or_else(|| raw_name.to_string());
+        let crate_name = rust_crate_name(raw_name, &name);
+
+        let section = &elf.section_headers[symbol.st_shndx];
+        if section.sh_type != SHT_NOBITS {
+            // Relocatable objects store section-relative symbol values
+            let relative = if elf.header.e_type == goblin::elf::header::ET_REL {
+                symbol.st_value
+            } else {
+                symbol.st_value.wrapping_sub(section.sh_addr)
+            };
+            if relative < section.sh_size {
+                let offset = section.sh_offset.saturating_add(relative);
+                let size = symbol.st_size.min(section.sh_size - relative);
+                symbols.claim_file(&name, offset, size);
+                crates.claim_file(&crate_name, offset, size);
+            }
+        }
+        symbols.claim_vm(&name, symbol.st_value, symbol.st_size);
+        crates.claim_vm(&crate_name, symbol.st_value, symbol.st_size);
+    }
+
+    let mut compile_units = new_breakdown();
+    // Units without usable ranges simply leave their bytes unattributed
+    let _ = attribute_compile_units(buffer, &elf, &mut compile_units);
+
+    Ok(SizeReport {
+        file_size: total(&file_domain),
+        vm_size: total(&vm_domain),
+        sections: sections.finish(),
+        segments: segments.finish(),
+        symbols: symbols.finish(),
+        compile_units: compile_units.finish(),
+        crates: crates.finish(),
+    })
+}
+
+fn attribute_compile_units(buffer: &[u8], elf: &Elf, breakdown: &mut Breakdown) -> Result<()> {
+    let object_file = object::File::parse(buffer)?;
+    let dwarf_sections = load_dwarf_sections(&object_file)?;
+    let dwarf =
+        dwarf_sections.borrow(|section| gimli::EndianSlice::new(section, gimli::LittleEndian));
+
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let name = unit
+            .name
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "<unknown>".to_string());
+        let mut ranges = dwarf.unit_ranges(&unit)?;
+        while let Some(range) = ranges.next()? {
+            let size = range.end.saturating_sub(range.begin);
+            if range.begin == 0 || size == 0 {
+                // Discarded by the linker (e.g. --gc-sections)
+                continue;
+            }
+            breakdown.claim_vm(&name, range.begin, size);
+            for (offset, size) in file_ranges(elf, range.begin, size) {
+                breakdown.claim_file(&name, offset, size);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// File ranges backing the loaded addresses `[address, address + size)`
+fn file_ranges(elf: &Elf, address: u64, size: u64) -> Vec<(u64, u64)> {
+    let end = address.saturating_add(size);
+    elf.program_headers
+        .iter()
+        .filter(|segment| segment.p_type == PT_LOAD)
+        .filter_map(|segment| {
+            let start = address.max(segment.p_vaddr);
+            let stop = end.min(segment.p_vaddr.saturating_add(segment.p_filesz));
+            (start < stop).then(|| {
+                let offset = segment.p_offset.saturating_add(start - segment.p_vaddr);
+                (offset, stop - start)
+            })
+        })
+        .collect()
+}
+
+/// Loaded addresses of the file bytes `[offset, offset + size)`
+fn vm_ranges(elf: &Elf, offset: u64, size: u64) -> Vec<(u64, u64)> {
+    let end = offset.saturating_add(size);
+    elf.program_headers
+        .iter()
+        .filter(|segment| segment.p_type == PT_LOAD)
+        .filter_map(|segment| {
+            let start = offset.max(segment.p_offset);
+            let stop = end.min(segment.p_offset.saturating_add(segment.p_filesz));
+            (start < stop).then(|| {
+                let address = segment.p_vaddr.saturating_add(start - segment.p_offset);
+                (address, stop - start)
+            })
+        })
+        .collect()
+}
+
+/// First path component of a Rust symbol (`core`, `std`, `alloc`, the binary's
+/// own crate, ...) or [`NON_RUST`]. C++ and plain C names also demangle with
+/// the legacy scheme, so only names carrying a hash or using v0 mangling count
+fn rust_crate_name(raw_name: &str, demangled: &str) -> String {
+    let is_rust = raw_name.starts_with("_R")
+        || rustc_demangle::try_demangle(raw_name)
+            .is_ok_and(|symbol| format!("{symbol}") != format!("{symbol:#}"));
+    if !is_rust {
+        return NON_RUST.to_string();
+    }
+    // `<alloc::vec::Vec<T> as core::ops::Drop>::drop` belongs to `alloc`
+    demangled
+        .trim_start_matches('<')
+        .split([':', '<', ' '])
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(demangled)
+        .to_string()
+}
+
+fn merge_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+fn total(domain: &[(u64, u64)]) -> u64 {
+    domain.iter().map(|(start, end)| end - start).sum()
+}
+
+/// Disjoint ranges of an address space already attributed to some entry
+struct Coverage {
+    domain: Vec<(u64, u64)>,
+    claimed: BTreeMap<u64, u64>,
+}
+
+impl Coverage {
+    fn new(domain: &[(u64, u64)]) -> Self {
+        Coverage {
+            domain: domain.to_vec(),
+            claimed: BTreeMap::new(),
+        }
+    }
+
+    /// Claim the unclaimed bytes of `[start, start + size)` inside the domain
+    /// and return how many there were
+    fn claim(&mut self, start: u64, size: u64) -> u64 {
+        let end = start.saturating_add(size);
+        let mut gained = 0;
+        for index in 0..self.domain.len() {
+            let (domain_start, domain_end) = self.domain[index];
+            let (start, end) = (start.max(domain_start), end.min(domain_end));
+            if start >= end {
+                continue;
+            }
+            // Claimed ranges overlapping [start, end), in address order
+            let overlapping: Vec<(u64, u64)> = self
+                .claimed
+                .range(..start)
+                .next_back()
+                .filter(|(_, &claimed_end)| claimed_end > start)
+                .into_iter()
+                .chain(self.claimed.range(start..end))
+                .map(|(&claimed_start, &claimed_end)| (claimed_start, claimed_end))
+                .collect();
+            let mut cursor = start;
+            let mut gaps = Vec::new();
+            for (claimed_start, claimed_end) in overlapping {
+                if claimed_start > cursor {
+                    gaps.push((cursor, claimed_start));
+                }
+                cursor = cursor.max(claimed_end);
+            }
+            if cursor < end {
+                gaps.push((cursor, end));
+            }
+            for (gap_start, gap_end) in gaps {
+                self.claimed.insert(gap_start, gap_end);
+                gained += gap_end - gap_start;
+            }
+        }
+        gained
+    }
+}
+
+/// One breakdown: entry sizes plus the coverage of both address spaces
+struct Breakdown {
+    file: Coverage,
+    vm: Coverage,
+    file_total: u64,
+    vm_total: u64,
+    entries: HashMap<String, SizeEntry>,
+}
+
+impl Breakdown {
+    fn new(file_domain: &[(u64, u64)], vm_domain: &[(u64, u64)]) -> Self {
+        Breakdown {
+            file: Coverage::new(file_domain),
+            vm: Coverage::new(vm_domain),
+            file_total: total(file_domain),
+            vm_total: total(vm_domain),
+            entries: HashMap::new(),
+        }
+    }
+
+    fn entry(&mut self, name: &str) -> &mut SizeEntry {
+        self.entries
+            .entry(name.to_string())
+            .or_insert_with(|| SizeEntry {
+                name: name.to_string(),
+                ..Default::default()
+            })
+    }
+
+    fn claim_file(&mut self, name: &str, offset: u64, size: u64) {
+        let gained = self.file.claim(offset, size);
+        self.entry(name).file_size += gained;
+    }
+
+    fn claim_vm(&mut self, name: &str, address: u64, size: u64) {
+        let gained = self.vm.claim(address, size);
+        self.entry(name).vm_size += gained;
+    }
+
+    /// Entries largest first, with the remainder of both totals as [`UNATTRIBUTED`]
+    fn finish(mut self) -> Vec<SizeEntry> {
+        let file_size = self.file_total - self.entries.values().map(|e| e.file_size).sum::<u64>();
+        let vm_size = self.vm_total - self.entries.values().map(|e| e.vm_size).sum::<u64>();
+        let unattributed = self.entry(UNATTRIBUTED);
+        unattributed.file_size += file_size;
+        unattributed.vm_size += vm_size;
+
+        let mut entries: Vec<SizeEntry> = self
+            .entries
+            .into_values()
+            .filter(|entry| entry.file_size > 0 || entry.vm_size > 0)
+            .collect();
+        entries.sort_by(|a, b| {
+            b.vm_size
+                .max(b.file_size)
+                .cmp(&a.vm_size.max(a.file_size))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coverage_claims_each_byte_once() {
+        let mut coverage = Coverage::new(&[(0x100, 0x200), (0x300, 0x400)]);
+        assert_eq!(coverage.claim(0x100, 0x10), 0x10);
+        // Overlaps the first claim on both sides
+        assert_eq!(coverage.claim(0xf0, 0x40), 0x20);
+        assert_eq!(coverage.claim(0x108, 0x8), 0);
+        // Spans the hole between the two domain ranges
+        assert_eq!(coverage.claim(0x1f0, 0x120), 0x10 + 0x10);
+        assert_eq!(coverage.claim(0x500, 0x10), 0);
+    }
+
+    #[test]
+    fn test_breakdown_totals_add_up() {
+        let mut breakdown = Breakdown::new(&[(0, 100)], &[(0x1000, 0x1040)]);
+        breakdown.claim_file("a", 0, 30);
+        breakdown.claim_file("b", 20, 30);
+        breakdown.claim_vm("a", 0x1000, 0x20);
+        let entries = breakdown.finish();
+        assert_eq!(
+            entries,
+            vec![
+                SizeEntry {
+                    name: UNATTRIBUTED.to_string(),
+                    file_size: 50,
+                    vm_size: 0x20,
+                },
+                SizeEntry {
+                    name: "a".to_string(),
+                    file_size: 30,
+                    vm_size: 0x20,
+                },
+                SizeEntry {
+                    name: "b".to_string(),
+                    file_size: 20,
+                    vm_size: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rust_crate_names() {
+        let mangled = "_ZN4core3fmt5write17h0123456789abcdefE";
+        let demangled = demangle_symbol(mangled).unwrap();
+        assert_eq!(rust_crate_name(mangled, &demangled), "core");
+        assert_eq!(
+            rust_crate_name(
+                "_ZN5alloc3vec1a17h0123456789abcdefE",
+                "<alloc::vec::Vec<T> as core::ops::drop::Drop>::drop"
+            ),
+            "alloc"
+        );
+        // C++ names demangle with the legacy scheme but carry no hash
+        assert_eq!(rust_crate_name("_ZN3foo3barE", "foo::bar"), NON_RUST);
+        assert_eq!(rust_crate_name("main", "main"), NON_RUST);
+    }
+}
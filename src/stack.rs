@@ -0,0 +1,578 @@
+//! Worst-case stack usage: the frame size of each function, from the
+//! `.stack_sizes` section of `-fstack-size-section` or `-Z emit-stack-sizes`,
+//! the `.su` files of GCC `-fstack-usage`, or the largest CFA offset of its
+//! CFI, summed along the deepest path of the static call graph
+//!
+//! The bound is only as good as the graph: recursion, indirect calls,
+//! functions of other objects and frames of unknown size leave the worst
+//! case of every root reaching them incomplete, and are listed with it.
+
+use crate::callgraph::call_graph_from_bytes;
+use crate::symbolize::Symbolizer;
+use crate::unwind::Unwinder;
+use anyhow::{Context, Result};
+use object::{Architecture, Object as ObjectTrait, ObjectSection};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StackReport {
+    pub functions: Vec<FunctionStack>,
+    /// Worst case from each root, deepest first
+    pub roots: Vec<StackRoot>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FunctionStack {
+    pub name: String,
+    /// None for functions of other objects
+    pub address: Option<u64>,
+    /// Bytes the function adds to the stack as its source counts them; CFI
+    /// and GCC include the return address an x86 call pushes
+    pub frame_size: Option<u64>,
+    pub source: String, // "stack_sizes", "stack_usage", "cfi", "none"
+    /// The frame may be larger: it allocates a variable amount (`alloca`,
+    /// variable-length arrays), or its CFA moved to the frame pointer
+    /// before the stack grew further
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dynamic: bool,
+    /// Part of a cycle of calls
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub recursive: bool,
+    pub indirect_calls: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StackRoot {
+    pub name: String,
+    /// Deepest stack from the root's own frame down, in bytes
+    pub worst_case: u64,
+    /// The worst case holds whatever the program does: nothing reachable is unknown
+    pub bounded: bool,
+    /// The call chain reaching the worst case, starting at the root
+    pub path: Vec<String>,
+    pub unknowns: Vec<StackUnknown>,
+}
+
+/// A reachable function whose stack the worst case cannot account for
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct StackUnknown {
+    pub function: String,
+    pub reason: String, // "recursion", "indirect_call", "external", "unknown_frame", "dynamic_frame"
+}
+
+/// Frame size and whether it may be exceeded at run time
+type Frame = (u64, bool);
+
+/// Compute the stack usage of the ELF file at `path` from each of `roots`,
+/// or from every function nothing calls when empty, reading the frame
+/// sizes of the `stack_usage` files written by GCC `-fstack-usage`
+pub fn stack_usage_files(
+    path: &str,
+    roots: &[String],
+    stack_usage: &[String],
+) -> Result<StackReport> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    let stack_usage = stack_usage
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read stack usage file: {path}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    stack_usage_from_bytes(&buffer, roots, &stack_usage)
+}
+
+/// Compute the stack usage from an ELF byte buffer and the contents of
+/// `.su` files (WebAssembly-compatible)
+pub fn stack_usage_from_bytes(
+    buffer: &[u8],
+    roots: &[String],
+    stack_usage: &[String],
+) -> Result<StackReport> {
+    let graph = call_graph_from_bytes(buffer)?;
+    let object_file = object::File::parse(buffer)?;
+    let stack_sizes = stack_sizes(&object_file);
+    let cfi = cfi_frame_sizes(buffer);
+    // Static functions of several files may share a name
+    let mut usage: HashMap<&str, Vec<(&str, Frame)>> = HashMap::new();
+    for (file, name, frame) in stack_usage.iter().flat_map(|text| parse_stack_usage(text)) {
+        usage.entry(name).or_default().push((file, frame));
+    }
+    let symbolizer = Symbolizer::new(buffer).ok();
+    // Function symbols of Thumb code have the low bit set, unlike the
+    // addresses of the call graph
+    let thumb = object_file.architecture() == Architecture::Arm;
+
+    let index: HashMap<&str, usize> = graph
+        .functions
+        .iter()
+        .enumerate()
+        .map(|(index, function)| (function.name.as_str(), index))
+        .collect();
+    let mut callees = vec![Vec::new(); graph.functions.len()];
+    let mut called = vec![false; graph.functions.len()];
+    for edge in graph.edges.iter().filter(|edge| edge.kind != "reference") {
+        let (Some(&caller), Some(&callee)) = (
+            index.get(edge.caller.as_str()),
+            index.get(edge.callee.as_str()),
+        ) else {
+            continue;
+        };
+        callees[caller].push((callee, edge.kind == "tail_call"));
+        called[callee] = true;
+    }
+    let mut indirect_calls = vec![0; graph.functions.len()];
+    for call in &graph.indirect_calls {
+        if let Some(&caller) = index.get(call.caller.as_str()) {
+            indirect_calls[caller] += 1;
+        }
+    }
+    let recursive = recursive_functions(&callees);
+
+    let mut report = StackReport::default();
+    for (i, function) in graph.functions.iter().enumerate() {
+        let by_address = |sizes: &HashMap<u64, Frame>| {
+            let address = function.address?;
+            sizes
+                .get(&address)
+                .or_else(|| sizes.get(&(address | 1)).filter(|_| thumb))
+                .copied()
+        };
+        let base = function
+            .name
+            .rsplit_once("@0x")
+            .map_or(function.name.as_str(), |(base, _)| base);
+        let by_name = |address: u64| {
+            let entries = usage.get(base)?;
+            let file = symbolizer
+                .as_ref()
+                .and_then(|symbolizer| symbolizer.location(address))
+                .map(|location| location.file);
+            stack_usage_frame(entries, file.as_deref())
+        };
+        let (frame, source) = match function.address {
+            None => (None, "none"),
+            Some(address) => {
+                if let Some(frame) = by_address(&stack_sizes) {
+                    (Some(frame), "stack_sizes")
+                } else if let Some(frame) = by_name(address) {
+                    (Some(frame), "stack_usage")
+                } else if let Some(frame) = by_address(&cfi) {
+                    (Some(frame), "cfi")
+                } else {
+                    (None, "none")
+                }
+            }
+        };
+        report.functions.push(FunctionStack {
+            name: function.name.clone(),
+            address: function.address,
+            frame_size: frame.map(|(size, _)| size),
+            source: source.to_string(),
+            dynamic: frame.is_some_and(|(_, dynamic)| dynamic),
+            recursive: recursive[i],
+            indirect_calls: indirect_calls[i],
+        });
+    }
+
+    let root_indices: Vec<usize> = if roots.is_empty() {
+        (0..graph.functions.len())
+            .filter(|&i| !called[i] && graph.functions[i].address.is_some())
+            .collect()
+    } else {
+        let mut indices = Vec::new();
+        for name in roots {
+            let found = graph.resolve(name);
+            if found.is_empty() {
+                anyhow::bail!("No function named {name}");
+            }
+            indices.extend(found.iter().map(|name| index[name]));
+        }
+        indices
+    };
+
+    let mut walk = Walk {
+        callees: &callees,
+        frames: report
+            .functions
+            .iter()
+            .map(|function| function.frame_size.unwrap_or(0))
+            .collect(),
+        on_path: vec![false; graph.functions.len()],
+        total: vec![None; graph.functions.len()],
+        next: vec![None; graph.functions.len()],
+    };
+    for root in root_indices {
+        let worst_case = walk.depth(root);
+        let mut path = vec![graph.functions[root].name.clone()];
+        let mut current = root;
+        while let Some(next) = walk.next[current] {
+            path.push(graph.functions[next].name.clone());
+            current = next;
+        }
+        let unknowns = unknowns(&callees, &report.functions, root);
+        report.roots.push(StackRoot {
+            name: graph.functions[root].name.clone(),
+            worst_case,
+            bounded: unknowns.is_empty(),
+            path,
+            unknowns,
+        });
+    }
+    report
+        .roots
+        .sort_by(|a, b| b.worst_case.cmp(&a.worst_case).then(a.name.cmp(&b.name)));
+    Ok(report)
+}
+
+/// Memoized deepest stack below each function, with the callee it goes through
+struct Walk<'a> {
+    callees: &'a [Vec<(usize, bool)>],
+    frames: Vec<u64>,
+    on_path: Vec<bool>,
+    total: Vec<Option<u64>>,
+    next: Vec<Option<usize>>,
+}
+
+impl Walk<'_> {
+    fn depth(&mut self, function: usize) -> u64 {
+        if let Some(total) = self.total[function] {
+            return total;
+        }
+        self.on_path[function] = true;
+        let frame = self.frames[function];
+        let mut total = frame;
+        let mut next = None;
+        for &(callee, tail) in &self.callees[function] {
+            // Each function of a cycle is counted once
+            if self.on_path[callee] {
+                continue;
+            }
+            let below = self.depth(callee);
+            // A tail call releases the caller's frame first
+            let through = if tail {
+                below
+            } else {
+                frame.saturating_add(below)
+            };
+            if through > total {
+                total = through;
+                next = Some(callee);
+            }
+        }
+        self.on_path[function] = false;
+        self.total[function] = Some(total);
+        self.next[function] = next;
+        total
+    }
+}
+
+/// Functions reachable from `root` along calls whose stack is not accounted for
+fn unknowns(
+    callees: &[Vec<(usize, bool)>],
+    functions: &[FunctionStack],
+    root: usize,
+) -> Vec<StackUnknown> {
+    let mut reached = vec![false; functions.len()];
+    reached[root] = true;
+    let mut queue = vec![root];
+    while let Some(function) = queue.pop() {
+        for &(callee, _) in &callees[function] {
+            if !reached[callee] {
+                reached[callee] = true;
+                queue.push(callee);
+            }
+        }
+    }
+    let mut unknowns = BTreeSet::new();
+    for (function, _) in functions
+        .iter()
+        .zip(reached)
+        .filter(|(_, reached)| *reached)
+    {
+        let reasons = [
+            (function.recursive, "recursion"),
+            (function.indirect_calls > 0, "indirect_call"),
+            (function.address.is_none(), "external"),
+            (
+                function.address.is_some() && function.frame_size.is_none(),
+                "unknown_frame",
+            ),
+            (function.dynamic, "dynamic_frame"),
+        ];
+        for (_, reason) in reasons.iter().filter(|(applies, _)| *applies) {
+            unknowns.insert((function.name.clone(), *reason));
+        }
+    }
+    unknowns
+        .into_iter()
+        .map(|(function, reason)| StackUnknown {
+            function,
+            reason: reason.to_string(),
+        })
+        .collect()
+}
+
+/// Functions on a cycle of calls, from the strongly connected components
+/// of the graph (Tarjan)
+fn recursive_functions(callees: &[Vec<(usize, bool)>]) -> Vec<bool> {
+    struct Tarjan<'a> {
+        callees: &'a [Vec<(usize, bool)>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        counter: usize,
+        recursive: Vec<bool>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.counter);
+            self.low[v] = self.counter;
+            self.counter += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+            for &(w, _) in &self.callees[v] {
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    }
+                    Some(index) if self.on_stack[w] => self.low[v] = self.low[v].min(index),
+                    Some(_) => {}
+                }
+            }
+            if Some(self.low[v]) == self.index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = self.stack.pop() {
+                    self.on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                let cycle =
+                    component.len() > 1 || self.callees[v].iter().any(|&(callee, _)| callee == v);
+                for w in component {
+                    self.recursive[w] = cycle;
+                }
+            }
+        }
+    }
+
+    let count = callees.len();
+    let mut tarjan = Tarjan {
+        callees,
+        index: vec![None; count],
+        low: vec![0; count],
+        stack: Vec::new(),
+        on_stack: vec![false; count],
+        counter: 0,
+        recursive: vec![false; count],
+    };
+    for v in 0..count {
+        if tarjan.index[v].is_none() {
+            tarjan.visit(v);
+        }
+    }
+    tarjan.recursive
+}
+
+/// Frame sizes of the `.stack_sizes` sections by function address: each
+/// entry is the address followed by the size as ULEB128
+fn stack_sizes(object_file: &object::File) -> HashMap<u64, Frame> {
+    let address_size = if object_file.is_64() { 8 } else { 4 };
+    let little_endian = object_file.is_little_endian();
+    let mut sizes = HashMap::new();
+    for section in object_file
+        .sections()
+        .filter(|section| section.name() == Ok(".stack_sizes"))
+    {
+        let data = section.data().unwrap_or_default();
+        sizes.extend(parse_stack_sizes(data, address_size, little_endian));
+    }
+    sizes
+}
+
+fn parse_stack_sizes(data: &[u8], address_size: usize, little_endian: bool) -> Vec<(u64, Frame)> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset + address_size < data.len() {
+        let mut bytes = [0u8; 8];
+        let field = &data[offset..offset + address_size];
+        let address = if little_endian {
+            bytes[..address_size].copy_from_slice(field);
+            u64::from_le_bytes(bytes)
+        } else {
+            bytes[8 - address_size..].copy_from_slice(field);
+            u64::from_be_bytes(bytes)
+        };
+        offset += address_size;
+        let mut size = 0u64;
+        let mut shift = 0;
+        while let Some(&byte) = data.get(offset) {
+            offset += 1;
+            if shift < 64 {
+                size |= u64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        entries.push((address, (size, false)));
+    }
+    entries
+}
+
+/// Frame sizes of GCC `-fstack-usage` lines such as
+/// `main.c:12:5:main\t32\tstatic`, by source file and function name; a
+/// qualifier of `dynamic` without `bounded` marks a frame that may grow
+fn parse_stack_usage(text: &str) -> Vec<(&str, &str, Frame)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let location = fields.next()?;
+            let size = fields.next()?.trim().parse().ok()?;
+            let qualifier = fields.next().unwrap_or("static").trim();
+            // `file:line:column:name`, where a C++ name is a signature with colons
+            let mut parts = location.splitn(4, ':');
+            let file = parts.next()?;
+            let name = parts.nth(2)?;
+            let name = match name.split_once('(') {
+                Some((head, _)) => head.rsplit(' ').next().unwrap_or(head),
+                None => name,
+            };
+            let dynamic = qualifier.contains("dynamic") && !qualifier.contains("bounded");
+            Some((file, name, (size, dynamic)))
+        })
+        .collect()
+}
+
+/// Frame of a function from the `.su` entries of its name, as (file,
+/// frame): differing entries are told apart by the source file DWARF gives
+/// the function, or leave the frame unknown
+fn stack_usage_frame(entries: &[(&str, Frame)], file: Option<&str>) -> Option<Frame> {
+    let (_, first) = *entries.first()?;
+    if entries.iter().all(|&(_, frame)| frame == first) {
+        return Some(first);
+    }
+    let file = Path::new(file?);
+    let mut matching = entries
+        .iter()
+        .filter(|(su_file, _)| file.ends_with(su_file.trim_start_matches("./")))
+        .map(|&(_, frame)| frame);
+    let frame = matching.next()?;
+    matching.all(|other| other == frame).then_some(frame)
+}
+
+/// Largest stack-pointer offset of the CFA over each FDE, by start address;
+/// dynamic when the CFA is ever based on another register
+fn cfi_frame_sizes(buffer: &[u8]) -> HashMap<u64, Frame> {
+    let mut sizes = HashMap::new();
+    let Ok(unwinder) = Unwinder::new(buffer) else {
+        return sizes;
+    };
+    let Some(stack_pointer) = unwinder.stack_pointer() else {
+        return sizes;
+    };
+    for table in unwinder.tables() {
+        if table.rows.is_empty() {
+            continue;
+        }
+        let mut size = 0i64;
+        let mut dynamic = false;
+        for row in &table.rows {
+            match row
+                .cfa
+                .strip_prefix(stack_pointer.as_str())
+                .and_then(|offset| offset.parse::<i64>().ok())
+            {
+                Some(offset) => size = size.max(offset),
+                None => dynamic = true,
+            }
+        }
+        sizes
+            .entry(table.start)
+            .or_insert((size.max(0) as u64, dynamic));
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stack_sizes_and_usage() {
+        let data = [
+            0x00, 0x10, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, // 0x401000: 16
+            0x40, 0x10, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x90, 0x03, // 0x401040: 400
+        ];
+        let entries = parse_stack_sizes(&data, 8, true);
+        assert_eq!(
+            entries,
+            vec![(0x401000, (16, false)), (0x401040, (400, false))]
+        );
+        let entries = parse_stack_sizes(&[0x00, 0x00, 0x80, 0x00, 0x08], 4, false);
+        assert_eq!(entries, vec![(0x8000, (8, false))]);
+
+        let usage = "t.c:3:31:big\t152\tstatic\n\
+                     t.c:9:5:grow\t48\tdynamic\n\
+                     t.c:12:5:fixed\t64\tdynamic,bounded\n\
+                     a.cc:4:5:int ns::f(int)\t32\tstatic\n";
+        let usage = parse_stack_usage(usage);
+        assert_eq!(
+            usage,
+            vec![
+                ("t.c", "big", (152, false)),
+                ("t.c", "grow", (48, true)),
+                ("t.c", "fixed", (64, false)),
+                ("a.cc", "ns::f", (32, false)),
+            ]
+        );
+
+        // Static functions of the same name in two files
+        let entries = [("src/a.c", (16, false)), ("src/b.c", (96, false))];
+        assert_eq!(
+            stack_usage_frame(&entries, Some("/build/src/b.c")),
+            Some((96, false))
+        );
+        assert_eq!(stack_usage_frame(&entries, Some("/build/c.c")), None);
+        assert_eq!(stack_usage_frame(&entries, None), None);
+        assert_eq!(stack_usage_frame(&entries[..1], None), Some((16, false)));
+    }
+
+    #[test]
+    fn test_walk_through_calls_tail_calls_and_cycles() {
+        // 0 calls 1 and tail-calls 2; 1 and 3 call each other
+        let callees = vec![
+            vec![(1, false), (2, true)],
+            vec![(3, false)],
+            vec![],
+            vec![(1, false)],
+        ];
+        let recursive = recursive_functions(&callees);
+        assert_eq!(recursive, vec![false, true, false, true]);
+
+        let mut walk = Walk {
+            callees: &callees,
+            frames: vec![16, 32, 500, 8],
+            on_path: vec![false; 4],
+            total: vec![None; 4],
+            next: vec![None; 4],
+        };
+        // The tail call does not stack on the caller's frame
+        assert_eq!(walk.depth(0), 500);
+        assert_eq!(walk.next[0], Some(2));
+        assert_eq!(walk.depth(1), 40);
+        walk.frames[2] = 10;
+        walk.total = vec![None; 4];
+        assert_eq!(walk.depth(0), 56);
+        assert_eq!(walk.next[0], Some(1));
+    }
+}
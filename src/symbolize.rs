@@ -0,0 +1,305 @@
+//! Address to source mapping from DWARF: the function containing a code
+//! address with the chain of functions inlined into it, and the source
+//! line of each
+
+use crate::{
+    collect_file_names, demangle_symbol, get_die_linkage_name, get_die_name, load_dwarf_sections,
+    SourceLocation,
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+type Reader<'a> = gimli::EndianSlice<'a, gimli::LittleEndian>;
+
+/// One source-level frame at a code address
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SourceFrame {
+    /// Demangled linkage name where there is one, otherwise the DWARF name
+    pub function: String,
+    /// The line being executed in the innermost frame, the call site in the others
+    pub location: Option<SourceLocation>,
+    /// Inlined into the next frame rather than called
+    pub inlined: bool,
+}
+
+pub struct Symbolizer<'a> {
+    sections: gimli::DwarfSections<Cow<'a, [u8]>>,
+    /// Link-time address ranges of each compilation unit
+    units: Vec<(u64, u64, gimli::DebugInfoOffset)>,
+}
+
+impl<'a> Symbolizer<'a> {
+    /// Index the compilation units of `buffer`; an ELF file without DWARF
+    /// yields a symbolizer that knows no addresses
+    pub fn new(buffer: &'a [u8]) -> Result<Self> {
+        let object_file = object::File::parse(buffer)?;
+        let sections = load_dwarf_sections(&object_file)?;
+        let mut symbolizer = Symbolizer {
+            sections,
+            units: Vec::new(),
+        };
+        symbolizer.units = symbolizer.unit_ranges().unwrap_or_default();
+        Ok(symbolizer)
+    }
+
+    fn dwarf(&self) -> gimli::Dwarf<Reader<'_>> {
+        self.sections
+            .borrow(|section| gimli::EndianSlice::new(section, gimli::LittleEndian))
+    }
+
+    fn unit_ranges(&self) -> Result<Vec<(u64, u64, gimli::DebugInfoOffset)>> {
+        let dwarf = self.dwarf();
+        let mut units = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            let Some(offset) = header.offset().as_debug_info_offset() else {
+                continue;
+            };
+            let unit = dwarf.unit(header)?;
+            let mut ranges = dwarf.unit_ranges(&unit)?;
+            while let Some(range) = ranges.next()? {
+                // Discarded functions are left at address 0 by the linker
+                if range.begin < range.end && range.begin != 0 {
+                    units.push((range.begin, range.end, offset));
+                }
+            }
+        }
+        units.sort_by_key(|&(begin, _, _)| begin);
+        Ok(units)
+    }
+
+    /// Frames at the link-time `address`, innermost first: the functions
+    /// inlined at that point, then the function they were inlined into.
+    /// Empty when no compilation unit covers the address
+    pub fn frames(&self, address: u64) -> Vec<SourceFrame> {
+        self.try_frames(address).unwrap_or_default()
+    }
+
+    /// Source line of the instruction at the link-time `address`
+    pub fn location(&self, address: u64) -> Option<SourceLocation> {
+        let dwarf = self.dwarf();
+        for offset in self.units_containing(address) {
+            let Ok(unit) = self.unit(&dwarf, offset) else {
+                continue;
+            };
+            let files = collect_file_names(&dwarf, &unit).unwrap_or_default();
+            if let Ok(Some(location)) = line_location(&unit, &files, address) {
+                return Some(location);
+            }
+        }
+        None
+    }
+
+    /// Line table rows covering `start..end`, sorted by address; the first
+    /// is the row in effect at `start`
+    pub fn line_rows(&self, start: u64, end: u64) -> Vec<(u64, SourceLocation)> {
+        let dwarf = self.dwarf();
+        let mut rows = Vec::new();
+        for offset in self.units_containing(start) {
+            let Ok(unit) = self.unit(&dwarf, offset) else {
+                continue;
+            };
+            let files = collect_file_names(&dwarf, &unit).unwrap_or_default();
+            if let Ok(found) = line_rows(&unit, &files, start, end) {
+                rows.extend(found);
+            }
+            if !rows.is_empty() {
+                break;
+            }
+        }
+        rows.sort_by_key(|&(address, _)| address);
+        rows
+    }
+
+    fn units_containing(&self, address: u64) -> impl Iterator<Item = gimli::DebugInfoOffset> + '_ {
+        let end = self
+            .units
+            .partition_point(|&(begin, _, _)| begin <= address);
+        self.units[..end]
+            .iter()
+            .filter(move |&&(_, end, _)| address < end)
+            .map(|&(_, _, offset)| offset)
+    }
+
+    fn unit<'d>(
+        &self,
+        dwarf: &gimli::Dwarf<Reader<'d>>,
+        offset: gimli::DebugInfoOffset,
+    ) -> Result<gimli::Unit<Reader<'d>>> {
+        let header = dwarf.debug_info.header_from_offset(offset)?;
+        Ok(dwarf.unit(header)?)
+    }
+
+    fn try_frames(&self, address: u64) -> Result<Vec<SourceFrame>> {
+        let dwarf = self.dwarf();
+        for offset in self.units_containing(address) {
+            let unit = self.unit(&dwarf, offset)?;
+            let mut chain = Vec::new();
+            let mut tree = unit.entries_tree(None)?;
+            find_scopes(&dwarf, &unit, tree.root()?, address, &mut chain)?;
+            if chain.is_empty() {
+                continue;
+            }
+
+            let files = collect_file_names(&dwarf, &unit)?;
+            let mut frames = Vec::new();
+            let mut location = line_location(&unit, &files, address)?;
+            for (depth, &scope) in chain.iter().enumerate().rev() {
+                let entry = unit.entry(scope)?;
+                let name = match get_die_linkage_name(&dwarf, &unit, &entry)? {
+                    Some(linkage_name) => demangle_symbol(&linkage_name),
+                    None => None,
+                };
+                let name = match name {
+                    Some(name) => Some(name),
+                    None => get_die_name(&dwarf, &unit, &entry)?,
+                };
+                frames.push(SourceFrame {
+                    function: name.unwrap_or_else(|| "<unknown>".to_string()),
+                    location,
+                    inlined: depth > 0,
+                });
+                // The caller is executing the call site of the inlined instance
+                location = call_location(&entry, &files)?;
+            }
+            return Ok(frames);
+        }
+        Ok(Vec::new())
+    }
+}
+
+/// Append the subprogram containing `address` below `node`, then the inlined
+/// instances nested in it, outermost first
+fn find_scopes(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    node: gimli::EntriesTreeNode<Reader>,
+    address: u64,
+    chain: &mut Vec<gimli::UnitOffset>,
+) -> Result<bool> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        let is_function = matches!(
+            entry.tag(),
+            gimli::DW_TAG_subprogram | gimli::DW_TAG_inlined_subroutine
+        );
+        // Scopes that can hold functions or inlined instances without being one
+        let is_scope = matches!(
+            entry.tag(),
+            gimli::DW_TAG_lexical_block
+                | gimli::DW_TAG_namespace
+                | gimli::DW_TAG_module
+                | gimli::DW_TAG_class_type
+                | gimli::DW_TAG_structure_type
+                | gimli::DW_TAG_union_type
+        );
+        if is_function && contains(dwarf, unit, entry, address)? == Some(true) {
+            chain.push(entry.offset());
+            find_scopes(dwarf, unit, child, address, chain)?;
+            return Ok(true);
+        }
+        if is_scope
+            && contains(dwarf, unit, entry, address)? != Some(false)
+            && find_scopes(dwarf, unit, child, address, chain)?
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether the code ranges of `entry` contain `address`; None when it has none
+fn contains(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    entry: &gimli::DebuggingInformationEntry<Reader>,
+    address: u64,
+) -> Result<Option<bool>> {
+    let mut ranges = dwarf.die_ranges(unit, entry)?;
+    let mut any = false;
+    while let Some(range) = ranges.next()? {
+        if range.begin <= address && address < range.end {
+            return Ok(Some(true));
+        }
+        any = true;
+    }
+    Ok(any.then_some(false))
+}
+
+fn call_location(
+    entry: &gimli::DebuggingInformationEntry<Reader>,
+    files: &HashMap<u64, String>,
+) -> Result<Option<SourceLocation>> {
+    let Some(file) = entry
+        .attr_value(gimli::DW_AT_call_file)?
+        .and_then(|file| file_index(&file))
+    else {
+        return Ok(None);
+    };
+    let number = |attr| -> Result<Option<u64>> {
+        Ok(entry
+            .attr_value(attr)?
+            .and_then(|value| value.udata_value()))
+    };
+    Ok(Some(SourceLocation {
+        file: files.get(&file).cloned().unwrap_or_default(),
+        line: number(gimli::DW_AT_call_line)?.filter(|&line| line != 0),
+        column: number(gimli::DW_AT_call_column)?.filter(|&column| column != 0),
+    }))
+}
+
+fn file_index(value: &gimli::AttributeValue<Reader>) -> Option<u64> {
+    match *value {
+        gimli::AttributeValue::FileIndex(index) => Some(index),
+        _ => value.udata_value(),
+    }
+}
+
+/// Line table row covering `address`
+fn line_location(
+    unit: &gimli::Unit<Reader>,
+    files: &HashMap<u64, String>,
+    address: u64,
+) -> Result<Option<SourceLocation>> {
+    Ok(line_rows(unit, files, address, address + 1)?
+        .into_iter()
+        .next()
+        .map(|(_, location)| location))
+}
+
+/// Line table rows whose address range overlaps `start..end`
+fn line_rows(
+    unit: &gimli::Unit<Reader>,
+    files: &HashMap<u64, String>,
+    start: u64,
+    end: u64,
+) -> Result<Vec<(u64, SourceLocation)>> {
+    let Some(program) = unit.line_program.clone() else {
+        return Ok(Vec::new());
+    };
+    let mut rows = program.rows();
+    let mut found = Vec::new();
+    let mut previous: Option<(u64, SourceLocation)> = None;
+    while let Some((_, row)) = rows.next_row()? {
+        if let Some((address, location)) = previous.take() {
+            if address < row.address() && address < end && start < row.address() {
+                found.push((address.max(start), location));
+            }
+        }
+        if !row.end_sequence() {
+            let location = SourceLocation {
+                file: files.get(&row.file_index()).cloned().unwrap_or_default(),
+                line: row.line().map(|line| line.get()),
+                column: match row.column() {
+                    gimli::ColumnType::LeftEdge => None,
+                    gimli::ColumnType::Column(column) => Some(column.get()),
+                },
+            };
+            previous = Some((row.address(), location));
+        }
+    }
+    Ok(found)
+}
@@ -0,0 +1,756 @@
+//! Stack unwinding from call frame information: the FDEs of `.eh_frame`
+//! (looked up through `.eh_frame_hdr`) and `.debug_frame` as per-function
+//! unwind tables, and an unwinder producing symbolised backtraces with
+//! inlined frames from a register set and the memory of a process
+
+use crate::core_dump::RegisterValue;
+use crate::symbolize::Symbolizer;
+use crate::values::Image;
+use crate::SourceLocation;
+use anyhow::{Context, Result};
+use gimli::UnwindSection;
+use goblin::elf::header::{EM_386, EM_AARCH64, EM_ARM, EM_RISCV, EM_X86_64};
+use object::{Object as ObjectTrait, ObjectSection};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+
+/// Unwinding stops after this many physical frames
+pub const MAX_FRAMES: usize = 256;
+
+type Reader<'a> = gimli::EndianSlice<'a, gimli::LittleEndian>;
+type Row = gimli::UnwindTableRow<usize>;
+
+/// Unwind table of one FDE
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FunctionUnwind {
+    pub start: u64,
+    pub end: u64,
+    pub section: String, // ".eh_frame", ".debug_frame"
+    /// The CIE marks the function as a signal trampoline (augmentation `S`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub signal_frame: bool,
+    pub rows: Vec<UnwindRow>,
+}
+
+/// Rules for recovering the caller's registers over a range of instructions
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UnwindRow {
+    pub start: u64,
+    pub end: u64,
+    /// Canonical frame address, e.g. `rsp+8`
+    pub cfa: String,
+    pub registers: Vec<RegisterRule>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RegisterRule {
+    pub register: String,
+    /// `[cfa-16]` (saved at), `cfa-16` (value), a register name, `undefined`,
+    /// `same`, `[expr]`, `expr` or `arch`
+    pub rule: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Backtrace {
+    /// Innermost first; a physical frame with inlined calls yields one frame per function
+    pub frames: Vec<Frame>,
+    /// Why unwinding stopped, e.g. `outermost frame` or `no unwind information for 0x401000`
+    pub end: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Frame {
+    /// Number of the physical frame; inlined frames share it with their caller
+    pub index: usize,
+    pub pc: u64,
+    /// Canonical frame address, when the caller's frame was recovered
+    pub cfa: Option<u64>,
+    pub function: Option<String>,
+    pub location: Option<SourceLocation>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub inlined: bool,
+    /// Mapped file containing the pc, for frames outside the executable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    /// How the registers of this frame were found
+    pub method: String, // "registers", "cfi", "frame_pointer"
+}
+
+/// Register conventions of an architecture, indexed by DWARF register number
+struct Arch {
+    names: &'static [&'static str],
+    /// Name of the program counter in register sets
+    pc: &'static str,
+    sp: u16,
+    /// Frame pointer, and where a frame record stores the caller's frame
+    /// pointer and return address relative to it, and where the caller's
+    /// stack pointer is
+    fp: u16,
+    frame_record: [i64; 3],
+    /// Link register, which also answers to `lr`
+    link: Option<u16>,
+    /// Registers also answer to `x0` to `x31`
+    numbered: bool,
+}
+
+const X86_64: Arch = Arch {
+    names: &[
+        "rax", "rdx", "rcx", "rbx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12",
+        "r13", "r14", "r15", "rip",
+    ],
+    pc: "rip",
+    sp: 7,
+    fp: 6,
+    frame_record: [0, 8, 16],
+    link: None,
+    numbered: false,
+};
+
+const AARCH64: Arch = Arch {
+    names: &[
+        "x0",
+        "x1",
+        "x2",
+        "x3",
+        "x4",
+        "x5",
+        "x6",
+        "x7",
+        "x8",
+        "x9",
+        "x10",
+        "x11",
+        "x12",
+        "x13",
+        "x14",
+        "x15",
+        "x16",
+        "x17",
+        "x18",
+        "x19",
+        "x20",
+        "x21",
+        "x22",
+        "x23",
+        "x24",
+        "x25",
+        "x26",
+        "x27",
+        "x28",
+        "x29",
+        "x30",
+        "sp",
+        "pc",
+        "elr_mode",
+        "ra_sign_state",
+    ],
+    pc: "pc",
+    sp: 31,
+    fp: 29,
+    frame_record: [0, 8, 16],
+    link: Some(30),
+    numbered: false,
+};
+
+const RISCV: Arch = Arch {
+    names: &[
+        "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+        "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+        "t5", "t6",
+    ],
+    pc: "pc",
+    sp: 2,
+    fp: 8,
+    frame_record: [-16, -8, 0],
+    link: Some(1),
+    numbered: true,
+};
+
+impl Arch {
+    fn of(machine: u16) -> Option<&'static Arch> {
+        match machine {
+            EM_X86_64 => Some(&X86_64),
+            EM_AARCH64 => Some(&AARCH64),
+            EM_RISCV => Some(&RISCV),
+            _ => None,
+        }
+    }
+
+    fn number(&self, name: &str) -> Option<u16> {
+        if let Some(index) = self.names.iter().position(|&n| n == name) {
+            return Some(index as u16);
+        }
+        match name {
+            "fp" => Some(self.fp),
+            "lr" => self.link,
+            _ if self.numbered => name
+                .strip_prefix('x')
+                .and_then(|n| n.parse().ok())
+                .filter(|&n: &u16| n < 32),
+            _ => None,
+        }
+    }
+}
+
+fn register_name(arch: Option<&Arch>, register: gimli::Register) -> String {
+    arch.and_then(|arch| arch.names.get(usize::from(register.0)))
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("r{}", register.0))
+}
+
+/// Registers of one frame, by DWARF register number
+#[derive(Clone)]
+struct State {
+    pc: u64,
+    registers: HashMap<u16, u64>,
+}
+
+enum Step {
+    Caller(State, bool),
+    Outermost,
+    NoInfo,
+    Failed(String),
+}
+
+pub struct Unwinder<'a> {
+    arch: Option<&'static Arch>,
+    machine: u16,
+    address_size: u8,
+    eh_frame: &'a [u8],
+    eh_frame_hdr: &'a [u8],
+    debug_frame: Cow<'a, [u8]>,
+    bases: gimli::BaseAddresses,
+    symbolizer: Symbolizer<'a>,
+}
+
+impl<'a> Unwinder<'a> {
+    pub fn new(executable: &'a [u8]) -> Result<Self> {
+        let elf = match goblin::Object::parse(executable)? {
+            goblin::Object::Elf(elf) => elf,
+            _ => anyhow::bail!("File is not a valid ELF binary"),
+        };
+        let object_file = object::File::parse(executable)?;
+        let section = |name: &str| {
+            object_file
+                .section_by_name(name)
+                .map(|section| (section.address(), section.data().unwrap_or_default()))
+                .unwrap_or_default()
+        };
+        let (eh_frame_address, eh_frame) = section(".eh_frame");
+        let (eh_frame_hdr_address, eh_frame_hdr) = section(".eh_frame_hdr");
+        let debug_frame = object_file
+            .section_by_name(".debug_frame")
+            .and_then(|section| section.uncompressed_data().ok())
+            .unwrap_or_default();
+        let bases = gimli::BaseAddresses::default()
+            .set_eh_frame(eh_frame_address)
+            .set_eh_frame_hdr(eh_frame_hdr_address)
+            .set_text(section(".text").0)
+            .set_got(section(".got").0);
+
+        Ok(Unwinder {
+            arch: Arch::of(elf.header.e_machine),
+            machine: elf.header.e_machine,
+            address_size: if elf.is_64 { 8 } else { 4 },
+            eh_frame,
+            eh_frame_hdr,
+            debug_frame,
+            bases,
+            symbolizer: Symbolizer::new(executable)?,
+        })
+    }
+
+    fn eh_frame(&self) -> gimli::EhFrame<Reader<'a>> {
+        let mut section = gimli::EhFrame::new(self.eh_frame, gimli::LittleEndian);
+        section.set_address_size(self.address_size);
+        if self.machine == EM_AARCH64 {
+            section.set_vendor(gimli::Vendor::AArch64);
+        }
+        section
+    }
+
+    fn debug_frame(&self) -> gimli::DebugFrame<Reader<'_>> {
+        let mut section = gimli::DebugFrame::new(&self.debug_frame, gimli::LittleEndian);
+        section.set_address_size(self.address_size);
+        if self.machine == EM_AARCH64 {
+            section.set_vendor(gimli::Vendor::AArch64);
+        }
+        section
+    }
+
+    /// Every FDE with its rows, sorted by address
+    pub fn tables(&self) -> Vec<FunctionUnwind> {
+        let mut tables = Vec::new();
+        self.collect_tables(&self.eh_frame(), ".eh_frame", &mut tables);
+        self.collect_tables(&self.debug_frame(), ".debug_frame", &mut tables);
+        tables.sort_by_key(|table| (table.start, table.end));
+        tables
+    }
+
+    /// Name of the stack pointer in CFA rules, e.g. `rsp` in `rsp+8`
+    pub fn stack_pointer(&self) -> Option<String> {
+        let register = match (self.arch, self.machine) {
+            (Some(arch), _) => arch.sp,
+            (None, EM_386) => 4,
+            (None, EM_ARM) => 13,
+            _ => return None,
+        };
+        Some(register_name(self.arch, gimli::Register(register)))
+    }
+
+    fn collect_tables<'s, S: UnwindSection<Reader<'s>>>(
+        &self,
+        section: &S,
+        name: &str,
+        tables: &mut Vec<FunctionUnwind>,
+    ) {
+        let mut ctx = gimli::UnwindContext::new();
+        let mut entries = section.entries(&self.bases);
+        while let Ok(Some(entry)) = entries.next() {
+            let gimli::CieOrFde::Fde(partial) = entry else {
+                continue;
+            };
+            let Ok(fde) = partial.parse(S::cie_from_offset) else {
+                continue;
+            };
+            // Zero-length FDEs are left behind by discarded functions
+            if fde.len() == 0 {
+                continue;
+            }
+            // A corrupt FDE may claim a range past the end of the address space
+            let Some(end) = fde.initial_address().checked_add(fde.len()) else {
+                continue;
+            };
+            let mut rows = Vec::new();
+            if let Ok(mut table) = fde.rows(section, &self.bases, &mut ctx) {
+                while let Ok(Some(row)) = table.next_row() {
+                    rows.push(UnwindRow {
+                        start: row.start_address(),
+                        end: row.end_address(),
+                        cfa: self.describe_cfa(row.cfa()),
+                        registers: row
+                            .registers()
+                            .map(|(register, rule)| RegisterRule {
+                                register: register_name(self.arch, *register),
+                                rule: self.describe_rule(rule),
+                            })
+                            .collect(),
+                    });
+                }
+            }
+            tables.push(FunctionUnwind {
+                start: fde.initial_address(),
+                end,
+                section: name.to_string(),
+                signal_frame: fde.cie().is_signal_trampoline(),
+                rows,
+            });
+        }
+    }
+
+    fn describe_cfa(&self, cfa: &gimli::CfaRule<usize>) -> String {
+        match cfa {
+            gimli::CfaRule::RegisterAndOffset { register, offset } => {
+                format!("{}{offset:+}", register_name(self.arch, *register))
+            }
+            gimli::CfaRule::Expression(_) => "expr".to_string(),
+        }
+    }
+
+    fn describe_rule(&self, rule: &gimli::RegisterRule<usize>) -> String {
+        match rule {
+            gimli::RegisterRule::Undefined => "undefined".to_string(),
+            gimli::RegisterRule::SameValue => "same".to_string(),
+            gimli::RegisterRule::Offset(offset) => format!("[cfa{offset:+}]"),
+            gimli::RegisterRule::ValOffset(offset) => format!("cfa{offset:+}"),
+            gimli::RegisterRule::Register(register) => register_name(self.arch, *register),
+            gimli::RegisterRule::Expression(_) => "[expr]".to_string(),
+            gimli::RegisterRule::ValExpression(_) => "expr".to_string(),
+            gimli::RegisterRule::Constant(value) => format!("{value:#x}"),
+            _ => "arch".to_string(),
+        }
+    }
+
+    /// Unwind the stack of a thread from its registers, reading saved
+    /// registers and return addresses from `image`
+    pub fn backtrace(&self, image: &Image, registers: &[RegisterValue]) -> Result<Backtrace> {
+        let arch = self
+            .arch
+            .context("Unwinding is only supported on x86_64, aarch64 and riscv")?;
+        let mut state = State {
+            pc: 0,
+            registers: HashMap::new(),
+        };
+        for register in registers {
+            if register.name == arch.pc || register.name == "pc" {
+                state.pc = register.value;
+            } else if let Some(number) = arch.number(&register.name) {
+                state.registers.insert(number, register.value);
+            }
+        }
+        if state.pc == 0 || !state.registers.contains_key(&arch.sp) {
+            anyhow::bail!("The program counter and stack pointer are required to unwind");
+        }
+
+        let mut backtrace = Backtrace::default();
+        let mut method = "registers";
+        let mut signal = false;
+        for index in 0..MAX_FRAMES {
+            // A return address points after the call, which may already be
+            // the next function or line
+            let lookup = if index == 0 || signal {
+                state.pc
+            } else {
+                state.pc - 1
+            };
+            let step = match self.step(arch, image, &state, lookup) {
+                Step::NoInfo => match self.frame_pointer_step(arch, image, &state) {
+                    Some(caller) => Step::Caller(caller, false),
+                    None => Step::Failed(format!("no unwind information for {:#x}", state.pc)),
+                },
+                step => step,
+            };
+            let caller = match step {
+                Step::Caller(caller, _) if caller.pc == 0 => None,
+                Step::Caller(caller, trampoline) => Some((caller, trampoline)),
+                Step::Outermost => None,
+                Step::Failed(reason) => {
+                    backtrace.end = reason;
+                    None
+                }
+                Step::NoInfo => unreachable!(),
+            };
+            let cfa = caller
+                .as_ref()
+                .and_then(|(caller, _)| caller.registers.get(&arch.sp).copied());
+            self.push_frames(
+                &mut backtrace.frames,
+                image,
+                index,
+                &state,
+                lookup,
+                cfa,
+                method,
+            );
+
+            let Some((caller, trampoline)) = caller else {
+                if backtrace.end.is_empty() {
+                    backtrace.end = "outermost frame".to_string();
+                }
+                return Ok(backtrace);
+            };
+            // The stack grows down, so callers' frames are at higher addresses
+            if caller.registers[&arch.sp] <= state.registers[&arch.sp] && !trampoline {
+                backtrace.end = "stack pointer did not increase".to_string();
+                return Ok(backtrace);
+            }
+            method = if self.has_cfi(image, lookup) {
+                "cfi"
+            } else {
+                "frame_pointer"
+            };
+            signal = trampoline;
+            state = caller;
+        }
+        backtrace.end = "frame limit reached".to_string();
+        Ok(backtrace)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push_frames(
+        &self,
+        frames: &mut Vec<Frame>,
+        image: &Image,
+        index: usize,
+        state: &State,
+        lookup: u64,
+        cfa: Option<u64>,
+        method: &str,
+    ) {
+        let frame = Frame {
+            index,
+            pc: state.pc,
+            cfa,
+            method: method.to_string(),
+            ..Default::default()
+        };
+        let source_frames = self.symbolizer.frames(lookup.wrapping_sub(image.bias()));
+        if source_frames.is_empty() {
+            frames.push(Frame {
+                function: image.describe(lookup),
+                ..frame
+            });
+            return;
+        }
+        for source in source_frames {
+            frames.push(Frame {
+                function: Some(source.function),
+                location: source.location,
+                inlined: source.inlined,
+                ..frame.clone()
+            });
+        }
+    }
+
+    fn has_cfi(&self, image: &Image, lookup: u64) -> bool {
+        let address = lookup.wrapping_sub(image.bias());
+        self.eh_frame_fde(&self.eh_frame(), address).is_ok()
+            || self
+                .debug_frame()
+                .fde_for_address(&self.bases, address, gimli::DebugFrame::cie_from_offset)
+                .is_ok()
+    }
+
+    fn eh_frame_fde(
+        &self,
+        eh_frame: &gimli::EhFrame<Reader<'a>>,
+        address: u64,
+    ) -> gimli::Result<gimli::FrameDescriptionEntry<Reader<'a>>> {
+        let header = gimli::EhFrameHdr::new(self.eh_frame_hdr, gimli::LittleEndian)
+            .parse(&self.bases, self.address_size);
+        match header.as_ref().ok().and_then(|header| header.table()) {
+            Some(table) => table.fde_for_address(
+                eh_frame,
+                &self.bases,
+                address,
+                gimli::EhFrame::cie_from_offset,
+            ),
+            None => eh_frame.fde_for_address(&self.bases, address, gimli::EhFrame::cie_from_offset),
+        }
+    }
+
+    /// Recover the caller's registers from the CFI row covering `lookup`
+    fn step(&self, arch: &Arch, image: &Image, state: &State, lookup: u64) -> Step {
+        let address = lookup.wrapping_sub(image.bias());
+        let mut ctx = gimli::UnwindContext::new();
+        let eh_frame = self.eh_frame();
+        if let Ok(fde) = self.eh_frame_fde(&eh_frame, address) {
+            return match fde.unwind_info_for_address(&eh_frame, &self.bases, &mut ctx, address) {
+                Ok(row) => self.apply(arch, image, state, &eh_frame, fde.cie(), row),
+                Err(e) => Step::Failed(format!("invalid CFI for {:#x}: {e}", state.pc)),
+            };
+        }
+        let debug_frame = self.debug_frame();
+        if let Ok(fde) =
+            debug_frame.fde_for_address(&self.bases, address, gimli::DebugFrame::cie_from_offset)
+        {
+            return match fde.unwind_info_for_address(&debug_frame, &self.bases, &mut ctx, address) {
+                Ok(row) => self.apply(arch, image, state, &debug_frame, fde.cie(), row),
+                Err(e) => Step::Failed(format!("invalid CFI for {:#x}: {e}", state.pc)),
+            };
+        }
+        Step::NoInfo
+    }
+
+    fn apply<'s, S: UnwindSection<Reader<'s>>>(
+        &self,
+        arch: &Arch,
+        image: &Image,
+        state: &State,
+        section: &S,
+        cie: &gimli::CommonInformationEntry<Reader<'s>>,
+        row: &Row,
+    ) -> Step {
+        let word = u64::from(self.address_size);
+        let evaluate = |expression: &gimli::UnwindExpression<usize>, cfa: Option<u64>| {
+            let expression = expression.get(section).ok()?;
+            evaluate(expression, cie.encoding(), state, image, cfa)
+        };
+        let cfa = match row.cfa() {
+            gimli::CfaRule::RegisterAndOffset { register, offset } => state
+                .registers
+                .get(&register.0)
+                .map(|base| base.wrapping_add(*offset as u64)),
+            gimli::CfaRule::Expression(expression) => evaluate(expression, None),
+        };
+        let Some(cfa) = cfa else {
+            return Step::Failed(format!("CFA of the frame at {:#x} is unknown", state.pc));
+        };
+
+        // Registers without a rule are assumed to be preserved by the callee
+        let mut caller = state.clone();
+        for (register, rule) in row.registers() {
+            let value = match rule {
+                gimli::RegisterRule::SameValue => continue,
+                gimli::RegisterRule::Offset(offset) => {
+                    image.read_uint(cfa.wrapping_add(*offset as u64), word)
+                }
+                gimli::RegisterRule::ValOffset(offset) => Some(cfa.wrapping_add(*offset as u64)),
+                gimli::RegisterRule::Register(other) => state.registers.get(&other.0).copied(),
+                gimli::RegisterRule::Expression(expression) => evaluate(expression, Some(cfa))
+                    .and_then(|address| image.read_uint(address, word)),
+                gimli::RegisterRule::ValExpression(expression) => evaluate(expression, Some(cfa)),
+                gimli::RegisterRule::Constant(value) => Some(*value),
+                _ => None,
+            };
+            match value {
+                Some(value) => caller.registers.insert(register.0, value),
+                None => caller.registers.remove(&register.0),
+            };
+        }
+
+        let return_address = cie.return_address_register();
+        if matches!(row.register(return_address), gimli::RegisterRule::Undefined) {
+            return Step::Outermost;
+        }
+        let Some(pc) = caller.registers.get(&return_address.0).copied() else {
+            return Step::Failed(format!(
+                "return address of the frame at {:#x} is unreadable",
+                state.pc
+            ));
+        };
+        caller.pc = strip_pointer_authentication(self.machine, pc);
+        caller.registers.insert(arch.sp, cfa);
+        Step::Caller(caller, cie.is_signal_trampoline())
+    }
+
+    /// Follow the frame pointer chain of code without CFI, as long as the
+    /// code belongs to the executable
+    fn frame_pointer_step(&self, arch: &Arch, image: &Image, state: &State) -> Option<State> {
+        image.describe(state.pc)?;
+        let fp = *state.registers.get(&arch.fp)?;
+        let word = u64::from(self.address_size);
+        let [saved_fp, return_address, cfa] = arch
+            .frame_record
+            .map(|offset| fp.wrapping_add(offset as u64));
+        let mut caller = state.clone();
+        caller
+            .registers
+            .insert(arch.fp, image.read_uint(saved_fp, word)?);
+        caller.registers.insert(arch.sp, cfa);
+        caller.pc =
+            strip_pointer_authentication(self.machine, image.read_uint(return_address, word)?);
+        Some(caller)
+    }
+}
+
+/// Drop the pointer authentication code from a signed aarch64 return address
+fn strip_pointer_authentication(machine: u16, address: u64) -> u64 {
+    if machine == EM_AARCH64 {
+        address & 0x0000_ffff_ffff_ffff
+    } else {
+        address
+    }
+}
+
+fn evaluate(
+    expression: gimli::Expression<Reader>,
+    encoding: gimli::Encoding,
+    state: &State,
+    image: &Image,
+    initial: Option<u64>,
+) -> Option<u64> {
+    let mut evaluation = expression.evaluation(encoding);
+    if let Some(initial) = initial {
+        evaluation.set_initial_value(initial);
+    }
+    let mut result = evaluation.evaluate().ok()?;
+    loop {
+        result = match result {
+            gimli::EvaluationResult::Complete => break,
+            gimli::EvaluationResult::RequiresMemory { address, size, .. } => {
+                let value = image.read_uint(address, u64::from(size))?;
+                evaluation
+                    .resume_with_memory(gimli::Value::Generic(value))
+                    .ok()?
+            }
+            gimli::EvaluationResult::RequiresRegister { register, .. } => {
+                let value = *state.registers.get(&register.0)?;
+                evaluation
+                    .resume_with_register(gimli::Value::Generic(value))
+                    .ok()?
+            }
+            gimli::EvaluationResult::RequiresRelocatedAddress(address) => evaluation
+                .resume_with_relocated_address(address.wrapping_add(image.bias()))
+                .ok()?,
+            _ => return None,
+        };
+    }
+    match evaluation.as_result().first()?.location {
+        gimli::Location::Address { address } => Some(address),
+        gimli::Location::Value { value } => value.to_u64(u64::MAX).ok(),
+        _ => None,
+    }
+}
+
+/// Per-function unwind tables of an ELF file
+pub fn unwind_tables(buffer: &[u8]) -> Result<Vec<FunctionUnwind>> {
+    Ok(Unwinder::new(buffer)?.tables())
+}
+
+/// Unwind a raw memory snapshot taken at `address`, usually the stack,
+/// of a process running `executable` loaded `load_bias` bytes above its
+/// link address
+pub fn backtrace_files(
+    executable_path: &str,
+    memory_path: &str,
+    address: u64,
+    load_bias: u64,
+    registers: &[RegisterValue],
+) -> Result<Backtrace> {
+    let executable = fs::read(executable_path)
+        .with_context(|| format!("Failed to read ELF file: {executable_path}"))?;
+    let memory =
+        fs::read(memory_path).with_context(|| format!("Failed to read memory: {memory_path}"))?;
+    backtrace_from_bytes(&executable, &memory, address, load_bias, registers)
+}
+
+/// Unwind a raw memory snapshot in memory (WebAssembly-compatible)
+pub fn backtrace_from_bytes(
+    executable: &[u8],
+    memory: &[u8],
+    address: u64,
+    load_bias: u64,
+    registers: &[RegisterValue],
+) -> Result<Backtrace> {
+    let image = Image::from_snapshot(memory, address, executable, load_bias)?;
+    Unwinder::new(executable)?.backtrace(&image, registers)
+}
+
+/// Parse a decimal or `0x`-prefixed hexadecimal number
+pub fn parse_number(text: &str) -> Result<u64> {
+    let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => text.parse(),
+    };
+    parsed.with_context(|| format!("Invalid number: {text}"))
+}
+
+/// Parse a `name=value` register assignment such as `rsp=0x7ffc1000`
+pub fn parse_register(text: &str) -> Result<RegisterValue> {
+    let (name, value) = text
+        .split_once('=')
+        .with_context(|| format!("Expected NAME=VALUE, got {text}"))?;
+    Ok(RegisterValue {
+        name: name.trim().to_string(),
+        value: parse_number(value.trim())?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_numbers() {
+        assert_eq!(X86_64.number("rsp"), Some(7));
+        assert_eq!(X86_64.number("x1"), None);
+        assert_eq!(AARCH64.number("lr"), Some(30));
+        assert_eq!(AARCH64.number("sp"), Some(31));
+        assert_eq!(RISCV.number("fp"), Some(8));
+        assert_eq!(RISCV.number("x1"), Some(1));
+        assert_eq!(register_name(Some(&RISCV), gimli::Register(10)), "a0");
+        assert_eq!(register_name(None, gimli::Register(3)), "r3");
+    }
+
+    #[test]
+    fn test_parse_register() {
+        let register = parse_register("rsp=0x7ffc1000").unwrap();
+        assert_eq!(register.name, "rsp");
+        assert_eq!(register.value, 0x7ffc_1000);
+        assert_eq!(parse_register("x0 = 42").unwrap().value, 42);
+        assert!(parse_register("rsp").is_err());
+    }
+}
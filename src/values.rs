@@ -0,0 +1,972 @@
+//! Initial values of global variables, read from the file bytes of their
+//! section and decoded according to their DWARF types. Pointers are resolved
+//! through dynamic relocations and the symbol table
+
+use crate::c_header::{is_anonymous_member, TypeIndex};
+use crate::{analyze_elf_from_bytes_with_dwarf, demangle_symbol, ElfInfo, MemberInfo, TypeInfo};
+use anyhow::{Context, Result};
+use goblin::elf::program_header::PT_LOAD;
+use goblin::elf::section_header::{SHF_ALLOC, SHF_TLS, SHT_NOBITS};
+use goblin::elf::sym::{STT_FILE, STT_FUNC, STT_GNU_IFUNC, STT_SECTION, STT_TLS};
+use goblin::Object;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// Array elements decoded before the rest is only counted
+pub const MAX_ELEMENTS: usize = 256;
+
+/// Longest string read through a `char *`
+const MAX_STRING: usize = 256;
+
+/// Largest read copied into a zero-filled buffer, such as a variable of
+/// `.bss`; a corrupt section or type size must not allocate gigabytes
+const MAX_ZERO_FILL: u64 = 16 << 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ValuesReport {
+    /// Globals and function-level statics, by address
+    pub variables: Vec<GlobalValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GlobalValue {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    pub address: u64,
+    pub size: u64,
+    pub section: String,
+    /// Lives in a section without file bytes (`.bss`), so it starts out zeroed
+    pub zero_initialized: bool,
+    pub value: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Value {
+    pub kind: String, // "integer", "float", "bool", "char", "enum", "pointer", "string", "array", "struct", "union", "variant", "bytes", "unavailable"
+    /// Rendering of scalars and strings: `42`, `RED`, `&table + 8`, `"text"`;
+    /// the active variant of a Rust enum
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    /// Address held by a pointer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<u64>,
+    /// Symbol a pointer points into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Members of structs and unions, or the payload of a Rust enum variant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub elements: Vec<Value>,
+    /// Array elements past `MAX_ELEMENTS` that were left out
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub omitted: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FieldValue {
+    pub name: String,
+    pub value: Value,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// Decode the initial values of the globals in the ELF file at `path`
+pub fn read_initial_values(path: &str) -> Result<ValuesReport> {
+    let buffer = fs::read(path).with_context(|| format!("Failed to read ELF file: {path}"))?;
+    read_initial_values_from_bytes(&buffer)
+}
+
+/// Decode the initial values of globals in memory (WebAssembly-compatible)
+pub fn read_initial_values_from_bytes(buffer: &[u8]) -> Result<ValuesReport> {
+    let elf_info = analyze_elf_from_bytes_with_dwarf(buffer)?;
+    let image = Image::from_elf(buffer)?;
+    Ok(ValuesReport {
+        variables: global_values(&elf_info, &image),
+    })
+}
+
+/// Decode every global with a static address from `image`; addresses are
+/// reported as mapped in `image`
+pub fn global_values(elf_info: &ElfInfo, image: &Image) -> Vec<GlobalValue> {
+    let decoder = Decoder::new(elf_info, image);
+    let mut seen = HashSet::new();
+    let mut variables = Vec::new();
+    for variable in &elf_info.variables {
+        let Some(address) = variable.address else {
+            continue;
+        };
+        let Some(section) = elf_info.section_containing(address) else {
+            continue;
+        };
+        if !seen.insert((address, variable.name.as_str())) {
+            continue;
+        }
+        let size = decoder.size_of(&variable.type_info);
+        let runtime = address.wrapping_add(image.bias);
+        let value = match image.read(runtime, size) {
+            Some(bytes) => decoder.decode(&variable.type_info, &bytes, runtime),
+            None => Value::unavailable(),
+        };
+        variables.push(GlobalValue {
+            name: variable.name.clone(),
+            qualified_name: variable.qualified_name.clone(),
+            address: runtime,
+            size,
+            section: section.name.clone(),
+            zero_initialized: section.kind == "nobits",
+            value,
+        });
+    }
+    variables.sort_by(|a, b| a.address.cmp(&b.address).then(a.name.cmp(&b.name)));
+    variables
+}
+
+/// Address range backed by bytes
+struct Region<'a> {
+    address: u64,
+    size: u64,
+    data: &'a [u8],
+    /// Bytes past the end of `data` read as zero (`.bss`); otherwise they are
+    /// missing, like segments a core dump left out
+    zero_fill: bool,
+}
+
+struct Symbol {
+    name: String,
+    address: u64,
+    size: u64,
+    function: bool,
+}
+
+/// Memory of a program: the bytes of the allocated sections of an ELF file,
+/// or of a core dump backed by its executable, with the symbols and the
+/// dynamic relocations the loader applies to pointers
+pub struct Image<'a> {
+    /// Searched in order
+    regions: Vec<Region<'a>>,
+    /// Load address of a position-independent executable
+    bias: u64,
+    little_endian: bool,
+    /// Sorted by address
+    symbols: Vec<Symbol>,
+    /// Symbol and addend stored at an address by the dynamic loader; the
+    /// symbol is None for relative relocations
+    relocations: HashMap<u64, (Option<String>, Option<i64>)>,
+}
+
+impl<'a> Image<'a> {
+    pub fn from_elf(buffer: &'a [u8]) -> Result<Self> {
+        let elf = parse_elf(buffer)?;
+        let mut relocations = HashMap::new();
+        for relocation in elf.dynrelas.iter().chain(elf.dynrels.iter()) {
+            let symbol = match relocation.r_sym {
+                0 => None,
+                index => elf
+                    .dynsyms
+                    .get(index)
+                    .and_then(|symbol| elf.dynstrtab.get_at(symbol.st_name))
+                    .map(|name| demangle_symbol(name).unwrap_or_else(|| name.to_string())),
+            };
+            relocations.insert(relocation.r_offset, (symbol, relocation.r_addend));
+        }
+        Ok(Image {
+            regions: section_regions(&elf, buffer, 0),
+            bias: 0,
+            little_endian: elf.little_endian,
+            symbols: symbols(&elf, 0),
+            relocations,
+        })
+    }
+
+    /// Memory of a crashed process: the segments of its core dump, then the
+    /// file bytes of `executable` loaded `bias` bytes above its link address
+    /// for whatever the kernel did not dump. Relocations are already applied
+    pub fn from_core(core: &'a [u8], executable: &'a [u8], bias: u64) -> Result<Self> {
+        let core_elf = parse_elf(core)?;
+        let elf = parse_elf(executable)?;
+        let mut regions: Vec<Region> = core_elf
+            .program_headers
+            .iter()
+            .filter(|segment| segment.p_type == PT_LOAD && segment.p_memsz > 0)
+            .map(|segment| {
+                let start = (segment.p_offset as usize).min(core.len());
+                let end =
+                    (segment.p_offset.saturating_add(segment.p_filesz) as usize).min(core.len());
+                Region {
+                    address: segment.p_vaddr,
+                    size: segment.p_memsz,
+                    data: &core[start..end],
+                    zero_fill: false,
+                }
+            })
+            .collect();
+        regions.extend(section_regions(&elf, executable, bias));
+        Ok(Image {
+            regions,
+            bias,
+            little_endian: core_elf.little_endian,
+            symbols: symbols(&elf, bias),
+            relocations: HashMap::new(),
+        })
+    }
+
+    /// Memory of a running process: a raw dump of `memory` taken at
+    /// `address`, usually the stack, over the file bytes of `executable`
+    /// loaded `bias` bytes above its link address
+    pub fn from_snapshot(
+        memory: &'a [u8],
+        address: u64,
+        executable: &'a [u8],
+        bias: u64,
+    ) -> Result<Self> {
+        let elf = parse_elf(executable)?;
+        let mut regions = vec![Region {
+            address,
+            size: memory.len() as u64,
+            data: memory,
+            zero_fill: false,
+        }];
+        regions.extend(section_regions(&elf, executable, bias));
+        Ok(Image {
+            regions,
+            bias,
+            little_endian: elf.little_endian,
+            symbols: symbols(&elf, bias),
+            relocations: HashMap::new(),
+        })
+    }
+
+    /// Load address of a position-independent executable
+    pub fn bias(&self) -> u64 {
+        self.bias
+    }
+
+    /// Unsigned integer of `size` bytes at `address` in the target byte order
+    pub fn read_uint(&self, address: u64, size: u64) -> Option<u64> {
+        self.read(address, size)
+            .map(|bytes| self.unsigned(&bytes) as u64)
+    }
+
+    /// `size` bytes at `address`, or None when the range is not mapped or
+    /// would zero-fill more than `MAX_ZERO_FILL` bytes
+    pub fn read(&self, address: u64, size: u64) -> Option<Cow<'a, [u8]>> {
+        let end = address.checked_add(size)?;
+        let region = self.regions.iter().find(|region| {
+            address >= region.address
+                && region
+                    .address
+                    .checked_add(region.size)
+                    .is_some_and(|region_end| end <= region_end)
+                && (region.zero_fill || end - region.address <= region.data.len() as u64)
+        })?;
+        let start = usize::try_from(address - region.address).ok()?;
+        let end = start.checked_add(usize::try_from(size).ok()?)?;
+        if end <= region.data.len() {
+            return Some(Cow::Borrowed(&region.data[start..end]));
+        }
+        if size > MAX_ZERO_FILL {
+            return None;
+        }
+        let mut bytes = vec![0; size as usize];
+        if start < region.data.len() {
+            bytes[..region.data.len() - start].copy_from_slice(&region.data[start..]);
+        }
+        Some(Cow::Owned(bytes))
+    }
+
+    /// `function + offset` for a code address
+    pub(crate) fn describe(&self, address: u64) -> Option<String> {
+        self.symbolize(address)
+            .map(|(symbol, offset)| match offset {
+                0 => symbol.name.clone(),
+                offset => format!("{} + {offset}", symbol.name),
+            })
+    }
+
+    /// Symbol containing `address`, with the offset into it
+    fn symbolize(&self, address: u64) -> Option<(&Symbol, u64)> {
+        let index = self.symbols.partition_point(|s| s.address <= address);
+        // Symbols rarely nest, so only the closest few can contain the address
+        self.symbols[..index]
+            .iter()
+            .rev()
+            .take(16)
+            .find(|s| address - s.address < s.size.max(1))
+            .map(|symbol| (symbol, address - symbol.address))
+    }
+
+    /// Word stored at `address` once the loader has applied relative
+    /// relocations; None when it is bound to a symbol of another object
+    fn relocated(&self, address: u64, stored: u64) -> Option<u64> {
+        match self.relocations.get(&address) {
+            Some((Some(_), _)) => None,
+            Some((None, Some(addend))) => Some(*addend as u64),
+            _ => Some(stored),
+        }
+    }
+
+    fn unsigned(&self, bytes: &[u8]) -> u128 {
+        let mut value = 0u128;
+        if self.little_endian {
+            for &byte in bytes.iter().take(16).rev() {
+                value = value << 8 | u128::from(byte);
+            }
+        } else {
+            for &byte in bytes.iter().take(16) {
+                value = value << 8 | u128::from(byte);
+            }
+        }
+        value
+    }
+}
+
+fn parse_elf(buffer: &[u8]) -> Result<goblin::elf::Elf<'_>> {
+    match Object::parse(buffer)? {
+        Object::Elf(elf) => Ok(elf),
+        _ => anyhow::bail!("File is not a valid ELF binary"),
+    }
+}
+
+/// Allocated sections of `elf`, loaded `bias` bytes above their link address
+fn section_regions<'a>(elf: &goblin::elf::Elf, buffer: &'a [u8], bias: u64) -> Vec<Region<'a>> {
+    let mut regions = Vec::new();
+    for section in elf.section_headers.iter() {
+        // `.tbss` overlaps the sections after it: it only describes the TLS template
+        let tls_bss = section.sh_type == SHT_NOBITS && section.sh_flags & u64::from(SHF_TLS) != 0;
+        if section.sh_flags & u64::from(SHF_ALLOC) == 0 || section.sh_size == 0 || tls_bss {
+            continue;
+        }
+        let data = if section.sh_type == SHT_NOBITS {
+            &[][..]
+        } else {
+            let start = (section.sh_offset as usize).min(buffer.len());
+            let end =
+                (section.sh_offset.saturating_add(section.sh_size) as usize).min(buffer.len());
+            &buffer[start..end]
+        };
+        regions.push(Region {
+            address: section.sh_addr.wrapping_add(bias),
+            size: section.sh_size,
+            data,
+            zero_fill: true,
+        });
+    }
+    regions
+}
+
+/// Function and object symbols of `elf`, sorted by address
+fn symbols(elf: &goblin::elf::Elf, bias: u64) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    for (symtab, strtab) in [(&elf.syms, &elf.strtab), (&elf.dynsyms, &elf.dynstrtab)] {
+        for symbol in symtab.iter() {
+            if symbol.st_shndx == 0 || matches!(symbol.st_type(), STT_SECTION | STT_FILE | STT_TLS)
+            {
+                continue;
+            }
+            // Arm mapping symbols (`$x`, `$d`) mark code and data, not objects
+            let Some(raw_name) = strtab
+                .get_at(symbol.st_name)
+                .filter(|n| !n.is_empty() && !n.starts_with('$'))
+            else {
+                continue;
+            };
+            symbols.push(Symbol {
+                name: demangle_symbol(raw_name).unwrap_or_else(|| raw_name.to_string()),
+                address: symbol.st_value.wrapping_add(bias),
+                size: symbol.st_size,
+                function: matches!(symbol.st_type(), STT_FUNC | STT_GNU_IFUNC),
+            });
+        }
+    }
+    // Lookups search backwards, so sized symbols win at the same address
+    symbols.sort_by(|a, b| a.address.cmp(&b.address).then(a.size.cmp(&b.size)));
+    symbols.dedup_by(|a, b| a.address == b.address && a.name == b.name);
+    symbols
+}
+
+impl Value {
+    fn scalar(kind: &str, text: String) -> Self {
+        Value {
+            kind: kind.to_string(),
+            text,
+            ..Default::default()
+        }
+    }
+
+    fn unavailable() -> Self {
+        Value::scalar("unavailable", String::new())
+    }
+}
+
+struct Decoder<'a> {
+    index: TypeIndex<'a>,
+    image: &'a Image<'a>,
+    char_signed: bool,
+    x87: bool,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(elf_info: &'a ElfInfo, image: &'a Image<'a>) -> Self {
+        let x86 = matches!(elf_info.architecture.as_str(), "x86_64" | "i386");
+        Decoder {
+            index: TypeIndex::new(elf_info),
+            image,
+            char_signed: x86,
+            x87: x86,
+        }
+    }
+
+    fn size_of(&self, type_info: &'a TypeInfo) -> u64 {
+        type_info
+            .size
+            .or_else(|| self.index.definition(type_info.underlying()).size)
+            .unwrap_or(0)
+    }
+
+    /// Decode `bytes`, the object of type `type_info` stored at `address`
+    fn decode(&self, type_info: &'a TypeInfo, bytes: &[u8], address: u64) -> Value {
+        match type_info.kind.as_str() {
+            "typedef" | "const" | "volatile" | "restrict" | "atomic" => {
+                match type_info.target.as_deref() {
+                    Some(target) => self.decode(target, bytes, address),
+                    None => bytes_value(bytes),
+                }
+            }
+            "basic" => self.basic(type_info, bytes),
+            "pointer" | "reference" => self.pointer(type_info, bytes, address),
+            "array" => self.array(type_info, bytes, address),
+            "enum" | "struct" | "class" | "union" | "str" | "slice" | "trait_object" => {
+                let definition = self.index.definition(type_info);
+                if definition.kind == "enum" && definition.variant_part.is_none() {
+                    self.enumeration(definition, self.image.unsigned(bytes), bytes.len())
+                } else if definition.kind == "str" {
+                    self.rust_str(definition, bytes, address)
+                } else {
+                    self.aggregate(definition, bytes, address)
+                }
+            }
+            _ => bytes_value(bytes),
+        }
+    }
+
+    fn basic(&self, type_info: &TypeInfo, bytes: &[u8]) -> Value {
+        let name = type_info.name.as_str();
+        let raw = self.image.unsigned(bytes);
+        match name {
+            "_Bool" | "bool" => match raw {
+                0 => Value::scalar("bool", "false".to_string()),
+                1 => Value::scalar("bool", "true".to_string()),
+                _ => Value::scalar("bool", raw.to_string()),
+            },
+            _ if name.contains("complex") => bytes_value(bytes),
+            _ if name.contains("float")
+                || name.contains("double")
+                || name.starts_with("_Float")
+                || matches!(name, "f32" | "f64") =>
+            {
+                match self.float(bytes) {
+                    Some(text) => Value::scalar("float", text),
+                    None => bytes_value(bytes),
+                }
+            }
+            // Rust `char`
+            "char" if bytes.len() == 4 => {
+                let text = match char::from_u32(raw as u32) {
+                    Some(c) => format!("{raw} {c:?}"),
+                    None => raw.to_string(),
+                };
+                Value::scalar("char", text)
+            }
+            // `signed char` and `unsigned char` (`int8_t`, `uint8_t`) are small integers
+            "char" | "char8_t" if bytes.len() == 1 => {
+                let value = match name {
+                    "char" if self.char_signed => raw as u8 as i8 as i64,
+                    _ => raw as i64,
+                };
+                let escaped: String = std::ascii::escape_default(raw as u8)
+                    .map(char::from)
+                    .collect();
+                Value::scalar("char", format!("{value} '{escaped}'"))
+            }
+            _ if self.is_signed(name) => {
+                Value::scalar("integer", sign_extend(raw, bytes.len()).to_string())
+            }
+            _ => Value::scalar("integer", raw.to_string()),
+        }
+    }
+
+    fn is_signed(&self, name: &str) -> bool {
+        match name {
+            "wchar_t" => self.char_signed,
+            _ => {
+                !(name.contains("unsigned")
+                    || name.starts_with('u')
+                    || name.starts_with("char")
+                    || name == "bool")
+            }
+        }
+    }
+
+    /// IEEE binary32 and binary64, the x87 80-bit format and IEEE binary128,
+    /// rendered through f64
+    fn float(&self, bytes: &[u8]) -> Option<String> {
+        let raw = self.image.unsigned(bytes);
+        match bytes.len() {
+            4 => Some(f32::from_bits(raw as u32).to_string()),
+            8 => Some(f64::from_bits(raw as u64).to_string()),
+            10 | 12 | 16 if self.x87 => {
+                let mantissa = raw as u64;
+                let exponent = (raw >> 64) as u32 & 0x7fff;
+                let negative = (raw >> 79) & 1 == 1;
+                let magnitude = match exponent {
+                    0 if mantissa == 0 => 0.0,
+                    0x7fff if mantissa << 1 == 0 => f64::INFINITY,
+                    0x7fff => f64::NAN,
+                    _ => mantissa as f64 * 2f64.powi(exponent as i32 - 16383 - 63),
+                };
+                Some(signed_float(negative, magnitude))
+            }
+            16 => {
+                let mantissa = raw & ((1u128 << 112) - 1);
+                let exponent = (raw >> 112) as u32 & 0x7fff;
+                let negative = raw >> 127 == 1;
+                let magnitude = match exponent {
+                    0 if mantissa == 0 => 0.0,
+                    0x7fff if mantissa == 0 => f64::INFINITY,
+                    0x7fff => f64::NAN,
+                    0 => mantissa as f64 * 2f64.powi(-16382 - 112),
+                    _ => {
+                        (1.0 + mantissa as f64 / 2f64.powi(112))
+                            * 2f64.powi(exponent as i32 - 16383)
+                    }
+                };
+                Some(signed_float(negative, magnitude))
+            }
+            _ => None,
+        }
+    }
+
+    /// Enumerator named by `raw`, or the enumerators of a flag combination
+    fn enumeration(&self, type_info: &TypeInfo, raw: u128, size: usize) -> Value {
+        let signed = type_info.enumerators.iter().any(|e| e.value < 0);
+        let value = if signed {
+            sign_extend(raw, size) as i64
+        } else {
+            raw as i64
+        };
+        if let Some(enumerator) = type_info.enumerators.iter().find(|e| e.value == value) {
+            return Value::scalar("enum", enumerator.name.clone());
+        }
+        let flags: Vec<&str> = type_info
+            .enumerators
+            .iter()
+            .filter(|e| e.value > 0 && (e.value as u64).is_power_of_two())
+            .filter(|e| value & e.value != 0)
+            .map(|e| e.name.as_str())
+            .collect();
+        let covered = type_info
+            .enumerators
+            .iter()
+            .filter(|e| flags.contains(&e.name.as_str()))
+            .fold(0, |mask, e| mask | e.value);
+        let text = if !flags.is_empty() && covered == value {
+            flags.join(" | ")
+        } else {
+            value.to_string()
+        };
+        Value::scalar("enum", text)
+    }
+
+    fn pointer(&self, type_info: &'a TypeInfo, bytes: &[u8], address: u64) -> Value {
+        let stored = self.image.unsigned(bytes) as u64;
+        let (target, relocated) = match self.image.relocations.get(&address) {
+            Some((Some(symbol), addend)) => {
+                // Bound by the dynamic loader to a symbol of another object
+                let addend = addend.unwrap_or(stored as i64);
+                let text = match addend {
+                    0 => format!("&{symbol}"),
+                    addend => format!("&{symbol} + {addend}"),
+                };
+                return Value {
+                    kind: "pointer".to_string(),
+                    text,
+                    symbol: Some(symbol.clone()),
+                    ..Default::default()
+                };
+            }
+            Some((None, addend)) => (addend.map_or(stored, |addend| addend as u64), true),
+            None => (stored, false),
+        };
+        if target == 0 && !relocated {
+            return Value::scalar("pointer", "NULL".to_string());
+        }
+
+        let mut value = Value {
+            kind: "pointer".to_string(),
+            target: Some(target),
+            ..Default::default()
+        };
+        let symbol = self.image.symbolize(target);
+        if let Some((symbol, offset)) = symbol {
+            value.symbol = Some(symbol.name.clone());
+            value.text = match offset {
+                0 if symbol.function => symbol.name.clone(),
+                0 => format!("&{}", symbol.name),
+                offset => format!("&{} + {offset}", symbol.name),
+            };
+        } else {
+            value.text = format!("{target:#x}");
+        }
+        let points_to_char = type_info.target.as_deref().is_some_and(|target| {
+            let target = target.underlying();
+            target.kind == "basic" && target.size == Some(1) && target.name.contains("char")
+        });
+        if points_to_char && symbol.is_none_or(|(symbol, _)| !symbol.function) {
+            if let Some(text) = self.c_string(target) {
+                value.text = text;
+            }
+        }
+        value
+    }
+
+    /// NUL-terminated string at `address`, quoted
+    fn c_string(&self, address: u64) -> Option<String> {
+        let mut bytes = Vec::new();
+        for offset in 0..MAX_STRING as u64 {
+            let byte = *self.image.read(address.checked_add(offset)?, 1)?.first()?;
+            if byte == 0 {
+                return Some(quote(&bytes));
+            }
+            bytes.push(byte);
+        }
+        Some(format!("{}...", quote(&bytes)))
+    }
+
+    fn array(&self, type_info: &'a TypeInfo, bytes: &[u8], address: u64) -> Value {
+        let Some(array) = &type_info.array else {
+            return bytes_value(bytes);
+        };
+        let element = &array.element_type;
+        let element_size = self.size_of(element);
+        let counts: Vec<u64> = array
+            .dimensions
+            .iter()
+            .map(|dimension| {
+                if array.flexible {
+                    0
+                } else {
+                    dimension.count.unwrap_or(0)
+                }
+            })
+            .collect();
+        self.dimension(element, element_size, &counts, bytes, address)
+    }
+
+    /// Elements of the outermost of `counts`, each an array of the remaining dimensions
+    fn dimension(
+        &self,
+        element: &'a TypeInfo,
+        element_size: u64,
+        counts: &[u64],
+        bytes: &[u8],
+        address: u64,
+    ) -> Value {
+        let Some((&count, inner)) = counts.split_first() else {
+            return self.decode(element, bytes, address);
+        };
+        let stride = inner.iter().product::<u64>() * element_size;
+        let underlying = element.underlying();
+        if inner.is_empty()
+            && underlying.kind == "basic"
+            && underlying.size == Some(1)
+            && matches!(underlying.name.as_str(), "char" | "signed char")
+        {
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            return Value::scalar("string", quote(&bytes[..end]));
+        }
+
+        let mut value = Value {
+            kind: "array".to_string(),
+            ..Default::default()
+        };
+        for index in 0..count {
+            if index as usize == MAX_ELEMENTS {
+                value.omitted = count - index;
+                break;
+            }
+            let start = (index * stride) as usize;
+            let Some(element_bytes) = bytes.get(start..start + stride as usize) else {
+                break;
+            };
+            value.elements.push(self.dimension(
+                element,
+                element_size,
+                inner,
+                element_bytes,
+                address + index * stride,
+            ));
+        }
+        value
+    }
+
+    fn aggregate(&self, type_info: &'a TypeInfo, bytes: &[u8], address: u64) -> Value {
+        if let Some(variant_part) = &type_info.variant_part {
+            // The active variant of a Rust enum is selected by its tag
+            let tag = variant_part.discriminant.as_ref().and_then(|discriminant| {
+                let start = discriminant.offset as usize;
+                let size = self.size_of(&discriminant.type_info) as usize;
+                let stored = self.image.unsigned(bytes.get(start..start + size)?) as u64;
+                // A niche in a pointer bound to another object is never null
+                self.image.relocated(address + discriminant.offset, stored)
+            });
+            let variant = variant_part
+                .variants
+                .iter()
+                .find(|v| tag.is_some() && v.discriminant_value == tag)
+                .or_else(|| {
+                    variant_part
+                        .variants
+                        .iter()
+                        .find(|v| v.discriminant_value.is_none())
+                })
+                .or_else(|| variant_part.variants.first().filter(|_| tag.is_none()));
+            let Some(variant) = variant else {
+                return bytes_value(bytes);
+            };
+            let payload = self.index.definition(&variant.payload);
+            let mut value = Value {
+                kind: "variant".to_string(),
+                text: variant.name.clone(),
+                ..Default::default()
+            };
+            value.fields = self.fields(payload, bytes, address, variant.offset);
+            if let Some(discriminant) = &variant_part.discriminant {
+                value.fields.retain(|field| field.name != discriminant.name);
+            }
+            return value;
+        }
+
+        let kind = if type_info.kind == "union" {
+            "union"
+        } else {
+            "struct"
+        };
+        Value {
+            kind: kind.to_string(),
+            fields: self.fields(type_info, bytes, address, 0),
+            ..Default::default()
+        }
+    }
+
+    /// Members of `type_info` and its bases, for an object `base` bytes into `bytes`
+    fn fields(
+        &self,
+        type_info: &'a TypeInfo,
+        bytes: &[u8],
+        address: u64,
+        base: u64,
+    ) -> Vec<FieldValue> {
+        let mut members = Vec::new();
+        self.index
+            .collect_members(type_info, base * 8, &mut members);
+        members
+            .into_iter()
+            .map(|(base_bits, member)| FieldValue {
+                name: if is_anonymous_member(member) {
+                    String::new()
+                } else {
+                    member.name.clone()
+                },
+                value: self.member(member, base_bits, bytes, address),
+            })
+            .collect()
+    }
+
+    fn member(&self, member: &'a MemberInfo, base_bits: u64, bytes: &[u8], address: u64) -> Value {
+        if let Some(bits) = member.bit_size {
+            let start = base_bits + member.bit_offset.unwrap_or(member.offset * 8);
+            return self.bitfield(&member.type_info, bytes, start, bits);
+        }
+        let start = base_bits / 8 + member.offset;
+        let size = self.size_of(&member.type_info);
+        match bytes.get(start as usize..(start + size) as usize) {
+            Some(member_bytes) => self.decode(&member.type_info, member_bytes, address + start),
+            None => Value::unavailable(),
+        }
+    }
+
+    fn bitfield(&self, type_info: &'a TypeInfo, bytes: &[u8], start: u64, bits: u64) -> Value {
+        let first = (start / 8) as usize;
+        let last = (start + bits).div_ceil(8) as usize;
+        let Some(storage) = bytes.get(first..last).filter(|_| bits > 0) else {
+            return Value::unavailable();
+        };
+        let storage_bits = (last - first) as u64 * 8;
+        let shift = if self.image.little_endian {
+            start % 8
+        } else {
+            storage_bits - start % 8 - bits
+        };
+        let raw = (self.image.unsigned(storage) >> shift) & ((1u128 << bits) - 1);
+
+        let underlying = type_info.underlying();
+        let definition = self.index.definition(underlying);
+        if definition.kind == "enum" && definition.variant_part.is_none() {
+            return self.enumeration(definition, raw, bits.div_ceil(8) as usize);
+        }
+        match underlying.name.as_str() {
+            "_Bool" | "bool" => Value::scalar("bool", (raw != 0).to_string()),
+            name if self.is_signed(name) => {
+                let unused = 128 - bits;
+                Value::scalar("integer", (((raw << unused) as i128) >> unused).to_string())
+            }
+            _ => Value::scalar("integer", raw.to_string()),
+        }
+    }
+
+    /// Rust `&str`: a pointer and a length
+    fn rust_str(&self, type_info: &'a TypeInfo, bytes: &[u8], address: u64) -> Value {
+        let field = |name: &str| {
+            type_info
+                .members
+                .iter()
+                .find(|member| member.name == name)
+                .and_then(|member| {
+                    let start = member.offset as usize;
+                    let size = self.size_of(&member.type_info) as usize;
+                    bytes
+                        .get(start..start + size)
+                        .map(|field| (member.offset, self.image.unsigned(field) as u64))
+                })
+        };
+        let (Some((pointer_offset, stored)), Some((_, length))) =
+            (field("data_ptr"), field("length"))
+        else {
+            return self.aggregate(type_info, bytes, address);
+        };
+        let Some(pointer) = self.image.relocated(address + pointer_offset, stored) else {
+            return self.aggregate(type_info, bytes, address);
+        };
+        match self.image.read(pointer, length.min(MAX_STRING as u64 * 16)) {
+            Some(text) => Value::scalar("string", quote(&text)),
+            None => self.aggregate(type_info, bytes, address),
+        }
+    }
+}
+
+fn sign_extend(raw: u128, size: usize) -> i128 {
+    match size {
+        0 => 0,
+        1..16 => {
+            let unused = 128 - size as u32 * 8;
+            ((raw << unused) as i128) >> unused
+        }
+        _ => raw as i128,
+    }
+}
+
+fn signed_float(negative: bool, magnitude: f64) -> String {
+    if negative {
+        (-magnitude).to_string()
+    } else {
+        magnitude.to_string()
+    }
+}
+
+fn bytes_value(bytes: &[u8]) -> Value {
+    let text: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    Value::scalar("bytes", text.join(" "))
+}
+
+/// Double-quoted, with non-printable bytes escaped
+fn quote(bytes: &[u8]) -> String {
+    let escaped: String = bytes
+        .iter()
+        .flat_map(|&byte| std::ascii::escape_default(byte))
+        .map(char::from)
+        .collect();
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_double_formats() {
+        let image = Image {
+            regions: Vec::new(),
+            bias: 0,
+            little_endian: true,
+            symbols: Vec::new(),
+            relocations: HashMap::new(),
+        };
+        let elf_info = ElfInfo {
+            architecture: "x86_64".to_string(),
+            entry_point: 0,
+            sections: Vec::new(),
+            section_headers: Vec::new(),
+            file_type: "executable".to_string(),
+            endianness: "little_endian".to_string(),
+            functions: Vec::new(),
+            variables: Vec::new(),
+            types: Vec::new(),
+            type_conflicts: Vec::new(),
+        };
+        let mut decoder = Decoder::new(&elf_info, &image);
+
+        // 1.5 as x87 extended precision: explicit integer bit, exponent 0x3fff
+        let mut x87 = [0u8; 16];
+        x87[..8].copy_from_slice(&0xc000_0000_0000_0000u64.to_le_bytes());
+        x87[8..10].copy_from_slice(&0x3fffu16.to_le_bytes());
+        assert_eq!(decoder.float(&x87).as_deref(), Some("1.5"));
+
+        // -2.5 as IEEE binary128
+        decoder.x87 = false;
+        let binary128 = (1u128 << 127) | (0x4000u128 << 112) | (1u128 << 110);
+        assert_eq!(
+            decoder.float(&binary128.to_le_bytes()).as_deref(),
+            Some("-2.5")
+        );
+    }
+
+    #[test]
+    fn test_read_zero_fill() {
+        let region = |address: u64, size: u64, data: &'static [u8]| Region {
+            address,
+            size,
+            data,
+            zero_fill: true,
+        };
+        let image = Image {
+            regions: vec![
+                region(0x1000, 0x100, &[1, 2, 3, 4]),
+                // A corrupt section header: the end overflows
+                region(u64::MAX - 8, 0x100, &[]),
+                // A huge `.bss`
+                region(0x10_0000_0000, 1 << 40, &[]),
+            ],
+            bias: 0,
+            little_endian: true,
+            symbols: Vec::new(),
+            relocations: HashMap::new(),
+        };
+        assert_eq!(image.read(0x1000, 2).as_deref(), Some(&[1, 2][..]));
+        assert_eq!(image.read(0x1002, 4).as_deref(), Some(&[3, 4, 0, 0][..]));
+        assert!(image.read(0x1000, 0x101).is_none());
+        assert!(image.read(u64::MAX - 8, 4).is_none());
+        assert_eq!(image.read_uint(0x10_0000_0000, 8), Some(0));
+        assert!(image.read(0x10_0000_0000, 1 << 32).is_none());
+    }
+
+    #[test]
+    fn test_quote_escapes() {
+        assert_eq!(quote(b"hi\n\x01"), "\"hi\\n\\x01\"");
+    }
+}
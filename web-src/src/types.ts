@@ -3,46 +3,207 @@
 export interface WasmModule {
   analyze_files_wasm: (elfData: Uint8Array | null) => string;
   analyze_elf_wasm?: (elfData: Uint8Array) => string;
+  analyze_layouts_wasm?: (elfData: Uint8Array, cachelineSize: number) => string;
+  padding_report_wasm?: (elfData: Uint8Array) => string;
+  analyze_sizes_wasm?: (elfData: Uint8Array) => string;
+  diff_wasm?: (oldData: Uint8Array, newData: Uint8Array) => string;
+  check_abi_wasm?: (oldData: Uint8Array, newData: Uint8Array) => string;
+  generate_c_header_wasm?: (elfData: Uint8Array) => string;
+  generate_rust_bindings_wasm?: (elfData: Uint8Array) => string;
+  read_initial_values_wasm?: (elfData: Uint8Array) => string;
+  analyze_core_wasm?: (coreData: Uint8Array, elfData: Uint8Array) => string;
+  backtrace_wasm?: (
+    elfData: Uint8Array,
+    memory: Uint8Array,
+    address: bigint,
+    loadBias: bigint,
+    registers: string
+  ) => string;
+  cfi_coverage_wasm?: (elfData: Uint8Array) => string;
+  disassemble_wasm?: (elfData: Uint8Array, functionName: string) => string;
+  call_graph_wasm?: (elfData: Uint8Array) => string;
+  stack_usage_wasm?: (elfData: Uint8Array, stackUsage: string) => string;
+  dead_code_wasm?: (elfData: Uint8Array) => string;
+  checksec_wasm?: (elfData: Uint8Array) => string;
+  check_policy_wasm?: (elfData: Uint8Array, policy: string) => string;
 }
 
 export interface TypeInfo {
   name: string;
+  qualified_name?: string;
   size?: number;
-  kind: string; // "basic", "struct", "enum", "union", "pointer", "array"
+  alignment?: number;
+  kind: string; // "basic", "struct", "class", "enum", "union", "pointer", "reference", "array", "subroutine", "typedef", "const", "volatile", "restrict", "atomic", "slice", "str", "trait_object"
   members: MemberInfo[];
+  target?: TypeInfo;
+  array?: ArrayInfo;
+  subroutine?: SubroutineInfo;
+  class?: ClassInfo;
+  template_parameters?: TemplateParameter[];
+  enumerators?: EnumeratorInfo[];
+  variant_part?: VariantPartInfo;
+  decl_location?: SourceLocation;
+  units?: string[];
+  odr_conflict?: boolean;
+}
+
+export interface ClassInfo {
+  bases: BaseClassInfo[];
+  methods: MethodInfo[];
+  static_members: VariableInfo[];
+}
+
+export interface BaseClassInfo {
+  name: string;
+  offset?: number;
+  is_virtual: boolean;
+  accessibility: string;
+}
+
+export interface MethodInfo {
+  name: string;
+  linkage_name?: string;
+  accessibility: string;
+  signature: SubroutineInfo;
+  is_const: boolean;
+  is_static: boolean;
+  is_artificial: boolean;
+  virtuality: string; // "none", "virtual", "pure_virtual"
+  vtable_slot?: number;
+}
+
+export interface TemplateParameter {
+  name: string;
+  kind: string; // "type", "value", "template", "pack"
+  type_info?: TypeInfo;
+  value?: string;
+}
+
+export interface EnumeratorInfo {
+  name: string;
+  value: number;
+}
+
+export interface VariantPartInfo {
+  discriminant?: MemberInfo;
+  variants: VariantInfo[];
+  niche: boolean;
+}
+
+export interface VariantInfo {
+  name: string;
+  discriminant_value?: number;
+  offset: number;
+  payload: TypeInfo;
+}
+
+export interface SubroutineInfo {
+  return_type?: TypeInfo;
+  parameters: TypeInfo[];
+  variadic: boolean;
+  prototyped: boolean;
+  calling_convention: string;
+}
+
+export interface ArrayInfo {
+  element_type: TypeInfo;
+  dimensions: ArrayDimension[];
+  byte_stride?: number;
+  bit_stride?: number;
+  flexible: boolean;
+}
+
+export interface ArrayDimension {
+  lower_bound: number;
+  upper_bound?: number;
+  count?: number;
+  dynamic_bound?: string;
+  byte_stride?: number;
 }
 
 export interface MemberInfo {
   name: string;
   offset: number;
   type_info: TypeInfo;
+  accessibility?: string;
+  bit_size?: number;
+  bit_offset?: number;
+  alignment?: number;
 }
 
 export interface VariableInfo {
   name: string;
+  qualified_name?: string;
+  linkage_name?: string;
   address?: number;
   offset?: number;
   type_info: TypeInfo;
-  scope: string; // "global", "local", "parameter"
+  scope: string; // "global", "local", "parameter", "static_member"
+  decl_location?: SourceLocation;
 }
 
 export interface FunctionInfo {
   name: string;
+  qualified_name?: string;
+  linkage_name?: string;
   address: number;
   size?: number;
   parameters: VariableInfo[];
   return_type?: TypeInfo;
+  decl_location?: SourceLocation;
+}
+
+export interface SourceLocation {
+  file: string;
+  line?: number;
+  column?: number;
 }
 
 export interface ElfInfo {
   architecture: string;
   entry_point: number;
   sections: string[];
+  section_headers?: SectionInfo[];
   file_type: string;
   endianness: string;
   functions?: FunctionInfo[];
   variables?: VariableInfo[];
   types?: TypeInfo[];
+  type_conflicts?: TypeConflict[];
+}
+
+export interface SectionInfo {
+  name: string;
+  kind: string;
+  address: number;
+  offset: number;
+  size: number;
+  alloc: boolean;
+  write: boolean;
+  exec: boolean;
+}
+
+export interface TypeConflict {
+  name: string;
+  layouts: ConflictingLayout[];
+  differences: MemberDifference[];
+}
+
+export interface ConflictingLayout {
+  kind: string;
+  size?: number;
+  units: string[];
+}
+
+export interface MemberDifference {
+  name: string;
+  layouts: (MemberLayout | null)[];
+}
+
+export interface MemberLayout {
+  offset: number;
+  type_name: string;
+  size?: number;
 }
 
 export interface AnalysisResult {
@@ -63,4 +224,395 @@ export interface AppState {
   isLoading: boolean;
   error: string | null;
   results: AnalysisResult | null;
-}
\ No newline at end of file
+}
+
+export interface StructLayout {
+  name: string;
+  kind: string;
+  size: number;
+  alignment: number;
+  cacheline_size: number;
+  cachelines: number;
+  members: FieldLayout[];
+  holes: Hole[];
+  trailing_padding: number;
+  wasted_bytes: number;
+  suggested_order: string[];
+  suggested_size: number;
+  savings: number;
+}
+
+export interface FieldLayout {
+  name: string;
+  type_name: string;
+  offset: number;
+  size: number;
+  alignment: number;
+  bit_offset?: number;
+  bit_size?: number;
+  cacheline: number;
+  crosses_cacheline: boolean;
+  is_base?: boolean;
+}
+
+export interface Hole {
+  after?: string;
+  offset: number;
+  size: number;
+  bits: number;
+}
+
+export interface PaddingReport {
+  total_padding: number;
+  total_savings: number;
+  structs: PaddingRank[];
+}
+
+export interface PaddingRank {
+  name: string;
+  size: number;
+  padding: number;
+  savings: number;
+  instances: number;
+  total_padding: number;
+  total_savings: number;
+  variables: string[];
+}
+
+export interface SizeReport {
+  file_size: number;
+  vm_size: number;
+  sections: SizeEntry[];
+  segments: SizeEntry[];
+  symbols: SizeEntry[];
+  compile_units: SizeEntry[];
+  crates: SizeEntry[];
+}
+
+export interface SizeEntry {
+  name: string;
+  file_size: number;
+  vm_size: number;
+}
+
+export interface DiffReport {
+  summary: DiffSummary;
+  sections: SizeChange[];
+  symbols: SizeChange[];
+  functions: FunctionChange[];
+  types: TypeChange[];
+}
+
+export interface DiffSummary {
+  old_file_size: number;
+  new_file_size: number;
+  file_delta: number;
+  old_vm_size: number;
+  new_vm_size: number;
+  vm_delta: number;
+  symbols_added: number;
+  symbols_removed: number;
+  symbols_resized: number;
+  functions_changed: number;
+  types_changed: number;
+}
+
+export interface SizeChange {
+  name: string;
+  status: string;
+  old_file_size: number;
+  new_file_size: number;
+  file_delta: number;
+  old_vm_size: number;
+  new_vm_size: number;
+  vm_delta: number;
+}
+
+export interface FunctionChange {
+  name: string;
+  status: string;
+  old_signature?: string;
+  new_signature?: string;
+}
+
+export interface TypeChange {
+  name: string;
+  status: string;
+  old_size?: number;
+  new_size?: number;
+  members: MemberDifference[];
+}
+
+export interface AbiReport {
+  compatible: boolean;
+  old_soname?: string;
+  new_soname?: string;
+  breaking_changes: number;
+  compatible_changes: number;
+  changes: AbiChange[];
+}
+
+export interface AbiChange {
+  kind: string;
+  severity: string;
+  name: string;
+  description: string;
+}
+
+export interface ValuesReport {
+  variables: GlobalValue[];
+}
+
+export interface GlobalValue {
+  name: string;
+  qualified_name?: string;
+  address: number;
+  size: number;
+  section: string;
+  zero_initialized: boolean;
+  value: Value;
+}
+
+export interface Value {
+  kind: string;
+  text?: string;
+  target?: number;
+  symbol?: string;
+  fields?: FieldValue[];
+  elements?: Value[];
+  omitted?: number;
+}
+
+export interface FieldValue {
+  name: string;
+  value: Value;
+}
+
+export interface CoreReport {
+  architecture: string;
+  executable: string | null;
+  load_bias: number;
+  process: ProcessInfo | null;
+  signal: number;
+  signal_name?: string;
+  threads: ThreadInfo[];
+  mapped_files: MappedFile[];
+  auxv: AuxvEntry[];
+  variables: GlobalValue[];
+  warnings?: string[];
+}
+
+export interface ProcessInfo {
+  pid: number;
+  ppid: number;
+  pgrp: number;
+  sid: number;
+  uid: number;
+  gid: number;
+  state: string;
+  name: string;
+  args: string;
+}
+
+export interface ThreadInfo {
+  tid: number;
+  signal: number;
+  pc: number;
+  sp: number;
+  function: string | null;
+  registers: RegisterValue[];
+  backtrace?: Backtrace;
+}
+
+export interface RegisterValue {
+  name: string;
+  value: number;
+}
+
+export interface MappedFile {
+  start: number;
+  end: number;
+  offset: number;
+  path: string;
+}
+
+export interface AuxvEntry {
+  key: string;
+  value: number;
+  text?: string;
+}
+
+export interface Backtrace {
+  frames: Frame[];
+  end: string;
+}
+
+export interface Frame {
+  index: number;
+  pc: number;
+  cfa: number | null;
+  function: string | null;
+  location: SourceLocation | null;
+  inlined?: boolean;
+  module?: string;
+  method: string;
+}
+
+export interface CfiReport {
+  functions: FunctionCfi[];
+  covered: number;
+  uncovered: number;
+  mismatched: number;
+}
+
+export interface FunctionCfi {
+  name: string;
+  address: number;
+  size: number;
+  sections: string[];
+  fde_start: number | null;
+  fde_end: number | null;
+  dwarf_low_pc?: number;
+  dwarf_high_pc?: number;
+  mismatches: string[];
+  rows: UnwindRow[];
+}
+
+export interface UnwindRow {
+  start: number;
+  end: number;
+  cfa: string;
+  registers: RegisterRule[];
+}
+
+export interface RegisterRule {
+  register: string;
+  rule: string;
+}
+
+export interface DisassemblyReport {
+  architecture: string;
+  functions: FunctionDisassembly[];
+}
+
+export interface FunctionDisassembly {
+  name: string;
+  address: number;
+  size: number;
+  instructions: Instruction[];
+}
+
+export interface Instruction {
+  address: number;
+  bytes: string;
+  length: number;
+  mnemonic: string;
+  operands: string;
+  kind: string; // "call", "jump", "branch", "return", "indirect_call", "indirect_jump", "data", "other"
+  target?: number;
+  symbol?: string;
+  source?: SourceLocation;
+}
+
+export interface CallGraph {
+  entry?: string;
+  functions: CallGraphFunction[];
+  edges: CallEdge[];
+  indirect_calls: IndirectCall[];
+}
+
+export interface CallGraphFunction {
+  name: string;
+  address?: number;
+  size: number;
+}
+
+export interface CallEdge {
+  caller: string;
+  callee: string;
+  kind: string; // "call", "tail_call", "reference"
+  sites: number[];
+  origin: string; // "code", "dwarf", "both"
+}
+
+export interface IndirectCall {
+  caller: string;
+  address: number;
+  instruction: string;
+  location?: SourceLocation;
+}
+
+export interface StackReport {
+  functions: FunctionStack[];
+  roots: StackRoot[];
+}
+
+export interface FunctionStack {
+  name: string;
+  address?: number;
+  frame_size?: number;
+  source: string; // "stack_sizes", "stack_usage", "cfi", "none"
+  dynamic?: boolean;
+  recursive?: boolean;
+  indirect_calls: number;
+}
+
+export interface StackRoot {
+  name: string;
+  worst_case: number;
+  bounded: boolean;
+  path: string[];
+  unknowns: StackUnknown[];
+}
+
+export interface StackUnknown {
+  function: string;
+  reason: string; // "recursion", "indirect_call", "external", "unknown_frame", "dynamic_frame"
+}
+
+export interface DeadCodeReport {
+  functions: number;
+  total_size: number;
+  roots: RootFunction[];
+  dead: DeadFunction[];
+  reclaimable: number;
+}
+
+export interface RootFunction {
+  name: string;
+  reasons: string[]; // "entry", "exported", "init_fini", "vector_table", "relocation", "data_pointer", "untyped_code"
+}
+
+export interface DeadFunction {
+  name: string;
+  address: number;
+  size: number;
+  referenced_by: string[];
+}
+
+export interface ChecksecReport {
+  checks: SecurityCheck[];
+  passed: number;
+  warnings: number;
+  failed: number;
+}
+
+export interface SecurityCheck {
+  name: string; // "pie", "relro", "nx", "canary", "fortify", "cet", "bti", "pac", "rpath", "wx_segments"
+  status: string; // "pass", "warn", "fail"
+  detail: string;
+}
+
+export interface PolicyReport {
+  passed: boolean;
+  rules: string[];
+  violations: Violation[];
+}
+
+export interface Violation {
+  rule: string; // "max_text_size", "forbidden_symbols", "require_build_id", "required_hardening", "max_stack_depth", "require_bounded_stack"
+  subject: string;
+  message: string;
+  actual?: number;
+  limit?: number;
+}